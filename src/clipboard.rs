@@ -0,0 +1,243 @@
+//! Clipboard utilities.
+//!
+//! Provides safe wrappers for reading and writing the Windows clipboard,
+//! including text, file lists (`CF_HDROP`), and custom registered formats.
+
+use crate::error::{Error, Result};
+use crate::string::{from_wide, pack_multi_wide, WideString};
+use std::os::windows::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use windows::Win32::Foundation::{GlobalFree, BOOL, HANDLE, HGLOBAL, POINT};
+use windows::Win32::System::DataExchange::{
+    CloseClipboard, EmptyClipboard, GetClipboardData, IsClipboardFormatAvailable, OpenClipboard,
+    RegisterClipboardFormatW, SetClipboardData,
+};
+use windows::Win32::System::Memory::{
+    GlobalAlloc, GlobalLock, GlobalSize, GlobalUnlock, GMEM_MOVEABLE, GMEM_ZEROINIT,
+};
+use windows::Win32::UI::Shell::{DROPFILES, HDROP};
+
+/// Standard clipboard format identifiers.
+pub mod formats {
+    /// UTF-16 text (`CF_UNICODETEXT`).
+    pub const UNICODE_TEXT: u32 = 13;
+    /// A list of files (`CF_HDROP`).
+    pub const HDROP: u32 = 15;
+}
+
+/// Number of attempts to open the clipboard while another process holds it.
+const OPEN_ATTEMPTS: u32 = 10;
+
+/// RAII guard for an open clipboard.
+struct ClipboardGuard;
+
+impl ClipboardGuard {
+    fn open() -> Result<Self> {
+        let mut attempt = 0;
+        loop {
+            // SAFETY: OpenClipboard is safe to call; we close it in Drop
+            match unsafe { OpenClipboard(None) } {
+                Ok(()) => return Ok(Self),
+                // Another application may briefly hold the clipboard
+                Err(_) if attempt + 1 < OPEN_ATTEMPTS => {
+                    attempt += 1;
+                    std::thread::sleep(Duration::from_millis(10));
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    /// Returns the data handle for a format, or an error if it isn't present.
+    fn data(&self, format: u32) -> Result<HGLOBAL> {
+        // SAFETY: The clipboard is open
+        unsafe {
+            IsClipboardFormatAvailable(format).map_err(|_| {
+                Error::not_found(format!("Clipboard format {} not available", format))
+            })?;
+            let handle = GetClipboardData(format)?;
+            Ok(HGLOBAL(handle.0))
+        }
+    }
+
+    /// Empties the clipboard and places `hglobal` on it, transferring ownership.
+    fn set(&self, format: u32, hglobal: HGLOBAL) -> Result<()> {
+        // SAFETY: The clipboard is open. On success the system owns hglobal;
+        // on failure we still own it and must free it.
+        unsafe {
+            if let Err(e) =
+                EmptyClipboard().and_then(|()| SetClipboardData(format, HANDLE(hglobal.0)))
+            {
+                let _ = GlobalFree(hglobal);
+                return Err(e.into());
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for ClipboardGuard {
+    fn drop(&mut self) {
+        // SAFETY: We opened the clipboard in ClipboardGuard::open
+        unsafe {
+            let _ = CloseClipboard();
+        }
+    }
+}
+
+/// Allocates a movable global memory block and fills it with `f`.
+fn alloc_global(size: usize, f: impl FnOnce(*mut u8)) -> Result<HGLOBAL> {
+    // SAFETY: GlobalAlloc is safe; the block is zero-initialized and at least size bytes
+    unsafe {
+        let hglobal = GlobalAlloc(GMEM_MOVEABLE | GMEM_ZEROINIT, size)?;
+        let ptr = GlobalLock(hglobal) as *mut u8;
+        if ptr.is_null() {
+            let _ = GlobalFree(hglobal);
+            return Err(crate::error::last_error());
+        }
+        f(ptr);
+        let _ = GlobalUnlock(hglobal);
+        Ok(hglobal)
+    }
+}
+
+/// Copies the contents of a global memory block.
+fn read_global(hglobal: HGLOBAL) -> Result<Vec<u8>> {
+    // SAFETY: hglobal is a valid global memory handle owned by the clipboard.
+    // GlobalSize reports the size of the locked block.
+    unsafe {
+        let ptr = GlobalLock(hglobal) as *const u8;
+        if ptr.is_null() {
+            return Err(crate::error::last_error());
+        }
+        let size = GlobalSize(hglobal);
+        let bytes = std::slice::from_raw_parts(ptr, size).to_vec();
+        let _ = GlobalUnlock(hglobal);
+        Ok(bytes)
+    }
+}
+
+/// Gets the clipboard text.
+pub fn get_text() -> Result<String> {
+    let bytes = get_bytes(formats::UNICODE_TEXT)?;
+    let wide: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .collect();
+    from_wide(&wide)
+}
+
+/// Replaces the clipboard contents with text.
+pub fn set_text(text: &str) -> Result<()> {
+    let wide = WideString::new(text);
+    let slice = wide.as_slice();
+    let hglobal = alloc_global(std::mem::size_of_val(slice), |ptr| {
+        // SAFETY: The block is large enough for the whole string
+        unsafe { std::ptr::copy_nonoverlapping(slice.as_ptr(), ptr as *mut u16, slice.len()) }
+    })?;
+
+    ClipboardGuard::open()?.set(formats::UNICODE_TEXT, hglobal)
+}
+
+/// Gets the list of files on the clipboard (`CF_HDROP`), e.g. after "Copy" in Explorer.
+pub fn get_files() -> Result<Vec<PathBuf>> {
+    let clipboard = ClipboardGuard::open()?;
+    let hglobal = clipboard.data(formats::HDROP)?;
+    Ok(crate::window::dropped_files(HDROP(hglobal.0)))
+}
+
+/// Replaces the clipboard contents with a list of files (`CF_HDROP`).
+///
+/// Other applications, such as Explorer, can paste the files.
+pub fn set_files(paths: &[&Path]) -> Result<()> {
+    // The file list is a double-null-terminated list of wide paths,
+    // following the DROPFILES header.
+    let list = pack_multi_wide(paths.iter().map(|path| path.as_os_str().encode_wide()));
+
+    let header = std::mem::size_of::<DROPFILES>();
+    let hglobal = alloc_global(header + list.len() * 2, |ptr| {
+        // SAFETY: The block holds the header followed by the whole list
+        unsafe {
+            (ptr as *mut DROPFILES).write_unaligned(DROPFILES {
+                pFiles: header as u32,
+                pt: POINT::default(),
+                fNC: BOOL(0),
+                fWide: BOOL(1),
+            });
+            std::ptr::copy_nonoverlapping(list.as_ptr(), ptr.add(header) as *mut u16, list.len());
+        }
+    })?;
+
+    ClipboardGuard::open()?.set(formats::HDROP, hglobal)
+}
+
+/// Registers a custom clipboard format, returning its identifier.
+///
+/// Registering the same name again (in any process) returns the same identifier.
+pub fn register_format(name: &str) -> Result<u32> {
+    let name_wide = WideString::new(name);
+    // SAFETY: name_wide is a valid null-terminated wide string
+    let format = unsafe { RegisterClipboardFormatW(name_wide.as_pcwstr()) };
+    if format == 0 {
+        return Err(crate::error::last_error());
+    }
+    Ok(format)
+}
+
+/// Gets the raw bytes stored on the clipboard in `format`.
+///
+/// The returned data may include padding added by the system allocator.
+pub fn get_bytes(format: u32) -> Result<Vec<u8>> {
+    let clipboard = ClipboardGuard::open()?;
+    let hglobal = clipboard.data(format)?;
+    read_global(hglobal)
+}
+
+/// Replaces the clipboard contents with raw bytes in `format`.
+pub fn set_bytes(format: u32, data: &[u8]) -> Result<()> {
+    let hglobal = alloc_global(data.len().max(1), |ptr| {
+        // SAFETY: The block is at least data.len() bytes
+        unsafe { std::ptr::copy_nonoverlapping(data.as_ptr(), ptr, data.len()) }
+    })?;
+
+    ClipboardGuard::open()?.set(format, hglobal)
+}
+
+/// Checks whether the clipboard currently holds data in `format`.
+pub fn has_format(format: u32) -> bool {
+    // SAFETY: IsClipboardFormatAvailable does not require the clipboard to be open
+    unsafe { IsClipboardFormatAvailable(format).is_ok() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The clipboard is global state, so keep all round-trips in one test.
+    #[test]
+    #[ignore = "overwrites the user's clipboard"]
+    fn test_clipboard_round_trips() {
+        // The clipboard may be unavailable in headless environments
+        if ClipboardGuard::open().is_err() {
+            return;
+        }
+
+        let a = std::env::temp_dir().join("clipboard_a.txt");
+        let b = std::env::temp_dir().join("clipboard b.txt");
+        set_files(&[&a, &b]).unwrap();
+        assert!(has_format(formats::HDROP));
+        assert_eq!(get_files().unwrap(), [a, b]);
+
+        let format = register_format("ErgonomicWindowsTestFormat").unwrap();
+        assert_eq!(
+            register_format("ErgonomicWindowsTestFormat").unwrap(),
+            format
+        );
+        set_bytes(format, &[1, 2, 3, 4]).unwrap();
+        assert_eq!(&get_bytes(format).unwrap()[..4], &[1, 2, 3, 4]);
+
+        set_text("clipboard text").unwrap();
+        assert_eq!(get_text().unwrap(), "clipboard text");
+    }
+}
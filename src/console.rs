@@ -3,16 +3,23 @@
 //! Provides safe wrappers for Windows console operations including
 //! reading, writing, colors, cursor positioning, and screen buffers.
 
-use crate::error::Result;
-use windows::Win32::Foundation::HANDLE;
+use crate::error::{Error, Result};
+use std::sync::RwLock;
+use windows::Win32::Foundation::{BOOL, HANDLE};
+use windows::Win32::Storage::FileSystem::{
+    GetFileType, WriteFile, FILE_TYPE_CHAR, FILE_TYPE_DISK, FILE_TYPE_PIPE,
+};
 use windows::Win32::System::Console::{
     AllocConsole, FillConsoleOutputAttribute, FillConsoleOutputCharacterW, FreeConsole,
     GetConsoleCursorInfo, GetConsoleMode, GetConsoleScreenBufferInfo, GetConsoleTitleW,
-    GetStdHandle, ReadConsoleW, SetConsoleCursorInfo, SetConsoleCursorPosition, SetConsoleMode,
-    SetConsoleTextAttribute, SetConsoleTitleW, WriteConsoleW, CONSOLE_CHARACTER_ATTRIBUTES,
-    CONSOLE_CURSOR_INFO, CONSOLE_MODE, CONSOLE_SCREEN_BUFFER_INFO, COORD, ENABLE_ECHO_INPUT,
+    GetStdHandle, ReadConsoleW, ScrollConsoleScreenBufferW, SetConsoleCtrlHandler,
+    SetConsoleCursorInfo, SetConsoleCursorPosition, SetConsoleMode, SetConsoleTextAttribute,
+    SetConsoleTitleW, WriteConsoleW, CHAR_INFO, CHAR_INFO_0, CONSOLE_CHARACTER_ATTRIBUTES,
+    CONSOLE_CURSOR_INFO, CONSOLE_MODE, CONSOLE_SCREEN_BUFFER_INFO, COORD, CTRL_BREAK_EVENT,
+    CTRL_CLOSE_EVENT, CTRL_C_EVENT, CTRL_LOGOFF_EVENT, CTRL_SHUTDOWN_EVENT, ENABLE_ECHO_INPUT,
     ENABLE_LINE_INPUT, ENABLE_PROCESSED_INPUT, ENABLE_PROCESSED_OUTPUT,
-    ENABLE_VIRTUAL_TERMINAL_PROCESSING, STD_ERROR_HANDLE, STD_INPUT_HANDLE, STD_OUTPUT_HANDLE,
+    ENABLE_VIRTUAL_TERMINAL_PROCESSING, SMALL_RECT, STD_ERROR_HANDLE, STD_INPUT_HANDLE,
+    STD_OUTPUT_HANDLE,
 };
 
 /// Standard console handles.
@@ -133,6 +140,7 @@ impl Default for TextAttribute {
 pub struct Console {
     input: HANDLE,
     output: HANDLE,
+    error: HANDLE,
 }
 
 impl Console {
@@ -140,8 +148,13 @@ impl Console {
     pub fn current() -> Result<Self> {
         let input = get_std_handle(StdHandle::Input)?;
         let output = get_std_handle(StdHandle::Output)?;
+        let error = get_std_handle(StdHandle::Error)?;
 
-        Ok(Self { input, output })
+        Ok(Self {
+            input,
+            output,
+            error,
+        })
     }
 
     /// Allocates a new console for the current process.
@@ -189,15 +202,7 @@ impl Console {
 
     /// Writes a string to the console.
     pub fn write(&self, text: &str) -> Result<usize> {
-        let wide: Vec<u16> = text.encode_utf16().collect();
-        let mut written = 0u32;
-
-        // SAFETY: WriteConsoleW is safe with valid parameters
-        unsafe {
-            WriteConsoleW(self.output, &wide, Some(&mut written), None)?;
-        }
-
-        Ok(written as usize)
+        self.write_to(StdHandle::Output, text)
     }
 
     /// Writes a line to the console (adds newline).
@@ -207,6 +212,52 @@ impl Console {
         Ok(total)
     }
 
+    /// Writes a string to standard error.
+    pub fn write_err(&self, text: &str) -> Result<usize> {
+        self.write_to(StdHandle::Error, text)
+    }
+
+    /// Writes a line to standard error (adds newline).
+    pub fn write_line_err(&self, text: &str) -> Result<usize> {
+        let mut total = self.write_err(text)?;
+        total += self.write_err("\r\n")?;
+        Ok(total)
+    }
+
+    /// Writes a string to standard output or standard error.
+    ///
+    /// If the stream is a real console the text is written as UTF-16 with
+    /// `WriteConsoleW` and the result counts UTF-16 code units. If it has been
+    /// redirected to a file or pipe, the text is written as UTF-8 with `WriteFile`
+    /// and the result counts bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `handle` is [`StdHandle::Input`] or the write fails.
+    pub fn write_to(&self, handle: StdHandle, text: &str) -> Result<usize> {
+        let h = match handle {
+            StdHandle::Output => self.output,
+            StdHandle::Error => self.error,
+            StdHandle::Input => return Err(Error::custom("Cannot write to standard input")),
+        };
+
+        let mut written = 0u32;
+        if handle_output_type(h) == OutputType::Console {
+            let wide: Vec<u16> = text.encode_utf16().collect();
+            // SAFETY: WriteConsoleW is safe with valid parameters
+            unsafe {
+                WriteConsoleW(h, &wide, Some(&mut written), None)?;
+            }
+        } else {
+            // SAFETY: WriteFile is safe with a valid handle and buffer
+            unsafe {
+                WriteFile(h, Some(text.as_bytes()), Some(&mut written), None)?;
+            }
+        }
+
+        Ok(written as usize)
+    }
+
     /// Reads a line from the console.
     pub fn read_line(&self) -> Result<String> {
         let mut buffer = vec![0u16; 4096];
@@ -234,6 +285,81 @@ impl Console {
         crate::string::from_wide(&buffer[..end])
     }
 
+    /// Prompts for and reads a line without echoing it, e.g. a password.
+    ///
+    /// Only echo is turned off, so line editing such as Backspace still works.
+    /// The original input mode is restored afterwards, even if reading fails.
+    /// The whole line is consumed, however long, so nothing is left over for
+    /// the next read. The returned string has the trailing line break removed,
+    /// and the intermediate UTF-16 buffers are zeroed.
+    ///
+    /// With the default control handling, Ctrl+C ends the process before the
+    /// mode can be restored, leaving echo off in the console. Install a handler
+    /// with [`set_ctrl_handler`] that returns `true` to make the read fail
+    /// instead, so the mode is restored.
+    pub fn read_password(&self, prompt: &str) -> Result<String> {
+        self.write(prompt)?;
+
+        let result = {
+            let _mode = InputModeGuard::new(self.input, |mode| {
+                CONSOLE_MODE((mode.0 | ENABLE_LINE_INPUT.0) & !ENABLE_ECHO_INPUT.0)
+            })?;
+            self.read_secret_line()
+        };
+
+        // The Enter key wasn't echoed either
+        self.write("\r\n")?;
+        result
+    }
+
+    fn read_secret_line(&self) -> Result<String> {
+        let mut chunk = vec![0u16; 1024];
+        let mut line: Vec<u16> = Vec::new();
+
+        // A line longer than the buffer arrives over several reads; stop at
+        // the line feed (or end of input) so none of it is left behind
+        let result = loop {
+            let mut read = 0u32;
+            // SAFETY: ReadConsoleW is safe with valid parameters
+            let result = unsafe {
+                ReadConsoleW(
+                    self.input,
+                    chunk.as_mut_ptr() as *mut _,
+                    chunk.len() as u32,
+                    &mut read,
+                    None,
+                )
+            };
+            if let Err(e) = result {
+                break Err(Error::from(e));
+            }
+            let read = &chunk[..read as usize];
+            if line.capacity() - line.len() < read.len() {
+                // Grow by hand so the old allocation can be zeroed first
+                let mut bigger = Vec::with_capacity((line.len() + read.len()) * 2);
+                bigger.extend_from_slice(&line);
+                zero_wide(&mut line);
+                line = bigger;
+            }
+            line.extend_from_slice(read);
+            if read.is_empty() || read.last() == Some(&0x0A) {
+                break Ok(());
+            }
+        };
+
+        let text = result.and_then(|()| {
+            let end = line
+                .iter()
+                .rposition(|&c| c != 0x0D && c != 0x0A)
+                .map_or(0, |i| i + 1);
+            crate::string::from_wide(&line[..end])
+        });
+
+        zero_wide(&mut chunk);
+        zero_wide(&mut line);
+        text
+    }
+
     /// Sets the text color.
     pub fn set_text_attribute(&self, attr: TextAttribute) -> Result<()> {
         // SAFETY: SetConsoleTextAttribute is safe with valid handle
@@ -347,6 +473,58 @@ impl Console {
         Ok(())
     }
 
+    /// Moves a block of the screen buffer, like `ScrollConsoleScreenBufferW`.
+    ///
+    /// Rectangles are `(left, top, right, bottom)` in buffer character cells,
+    /// with both edges inclusive, and `dest_top_left` is the `(x, y)` cell the
+    /// top-left corner of `src_rect` moves to. Cells of `src_rect` that are
+    /// uncovered by the move are filled with `fill_char` in `fill_attr`. If
+    /// `clip` is given, only cells inside it change.
+    ///
+    /// For example, scrolling lines 1..=10 up by one line:
+    ///
+    /// ```no_run
+    /// use ergonomic_windows::console::{Console, TextAttribute};
+    ///
+    /// let console = Console::current()?;
+    /// let width = console.screen_buffer_info()?.size_x;
+    /// console.scroll((0, 1, width - 1, 10), (0, 0), None, ' ', TextAttribute::default())?;
+    /// # Ok::<(), ergonomic_windows::error::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `fill_char` is outside the Basic Multilingual Plane,
+    /// since each cell holds a single UTF-16 unit.
+    pub fn scroll(
+        &self,
+        src_rect: (i16, i16, i16, i16),
+        dest_top_left: (i16, i16),
+        clip: Option<(i16, i16, i16, i16)>,
+        fill_char: char,
+        fill_attr: TextAttribute,
+    ) -> Result<()> {
+        let fill = char_info(fill_char, fill_attr)?;
+        let src = small_rect(src_rect);
+        let clip = clip.map(small_rect);
+        let dest = COORD {
+            X: dest_top_left.0,
+            Y: dest_top_left.1,
+        };
+
+        // SAFETY: All structures live until the call returns
+        unsafe {
+            ScrollConsoleScreenBufferW(
+                self.output,
+                &src,
+                clip.as_ref().map(|c| c as *const _),
+                dest,
+                &fill,
+            )?;
+        }
+        Ok(())
+    }
+
     /// Enables virtual terminal processing (ANSI escape codes).
     pub fn enable_virtual_terminal(&self) -> Result<()> {
         let mut mode = CONSOLE_MODE(0);
@@ -397,6 +575,70 @@ impl Console {
     }
 }
 
+/// Changes a console input mode and restores the original on drop.
+struct InputModeGuard {
+    input: HANDLE,
+    original: CONSOLE_MODE,
+}
+
+impl InputModeGuard {
+    fn new(input: HANDLE, change: impl FnOnce(CONSOLE_MODE) -> CONSOLE_MODE) -> Result<Self> {
+        let mut original = CONSOLE_MODE(0);
+        // SAFETY: GetConsoleMode is safe with valid handle
+        unsafe {
+            GetConsoleMode(input, &mut original)?;
+        }
+        // SAFETY: SetConsoleMode is safe with valid handle
+        unsafe {
+            SetConsoleMode(input, change(original))?;
+        }
+        Ok(Self { input, original })
+    }
+}
+
+impl Drop for InputModeGuard {
+    fn drop(&mut self) {
+        // SAFETY: SetConsoleMode is safe with valid handle
+        unsafe {
+            let _ = SetConsoleMode(self.input, self.original);
+        }
+    }
+}
+
+/// Overwrites a buffer that held secret input.
+fn zero_wide(buffer: &mut [u16]) {
+    for c in buffer.iter_mut() {
+        // SAFETY: c is a valid, aligned element; the volatile write keeps
+        // the zeroing from being optimized away.
+        unsafe { std::ptr::write_volatile(c, 0) };
+    }
+}
+
+/// Converts a `(left, top, right, bottom)` tuple to a `SMALL_RECT`.
+fn small_rect((left, top, right, bottom): (i16, i16, i16, i16)) -> SMALL_RECT {
+    SMALL_RECT {
+        Left: left,
+        Top: top,
+        Right: right,
+        Bottom: bottom,
+    }
+}
+
+/// Builds the `CHAR_INFO` for a single cell.
+fn char_info(c: char, attr: TextAttribute) -> Result<CHAR_INFO> {
+    let mut units = [0u16; 2];
+    let [unit] = c.encode_utf16(&mut units) else {
+        return Err(Error::custom(format!(
+            "{:?} does not fit in a single console cell",
+            c
+        )));
+    };
+    Ok(CHAR_INFO {
+        Char: CHAR_INFO_0 { UnicodeChar: *unit },
+        Attributes: attr.to_u16(),
+    })
+}
+
 /// Information about the screen buffer.
 #[derive(Debug, Clone)]
 pub struct ScreenBufferInfo {
@@ -443,6 +685,164 @@ pub fn get_std_handle(handle: StdHandle) -> Result<HANDLE> {
     Ok(h)
 }
 
+/// The kind of device a standard handle is connected to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputType {
+    /// A real console.
+    Console,
+    /// A pipe (e.g. `program | more`).
+    Pipe,
+    /// A disk file (e.g. `program > out.txt`).
+    File,
+    /// No handle, or another kind of device such as `NUL`.
+    Unknown,
+}
+
+/// Determines what a standard handle is connected to.
+///
+/// This is the Windows equivalent of `isatty`: use it to decide whether to
+/// emit colors or VT sequences, or how to buffer output.
+pub fn output_type(handle: StdHandle) -> OutputType {
+    match get_std_handle(handle) {
+        Ok(h) => handle_output_type(h),
+        Err(_) => OutputType::Unknown,
+    }
+}
+
+/// Determines what an arbitrary handle is connected to.
+fn handle_output_type(h: HANDLE) -> OutputType {
+    if h.is_invalid() {
+        return OutputType::Unknown;
+    }
+
+    // SAFETY: h is a valid handle owned by the process.
+    let file_type = unsafe { GetFileType(h) };
+
+    match file_type {
+        FILE_TYPE_CHAR => {
+            // Character devices include NUL and serial ports; only a console
+            // handle supports GetConsoleMode.
+            let mut mode = CONSOLE_MODE::default();
+            // SAFETY: h is a valid handle and mode is a valid output parameter.
+            if unsafe { GetConsoleMode(h, &mut mode) }.is_ok() {
+                OutputType::Console
+            } else {
+                OutputType::Unknown
+            }
+        }
+        FILE_TYPE_PIPE => OutputType::Pipe,
+        FILE_TYPE_DISK => OutputType::File,
+        _ => OutputType::Unknown,
+    }
+}
+
+/// Returns true if the standard handle is connected to a real console.
+pub fn is_terminal(handle: StdHandle) -> bool {
+    output_type(handle) == OutputType::Console
+}
+
+/// A console control signal delivered to a handler installed with [`set_ctrl_handler`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CtrlEvent {
+    /// Ctrl+C was pressed.
+    CtrlC,
+    /// Ctrl+Break was pressed.
+    CtrlBreak,
+    /// The console window is being closed.
+    Close,
+    /// The user is logging off (normally only seen by services).
+    Logoff,
+    /// The system is shutting down (normally only seen by services).
+    Shutdown,
+}
+
+impl CtrlEvent {
+    fn from_raw(ctrl_type: u32) -> Option<Self> {
+        match ctrl_type {
+            CTRL_C_EVENT => Some(Self::CtrlC),
+            CTRL_BREAK_EVENT => Some(Self::CtrlBreak),
+            CTRL_CLOSE_EVENT => Some(Self::Close),
+            CTRL_LOGOFF_EVENT => Some(Self::Logoff),
+            CTRL_SHUTDOWN_EVENT => Some(Self::Shutdown),
+            _ => None,
+        }
+    }
+}
+
+type CtrlHandler = Box<dyn Fn(CtrlEvent) -> bool + Send + Sync>;
+
+/// The handler called by [`ctrl_trampoline`], if one is installed.
+static CTRL_HANDLER: RwLock<Option<CtrlHandler>> = RwLock::new(None);
+
+/// Installs a handler for console control signals such as Ctrl+C.
+///
+/// The handler runs on a new thread created by the system. Returning `true`
+/// marks the signal as handled; returning `false` passes it on, which for most
+/// signals ends with the process being terminated. Calling this again replaces
+/// the previous handler.
+///
+/// For [`CtrlEvent::Close`], [`CtrlEvent::Logoff`] and [`CtrlEvent::Shutdown`] the
+/// process is terminated once the handler returns, whatever it returns, and
+/// Windows only waits about 5 seconds for it before terminating the process
+/// anyway, so cleanup must be quick.
+///
+/// # Example
+///
+/// ```no_run
+/// use ergonomic_windows::console::{set_ctrl_handler, CtrlEvent};
+/// use std::sync::atomic::{AtomicBool, Ordering};
+///
+/// static STOP: AtomicBool = AtomicBool::new(false);
+///
+/// set_ctrl_handler(|event| {
+///     if event == CtrlEvent::CtrlC {
+///         STOP.store(true, Ordering::SeqCst);
+///         true
+///     } else {
+///         false
+///     }
+/// })?;
+/// # Ok::<(), ergonomic_windows::error::Error>(())
+/// ```
+pub fn set_ctrl_handler(handler: impl Fn(CtrlEvent) -> bool + Send + Sync + 'static) -> Result<()> {
+    let mut slot = CTRL_HANDLER.write().unwrap_or_else(|e| e.into_inner());
+    if slot.is_none() {
+        // SAFETY: ctrl_trampoline is a valid handler for the life of the process
+        unsafe {
+            SetConsoleCtrlHandler(Some(ctrl_trampoline), true)?;
+        }
+    }
+    *slot = Some(Box::new(handler));
+    Ok(())
+}
+
+/// Removes the handler installed by [`set_ctrl_handler`], restoring default handling.
+pub fn remove_ctrl_handler() -> Result<()> {
+    let mut slot = CTRL_HANDLER.write().unwrap_or_else(|e| e.into_inner());
+    if slot.take().is_some() {
+        // SAFETY: ctrl_trampoline was registered by set_ctrl_handler
+        unsafe {
+            SetConsoleCtrlHandler(Some(ctrl_trampoline), false)?;
+        }
+    }
+    Ok(())
+}
+
+/// Dispatches a control signal to the installed handler.
+///
+/// Panics are caught here, since unwinding into the system thread that called
+/// us is undefined behavior; a panicking handler counts as not handled.
+unsafe extern "system" fn ctrl_trampoline(ctrl_type: u32) -> BOOL {
+    let Some(event) = CtrlEvent::from_raw(ctrl_type) else {
+        return BOOL::from(false);
+    };
+    let handled = std::panic::catch_unwind(|| {
+        let slot = CTRL_HANDLER.read().unwrap_or_else(|e| e.into_inner());
+        slot.as_ref().is_some_and(|handler| handler(event))
+    });
+    BOOL::from(handled.unwrap_or(false))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -453,6 +853,41 @@ mod tests {
         assert_eq!(attr.to_u16(), 0x1F);
     }
 
+    #[test]
+    fn test_scroll_marshalling() {
+        let rect = small_rect((1, 2, 30, 4));
+        assert_eq!(
+            (rect.Left, rect.Top, rect.Right, rect.Bottom),
+            (1, 2, 30, 4)
+        );
+
+        let cell = char_info('#', TextAttribute::new(Color::White, Color::DarkBlue)).unwrap();
+        assert_eq!(unsafe { cell.Char.UnicodeChar }, '#' as u16);
+        assert_eq!(cell.Attributes, 0x1F);
+        assert!(char_info('😀', TextAttribute::default()).is_err());
+    }
+
+    #[test]
+    fn test_scroll_region() {
+        let Ok(console) = Console::current() else {
+            return;
+        };
+        // Fails when stdout is redirected rather than a console
+        let Ok(info) = console.screen_buffer_info() else {
+            return;
+        };
+        let bottom = info.size_y - 1;
+        console
+            .scroll(
+                (0, bottom, 9, bottom),
+                (0, bottom - 1),
+                Some((0, bottom - 1, 9, bottom)),
+                ' ',
+                TextAttribute::default(),
+            )
+            .unwrap();
+    }
+
     #[test]
     fn test_screen_buffer_info() {
         // This test only works if we have a console with valid handles
@@ -474,4 +909,84 @@ mod tests {
             let _ = Console::set_title(&original);
         }
     }
+
+    #[test]
+    fn test_write_to_streams() {
+        let Ok(console) = Console::current() else {
+            return;
+        };
+        // Standard handles may be missing entirely, e.g. in services
+        if output_type(StdHandle::Output) != OutputType::Unknown {
+            assert!(console.write_to(StdHandle::Output, "").is_ok());
+            assert!(console.write_line("console stdout test").is_ok());
+        }
+        if output_type(StdHandle::Error) != OutputType::Unknown {
+            assert!(console.write_err("").is_ok());
+            assert!(console.write_line_err("console stderr test").is_ok());
+        }
+        assert!(console.write_to(StdHandle::Input, "x").is_err());
+    }
+
+    #[test]
+    fn test_output_type_consistent() {
+        for handle in [StdHandle::Input, StdHandle::Output, StdHandle::Error] {
+            let kind = output_type(handle);
+            // Detection must be stable across calls
+            assert_eq!(output_type(handle), kind);
+            assert_eq!(is_terminal(handle), kind == OutputType::Console);
+        }
+    }
+
+    #[test]
+    fn test_ctrl_handler_dispatch() {
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        static BREAKS: AtomicU32 = AtomicU32::new(0);
+
+        set_ctrl_handler(|event| {
+            if event == CtrlEvent::CtrlBreak {
+                BREAKS.fetch_add(1, Ordering::SeqCst);
+                true
+            } else {
+                false
+            }
+        })
+        .unwrap();
+
+        // Generating a real Ctrl+Break would also reach the test runner sharing
+        // this console, so call the trampoline the way the system would
+        unsafe {
+            assert!(ctrl_trampoline(CTRL_BREAK_EVENT).as_bool());
+            assert!(!ctrl_trampoline(CTRL_C_EVENT).as_bool());
+            assert!(!ctrl_trampoline(0xFFFF).as_bool());
+        }
+        assert_eq!(BREAKS.load(Ordering::SeqCst), 1);
+
+        remove_ctrl_handler().unwrap();
+        assert!(!unsafe { ctrl_trampoline(CTRL_BREAK_EVENT) }.as_bool());
+    }
+
+    #[test]
+    fn test_input_mode_guard_restores_echo() {
+        let Ok(console) = Console::current() else {
+            return;
+        };
+        let mode = |input| {
+            let mut mode = CONSOLE_MODE(0);
+            unsafe { GetConsoleMode(input, &mut mode) }.map(|()| mode)
+        };
+        // Fails when stdin is redirected rather than a console
+        let Ok(original) = mode(console.input) else {
+            return;
+        };
+
+        {
+            let _guard = InputModeGuard::new(console.input, |mode| {
+                CONSOLE_MODE(mode.0 & !ENABLE_ECHO_INPUT.0)
+            })
+            .unwrap();
+            assert_eq!(mode(console.input).unwrap().0 & ENABLE_ECHO_INPUT.0, 0);
+        }
+        assert_eq!(mode(console.input).unwrap(), original);
+    }
 }
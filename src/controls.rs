@@ -7,18 +7,33 @@
 #![allow(clippy::too_many_arguments)] // Control constructors need many parameters
 
 use crate::error::{Error, Result};
-use crate::string::WideString;
-use windows::Win32::Foundation::{HINSTANCE, HWND, LPARAM, WPARAM};
-use windows::Win32::Graphics::Gdi::InvalidateRect;
+use crate::module::{Library, LoadFlags};
+use crate::string::{wide_len, WideString};
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, Ordering};
+use windows::core::PWSTR;
+use windows::Win32::Foundation::{COLORREF, HINSTANCE, HWND, LPARAM, POINT, RECT, WPARAM};
+use windows::Win32::Graphics::Gdi::{InvalidateRect, MapWindowPoints};
+use windows::Win32::UI::Controls::RichEdit::{
+    AURL_ENABLEURL, CFE_BOLD, CFE_EFFECTS, CFE_ITALIC, CFE_UNDERLINE, CFM_BACKCOLOR, CFM_BOLD,
+    CFM_COLOR, CFM_FACE, CFM_ITALIC, CFM_MASK, CFM_SIZE, CFM_UNDERLINE, CHARFORMAT2W, CHARRANGE,
+    EM_AUTOURLDETECT, EM_EXGETSEL, EM_EXSETSEL, EM_GETTEXTLENGTHEX, EM_SETCHARFORMAT,
+    GETTEXTLENGTHEX, GTL_NUMCHARS, GTL_PRECISE, MSFTEDIT_CLASS, SCF_SELECTION,
+};
 use windows::Win32::UI::Controls::{
-    InitCommonControlsEx, ICC_STANDARD_CLASSES, ICC_WIN95_CLASSES, INITCOMMONCONTROLSEX,
-    PBM_DELTAPOS, PBM_GETPOS, PBM_SETMARQUEE, PBM_SETPOS, PBM_SETRANGE32, PBM_SETSTEP, PBM_STEPIT,
-    PBS_MARQUEE, PBS_SMOOTH, PROGRESS_CLASSW,
+    InitCommonControlsEx, EM_GETCUEBANNER, EM_SETCUEBANNER, ICC_STANDARD_CLASSES, ICC_TAB_CLASSES,
+    ICC_UPDOWN_CLASS, ICC_WIN95_CLASSES, INITCOMMONCONTROLSEX, PBM_DELTAPOS, PBM_GETPOS,
+    PBM_SETMARQUEE, PBM_SETPOS, PBM_SETRANGE32, PBM_SETSTEP, PBM_STEPIT, PBS_MARQUEE, PBS_SMOOTH,
+    PROGRESS_CLASSW, TCIF_TEXT, TCITEMW, TCM_ADJUSTRECT, TCM_DELETEITEM, TCM_GETCURSEL,
+    TCM_GETITEMCOUNT, TCM_INSERTITEMW, TCM_SETCURSEL, UDM_GETPOS32, UDM_GETRANGE32, UDM_SETBUDDY,
+    UDM_SETPOS32, UDM_SETRANGE32, UDS_ALIGNRIGHT, UDS_ARROWKEYS, UDS_AUTOBUDDY, UDS_SETBUDDYINT,
+    UPDOWN_CLASSW, WC_TABCONTROLW,
 };
 use windows::Win32::UI::WindowsAndMessaging::{
-    CreateWindowExW, DestroyWindow, GetWindowLongPtrW, SendMessageW, SetWindowLongPtrW,
-    SetWindowTextW, ShowWindow, HMENU, SW_HIDE, SW_SHOW, WINDOW_EX_STYLE, WINDOW_STYLE, WM_GETTEXT,
-    WM_GETTEXTLENGTH, WS_BORDER, WS_CHILD, WS_DISABLED, WS_EX_CLIENTEDGE, WS_TABSTOP, WS_VISIBLE,
+    CreateWindowExW, DestroyWindow, GetClientRect, GetDlgCtrlID, GetParent, GetWindowLongPtrW,
+    PostMessageW, SendMessageW, SetWindowLongPtrW, SetWindowTextW, ShowWindow, HMENU, SW_HIDE,
+    SW_SHOW, WINDOW_EX_STYLE, WINDOW_STYLE, WM_GETTEXT, WM_GETTEXTLENGTH, WS_BORDER, WS_CHILD,
+    WS_CLIPSIBLINGS, WS_DISABLED, WS_EX_CLIENTEDGE, WS_TABSTOP, WS_VISIBLE, WS_VSCROLL,
 };
 
 // Button style constants (these are raw i32 values)
@@ -64,6 +79,11 @@ const LB_RESETCONTENT: u32 = 0x0184;
 const LB_GETCOUNT: u32 = 0x018B;
 const LB_GETCURSEL: u32 = 0x0188;
 const LB_SETCURSEL: u32 = 0x0186;
+const LB_GETTEXT: u32 = 0x0189;
+const LB_GETTEXTLEN: u32 = 0x018A;
+const LB_GETSELCOUNT: u32 = 0x0190;
+const LB_GETSELITEMS: u32 = 0x0191;
+const LB_ERR: isize = -1;
 
 // Combo box messages
 const CB_ADDSTRING: u32 = 0x0143;
@@ -71,6 +91,44 @@ const CB_RESETCONTENT: u32 = 0x014B;
 const CB_GETCOUNT: u32 = 0x0146;
 const CB_GETCURSEL: u32 = 0x0147;
 const CB_SETCURSEL: u32 = 0x014E;
+const CB_GETLBTEXT: u32 = 0x0148;
+const CB_GETLBTEXTLEN: u32 = 0x0149;
+
+/// Reads an item's text using a `*_GETTEXTLEN` / `*_GETTEXT` message pair.
+///
+/// The length message may overestimate (e.g. for DBCS text), so the result is
+/// truncated to the length actually copied.
+fn item_text(hwnd: HWND, len_msg: u32, text_msg: u32, index: i32) -> Option<String> {
+    if index < 0 {
+        return None;
+    }
+
+    // SAFETY: The length message has no pointer parameters
+    let len = unsafe { SendMessageW(hwnd, len_msg, WPARAM(index as usize), LPARAM(0)).0 };
+    // LB_ERR and CB_ERR are both -1
+    if len == LB_ERR {
+        return None;
+    }
+
+    let mut buffer = vec![0u16; len as usize + 1];
+    // SAFETY: buffer holds len characters plus the null terminator
+    let copied = unsafe {
+        SendMessageW(
+            hwnd,
+            text_msg,
+            WPARAM(index as usize),
+            LPARAM(buffer.as_mut_ptr() as isize),
+        )
+        .0
+    };
+    if copied == LB_ERR {
+        return None;
+    }
+
+    Some(String::from_utf16_lossy(
+        &buffer[..(copied as usize).min(len as usize)],
+    ))
+}
 
 /// Initialize common controls. Call this before creating any controls.
 ///
@@ -79,7 +137,7 @@ const CB_SETCURSEL: u32 = 0x014E;
 pub fn init_common_controls() -> Result<()> {
     let icc = INITCOMMONCONTROLSEX {
         dwSize: std::mem::size_of::<INITCOMMONCONTROLSEX>() as u32,
-        dwICC: ICC_STANDARD_CLASSES | ICC_WIN95_CLASSES,
+        dwICC: ICC_STANDARD_CLASSES | ICC_WIN95_CLASSES | ICC_UPDOWN_CLASS | ICC_TAB_CLASSES,
     };
 
     // SAFETY: InitCommonControlsEx is safe with valid parameters
@@ -216,6 +274,58 @@ impl Control {
             )
         }
     }
+
+    /// Sends a message to the control and returns its result.
+    ///
+    /// This is the escape hatch for control messages that have no dedicated
+    /// wrapper. The message is passed through unchecked, and a message the
+    /// control doesn't expect can leave it in an inconsistent state.
+    ///
+    /// # Safety
+    ///
+    /// `wparam` and `lparam` must be what `msg` expects. Messages that carry
+    /// pointers (`WM_GETTEXT`, `WM_SETTEXT`, most `*M_GET*` messages) need
+    /// them to point to valid buffers of the right size for the duration of
+    /// the call.
+    pub unsafe fn send_message(&self, msg: u32, wparam: usize, lparam: isize) -> isize {
+        SendMessageW(self.hwnd, msg, WPARAM(wparam), LPARAM(lparam)).0
+    }
+
+    /// Posts a message to the control's queue without waiting for it to be handled.
+    ///
+    /// # Safety
+    ///
+    /// The same requirements as [`send_message`](Self::send_message) apply,
+    /// except that any memory `wparam` or `lparam` points to must stay valid
+    /// until the message is processed, not just until this call returns.
+    pub unsafe fn post_message(&self, msg: u32, wparam: usize, lparam: isize) -> Result<()> {
+        PostMessageW(self.hwnd, msg, WPARAM(wparam), LPARAM(lparam))?;
+        Ok(())
+    }
+
+    /// Gets the control's identifier.
+    pub fn id(&self) -> u16 {
+        // SAFETY: GetDlgCtrlID is safe with valid HWND
+        unsafe { GetDlgCtrlID(self.hwnd) as u16 }
+    }
+
+    /// Changes the control's identifier.
+    pub fn set_id(&self, id: u16) {
+        // SAFETY: GWLP_ID manipulation is safe for child windows
+        unsafe {
+            SetWindowLongPtrW(
+                self.hwnd,
+                windows::Win32::UI::WindowsAndMessaging::GWLP_ID,
+                id as isize,
+            );
+        }
+    }
+
+    /// Gets the control's parent window, if it has one.
+    pub fn parent(&self) -> Option<HWND> {
+        // SAFETY: GetParent is safe with valid HWND
+        unsafe { GetParent(self.hwnd) }.ok()
+    }
 }
 
 impl Drop for Control {
@@ -480,6 +590,65 @@ impl Edit {
             );
         }
     }
+
+    /// Sets the gray placeholder text shown while the edit control is empty.
+    ///
+    /// With `show_when_focused` the banner stays visible after the control
+    /// gains focus, until the user types. The control keeps its own copy of
+    /// the text.
+    ///
+    /// Cue banners require visual styles (a comctl32 v6 manifest) and are not
+    /// supported by multiline edit controls.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Unsupported`] if the control rejects the banner.
+    pub fn set_cue_banner(control: &Control, text: &str, show_when_focused: bool) -> Result<()> {
+        let wide = WideString::new(text);
+        // SAFETY: wide is a valid null-terminated string for the duration of the call
+        let ok = unsafe {
+            SendMessageW(
+                control.hwnd(),
+                EM_SETCUEBANNER,
+                WPARAM(show_when_focused as usize),
+                LPARAM(wide.as_ptr() as isize),
+            )
+            .0
+        };
+        if ok == 0 {
+            return Err(Error::unsupported(
+                "cue banners require visual styles (comctl32 v6)",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Gets the placeholder text, or an empty string if none is set.
+    pub fn cue_banner(control: &Control) -> String {
+        let mut buffer = vec![0u16; 256];
+        loop {
+            // SAFETY: buffer is valid for buffer.len() characters
+            let ok = unsafe {
+                SendMessageW(
+                    control.hwnd(),
+                    EM_GETCUEBANNER,
+                    WPARAM(buffer.as_mut_ptr() as usize),
+                    LPARAM(buffer.len() as isize),
+                )
+                .0
+            };
+            if ok == 0 {
+                return String::new();
+            }
+
+            // The text is silently truncated, so retry while it fills the buffer
+            let len = wide_len(&buffer);
+            if len + 1 < buffer.len() || buffer.len() >= 0x1_0000 {
+                return String::from_utf16_lossy(&buffer[..len]);
+            }
+            buffer = vec![0u16; buffer.len() * 2];
+        }
+    }
 }
 
 /// A Windows static label control.
@@ -654,6 +823,113 @@ impl ProgressBar {
     }
 }
 
+/// A Windows up-down (spinner) control, usually attached to an edit control.
+///
+/// With `auto_buddy`, the control attaches itself to the sibling created just
+/// before it in z-order, positions itself against that sibling's right edge, and
+/// keeps its text in sync with the position. Create the edit control first and
+/// the up-down immediately after it for this to pick the right buddy; otherwise
+/// call [`UpDown::set_buddy`] explicitly.
+pub struct UpDown;
+
+impl UpDown {
+    /// Creates a new up-down control.
+    ///
+    /// The control sizes itself to its buddy, so no geometry is taken. If
+    /// `auto_buddy` is set, `UDS_AUTOBUDDY | UDS_SETBUDDYINT | UDS_ALIGNRIGHT` are used.
+    pub fn new(parent: HWND, id: u16, auto_buddy: bool) -> Result<Control> {
+        init_common_controls()?;
+
+        let mut win_style = WS_CHILD | WS_VISIBLE | WINDOW_STYLE(UDS_ARROWKEYS);
+        if auto_buddy {
+            win_style |= WINDOW_STYLE(UDS_AUTOBUDDY | UDS_SETBUDDYINT | UDS_ALIGNRIGHT);
+        }
+
+        // SAFETY: CreateWindowExW is safe with valid parameters
+        let hwnd = unsafe {
+            CreateWindowExW(
+                WINDOW_EX_STYLE::default(),
+                UPDOWN_CLASSW,
+                None,
+                win_style,
+                0,
+                0,
+                0,
+                0,
+                parent,
+                HMENU(id as isize as *mut _),
+                HINSTANCE::default(),
+                None,
+            )?
+        };
+
+        Ok(unsafe { Control::from_raw(hwnd, true) })
+    }
+
+    /// Attaches the up-down control to a buddy, typically an edit control.
+    pub fn set_buddy(control: &Control, buddy: &Control) {
+        // SAFETY: UDM_SETBUDDY takes the buddy window handle in wparam
+        unsafe {
+            SendMessageW(
+                control.hwnd(),
+                UDM_SETBUDDY,
+                WPARAM(buddy.hwnd().0 as usize),
+                LPARAM(0),
+            );
+        }
+    }
+
+    /// Sets the range of the control.
+    ///
+    /// `min` may be greater than `max`, in which case the up arrow decreases the value.
+    pub fn set_range(control: &Control, min: i32, max: i32) {
+        // SAFETY: UDM_SETRANGE32 is safe
+        unsafe {
+            SendMessageW(
+                control.hwnd(),
+                UDM_SETRANGE32,
+                WPARAM(min as usize),
+                LPARAM(max as isize),
+            );
+        }
+    }
+
+    /// Gets the range of the control as `(min, max)`.
+    pub fn range(control: &Control) -> (i32, i32) {
+        let mut min = 0i32;
+        let mut max = 0i32;
+        // SAFETY: UDM_GETRANGE32 writes to the two i32s passed by pointer
+        unsafe {
+            SendMessageW(
+                control.hwnd(),
+                UDM_GETRANGE32,
+                WPARAM(&mut min as *mut i32 as usize),
+                LPARAM(&mut max as *mut i32 as isize),
+            );
+        }
+        (min, max)
+    }
+
+    /// Sets the current position, clamped to the range.
+    pub fn set_pos(control: &Control, pos: i32) {
+        // SAFETY: UDM_SETPOS32 is safe
+        unsafe {
+            SendMessageW(
+                control.hwnd(),
+                UDM_SETPOS32,
+                WPARAM(0),
+                LPARAM(pos as isize),
+            );
+        }
+    }
+
+    /// Gets the current position.
+    pub fn pos(control: &Control) -> i32 {
+        // SAFETY: UDM_GETPOS32 with a null error pointer is safe
+        unsafe { SendMessageW(control.hwnd(), UDM_GETPOS32, WPARAM(0), LPARAM(0)).0 as i32 }
+    }
+}
+
 /// A Windows list box control.
 pub struct ListBox;
 
@@ -769,6 +1045,50 @@ impl ListBox {
         }
     }
 
+    /// Gets the text of the item at `index`, or `None` if the index is out of range.
+    ///
+    /// The buffer is sized with `LB_GETTEXTLEN`, so this only works for list boxes
+    /// that store strings (`LBS_HASSTRINGS`, which [`ListBox::new`] always sets).
+    pub fn item_text(control: &Control, index: i32) -> Option<String> {
+        item_text(control.hwnd(), LB_GETTEXTLEN, LB_GETTEXT, index)
+    }
+
+    /// Gets the text of the selected item, or `None` if nothing is selected.
+    ///
+    /// For multi-select list boxes, use [`ListBox::selected_indices`] instead.
+    pub fn selected_text(control: &Control) -> Option<String> {
+        Self::item_text(control, Self::selected_index(control))
+    }
+
+    /// Gets the indices of all selected items in a multi-select list box.
+    ///
+    /// Returns an empty vector for single-selection list boxes.
+    pub fn selected_indices(control: &Control) -> Vec<i32> {
+        // SAFETY: LB_GETSELCOUNT is safe
+        let count = unsafe { SendMessageW(control.hwnd(), LB_GETSELCOUNT, WPARAM(0), LPARAM(0)).0 };
+        if count == LB_ERR || count <= 0 {
+            return Vec::new();
+        }
+
+        let mut indices = vec![0i32; count as usize];
+        // SAFETY: indices has room for count entries
+        let copied = unsafe {
+            SendMessageW(
+                control.hwnd(),
+                LB_GETSELITEMS,
+                WPARAM(indices.len()),
+                LPARAM(indices.as_mut_ptr() as isize),
+            )
+            .0
+        };
+        if copied == LB_ERR {
+            return Vec::new();
+        }
+
+        indices.truncate(copied as usize);
+        indices
+    }
+
     /// Clears all items.
     pub fn clear(control: &Control) {
         // SAFETY: LB_RESETCONTENT is safe
@@ -866,6 +1186,19 @@ impl ComboBox {
         }
     }
 
+    /// Gets the text of the item at `index`, or `None` if the index is out of range.
+    ///
+    /// The buffer is sized with `CB_GETLBTEXTLEN`, so this only works for combo boxes
+    /// that store strings (`CBS_HASSTRINGS`, which [`ComboBox::new`] always sets).
+    pub fn item_text(control: &Control, index: i32) -> Option<String> {
+        item_text(control.hwnd(), CB_GETLBTEXTLEN, CB_GETLBTEXT, index)
+    }
+
+    /// Gets the text of the selected item, or `None` if nothing is selected.
+    pub fn selected_text(control: &Control) -> Option<String> {
+        Self::item_text(control, Self::selected_index(control))
+    }
+
     /// Clears all items.
     pub fn clear(control: &Control) {
         // SAFETY: CB_RESETCONTENT is safe
@@ -875,6 +1208,404 @@ impl ComboBox {
     }
 }
 
+/// A Windows tab control.
+///
+/// The control only draws the tabs; the pages are ordinary controls (often a
+/// panel per tab) that you create as siblings of the tab control, position
+/// in its [`display_rect`](TabControl::display_rect), and show or hide when
+/// the selection changes. The tab control reports that to its parent as a
+/// `WM_NOTIFY` with the `TCN_SELCHANGE` code:
+///
+/// ```ignore
+/// use windows::Win32::UI::Controls::{NMHDR, TCN_SELCHANGE};
+/// use windows::Win32::UI::WindowsAndMessaging::WM_NOTIFY;
+///
+/// fn handle_message(&mut self, msg: Message) -> Option<LRESULT> {
+///     if msg.msg == WM_NOTIFY {
+///         // SAFETY: WM_NOTIFY's lparam points to an NMHDR
+///         let header = unsafe { &*(msg.lparam.0 as *const NMHDR) };
+///         if header.hwndFrom == self.tabs.hwnd() && header.code == TCN_SELCHANGE {
+///             let selected = TabControl::selected_index(&self.tabs);
+///             for (i, page) in self.pages.iter().enumerate() {
+///                 if i as i32 == selected { page.show() } else { page.hide() }
+///             }
+///             return Some(LRESULT(0));
+///         }
+///     }
+///     None
+/// }
+/// ```
+///
+/// [`TabControl::set_selected_index`] does not send `TCN_SELCHANGE`, so update
+/// the pages yourself after calling it.
+pub struct TabControl;
+
+impl TabControl {
+    /// Creates a new tab control with no tabs.
+    pub fn new(parent: HWND, x: i32, y: i32, width: i32, height: i32, id: u16) -> Result<Control> {
+        init_common_controls()?;
+
+        // Pages are siblings, so clip them out of the tab control's painting
+        let win_style = WS_CHILD | WS_VISIBLE | WS_TABSTOP | WS_CLIPSIBLINGS;
+
+        // SAFETY: CreateWindowExW is safe with valid parameters
+        let hwnd = unsafe {
+            CreateWindowExW(
+                WINDOW_EX_STYLE::default(),
+                WC_TABCONTROLW,
+                None,
+                win_style,
+                x,
+                y,
+                width,
+                height,
+                parent,
+                HMENU(id as isize as *mut _),
+                HINSTANCE::default(),
+                None,
+            )?
+        };
+
+        Ok(unsafe { Control::from_raw(hwnd, true) })
+    }
+
+    /// Inserts a tab before `index` and returns the index it was given.
+    ///
+    /// An index past the end appends the tab. Returns -1 on failure.
+    pub fn add_tab(control: &Control, index: i32, label: &str) -> i32 {
+        let label = WideString::new(label);
+        let item = TCITEMW {
+            mask: TCIF_TEXT,
+            pszText: PWSTR(label.as_ptr() as *mut u16),
+            ..Default::default()
+        };
+        // SAFETY: TCM_INSERTITEMW copies the item and its text, which outlive the call
+        unsafe {
+            SendMessageW(
+                control.hwnd(),
+                TCM_INSERTITEMW,
+                WPARAM(index as usize),
+                LPARAM(&item as *const TCITEMW as isize),
+            )
+            .0 as i32
+        }
+    }
+
+    /// Removes the tab at `index`.
+    pub fn remove_tab(control: &Control, index: i32) {
+        // SAFETY: TCM_DELETEITEM is safe; an invalid index is ignored
+        unsafe {
+            SendMessageW(
+                control.hwnd(),
+                TCM_DELETEITEM,
+                WPARAM(index as usize),
+                LPARAM(0),
+            );
+        }
+    }
+
+    /// Gets the number of tabs.
+    pub fn count(control: &Control) -> i32 {
+        // SAFETY: TCM_GETITEMCOUNT is safe
+        unsafe { SendMessageW(control.hwnd(), TCM_GETITEMCOUNT, WPARAM(0), LPARAM(0)).0 as i32 }
+    }
+
+    /// Gets the index of the selected tab (-1 if none).
+    pub fn selected_index(control: &Control) -> i32 {
+        // SAFETY: TCM_GETCURSEL is safe
+        unsafe { SendMessageW(control.hwnd(), TCM_GETCURSEL, WPARAM(0), LPARAM(0)).0 as i32 }
+    }
+
+    /// Selects the tab at `index`.
+    pub fn set_selected_index(control: &Control, index: i32) {
+        // SAFETY: TCM_SETCURSEL is safe
+        unsafe {
+            SendMessageW(
+                control.hwnd(),
+                TCM_SETCURSEL,
+                WPARAM(index as usize),
+                LPARAM(0),
+            );
+        }
+    }
+
+    /// Gets the area below the tabs where page content goes.
+    ///
+    /// The rectangle is in the client coordinates of the tab control's parent,
+    /// ready to position sibling pages with. Call it again after resizing the
+    /// tab control or adding tabs, since the tabs can wrap to more rows.
+    pub fn display_rect(control: &Control) -> RECT {
+        let mut rect = RECT::default();
+        // SAFETY: rect is a valid output parameter for both calls and for
+        // TCM_ADJUSTRECT, which converts a window rect to its display area in place
+        unsafe {
+            let _ = GetClientRect(control.hwnd(), &mut rect);
+            SendMessageW(
+                control.hwnd(),
+                TCM_ADJUSTRECT,
+                WPARAM(0),
+                LPARAM(&mut rect as *mut RECT as isize),
+            );
+            if let Some(parent) = control.parent() {
+                let points =
+                    std::slice::from_raw_parts_mut(&mut rect as *mut RECT as *mut POINT, 2);
+                MapWindowPoints(control.hwnd(), parent, points);
+            }
+        }
+        rect
+    }
+}
+
+/// Character formatting for a range of text in a [`RichEdit`].
+///
+/// Only the fields that are set are applied; everything else in the range
+/// keeps its current formatting.
+#[derive(Debug, Clone, Default)]
+pub struct CharFormat {
+    /// Text color as `(red, green, blue)`.
+    pub color: Option<(u8, u8, u8)>,
+    /// Background (highlight) color as `(red, green, blue)`.
+    pub background: Option<(u8, u8, u8)>,
+    /// Bold on or off.
+    pub bold: Option<bool>,
+    /// Italic on or off.
+    pub italic: Option<bool>,
+    /// Single underline on or off.
+    pub underline: Option<bool>,
+    /// Font face name, at most 31 characters.
+    pub font: Option<String>,
+    /// Font size in points.
+    pub size: Option<f32>,
+}
+
+impl CharFormat {
+    fn to_native(&self) -> CHARFORMAT2W {
+        let mut format = CHARFORMAT2W::default();
+        format.Base.cbSize = std::mem::size_of::<CHARFORMAT2W>() as u32;
+        let base = &mut format.Base;
+
+        // dwMask selects which fields apply; each effect bit in dwEffects only
+        // counts if the matching CFM_ bit is set.
+        let mut effect = |mask: CFM_MASK, effect: CFE_EFFECTS, on: Option<bool>| {
+            if let Some(on) = on {
+                base.dwMask |= mask;
+                if on {
+                    base.dwEffects |= effect;
+                }
+            }
+        };
+        effect(CFM_BOLD, CFE_BOLD, self.bold);
+        effect(CFM_ITALIC, CFE_ITALIC, self.italic);
+        effect(CFM_UNDERLINE, CFE_UNDERLINE, self.underline);
+
+        if let Some((r, g, b)) = self.color {
+            base.dwMask |= CFM_COLOR;
+            base.crTextColor = rgb(r, g, b);
+        }
+        if let Some(font) = &self.font {
+            base.dwMask |= CFM_FACE;
+            for (dst, src) in base.szFaceName[..31].iter_mut().zip(font.encode_utf16()) {
+                *dst = src;
+            }
+        }
+        if let Some(points) = self.size {
+            base.dwMask |= CFM_SIZE;
+            base.yHeight = (points * 20.0).round() as i32; // twips
+        }
+        if let Some((r, g, b)) = self.background {
+            format.Base.dwMask |= CFM_BACKCOLOR;
+            format.crBackColor = rgb(r, g, b);
+        }
+        format
+    }
+}
+
+fn rgb(r: u8, g: u8, b: u8) -> COLORREF {
+    COLORREF(r as u32 | (g as u32) << 8 | (b as u32) << 16)
+}
+
+/// A rich edit control, for text with per-range colors and fonts.
+///
+/// Uses the RichEdit 4.1+ control from `Msftedit.dll`. The DLL must stay loaded
+/// for as long as any rich edit control exists, so it is loaded on the first
+/// [`RichEdit::new`] and kept loaded for the rest of the process.
+///
+/// Character positions count UTF-16 units, and every line break counts as one
+/// character: the control stores `\r\n` and `\n` as a single `\r`.
+///
+/// # Example
+///
+/// ```no_run
+/// # use windows::Win32::Foundation::HWND;
+/// # let parent = HWND::default();
+/// use ergonomic_windows::controls::{CharFormat, RichEdit};
+///
+/// let log = RichEdit::new(parent, 0, 0, 600, 400, 1)?;
+/// RichEdit::append_text(&log, "started\n");
+/// let range = RichEdit::append_text(&log, "error: disk full\n");
+/// let red = CharFormat { color: Some((200, 0, 0)), bold: Some(true), ..Default::default() };
+/// RichEdit::set_char_format(&log, range, &red)?;
+/// # Ok::<(), ergonomic_windows::error::Error>(())
+/// ```
+pub struct RichEdit;
+
+impl RichEdit {
+    /// Creates a new multiline rich edit control with a vertical scroll bar.
+    ///
+    /// Use [`Edit::set_readonly`] to make it read-only, e.g. for a log viewer.
+    pub fn new(parent: HWND, x: i32, y: i32, width: i32, height: i32, id: u16) -> Result<Control> {
+        load_msftedit()?;
+
+        let win_style = WS_CHILD
+            | WS_VISIBLE
+            | WS_TABSTOP
+            | WS_VSCROLL
+            | WINDOW_STYLE((ES_MULTILINE | ES_AUTOVSCROLL) as u32);
+
+        // SAFETY: CreateWindowExW is safe with valid parameters
+        let hwnd = unsafe {
+            CreateWindowExW(
+                WS_EX_CLIENTEDGE,
+                MSFTEDIT_CLASS,
+                None,
+                win_style,
+                x,
+                y,
+                width,
+                height,
+                parent,
+                HMENU(id as isize as *mut _),
+                HINSTANCE::default(),
+                None,
+            )?
+        };
+
+        Ok(unsafe { Control::from_raw(hwnd, true) })
+    }
+
+    /// Appends text at the end and returns the character range it now occupies.
+    ///
+    /// The selection is moved to the end of the text, which also scrolls it
+    /// into view.
+    pub fn append_text(control: &Control, text: &str) -> Range<u32> {
+        let start = Self::text_length(control);
+        // SAFETY: EM_SETSEL is safe
+        unsafe {
+            SendMessageW(
+                control.hwnd(),
+                EM_SETSEL,
+                WPARAM(start as usize),
+                LPARAM(start as isize),
+            );
+        }
+        Edit::replace_selection(control, text);
+        start..Self::text_length(control)
+    }
+
+    /// Gets the length of the text in characters.
+    pub fn text_length(control: &Control) -> u32 {
+        let query = GETTEXTLENGTHEX {
+            flags: GTL_NUMCHARS | GTL_PRECISE,
+            codepage: 1200, // UTF-16
+        };
+        // SAFETY: query is valid for the duration of the call
+        unsafe {
+            SendMessageW(
+                control.hwnd(),
+                EM_GETTEXTLENGTHEX,
+                WPARAM(&query as *const GETTEXTLENGTHEX as usize),
+                LPARAM(0),
+            )
+            .0 as u32
+        }
+    }
+
+    /// Applies `format` to the characters in `range`.
+    ///
+    /// The user's selection is restored afterwards.
+    pub fn set_char_format(
+        control: &Control,
+        range: Range<u32>,
+        format: &CharFormat,
+    ) -> Result<()> {
+        let native = format.to_native();
+        let mut previous = CHARRANGE::default();
+        let target = CHARRANGE {
+            cpMin: range.start as i32,
+            cpMax: range.end as i32,
+        };
+        // SAFETY: every pointer refers to a local that outlives its call
+        let ok = unsafe {
+            SendMessageW(
+                control.hwnd(),
+                EM_EXGETSEL,
+                WPARAM(0),
+                LPARAM(&mut previous as *mut CHARRANGE as isize),
+            );
+            SendMessageW(
+                control.hwnd(),
+                EM_EXSETSEL,
+                WPARAM(0),
+                LPARAM(&target as *const CHARRANGE as isize),
+            );
+            let ok = SendMessageW(
+                control.hwnd(),
+                EM_SETCHARFORMAT,
+                WPARAM(SCF_SELECTION as usize),
+                LPARAM(&native as *const CHARFORMAT2W as isize),
+            )
+            .0;
+            SendMessageW(
+                control.hwnd(),
+                EM_EXSETSEL,
+                WPARAM(0),
+                LPARAM(&previous as *const CHARRANGE as isize),
+            );
+            ok
+        };
+        if ok == 0 {
+            return Err(Error::custom("EM_SETCHARFORMAT rejected the format"));
+        }
+        Ok(())
+    }
+
+    /// Turns automatic link detection on or off.
+    ///
+    /// Detected URLs are underlined and colored; clicks on them are reported to
+    /// the parent as `EN_LINK` notifications once `ENM_LINK` is enabled with
+    /// `EM_SETEVENTMASK`.
+    pub fn set_auto_url_detect(control: &Control, enable: bool) -> Result<()> {
+        let flags = if enable { AURL_ENABLEURL } else { 0 };
+        // SAFETY: EM_AUTOURLDETECT is safe
+        let result = unsafe {
+            SendMessageW(
+                control.hwnd(),
+                EM_AUTOURLDETECT,
+                WPARAM(flags as usize),
+                LPARAM(0),
+            )
+            .0
+        };
+        if result != 0 {
+            return Err(Error::from_hresult(result as i32));
+        }
+        Ok(())
+    }
+}
+
+/// Loads `Msftedit.dll` once, keeping it loaded for the rest of the process.
+fn load_msftedit() -> Result<()> {
+    static LOADED: AtomicBool = AtomicBool::new(false);
+    if LOADED.load(Ordering::Acquire) {
+        return Ok(());
+    }
+    let library = Library::load_with_flags("msftedit.dll", LoadFlags::SEARCH_SYSTEM32)?;
+    // Controls of the class stop working if the DLL is unloaded.
+    std::mem::forget(library);
+    LOADED.store(true, Ordering::Release);
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -906,4 +1637,191 @@ mod tests {
         assert!(!style.password);
         assert_eq!(style.align, TextAlign::Left);
     }
+
+    /// Creates a hidden top-level window to parent test controls.
+    fn test_parent() -> Option<Control> {
+        let class = WideString::new("STATIC");
+        // SAFETY: CreateWindowExW is safe with valid parameters
+        let hwnd = unsafe {
+            CreateWindowExW(
+                WINDOW_EX_STYLE::default(),
+                class.as_pcwstr(),
+                None,
+                WINDOW_STYLE::default(),
+                0,
+                0,
+                200,
+                200,
+                None,
+                None,
+                HINSTANCE::default(),
+                None,
+            )
+        }
+        .ok()?;
+        Some(unsafe { Control::from_raw(hwnd, true) })
+    }
+
+    #[test]
+    fn test_list_box_item_text() {
+        // Window creation may fail in headless environments
+        let Some(parent) = test_parent() else {
+            return;
+        };
+        let Ok(list) = ListBox::new(parent.hwnd(), 0, 0, 100, 100, 1, true) else {
+            return;
+        };
+
+        ListBox::add_string(&list, "first");
+        ListBox::add_string(&list, "second");
+
+        assert_eq!(ListBox::item_text(&list, 0).as_deref(), Some("first"));
+        assert_eq!(ListBox::item_text(&list, 1).as_deref(), Some("second"));
+        assert_eq!(ListBox::item_text(&list, 2), None);
+        assert_eq!(ListBox::item_text(&list, -1), None);
+        assert!(ListBox::selected_indices(&list).is_empty());
+    }
+
+    #[test]
+    fn test_send_message() {
+        let Some(parent) = test_parent() else {
+            return;
+        };
+        let Ok(edit) = Edit::new(
+            parent.hwnd(),
+            "hello",
+            0,
+            0,
+            80,
+            20,
+            7,
+            EditStyle::default(),
+        ) else {
+            return;
+        };
+
+        // SAFETY: WM_GETTEXTLENGTH carries no pointers
+        let len = unsafe { edit.send_message(WM_GETTEXTLENGTH, 0, 0) };
+        assert_eq!(len as usize, edit.text().len());
+
+        assert_eq!(edit.id(), 7);
+        edit.set_id(8);
+        assert_eq!(edit.id(), 8);
+        assert_eq!(edit.parent(), Some(parent.hwnd()));
+    }
+
+    #[test]
+    fn test_cue_banner() {
+        let Some(parent) = test_parent() else {
+            return;
+        };
+        let Ok(edit) = Edit::new(parent.hwnd(), "", 0, 0, 80, 20, 1, EditStyle::default()) else {
+            return;
+        };
+
+        // Without a comctl32 v6 manifest the banner is rejected
+        if Edit::set_cue_banner(&edit, "Search...", true).is_err() {
+            return;
+        }
+        assert_eq!(Edit::cue_banner(&edit), "Search...");
+        assert_eq!(edit.text(), "");
+    }
+
+    #[test]
+    fn test_up_down_range_and_pos() {
+        let Some(parent) = test_parent() else {
+            return;
+        };
+        let Ok(edit) = Edit::new(parent.hwnd(), "0", 0, 0, 80, 20, 1, EditStyle::default()) else {
+            return;
+        };
+        let Ok(spinner) = UpDown::new(parent.hwnd(), 2, true) else {
+            return;
+        };
+        UpDown::set_buddy(&spinner, &edit);
+
+        UpDown::set_range(&spinner, -10, 500);
+        assert_eq!(UpDown::range(&spinner), (-10, 500));
+
+        UpDown::set_pos(&spinner, 42);
+        assert_eq!(UpDown::pos(&spinner), 42);
+        UpDown::set_pos(&spinner, 1000);
+        assert_eq!(UpDown::pos(&spinner), 500);
+    }
+
+    #[test]
+    fn test_combo_box_item_text() {
+        let Some(parent) = test_parent() else {
+            return;
+        };
+        let Ok(combo) = ComboBox::new(parent.hwnd(), 0, 0, 100, 100, 1, true) else {
+            return;
+        };
+
+        ComboBox::add_string(&combo, "alpha");
+        ComboBox::add_string(&combo, "beta");
+        assert_eq!(ComboBox::selected_text(&combo), None);
+
+        ComboBox::set_selected_index(&combo, 1);
+        assert_eq!(ComboBox::selected_text(&combo).as_deref(), Some("beta"));
+        assert_eq!(ComboBox::item_text(&combo, 0).as_deref(), Some("alpha"));
+        assert_eq!(ComboBox::item_text(&combo, 5), None);
+    }
+
+    #[test]
+    fn test_tab_control() {
+        let Some(parent) = test_parent() else {
+            return;
+        };
+        let Ok(tabs) = TabControl::new(parent.hwnd(), 10, 10, 180, 150, 1) else {
+            return;
+        };
+
+        assert_eq!(TabControl::add_tab(&tabs, 0, "General"), 0);
+        assert_eq!(TabControl::add_tab(&tabs, 1, "Advanced"), 1);
+        // Inserting at the front shifts the others
+        assert_eq!(TabControl::add_tab(&tabs, 0, "About"), 0);
+        assert_eq!(TabControl::count(&tabs), 3);
+
+        TabControl::set_selected_index(&tabs, 2);
+        assert_eq!(TabControl::selected_index(&tabs), 2);
+        TabControl::set_selected_index(&tabs, 1);
+        assert_eq!(TabControl::selected_index(&tabs), 1);
+
+        // The page area lies inside the control, below the tabs
+        let rect = TabControl::display_rect(&tabs);
+        assert!(rect.left >= 10 && rect.right <= 190);
+        assert!(rect.top > 10 && rect.bottom <= 160);
+
+        TabControl::remove_tab(&tabs, 0);
+        assert_eq!(TabControl::count(&tabs), 2);
+    }
+
+    #[test]
+    fn test_rich_edit() {
+        let Some(parent) = test_parent() else {
+            return;
+        };
+        let Ok(edit) = RichEdit::new(parent.hwnd(), 0, 0, 180, 180, 1) else {
+            return;
+        };
+
+        let first = RichEdit::append_text(&edit, "hello\n");
+        assert_eq!(first, 0..6);
+        let second = RichEdit::append_text(&edit, "world");
+        assert_eq!(second, 6..11);
+        assert_eq!(RichEdit::text_length(&edit), 11);
+
+        let format = CharFormat {
+            color: Some((255, 0, 0)),
+            background: Some((255, 255, 0)),
+            bold: Some(true),
+            italic: Some(false),
+            font: Some("Consolas".into()),
+            size: Some(10.5),
+            ..Default::default()
+        };
+        RichEdit::set_char_format(&edit, second, &format).unwrap();
+        RichEdit::set_auto_url_detect(&edit, true).unwrap();
+    }
 }
@@ -6,27 +6,74 @@
 #![allow(clippy::too_many_arguments)] // Drawing functions need many coordinate parameters
 
 use crate::error::Result;
+use crate::string::WideString;
+use std::path::Path;
+use windows::core::{Interface, GUID};
 use windows::Foundation::Numerics::Matrix3x2;
-use windows::Win32::Foundation::HWND;
+use windows::Win32::Foundation::{
+    CO_E_NOTINITIALIZED, D2DERR_RECREATE_TARGET, GENERIC_WRITE, HWND,
+};
 use windows::Win32::Graphics::Direct2D::Common::{
-    D2D1_ALPHA_MODE_PREMULTIPLIED, D2D1_COLOR_F, D2D1_PIXEL_FORMAT, D2D_POINT_2F, D2D_RECT_F,
-    D2D_SIZE_U,
+    D2D1_ALPHA_MODE_IGNORE, D2D1_ALPHA_MODE_PREMULTIPLIED, D2D1_COLOR_F, D2D1_PIXEL_FORMAT,
+    D2D_POINT_2F, D2D_RECT_F, D2D_SIZE_U,
 };
 use windows::Win32::Graphics::Direct2D::{
-    D2D1CreateFactory, ID2D1Factory, ID2D1HwndRenderTarget, ID2D1SolidColorBrush,
-    D2D1_BRUSH_PROPERTIES, D2D1_DRAW_TEXT_OPTIONS_NONE, D2D1_ELLIPSE, D2D1_FACTORY_OPTIONS,
-    D2D1_FACTORY_TYPE_SINGLE_THREADED, D2D1_HWND_RENDER_TARGET_PROPERTIES,
-    D2D1_PRESENT_OPTIONS_NONE, D2D1_RENDER_TARGET_PROPERTIES, D2D1_RENDER_TARGET_TYPE_DEFAULT,
-    D2D1_ROUNDED_RECT,
+    D2D1CreateDevice, D2D1CreateFactory, ID2D1Bitmap, ID2D1BitmapRenderTarget, ID2D1Device,
+    ID2D1DeviceContext, ID2D1Factory, ID2D1GdiInteropRenderTarget, ID2D1HwndRenderTarget,
+    ID2D1RenderTarget, ID2D1SolidColorBrush, D2D1_BITMAP_INTERPOLATION_MODE,
+    D2D1_BITMAP_INTERPOLATION_MODE_LINEAR, D2D1_BITMAP_INTERPOLATION_MODE_NEAREST_NEIGHBOR,
+    D2D1_BITMAP_OPTIONS_CANNOT_DRAW, D2D1_BITMAP_OPTIONS_CPU_READ, D2D1_BITMAP_OPTIONS_TARGET,
+    D2D1_BITMAP_PROPERTIES, D2D1_BITMAP_PROPERTIES1, D2D1_BRUSH_PROPERTIES,
+    D2D1_COMPATIBLE_RENDER_TARGET_OPTIONS_GDI_COMPATIBLE,
+    D2D1_COMPATIBLE_RENDER_TARGET_OPTIONS_NONE, D2D1_DC_INITIALIZE_MODE_COPY,
+    D2D1_DEVICE_CONTEXT_OPTIONS_NONE, D2D1_DRAW_TEXT_OPTIONS_NONE, D2D1_ELLIPSE,
+    D2D1_FACTORY_OPTIONS, D2D1_FACTORY_TYPE_SINGLE_THREADED, D2D1_HWND_RENDER_TARGET_PROPERTIES,
+    D2D1_MAP_OPTIONS_READ, D2D1_PRESENT_OPTIONS_NONE, D2D1_RENDER_TARGET_PROPERTIES,
+    D2D1_RENDER_TARGET_TYPE_DEFAULT, D2D1_RENDER_TARGET_USAGE,
+    D2D1_RENDER_TARGET_USAGE_GDI_COMPATIBLE, D2D1_RENDER_TARGET_USAGE_NONE, D2D1_ROUNDED_RECT,
+};
+use windows::Win32::Graphics::Direct3D::{
+    D3D_DRIVER_TYPE_HARDWARE, D3D_FEATURE_LEVEL, D3D_FEATURE_LEVEL_10_0, D3D_FEATURE_LEVEL_10_1,
+    D3D_FEATURE_LEVEL_11_0, D3D_FEATURE_LEVEL_11_1, D3D_FEATURE_LEVEL_9_1, D3D_FEATURE_LEVEL_9_2,
+    D3D_FEATURE_LEVEL_9_3,
+};
+use windows::Win32::Graphics::Direct3D11::{
+    D3D11CreateDevice, ID3D11Device, D3D11_CREATE_DEVICE_BGRA_SUPPORT, D3D11_SDK_VERSION,
 };
 use windows::Win32::Graphics::DirectWrite::{
-    DWriteCreateFactory, IDWriteFactory, IDWriteTextFormat, DWRITE_FACTORY_TYPE_SHARED,
-    DWRITE_FONT_STRETCH_NORMAL, DWRITE_FONT_STYLE_NORMAL, DWRITE_FONT_WEIGHT_NORMAL,
-    DWRITE_MEASURING_MODE_NATURAL, DWRITE_PARAGRAPH_ALIGNMENT_CENTER,
-    DWRITE_PARAGRAPH_ALIGNMENT_FAR, DWRITE_PARAGRAPH_ALIGNMENT_NEAR, DWRITE_TEXT_ALIGNMENT_CENTER,
-    DWRITE_TEXT_ALIGNMENT_JUSTIFIED, DWRITE_TEXT_ALIGNMENT_LEADING, DWRITE_TEXT_ALIGNMENT_TRAILING,
+    DWriteCreateFactory, IDWriteFactory, IDWriteTextFormat, IDWriteTextLayout,
+    DWRITE_FACTORY_TYPE_SHARED, DWRITE_FONT_STRETCH_NORMAL, DWRITE_FONT_STYLE_NORMAL,
+    DWRITE_FONT_WEIGHT, DWRITE_FONT_WEIGHT_BLACK, DWRITE_FONT_WEIGHT_BOLD,
+    DWRITE_FONT_WEIGHT_LIGHT, DWRITE_FONT_WEIGHT_MEDIUM, DWRITE_FONT_WEIGHT_NORMAL,
+    DWRITE_FONT_WEIGHT_SEMI_BOLD, DWRITE_FONT_WEIGHT_THIN, DWRITE_MEASURING_MODE_NATURAL,
+    DWRITE_PARAGRAPH_ALIGNMENT_CENTER, DWRITE_PARAGRAPH_ALIGNMENT_FAR,
+    DWRITE_PARAGRAPH_ALIGNMENT_NEAR, DWRITE_TEXT_ALIGNMENT_CENTER, DWRITE_TEXT_ALIGNMENT_JUSTIFIED,
+    DWRITE_TEXT_ALIGNMENT_LEADING, DWRITE_TEXT_ALIGNMENT_TRAILING, DWRITE_TEXT_METRICS,
+    DWRITE_TEXT_RANGE,
+};
+use windows::Win32::Graphics::Dxgi::Common::{
+    DXGI_FORMAT_B8G8R8A8_UNORM, DXGI_FORMAT_UNKNOWN, DXGI_SAMPLE_DESC,
+};
+use windows::Win32::Graphics::Dxgi::{
+    IDXGIDevice, IDXGIFactory2, IDXGISurface, IDXGISwapChain1, DXGI_ERROR_DEVICE_REMOVED,
+    DXGI_ERROR_DEVICE_RESET, DXGI_PRESENT, DXGI_SCALING_STRETCH, DXGI_SWAP_CHAIN_DESC1,
+    DXGI_SWAP_CHAIN_FLAG, DXGI_SWAP_EFFECT_FLIP_SEQUENTIAL, DXGI_USAGE_RENDER_TARGET_OUTPUT,
+};
+use windows::Win32::Graphics::Gdi::{
+    GetDC, GetDeviceCaps, InvalidateRect, ReleaseDC, HDC, LOGPIXELSX,
 };
-use windows::Win32::Graphics::Dxgi::Common::DXGI_FORMAT_B8G8R8A8_UNORM;
+use windows::Win32::Graphics::Imaging::{
+    CLSID_WICImagingFactory, GUID_ContainerFormatBmp, GUID_ContainerFormatJpeg,
+    GUID_ContainerFormatPng, GUID_WICPixelFormat24bppBGR, GUID_WICPixelFormat32bppBGRA,
+    GUID_WICPixelFormat32bppPBGRA, IWICBitmap, IWICImagingFactory, WICBitmapCacheOnLoad,
+    WICBitmapDitherTypeNone, WICBitmapEncoderNoCache, WICBitmapPaletteTypeCustom,
+};
+use windows::Win32::System::Com::{
+    CoCreateInstance, CoInitializeEx, CoUninitialize, CLSCTX_INPROC_SERVER, COINIT_MULTITHREADED,
+};
+
+/// Signature of `GetDpiForWindow`, resolved at runtime since it needs Windows 10 1607.
+type GetDpiForWindowFn = unsafe extern "system" fn(HWND) -> u32;
 
 /// A color with red, green, blue, and alpha components (0.0 - 1.0).
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -71,6 +118,91 @@ impl Color {
         )
     }
 
+    /// Converts the color to a hex value (0xRRGGBB), ignoring alpha.
+    ///
+    /// Components outside 0.0 - 1.0 are clamped.
+    pub fn to_hex(&self) -> u32 {
+        let (r, g, b, _) = self.to_rgba8();
+        ((r as u32) << 16) | ((g as u32) << 8) | b as u32
+    }
+
+    /// Converts the color to 8-bit RGBA values (0-255), clamping each component.
+    pub fn to_rgba8(&self) -> (u8, u8, u8, u8) {
+        fn channel(v: f32) -> u8 {
+            (v.clamp(0.0, 1.0) * 255.0).round() as u8
+        }
+        (
+            channel(self.r),
+            channel(self.g),
+            channel(self.b),
+            channel(self.a),
+        )
+    }
+
+    /// Creates an opaque color from hue (degrees), saturation and lightness (0.0 - 1.0).
+    pub fn from_hsl(h: f32, s: f32, l: f32) -> Self {
+        let h = h.rem_euclid(360.0) / 60.0;
+        let s = s.clamp(0.0, 1.0);
+        let l = l.clamp(0.0, 1.0);
+
+        let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+        let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+        let (r, g, b) = match h as u32 {
+            0 => (c, x, 0.0),
+            1 => (x, c, 0.0),
+            2 => (0.0, c, x),
+            3 => (0.0, x, c),
+            4 => (x, 0.0, c),
+            _ => (c, 0.0, x),
+        };
+        let m = l - c / 2.0;
+        Self::rgb(r + m, g + m, b + m)
+    }
+
+    /// Converts the color to hue (degrees, 0 - 360), saturation and lightness (0.0 - 1.0).
+    ///
+    /// Alpha is ignored. Grays report a hue and saturation of zero.
+    pub fn to_hsl(&self) -> (f32, f32, f32) {
+        let max = self.r.max(self.g).max(self.b);
+        let min = self.r.min(self.g).min(self.b);
+        let delta = max - min;
+        let l = (max + min) / 2.0;
+
+        if delta <= f32::EPSILON {
+            return (0.0, 0.0, l);
+        }
+
+        let s = delta / (1.0 - (2.0 * l - 1.0).abs());
+        let h = if max == self.r {
+            ((self.g - self.b) / delta).rem_euclid(6.0)
+        } else if max == self.g {
+            (self.b - self.r) / delta + 2.0
+        } else {
+            (self.r - self.g) / delta + 4.0
+        };
+        (h * 60.0, s, l)
+    }
+
+    /// Returns the same color with a different alpha.
+    pub const fn with_alpha(self, a: f32) -> Self {
+        Self { a, ..self }
+    }
+
+    /// Linearly interpolates between two colors, component-wise.
+    ///
+    /// `t` is clamped to 0.0 - 1.0; 0.0 returns `self` and 1.0 returns `other`.
+    pub fn lerp(self, other: Self, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        // Weighted form so both endpoints are exact in f32
+        let mix = |a: f32, b: f32| a * (1.0 - t) + b * t;
+        Self {
+            r: mix(self.r, other.r),
+            g: mix(self.g, other.g),
+            b: mix(self.b, other.b),
+            a: mix(self.a, other.a),
+        }
+    }
+
     // Common colors
     /// Black color.
     pub const BLACK: Self = Self::rgb(0.0, 0.0, 0.0);
@@ -138,6 +270,7 @@ pub enum ParagraphAlignment {
 /// The Direct2D factory - entry point for creating D2D resources.
 pub struct D2DFactory {
     factory: ID2D1Factory,
+    gdi_compatible: bool,
 }
 
 impl D2DFactory {
@@ -149,7 +282,28 @@ impl D2DFactory {
         let factory: ID2D1Factory =
             unsafe { D2D1CreateFactory(D2D1_FACTORY_TYPE_SINGLE_THREADED, Some(&options))? };
 
-        Ok(Self { factory })
+        Ok(Self {
+            factory,
+            gdi_compatible: false,
+        })
+    }
+
+    /// Makes render targets created from now on GDI-compatible.
+    ///
+    /// Only GDI-compatible targets support [`RenderTarget::gdi_interop`]. It is
+    /// off by default, since GDI compatibility limits the target to formats GDI
+    /// understands and can cost performance.
+    pub fn gdi_compatible(mut self, enabled: bool) -> Self {
+        self.gdi_compatible = enabled;
+        self
+    }
+
+    fn usage(&self) -> D2D1_RENDER_TARGET_USAGE {
+        if self.gdi_compatible {
+            D2D1_RENDER_TARGET_USAGE_GDI_COMPATIBLE
+        } else {
+            D2D1_RENDER_TARGET_USAGE_NONE
+        }
     }
 
     /// Creates a render target for a window.
@@ -174,6 +328,7 @@ impl D2DFactory {
             },
             dpiX: 0.0,
             dpiY: 0.0,
+            usage: self.usage(),
             ..Default::default()
         };
 
@@ -190,27 +345,509 @@ impl D2DFactory {
         };
 
         Ok(RenderTarget {
-            target: render_target,
+            target: render_target.clone().into(),
+            hwnd_target: Some(render_target),
+            bitmap: None,
+            gdi_compatible: self.gdi_compatible,
         })
     }
+
+    /// Creates an off-screen render target backed by a WIC bitmap.
+    ///
+    /// Useful for rendering without a window, e.g. to produce images or in tests.
+    ///
+    /// The target is backed by WIC, so COM must be initialized on the calling
+    /// thread (`CoInitializeEx`, any apartment) for as long as the target is in
+    /// use; otherwise this fails with `CO_E_NOTINITIALIZED`.
+    pub fn create_bitmap_render_target(&self, width: u32, height: u32) -> Result<RenderTarget> {
+        let wic = wic_factory()?;
+
+        // SAFETY: CreateBitmap is safe with a valid pixel format GUID
+        let bitmap = unsafe {
+            wic.CreateBitmap(
+                width,
+                height,
+                &GUID_WICPixelFormat32bppPBGRA,
+                WICBitmapCacheOnLoad,
+            )?
+        };
+
+        let render_target_properties = D2D1_RENDER_TARGET_PROPERTIES {
+            r#type: D2D1_RENDER_TARGET_TYPE_DEFAULT,
+            pixelFormat: D2D1_PIXEL_FORMAT {
+                format: DXGI_FORMAT_B8G8R8A8_UNORM,
+                alphaMode: D2D1_ALPHA_MODE_PREMULTIPLIED,
+            },
+            usage: self.usage(),
+            ..Default::default()
+        };
+
+        // SAFETY: CreateWicBitmapRenderTarget is safe with valid parameters
+        let target = unsafe {
+            self.factory
+                .CreateWicBitmapRenderTarget(&bitmap, &render_target_properties)?
+        };
+
+        Ok(RenderTarget {
+            target,
+            hwnd_target: None,
+            bitmap: Some(bitmap),
+            gdi_compatible: self.gdi_compatible,
+        })
+    }
+}
+
+/// Creates a WIC imaging factory. COM must already be initialized on the thread.
+fn wic_factory() -> Result<IWICImagingFactory> {
+    // SAFETY: CoCreateInstance is safe with a valid CLSID
+    unsafe { CoCreateInstance(&CLSID_WICImagingFactory, None, CLSCTX_INPROC_SERVER) }.map_err(|e| {
+        if e.code() == CO_E_NOTINITIALIZED {
+            crate::error::Error::custom(
+                "COM is not initialized on this thread; call CoInitializeEx first",
+            )
+        } else {
+            e.into()
+        }
+    })
+}
+
+/// Initializes COM on the calling thread for the duration of one operation.
+///
+/// Only for work whose COM objects are all released before the scope ends.
+/// Balances its own `CoInitializeEx` only, so a thread that had COM set up
+/// already, in either apartment, is left as it was.
+struct ComScope {
+    initialized: bool,
+}
+
+impl ComScope {
+    fn enter() -> Self {
+        // SAFETY: CoInitializeEx is safe to call repeatedly; it fails with
+        // RPC_E_CHANGED_MODE on a thread in another apartment, which is usable as is
+        let initialized = unsafe { CoInitializeEx(None, COINIT_MULTITHREADED) }.is_ok();
+        Self { initialized }
+    }
+}
+
+impl Drop for ComScope {
+    fn drop(&mut self) {
+        if self.initialized {
+            // SAFETY: balances the successful CoInitializeEx in enter
+            unsafe { CoUninitialize() };
+        }
+    }
+}
+
+/// An image file format for [`RenderTarget::save_image`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    /// Lossless PNG, with straight (non-premultiplied) alpha.
+    Png,
+    /// Lossy JPEG; alpha is discarded.
+    Jpeg,
+    /// Uncompressed BMP; alpha is discarded.
+    Bmp,
+}
+
+impl ImageFormat {
+    fn container(self) -> GUID {
+        match self {
+            Self::Png => GUID_ContainerFormatPng,
+            Self::Jpeg => GUID_ContainerFormatJpeg,
+            Self::Bmp => GUID_ContainerFormatBmp,
+        }
+    }
+
+    fn pixel_format(self) -> GUID {
+        match self {
+            Self::Png => GUID_WICPixelFormat32bppBGRA,
+            Self::Jpeg | Self::Bmp => GUID_WICPixelFormat24bppBGR,
+        }
+    }
+}
+
+/// Saves 32-bit straight-alpha BGRA pixels, row by row, as an image file.
+pub(crate) fn save_bgra_pixels(
+    width: u32,
+    height: u32,
+    pixels: &[u8],
+    path: &Path,
+    format: ImageFormat,
+) -> Result<()> {
+    let _com = ComScope::enter();
+    let wic = wic_factory()?;
+    // SAFETY: pixels holds `height` rows of `width * 4` bytes, copied by WIC
+    let bitmap = unsafe {
+        wic.CreateBitmapFromMemory(
+            width,
+            height,
+            &GUID_WICPixelFormat32bppBGRA,
+            width * 4,
+            pixels,
+        )?
+    };
+    encode_image(&wic, &bitmap, path, format)
+}
+
+/// Encodes a premultiplied BGRA WIC bitmap to an image file.
+fn encode_image(
+    wic: &IWICImagingFactory,
+    source: &IWICBitmap,
+    path: &Path,
+    format: ImageFormat,
+) -> Result<()> {
+    let mut pixel_format = format.pixel_format();
+    let path = WideString::from_path(path);
+
+    // SAFETY: All interfaces are valid and the path and GUIDs outlive the calls.
+    // The format converter un-premultiplies alpha when converting PBGRA to BGRA.
+    unsafe {
+        let converter = wic.CreateFormatConverter()?;
+        converter.Initialize(
+            source,
+            &pixel_format,
+            WICBitmapDitherTypeNone,
+            None,
+            0.0,
+            WICBitmapPaletteTypeCustom,
+        )?;
+
+        let stream = wic.CreateStream()?;
+        stream.InitializeFromFilename(path.as_pcwstr(), GENERIC_WRITE.0)?;
+
+        let encoder = wic.CreateEncoder(&format.container(), std::ptr::null())?;
+        encoder.Initialize(&stream, WICBitmapEncoderNoCache)?;
+
+        let mut frame = None;
+        encoder.CreateNewFrame(&mut frame, std::ptr::null_mut())?;
+        let frame =
+            frame.ok_or_else(|| crate::error::Error::custom("WIC encoder returned no frame"))?;
+        frame.Initialize(None)?;
+
+        let mut width = 0;
+        let mut height = 0;
+        source.GetSize(&mut width, &mut height)?;
+        frame.SetSize(width, height)?;
+        frame.SetPixelFormat(&mut pixel_format)?;
+        frame.WriteSource(&converter, std::ptr::null())?;
+        frame.Commit()?;
+        encoder.Commit()?;
+    }
+    Ok(())
+}
+
+/// A Direct2D device on top of a Direct3D 11 device.
+///
+/// Unlike [`D2DFactory`], which hides the GPU behind window render targets, a
+/// `Device` exposes the whole stack: a D3D11 device created with BGRA support,
+/// the DXGI device and adapter behind it, and the Direct2D device built from
+/// those with `D2D1CreateDevice`. Targets created from it render into DXGI
+/// surfaces, so Direct2D output can share a swap chain with Direct3D content.
+///
+/// Requires Direct2D 1.1 (Windows 8 or later) and a hardware Direct3D driver.
+pub struct Device {
+    d3d_device: ID3D11Device,
+    d2d_device: ID2D1Device,
+    feature_level: D3D_FEATURE_LEVEL,
+}
+
+impl Device {
+    /// Creates a hardware Direct3D 11 device and a Direct2D device on top of it.
+    ///
+    /// Feature levels 11.1 down to 9.1 are requested, highest first; the one
+    /// obtained is reported by [`feature_level`](Self::feature_level). Fails
+    /// if no hardware driver is available.
+    pub fn new() -> Result<Self> {
+        let feature_levels = [
+            D3D_FEATURE_LEVEL_11_1,
+            D3D_FEATURE_LEVEL_11_0,
+            D3D_FEATURE_LEVEL_10_1,
+            D3D_FEATURE_LEVEL_10_0,
+            D3D_FEATURE_LEVEL_9_3,
+            D3D_FEATURE_LEVEL_9_2,
+            D3D_FEATURE_LEVEL_9_1,
+        ];
+        let mut d3d_device = None;
+        let mut feature_level = D3D_FEATURE_LEVEL::default();
+
+        // SAFETY: The output pointers are valid for the duration of the call.
+        // Direct2D needs BGRA support to draw into the device's surfaces.
+        unsafe {
+            D3D11CreateDevice(
+                None,
+                D3D_DRIVER_TYPE_HARDWARE,
+                None,
+                D3D11_CREATE_DEVICE_BGRA_SUPPORT,
+                Some(&feature_levels),
+                D3D11_SDK_VERSION,
+                Some(&mut d3d_device),
+                Some(&mut feature_level),
+                None,
+            )?;
+        }
+        let d3d_device = d3d_device
+            .ok_or_else(|| crate::error::Error::custom("D3D11CreateDevice returned no device"))?;
+
+        let dxgi_device: IDXGIDevice = d3d_device.cast()?;
+        // SAFETY: D2D1CreateDevice is safe with a valid DXGI device
+        let d2d_device = unsafe { D2D1CreateDevice(&dxgi_device, None)? };
+
+        Ok(Self {
+            d3d_device,
+            d2d_device,
+            feature_level,
+        })
+    }
+
+    /// Gets the Direct3D feature level of the device, e.g. `D3D_FEATURE_LEVEL_11_0`.
+    pub fn feature_level(&self) -> D3D_FEATURE_LEVEL {
+        self.feature_level
+    }
+
+    /// Gets the underlying Direct3D 11 device, for rendering D3D content.
+    pub fn d3d_device(&self) -> &ID3D11Device {
+        &self.d3d_device
+    }
+
+    /// Creates a flip-model swap chain for a window and a render target drawing into it.
+    ///
+    /// `size` is in pixels. The swap chain has two BGRA buffers and uses
+    /// `DXGI_SWAP_EFFECT_FLIP_SEQUENTIAL`, so frames are shown tear-free by
+    /// [`SwapChainTarget::present`].
+    pub fn create_swap_chain_target(
+        &self,
+        hwnd: HWND,
+        size: (u32, u32),
+    ) -> Result<SwapChainTarget> {
+        let dxgi_device: IDXGIDevice = self.d3d_device.cast()?;
+
+        // SAFETY: The adapter's parent is the DXGI factory that created it, and
+        // the swap chain description outlives the call.
+        let swap_chain = unsafe {
+            let adapter = dxgi_device.GetAdapter()?;
+            let factory: IDXGIFactory2 = adapter.GetParent()?;
+
+            let desc = DXGI_SWAP_CHAIN_DESC1 {
+                Width: size.0,
+                Height: size.1,
+                Format: DXGI_FORMAT_B8G8R8A8_UNORM,
+                SampleDesc: DXGI_SAMPLE_DESC {
+                    Count: 1,
+                    Quality: 0,
+                },
+                BufferUsage: DXGI_USAGE_RENDER_TARGET_OUTPUT,
+                BufferCount: 2,
+                Scaling: DXGI_SCALING_STRETCH,
+                SwapEffect: DXGI_SWAP_EFFECT_FLIP_SEQUENTIAL,
+                ..Default::default()
+            };
+            factory.CreateSwapChainForHwnd(&self.d3d_device, hwnd, &desc, None, None)?
+        };
+
+        // SAFETY: CreateDeviceContext is safe
+        let context = unsafe {
+            self.d2d_device
+                .CreateDeviceContext(D2D1_DEVICE_CONTEXT_OPTIONS_NONE)?
+        };
+
+        let target = SwapChainTarget {
+            target: RenderTarget {
+                target: context.clone().into(),
+                hwnd_target: None,
+                bitmap: None,
+                gdi_compatible: false,
+            },
+            context,
+            swap_chain,
+        };
+        target.bind_back_buffer()?;
+        Ok(target)
+    }
+}
+
+/// A render target drawing into a DXGI swap chain, created by
+/// [`Device::create_swap_chain_target`].
+///
+/// Draw with the usual [`RenderTarget`] operations, then call
+/// [`present`](Self::present) to show the frame.
+pub struct SwapChainTarget {
+    target: RenderTarget,
+    context: ID2D1DeviceContext,
+    swap_chain: IDXGISwapChain1,
+}
+
+impl SwapChainTarget {
+    /// Gets the render target to draw into.
+    ///
+    /// The swap chain isn't GDI-compatible, so [`RenderTarget::gdi_interop`]
+    /// returns an error on it.
+    pub fn render_target(&self) -> &RenderTarget {
+        &self.target
+    }
+
+    /// Presents the drawn frame.
+    ///
+    /// `sync_interval` is the number of vertical blanks to wait for: 0 presents
+    /// immediately, 1 synchronizes with the display. Returns
+    /// [`DrawOutcome::NeedsRecreate`] if the device was removed or reset, in
+    /// which case the [`Device`] and everything created from it must be rebuilt.
+    pub fn present(&self, sync_interval: u32) -> Result<DrawOutcome> {
+        // SAFETY: Present is safe on a valid swap chain
+        let hr = unsafe { self.swap_chain.Present(sync_interval, DXGI_PRESENT(0)) };
+        if hr == DXGI_ERROR_DEVICE_REMOVED || hr == DXGI_ERROR_DEVICE_RESET {
+            return Ok(DrawOutcome::NeedsRecreate);
+        }
+        hr.ok()?;
+        Ok(DrawOutcome::Ok)
+    }
+
+    /// Resizes the swap chain buffers, e.g. after the window is resized.
+    ///
+    /// Must not be called between `begin_draw` and `end_draw`.
+    pub fn resize(&self, width: u32, height: u32) -> Result<()> {
+        // SAFETY: The device context's reference to the old back buffer is
+        // released before ResizeBuffers, which fails while buffers are in use.
+        unsafe {
+            self.context.SetTarget(None);
+            self.swap_chain.ResizeBuffers(
+                0,
+                width,
+                height,
+                DXGI_FORMAT_UNKNOWN,
+                DXGI_SWAP_CHAIN_FLAG(0),
+            )?;
+        }
+        self.bind_back_buffer()
+    }
+
+    /// Gets the underlying swap chain, for presenting Direct3D content.
+    pub fn swap_chain(&self) -> &IDXGISwapChain1 {
+        &self.swap_chain
+    }
+
+    /// Makes the swap chain's back buffer the device context's target.
+    fn bind_back_buffer(&self) -> Result<()> {
+        let props = D2D1_BITMAP_PROPERTIES1 {
+            pixelFormat: D2D1_PIXEL_FORMAT {
+                format: DXGI_FORMAT_B8G8R8A8_UNORM,
+                alphaMode: D2D1_ALPHA_MODE_IGNORE,
+            },
+            dpiX: 96.0,
+            dpiY: 96.0,
+            bitmapOptions: D2D1_BITMAP_OPTIONS_TARGET | D2D1_BITMAP_OPTIONS_CANNOT_DRAW,
+            ..Default::default()
+        };
+
+        // SAFETY: Buffer 0 of a flip-model swap chain is its back buffer, and
+        // the bitmap properties match its format.
+        unsafe {
+            let surface: IDXGISurface = self.swap_chain.GetBuffer(0)?;
+            let bitmap = self
+                .context
+                .CreateBitmapFromDxgiSurface(&surface, Some(&props))?;
+            self.context.SetTarget(&bitmap);
+        }
+        Ok(())
+    }
+}
+
+/// The result of a [`RenderTarget::draw`] pass.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrawOutcome {
+    /// The frame was drawn successfully.
+    Ok,
+    /// The device was lost (e.g. GPU reset or display change). The render target
+    /// and all resources created from it must be recreated before drawing again.
+    NeedsRecreate,
 }
 
 /// A Direct2D render target for drawing.
 pub struct RenderTarget {
-    target: ID2D1HwndRenderTarget,
+    target: ID2D1RenderTarget,
+    hwnd_target: Option<ID2D1HwndRenderTarget>,
+    #[allow(dead_code)] // Keeps the backing bitmap of off-screen targets alive
+    bitmap: Option<IWICBitmap>,
+    gdi_compatible: bool,
 }
 
 impl RenderTarget {
     /// Resizes the render target to match the window size.
+    ///
+    /// Only window render targets can be resized.
     pub fn resize(&self, width: u32, height: u32) -> Result<()> {
+        let target = self.hwnd_target.as_ref().ok_or_else(|| {
+            crate::error::Error::custom("Only window render targets can be resized")
+        })?;
+
         let size = D2D_SIZE_U { width, height };
         // SAFETY: Resize is safe
         unsafe {
-            self.target.Resize(&size)?;
+            target.Resize(&size)?;
         }
         Ok(())
     }
 
+    /// Runs a drawing pass between `BeginDraw` and `EndDraw`.
+    ///
+    /// `EndDraw` is always called, even if `f` fails. If Direct2D reports
+    /// `D2DERR_RECREATE_TARGET`, this returns [`DrawOutcome::NeedsRecreate`] and the
+    /// caller must rebuild the render target and its resources; that outcome
+    /// wins over an error from `f`.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// if target.draw(|rt| {
+    ///     rt.clear(Color::WHITE);
+    ///     Ok(())
+    /// })? == DrawOutcome::NeedsRecreate {
+    ///     target = factory.create_hwnd_render_target(hwnd)?;
+    /// }
+    /// ```
+    pub fn draw(&self, f: impl FnOnce(&RenderTarget) -> Result<()>) -> Result<DrawOutcome> {
+        self.begin_draw();
+        let result = f(self);
+
+        // SAFETY: EndDraw is safe and balances the BeginDraw above
+        let end = unsafe { self.target.EndDraw(None, None) };
+        // A lost device takes precedence: drawing calls may have failed because
+        // of it, and the caller has to rebuild either way.
+        if matches!(&end, Err(e) if e.code() == D2DERR_RECREATE_TARGET) {
+            return Ok(DrawOutcome::NeedsRecreate);
+        }
+        result?;
+        end?;
+        Ok(DrawOutcome::Ok)
+    }
+
+    /// Gets a GDI device context for the render target, to mix GDI and Direct2D drawing.
+    ///
+    /// Must be called between `begin_draw` and `end_draw`, and the scope must be
+    /// dropped before any further Direct2D drawing or `end_draw`. The DC is obtained
+    /// with `D2D1_DC_INITIALIZE_MODE_COPY`, so it starts with the Direct2D content
+    /// drawn so far and GDI output is composited on top of it (the alternative,
+    /// `D2D1_DC_INITIALIZE_MODE_CLEAR`, would discard that content).
+    ///
+    /// Only targets created by a factory with
+    /// [`gdi_compatible`](D2DFactory::gdi_compatible) enabled, and compatible
+    /// targets made from them, support this; on others, including swap chain
+    /// targets, it returns an error.
+    pub fn gdi_interop(&self) -> Result<GdiInteropScope<'_>> {
+        if !self.gdi_compatible {
+            return Err(crate::error::Error::unsupported(
+                "GDI interop needs a GDI-compatible render target; see D2DFactory::gdi_compatible",
+            ));
+        }
+        let interop: ID2D1GdiInteropRenderTarget = self.target.cast()?;
+        // SAFETY: GetDC is safe on a GDI-compatible target inside BeginDraw/EndDraw
+        let hdc = unsafe { interop.GetDC(D2D1_DC_INITIALIZE_MODE_COPY)? };
+        Ok(GdiInteropScope {
+            interop,
+            hdc,
+            _target: std::marker::PhantomData,
+        })
+    }
+
     /// Begins drawing operations.
     pub fn begin_draw(&self) {
         // SAFETY: BeginDraw is safe
@@ -451,12 +1088,505 @@ impl RenderTarget {
         }
     }
 
+    /// Draws a text layout with its top-left corner at `(x, y)`.
+    ///
+    /// Text without a per-range brush (see [`TextLayout::set_drawing_effect`])
+    /// is drawn with `brush`. Colored runs only render through this method;
+    /// [`draw_text`](Self::draw_text) builds its own layout and has no ranges.
+    pub fn draw_text_layout(&self, x: f32, y: f32, layout: &TextLayout, brush: &SolidBrush) {
+        // SAFETY: DrawTextLayout is safe
+        unsafe {
+            self.target.DrawTextLayout(
+                D2D_POINT_2F { x, y },
+                &layout.layout,
+                &brush.brush,
+                D2D1_DRAW_TEXT_OPTIONS_NONE,
+            );
+        }
+    }
+
     /// Gets the size of the render target.
     pub fn size(&self) -> (f32, f32) {
         // SAFETY: GetSize is safe
         let size = unsafe { self.target.GetSize() };
         (size.width, size.height)
     }
+
+    /// Gets the target's DPI as `(x, y)`.
+    ///
+    /// Direct2D measures every drawing coordinate in device-independent
+    /// pixels (DIPs), where one DIP is 1/96 inch. At 96 DPI one DIP is one
+    /// physical pixel; at 192 DPI (200% scaling) it is two.
+    pub fn dpi(&self) -> (f32, f32) {
+        let (mut x, mut y) = (0.0, 0.0);
+        // SAFETY: GetDpi writes to the two valid out-pointers
+        unsafe { self.target.GetDpi(&mut x, &mut y) };
+        (x, y)
+    }
+
+    /// Sets the target's DPI, changing how DIPs map to physical pixels.
+    ///
+    /// Passing `0.0` for both restores the system default.
+    pub fn set_dpi(&self, dpi_x: f32, dpi_y: f32) {
+        // SAFETY: SetDpi is safe with any values; D2D ignores invalid ones
+        unsafe { self.target.SetDpi(dpi_x, dpi_y) };
+    }
+
+    /// Applies the DPI of the monitor `hwnd` is on (`GetDpiForWindow`).
+    ///
+    /// Call this after creating a window target and again on `WM_DPICHANGED`
+    /// so coordinates passed to drawing calls stay in step with the window's
+    /// scale factor. Before Windows 10 1607, which lacks `GetDpiForWindow`,
+    /// this falls back to the system DPI from the window's device context.
+    pub fn set_dpi_from_window(&self, hwnd: HWND) -> Result<()> {
+        // GetDC(null) would return the screen DC on the fallback path
+        if hwnd.is_invalid() {
+            return Err(crate::error::Error::invalid_handle(
+                "set_dpi_from_window needs a window, not a null HWND",
+            ));
+        }
+        let dpi = match crate::resolve!("user32.dll", "GetDpiForWindow", GetDpiForWindowFn) {
+            // SAFETY: GetDpiForWindow returns 0 for an invalid window
+            Ok(get_dpi) => unsafe { get_dpi(hwnd) },
+            Err(_) => {
+                // SAFETY: GetDC returns null for an invalid window; a DC it
+                // returns is released below
+                unsafe {
+                    let dc = GetDC(hwnd);
+                    if dc.is_invalid() {
+                        0
+                    } else {
+                        let dpi = GetDeviceCaps(dc, LOGPIXELSX);
+                        ReleaseDC(hwnd, dc);
+                        dpi.max(0) as u32
+                    }
+                }
+            }
+        };
+        if dpi == 0 {
+            return Err(crate::error::Error::custom(
+                "Failed to read the window's DPI: invalid window",
+            ));
+        }
+        self.set_dpi(dpi as f32, dpi as f32);
+        Ok(())
+    }
+
+    /// Converts DIPs to physical pixels using the target's horizontal DPI.
+    #[inline]
+    pub fn dip_to_px(&self, dips: f32) -> f32 {
+        dips * self.dpi().0 / 96.0
+    }
+
+    /// Converts physical pixels to DIPs using the target's horizontal DPI.
+    #[inline]
+    pub fn px_to_dip(&self, px: f32) -> f32 {
+        px * 96.0 / self.dpi().0
+    }
+
+    /// Saves the render target's contents to an image file.
+    ///
+    /// Only off-screen targets from [`D2DFactory::create_bitmap_render_target`]
+    /// can be saved this way; call it after `end_draw`. A window render target
+    /// can't be read back, so to capture what you draw to a window, render the
+    /// frame into a target from [`RenderTarget::create_compatible`] instead,
+    /// save that, and draw its bitmap to the window.
+    pub fn save_image(&self, path: &Path, format: ImageFormat) -> Result<()> {
+        let bitmap = self.bitmap.as_ref().ok_or_else(|| {
+            crate::error::Error::custom(
+                "Window render targets can't be saved; render into create_compatible() instead",
+            )
+        })?;
+        encode_image(&wic_factory()?, bitmap, path, format)
+    }
+
+    /// Creates an off-screen render target compatible with this one.
+    ///
+    /// `size` is in pixels. The new target shares this target's device, so its
+    /// contents can be drawn back here with [`BitmapRenderTarget::bitmap`] and
+    /// saved with [`BitmapRenderTarget::save_image`].
+    pub fn create_compatible(&self, size: (u32, u32)) -> Result<BitmapRenderTarget> {
+        let pixel_size = D2D_SIZE_U {
+            width: size.0,
+            height: size.1,
+        };
+
+        // SAFETY: The size outlives the call
+        let bitmap_target = unsafe {
+            self.target.CreateCompatibleRenderTarget(
+                None,
+                Some(&pixel_size),
+                None,
+                if self.gdi_compatible {
+                    D2D1_COMPATIBLE_RENDER_TARGET_OPTIONS_GDI_COMPATIBLE
+                } else {
+                    D2D1_COMPATIBLE_RENDER_TARGET_OPTIONS_NONE
+                },
+            )?
+        };
+
+        Ok(BitmapRenderTarget {
+            target: RenderTarget {
+                target: bitmap_target.clone().into(),
+                hwnd_target: None,
+                bitmap: None,
+                gdi_compatible: self.gdi_compatible,
+            },
+            bitmap_target,
+        })
+    }
+
+    /// Creates a bitmap from 32-bit premultiplied BGRA pixels, row by row.
+    ///
+    /// Like brushes, bitmaps belong to the render target that created them.
+    pub fn create_bitmap(&self, width: u32, height: u32, pixels: &[u8]) -> Result<D2DBitmap> {
+        let needed = width as usize * height as usize * 4;
+        if pixels.len() != needed {
+            return Err(crate::error::Error::buffer_too_small(needed, pixels.len()));
+        }
+
+        let props = D2D1_BITMAP_PROPERTIES {
+            pixelFormat: D2D1_PIXEL_FORMAT {
+                format: DXGI_FORMAT_B8G8R8A8_UNORM,
+                alphaMode: D2D1_ALPHA_MODE_PREMULTIPLIED,
+            },
+            dpiX: 96.0,
+            dpiY: 96.0,
+        };
+
+        // SAFETY: pixels holds height rows of width * 4 bytes
+        let bitmap = unsafe {
+            self.target.CreateBitmap(
+                D2D_SIZE_U { width, height },
+                Some(pixels.as_ptr() as *const _),
+                width * 4,
+                &props,
+            )?
+        };
+
+        Ok(D2DBitmap { bitmap })
+    }
+
+    /// Draws the `src` region of a bitmap into the `dest` rectangle.
+    ///
+    /// Both rectangles are `(x, y, width, height)`; `src` is in bitmap pixels.
+    /// This is the primitive for sprite atlases and cropping. Use
+    /// [`InterpolationMode::NearestNeighbor`] to keep pixel art sharp when scaling.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `src` doesn't lie within the bitmap.
+    pub fn draw_bitmap_region(
+        &self,
+        bitmap: &D2DBitmap,
+        dest: (f32, f32, f32, f32),
+        src: (f32, f32, f32, f32),
+        opacity: f32,
+        interpolation: InterpolationMode,
+    ) -> Result<()> {
+        let (width, height) = bitmap.size();
+        let (x, y, w, h) = src;
+        if x < 0.0 || y < 0.0 || w < 0.0 || h < 0.0 || x + w > width as f32 || y + h > height as f32
+        {
+            return Err(crate::error::Error::custom(format!(
+                "Source rectangle {:?} is outside the {}x{} bitmap",
+                src, width, height
+            )));
+        }
+
+        let to_rect = |(x, y, w, h): (f32, f32, f32, f32)| D2D_RECT_F {
+            left: x,
+            top: y,
+            right: x + w,
+            bottom: y + h,
+        };
+        let dest = to_rect(dest);
+        let src = to_rect(src);
+
+        // SAFETY: The rectangles live until the call returns
+        unsafe {
+            self.target.DrawBitmap(
+                &bitmap.bitmap,
+                Some(&dest),
+                opacity,
+                interpolation.to_native(),
+                Some(&src),
+            );
+        }
+        Ok(())
+    }
+}
+
+/// An off-screen render target created by [`RenderTarget::create_compatible`].
+pub struct BitmapRenderTarget {
+    target: RenderTarget,
+    bitmap_target: ID2D1BitmapRenderTarget,
+}
+
+impl BitmapRenderTarget {
+    /// Gets the render target to draw into.
+    pub fn render_target(&self) -> &RenderTarget {
+        &self.target
+    }
+
+    /// Gets the bitmap holding this target's contents.
+    ///
+    /// The bitmap can be drawn on the render target this one was created from.
+    pub fn bitmap(&self) -> Result<D2DBitmap> {
+        // SAFETY: GetBitmap is safe
+        let bitmap = unsafe { self.bitmap_target.GetBitmap()? };
+        Ok(D2DBitmap { bitmap })
+    }
+
+    /// Saves the target's contents to an image file.
+    ///
+    /// Call this after `end_draw`. Reading the pixels back requires Direct2D 1.1
+    /// (Windows 8 or later). Encoding uses WIC; COM is initialized for the call
+    /// if the thread hasn't done so and released again afterwards.
+    pub fn save_image(&self, path: &Path, format: ImageFormat) -> Result<()> {
+        let context: ID2D1DeviceContext = self.target.target.cast()?;
+        // SAFETY: GetBitmap is safe
+        let source = unsafe { self.bitmap_target.GetBitmap()? };
+        // SAFETY: GetPixelSize is safe
+        let size = unsafe { source.GetPixelSize() };
+
+        let props = D2D1_BITMAP_PROPERTIES1 {
+            pixelFormat: D2D1_PIXEL_FORMAT {
+                format: DXGI_FORMAT_B8G8R8A8_UNORM,
+                alphaMode: D2D1_ALPHA_MODE_PREMULTIPLIED,
+            },
+            dpiX: 96.0,
+            dpiY: 96.0,
+            bitmapOptions: D2D1_BITMAP_OPTIONS_CPU_READ | D2D1_BITMAP_OPTIONS_CANNOT_DRAW,
+            ..Default::default()
+        };
+
+        // SAFETY: The staging bitmap has the source's size and pixel format, and
+        // the mapped rows are copied out before Unmap.
+        let pixels = unsafe {
+            let staging = context.CreateBitmap(size, None, 0, &props)?;
+            staging.CopyFromBitmap(None, &source, None)?;
+
+            let mapped = staging.Map(D2D1_MAP_OPTIONS_READ)?;
+            let row_len = size.width as usize * 4;
+            let mut pixels = Vec::with_capacity(row_len * size.height as usize);
+            for row in 0..size.height as usize {
+                let start = mapped.bits.add(row * mapped.pitch as usize);
+                pixels.extend_from_slice(std::slice::from_raw_parts(start, row_len));
+            }
+            staging.Unmap()?;
+            pixels
+        };
+
+        let _com = ComScope::enter();
+        let wic = wic_factory()?;
+        // SAFETY: pixels holds height rows of width * 4 bytes
+        let bitmap = unsafe {
+            wic.CreateBitmapFromMemory(
+                size.width,
+                size.height,
+                &GUID_WICPixelFormat32bppPBGRA,
+                size.width * 4,
+                &pixels,
+            )?
+        };
+        encode_image(&wic, &bitmap, path, format)
+    }
+}
+
+/// How bitmap pixels are sampled when drawn scaled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InterpolationMode {
+    /// Uses the nearest pixel; keeps hard edges, for pixel art.
+    NearestNeighbor,
+    /// Blends neighboring pixels for smooth scaling.
+    #[default]
+    Linear,
+}
+
+impl InterpolationMode {
+    fn to_native(self) -> D2D1_BITMAP_INTERPOLATION_MODE {
+        match self {
+            Self::NearestNeighbor => D2D1_BITMAP_INTERPOLATION_MODE_NEAREST_NEIGHBOR,
+            Self::Linear => D2D1_BITMAP_INTERPOLATION_MODE_LINEAR,
+        }
+    }
+}
+
+/// A Direct2D bitmap that can be drawn onto the render target that created it.
+pub struct D2DBitmap {
+    bitmap: ID2D1Bitmap,
+}
+
+impl D2DBitmap {
+    /// Gets the size of the bitmap in pixels.
+    pub fn size(&self) -> (u32, u32) {
+        // SAFETY: GetPixelSize is safe
+        let size = unsafe { self.bitmap.GetPixelSize() };
+        (size.width, size.height)
+    }
+}
+
+/// A Direct2D drawing surface that owns its factory and render target.
+///
+/// This is the batteries-included path for a Direct2D window: it begins and
+/// ends each frame, keeps the target sized to the window, and rebuilds the
+/// target after a device loss. Delegate to it from a window's message handler:
+///
+/// ```no_run
+/// use ergonomic_windows::d2d::{Color, D2DCanvas};
+/// use ergonomic_windows::window::{Message, MessageHandler, PaintContext};
+/// use windows::Win32::Foundation::{HWND, LRESULT};
+///
+/// struct App {
+///     canvas: Option<D2DCanvas>,
+/// }
+///
+/// impl MessageHandler for App {
+///     fn handle_message(&mut self, _msg: Message) -> Option<LRESULT> {
+///         None
+///     }
+///
+///     fn on_create(&mut self, hwnd: HWND) -> bool {
+///         self.canvas = D2DCanvas::new(hwnd).ok();
+///         self.canvas.is_some()
+///     }
+///
+///     fn on_paint(&mut self, _hwnd: HWND, _ctx: &PaintContext) {
+///         if let Some(canvas) = &mut self.canvas {
+///             let _ = canvas.render(|rt| rt.clear(Color::WHITE));
+///         }
+///     }
+///
+///     fn on_size(&mut self, _hwnd: HWND, width: u32, height: u32) {
+///         if let Some(canvas) = &mut self.canvas {
+///             let _ = canvas.on_resize(width, height);
+///         }
+///     }
+/// }
+/// ```
+///
+/// Resources such as brushes are tied to the render target, so create them
+/// inside the render closure, or recreate them whenever the target changes.
+pub struct D2DCanvas {
+    factory: D2DFactory,
+    hwnd: Option<HWND>,
+    size: (u32, u32),
+    target: Option<RenderTarget>,
+}
+
+impl D2DCanvas {
+    /// Creates a canvas that draws into a window's client area.
+    pub fn new(hwnd: HWND) -> Result<Self> {
+        let factory = D2DFactory::new()?;
+        let target = factory.create_hwnd_render_target(hwnd)?;
+        let (width, height) = target.size();
+        Ok(Self {
+            factory,
+            hwnd: Some(hwnd),
+            size: (width as u32, height as u32),
+            target: Some(target),
+        })
+    }
+
+    /// Creates a canvas backed by an off-screen bitmap.
+    ///
+    /// As with [`D2DFactory::create_bitmap_render_target`], COM must be
+    /// initialized on the calling thread.
+    pub fn offscreen(width: u32, height: u32) -> Result<Self> {
+        let factory = D2DFactory::new()?;
+        let target = factory.create_bitmap_render_target(width, height)?;
+        Ok(Self {
+            factory,
+            hwnd: None,
+            size: (width, height),
+            target: Some(target),
+        })
+    }
+
+    /// Draws a frame.
+    ///
+    /// `f` runs between `BeginDraw` and `EndDraw`. If the device was lost, the
+    /// render target is dropped and rebuilt for the next frame, and a window
+    /// canvas invalidates its window so that next frame is drawn promptly.
+    pub fn render(&mut self, f: impl FnOnce(&RenderTarget)) -> Result<()> {
+        let outcome = self.target()?.draw(|rt| {
+            f(rt);
+            Ok(())
+        })?;
+
+        if outcome == DrawOutcome::NeedsRecreate {
+            self.target = None;
+            if let Some(hwnd) = self.hwnd {
+                // SAFETY: hwnd is the window this canvas was created for
+                unsafe {
+                    let _ = InvalidateRect(hwnd, None, false);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Resizes the canvas, typically from `WM_SIZE`.
+    pub fn on_resize(&mut self, width: u32, height: u32) -> Result<()> {
+        self.size = (width, height);
+        match (&self.target, self.hwnd) {
+            (Some(target), Some(_)) => target.resize(width, height),
+            // Bitmap targets have a fixed size, so rebuild on the next frame
+            _ => {
+                self.target = None;
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns the size of the canvas in pixels.
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    /// Returns the factory used to create the render target.
+    pub fn factory(&self) -> &D2DFactory {
+        &self.factory
+    }
+
+    /// Returns the render target, creating it if needed.
+    fn target(&mut self) -> Result<&RenderTarget> {
+        let target = match (self.target.take(), self.hwnd) {
+            (Some(target), _) => target,
+            (None, Some(hwnd)) => self.factory.create_hwnd_render_target(hwnd)?,
+            (None, None) => self
+                .factory
+                .create_bitmap_render_target(self.size.0, self.size.1)?,
+        };
+        Ok(self.target.insert(target))
+    }
+}
+
+/// A GDI device context borrowed from a [`RenderTarget`], released on drop.
+///
+/// Created by [`RenderTarget::gdi_interop`].
+pub struct GdiInteropScope<'a> {
+    interop: ID2D1GdiInteropRenderTarget,
+    hdc: HDC,
+    _target: std::marker::PhantomData<&'a RenderTarget>,
+}
+
+impl GdiInteropScope<'_> {
+    /// Returns the device context for GDI drawing.
+    pub fn hdc(&self) -> HDC {
+        self.hdc
+    }
+}
+
+impl Drop for GdiInteropScope<'_> {
+    fn drop(&mut self) {
+        // SAFETY: balances the GetDC in RenderTarget::gdi_interop; None marks the
+        // whole target as updated.
+        unsafe {
+            let _ = self.interop.ReleaseDC(None);
+        }
+    }
 }
 
 /// A solid color brush for painting.
@@ -531,6 +1661,60 @@ impl DWriteFactory {
 
         Ok(TextFormat { format })
     }
+
+    /// Lays out `text` in a box of the given size, starting from `format`.
+    ///
+    /// Unlike a [`TextFormat`], a layout holds its text, so formatting can be
+    /// changed for ranges of it. Draw it with [`RenderTarget::draw_text_layout`].
+    pub fn create_text_layout(
+        &self,
+        text: &str,
+        format: &TextFormat,
+        max_width: f32,
+        max_height: f32,
+    ) -> Result<TextLayout> {
+        let wide: Vec<u16> = text.encode_utf16().collect();
+        // SAFETY: CreateTextLayout copies the text
+        let layout = unsafe {
+            self.factory
+                .CreateTextLayout(&wide, &format.format, max_width, max_height)?
+        };
+        Ok(TextLayout { layout })
+    }
+}
+
+/// The weight (boldness) of a font.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FontWeight {
+    /// Thin (100).
+    Thin,
+    /// Light (300).
+    Light,
+    /// Normal (400).
+    #[default]
+    Normal,
+    /// Medium (500).
+    Medium,
+    /// Semi-bold (600).
+    SemiBold,
+    /// Bold (700).
+    Bold,
+    /// Black (900).
+    Black,
+}
+
+impl FontWeight {
+    fn to_native(self) -> DWRITE_FONT_WEIGHT {
+        match self {
+            Self::Thin => DWRITE_FONT_WEIGHT_THIN,
+            Self::Light => DWRITE_FONT_WEIGHT_LIGHT,
+            Self::Normal => DWRITE_FONT_WEIGHT_NORMAL,
+            Self::Medium => DWRITE_FONT_WEIGHT_MEDIUM,
+            Self::SemiBold => DWRITE_FONT_WEIGHT_SEMI_BOLD,
+            Self::Bold => DWRITE_FONT_WEIGHT_BOLD,
+            Self::Black => DWRITE_FONT_WEIGHT_BLACK,
+        }
+    }
 }
 
 /// A text format for controlling text appearance.
@@ -571,10 +1755,99 @@ impl TextFormat {
     }
 }
 
+/// A block of text laid out with a format, supporting per-range formatting.
+///
+/// Ranges are `(start, length)` in UTF-16 code units of the layout's text, so
+/// characters outside the Basic Multilingual Plane count as two.
+pub struct TextLayout {
+    layout: IDWriteTextLayout,
+}
+
+/// Measurements of a laid-out block of text, in device-independent pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextMetrics {
+    /// The width of the widest line, excluding trailing whitespace.
+    pub width: f32,
+    /// The height of all lines.
+    pub height: f32,
+    /// The number of lines.
+    pub line_count: u32,
+}
+
+impl TextLayout {
+    /// Sets the font weight for a range of the text.
+    pub fn set_font_weight(&self, range: (u32, u32), weight: FontWeight) -> Result<()> {
+        // SAFETY: SetFontWeight is safe; out-of-range text is ignored
+        unsafe {
+            self.layout
+                .SetFontWeight(weight.to_native(), text_range(range))?;
+        }
+        Ok(())
+    }
+
+    /// Sets the font size, in device-independent pixels, for a range of the text.
+    pub fn set_font_size(&self, range: (u32, u32), size: f32) -> Result<()> {
+        // SAFETY: SetFontSize is safe; out-of-range text is ignored
+        unsafe {
+            self.layout.SetFontSize(size, text_range(range))?;
+        }
+        Ok(())
+    }
+
+    /// Underlines a range of the text, or removes its underline.
+    pub fn set_underline(&self, range: (u32, u32), underline: bool) -> Result<()> {
+        // SAFETY: SetUnderline is safe; out-of-range text is ignored
+        unsafe {
+            self.layout.SetUnderline(underline, text_range(range))?;
+        }
+        Ok(())
+    }
+
+    /// Draws a range of the text with `brush` instead of the default brush.
+    ///
+    /// The layout keeps a reference to the brush, which must come from the
+    /// render target the layout is drawn on. Only
+    /// [`RenderTarget::draw_text_layout`] honors it.
+    pub fn set_drawing_effect(&self, range: (u32, u32), brush: &SolidBrush) -> Result<()> {
+        // SAFETY: SetDrawingEffect takes its own reference to the brush
+        unsafe {
+            self.layout
+                .SetDrawingEffect(&brush.brush, text_range(range))?;
+        }
+        Ok(())
+    }
+
+    /// Measures the laid-out text.
+    pub fn metrics(&self) -> Result<TextMetrics> {
+        let mut metrics = DWRITE_TEXT_METRICS::default();
+        // SAFETY: metrics is a valid output parameter
+        unsafe {
+            self.layout.GetMetrics(&mut metrics)?;
+        }
+        Ok(TextMetrics {
+            width: metrics.width,
+            height: metrics.height,
+            line_count: metrics.lineCount,
+        })
+    }
+}
+
+fn text_range((start, length): (u32, u32)) -> DWRITE_TEXT_RANGE {
+    DWRITE_TEXT_RANGE {
+        startPosition: start,
+        length,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Bitmap render targets need COM; tests leave it initialized on their thread.
+    fn init_com() {
+        let _ = unsafe { CoInitializeEx(None, COINIT_MULTITHREADED) };
+    }
+
     #[test]
     fn test_color_rgb() {
         let c = Color::rgb(0.5, 0.25, 0.75);
@@ -592,6 +1865,53 @@ mod tests {
         assert!((c.b - 0.25).abs() < 0.01);
     }
 
+    #[test]
+    fn test_color_hex_round_trip() {
+        for hex in [0x000000, 0xFFFFFF, 0xFF8040, 0x123456, 0xABCDEF] {
+            assert_eq!(Color::from_hex(hex).to_hex(), hex);
+        }
+        assert_eq!(Color::rgba(2.0, -1.0, 0.5, 1.0).to_hex(), 0xFF0080);
+        assert_eq!(
+            Color::from_rgb8(1, 2, 3).with_alpha(0.0).to_rgba8(),
+            (1, 2, 3, 0)
+        );
+    }
+
+    #[test]
+    fn test_color_hsl_round_trip() {
+        assert_eq!(Color::from_hsl(0.0, 1.0, 0.5).to_hex(), 0xFF0000);
+        assert_eq!(Color::from_hsl(120.0, 1.0, 0.5).to_hex(), 0x00FF00);
+        assert_eq!(Color::from_hsl(240.0, 1.0, 0.5).to_hex(), 0x0000FF);
+
+        for hex in [0xFF8040, 0x123456, 0xABCDEF, 0x808080] {
+            let c = Color::from_hex(hex);
+            let (h, s, l) = c.to_hsl();
+            let back = Color::from_hsl(h, s, l);
+            assert!((back.r - c.r).abs() < 1e-4);
+            assert!((back.g - c.g).abs() < 1e-4);
+            assert!((back.b - c.b).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_color_lerp() {
+        let a = Color::rgba(0.0, 0.2, 0.4, 0.0);
+        let b = Color::rgba(1.0, 0.6, 0.0, 1.0);
+        assert_eq!(a.lerp(b, 0.0), a);
+        assert_eq!(a.lerp(b, 1.0), b);
+
+        let mid = a.lerp(b, 0.5);
+        assert!((mid.r - 0.5).abs() < 1e-6);
+        assert!((mid.a - 0.5).abs() < 1e-6);
+
+        // `a + (b - a) * t` rounds away from `b` for values like these
+        let x = Color::rgba(0.1, 0.7, 0.33, 0.9);
+        let y = Color::rgba(0.3, 0.15, 0.97, 0.45);
+        assert_eq!(x.lerp(y, 1.0), y);
+        assert_eq!(y.lerp(x, 1.0), x);
+        assert_eq!(x.lerp(y, 0.0), x);
+    }
+
     #[test]
     fn test_color_constants() {
         assert_eq!(Color::BLACK.r, 0.0);
@@ -611,4 +1931,298 @@ mod tests {
         // This may fail if DWrite is not available
         let _ = DWriteFactory::new();
     }
+
+    #[test]
+    fn test_draw_offscreen() {
+        init_com();
+        let factory = D2DFactory::new().unwrap();
+        let target = factory.create_bitmap_render_target(64, 64).unwrap();
+        assert_eq!(target.size(), (64.0, 64.0));
+
+        let outcome = target
+            .draw(|rt| {
+                rt.clear(Color::WHITE);
+                let brush = rt.create_solid_brush(Color::RED)?;
+                rt.fill_rect(8.0, 8.0, 16.0, 16.0, &brush);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(outcome, DrawOutcome::Ok);
+
+        // Offscreen targets cannot be resized
+        assert!(target.resize(32, 32).is_err());
+    }
+
+    #[test]
+    fn test_dpi_conversion() {
+        init_com();
+        let factory = D2DFactory::new().unwrap();
+        let target = factory.create_bitmap_render_target(64, 64).unwrap();
+
+        target.set_dpi(192.0, 192.0);
+        assert_eq!(target.dpi(), (192.0, 192.0));
+        assert_eq!(target.dip_to_px(10.0), 20.0);
+        assert_eq!(target.px_to_dip(20.0), 10.0);
+
+        assert!(matches!(
+            target.set_dpi_from_window(HWND::default()),
+            Err(crate::error::Error::InvalidHandle { .. })
+        ));
+        // A failed call leaves the DPI alone
+        assert_eq!(target.dpi(), (192.0, 192.0));
+    }
+
+    #[test]
+    fn test_gdi_interop_offscreen() {
+        init_com();
+        let factory = D2DFactory::new().unwrap().gdi_compatible(true);
+        let target = factory.create_bitmap_render_target(32, 32).unwrap();
+
+        let outcome = target
+            .draw(|rt| {
+                rt.clear(Color::WHITE);
+                let scope = rt.gdi_interop()?;
+                assert!(!scope.hdc().is_invalid());
+                drop(scope);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(outcome, DrawOutcome::Ok);
+    }
+
+    #[test]
+    fn test_gdi_interop_requires_opt_in() {
+        init_com();
+        let factory = D2DFactory::new().unwrap();
+        let target = factory.create_bitmap_render_target(16, 16).unwrap();
+
+        target.begin_draw();
+        assert!(matches!(
+            target.gdi_interop(),
+            Err(crate::error::Error::Unsupported(_))
+        ));
+        target.end_draw().unwrap();
+    }
+
+    #[test]
+    fn test_draw_propagates_closure_error() {
+        init_com();
+        let factory = D2DFactory::new().unwrap();
+        let target = factory.create_bitmap_render_target(16, 16).unwrap();
+
+        let result = target.draw(|_| Err(crate::error::Error::custom("draw failed")));
+        assert!(result.is_err());
+
+        // EndDraw was called, so a new pass can begin
+        assert_eq!(target.draw(|_| Ok(())).unwrap(), DrawOutcome::Ok);
+    }
+
+    #[test]
+    fn test_canvas_render_and_resize() {
+        init_com();
+        let mut canvas = D2DCanvas::offscreen(16, 16).unwrap();
+        assert_eq!(canvas.size(), (16, 16));
+
+        let mut rendered = false;
+        canvas
+            .render(|rt| {
+                rt.clear(Color::BLUE);
+                rendered = true;
+            })
+            .unwrap();
+        assert!(rendered);
+
+        canvas.on_resize(32, 24).unwrap();
+        assert_eq!(canvas.size(), (32, 24));
+
+        let mut target_size = (0.0, 0.0);
+        canvas.render(|rt| target_size = rt.size()).unwrap();
+        assert_eq!(target_size, (32.0, 24.0));
+    }
+
+    #[test]
+    fn test_draw_bitmap_region() {
+        init_com();
+        let factory = D2DFactory::new().unwrap();
+        let target = factory.create_bitmap_render_target(64, 64).unwrap();
+
+        // A 32x32 atlas of four 16x16 tiles
+        let pixels: Vec<u8> = (0..32 * 32)
+            .flat_map(|i| {
+                let tile = (i % 32 / 16) + (i / 32 / 16) * 2;
+                [tile as u8 * 60, 0, 0, 255]
+            })
+            .collect();
+        let atlas = target.create_bitmap(32, 32, &pixels).unwrap();
+        assert_eq!(atlas.size(), (32, 32));
+        assert!(target.create_bitmap(32, 32, &pixels[4..]).is_err());
+
+        let outcome = target
+            .draw(|rt| {
+                rt.clear(Color::WHITE);
+                rt.draw_bitmap_region(
+                    &atlas,
+                    (0.0, 0.0, 32.0, 32.0),
+                    (16.0, 16.0, 16.0, 16.0),
+                    1.0,
+                    InterpolationMode::NearestNeighbor,
+                )?;
+                assert!(rt
+                    .draw_bitmap_region(
+                        &atlas,
+                        (0.0, 0.0, 16.0, 16.0),
+                        (24.0, 0.0, 16.0, 16.0),
+                        1.0,
+                        InterpolationMode::Linear,
+                    )
+                    .is_err());
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(outcome, DrawOutcome::Ok);
+    }
+
+    /// Reads the pixel size of an image file back with WIC.
+    fn image_size(path: &Path) -> (u32, u32) {
+        use windows::Win32::Foundation::GENERIC_READ;
+        use windows::Win32::Graphics::Imaging::WICDecodeMetadataCacheOnDemand;
+
+        let wic = wic_factory().unwrap();
+        let path = WideString::from_path(path);
+        let (mut width, mut height) = (0, 0);
+        unsafe {
+            let decoder = wic
+                .CreateDecoderFromFilename(
+                    path.as_pcwstr(),
+                    None,
+                    GENERIC_READ,
+                    WICDecodeMetadataCacheOnDemand,
+                )
+                .unwrap();
+            let frame = decoder.GetFrame(0).unwrap();
+            frame.GetSize(&mut width, &mut height).unwrap();
+        }
+        (width, height)
+    }
+
+    #[test]
+    fn test_save_image() {
+        init_com();
+        let factory = D2DFactory::new().unwrap();
+        let target = factory.create_bitmap_render_target(32, 24).unwrap();
+        let compatible = target.create_compatible((20, 10)).unwrap();
+
+        let brush = compatible
+            .render_target()
+            .create_solid_brush(Color::from_rgb8(255, 0, 0).with_alpha(0.5))
+            .unwrap();
+        let outcome = compatible
+            .render_target()
+            .draw(|rt| {
+                rt.clear(Color::TRANSPARENT);
+                rt.fill_rect(2.0, 2.0, 10.0, 5.0, &brush);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(outcome, DrawOutcome::Ok);
+
+        let dir = std::env::temp_dir();
+        let compatible_png = dir.join(format!(
+            "ergonomic_windows_compatible_{}.png",
+            std::process::id()
+        ));
+        match compatible.save_image(&compatible_png, ImageFormat::Png) {
+            Ok(()) => {
+                assert_eq!(image_size(&compatible_png), (20, 10));
+                let _ = std::fs::remove_file(&compatible_png);
+            }
+            // Reading back needs Direct2D 1.1 (ID2D1DeviceContext)
+            Err(crate::error::Error::Windows(e))
+                if e.code() == windows::Win32::Foundation::E_NOINTERFACE => {}
+            Err(e) => panic!("saving the compatible target failed: {}", e),
+        }
+
+        target
+            .draw(|rt| {
+                rt.clear(Color::WHITE);
+                Ok(())
+            })
+            .unwrap();
+        let target_jpg = dir.join(format!(
+            "ergonomic_windows_target_{}.jpg",
+            std::process::id()
+        ));
+        target.save_image(&target_jpg, ImageFormat::Jpeg).unwrap();
+        assert_eq!(image_size(&target_jpg), (32, 24));
+        let _ = std::fs::remove_file(&target_jpg);
+    }
+
+    #[test]
+    fn test_text_layout_ranges() {
+        init_com();
+        let dwrite = DWriteFactory::new().unwrap();
+        let format = dwrite.create_text_format("Segoe UI", 16.0).unwrap();
+        let layout = dwrite
+            .create_text_layout("Hello world", &format, 400.0, 100.0)
+            .unwrap();
+        let plain = layout.metrics().unwrap();
+        assert_eq!(plain.line_count, 1);
+
+        layout.set_font_weight((0, 5), FontWeight::Bold).unwrap();
+        let bold = layout.metrics().unwrap();
+        assert!(bold.width > plain.width);
+
+        layout.set_font_size((6, 5), 32.0).unwrap();
+        assert!(layout.metrics().unwrap().height > bold.height);
+        layout.set_underline((0, 11), true).unwrap();
+
+        let factory = D2DFactory::new().unwrap();
+        let target = factory.create_bitmap_render_target(400, 100).unwrap();
+        let black = target.create_solid_brush(Color::BLACK).unwrap();
+        let red = target.create_solid_brush(Color::RED).unwrap();
+        layout.set_drawing_effect((6, 5), &red).unwrap();
+
+        let outcome = target
+            .draw(|rt| {
+                rt.clear(Color::WHITE);
+                rt.draw_text_layout(0.0, 0.0, &layout, &black);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(outcome, DrawOutcome::Ok);
+    }
+
+    #[test]
+    fn test_swap_chain_target_present() {
+        use crate::window::{DefaultHandler, WindowBuilder};
+
+        // Needs a hardware Direct3D driver and a desktop to create a window on
+        let Ok(device) = Device::new() else {
+            return;
+        };
+        let Ok(window) = WindowBuilder::new()
+            .class_name(format!("ErgonomicD2DSwapChainTest_{}", std::process::id()))
+            .size(64, 64)
+            .build(DefaultHandler)
+        else {
+            return;
+        };
+
+        let target = device
+            .create_swap_chain_target(window.hwnd(), (64, 64))
+            .unwrap();
+        let outcome = target
+            .render_target()
+            .draw(|rt| {
+                rt.clear(Color::BLUE);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(outcome, DrawOutcome::Ok);
+        assert_eq!(target.render_target().size(), (64.0, 64.0));
+        assert_eq!(target.present(0).unwrap(), DrawOutcome::Ok);
+
+        target.resize(32, 32).unwrap();
+        assert_eq!(target.render_target().size(), (32.0, 32.0));
+    }
 }
@@ -2,13 +2,18 @@
 //!
 //! Provides safe wrappers for Windows environment variable operations.
 
-use crate::error::Result;
-use crate::string::{from_wide, to_wide, WideString};
+use crate::error::{Error, Result};
+use crate::registry::{Access, Key, RootKey, Value};
+use crate::string::{equals_ignore_case, from_wide, to_wide, WideString};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use windows::Win32::Foundation::{ERROR_ACCESS_DENIED, ERROR_INVALID_PARAMETER, LPARAM, WPARAM};
 use windows::Win32::System::Environment::{
     ExpandEnvironmentStringsW, GetEnvironmentVariableW, SetEnvironmentVariableW,
 };
+use windows::Win32::UI::WindowsAndMessaging::{
+    SendMessageTimeoutW, HWND_BROADCAST, SMTO_ABORTIFHUNG, WM_SETTINGCHANGE,
+};
 
 /// Gets an environment variable.
 ///
@@ -175,6 +180,160 @@ pub fn exists(name: &str) -> bool {
     get(name).is_some()
 }
 
+/// Which persistent environment a PATH helper reads or writes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnvScope {
+    /// The current user's environment (`HKCU\Environment`).
+    User,
+    /// The system-wide environment. Writing it requires elevation.
+    Machine,
+}
+
+impl EnvScope {
+    fn key(self) -> (RootKey, &'static str) {
+        match self {
+            EnvScope::User => (RootKey::CURRENT_USER, "Environment"),
+            EnvScope::Machine => (
+                RootKey::LOCAL_MACHINE,
+                r"SYSTEM\CurrentControlSet\Control\Session Manager\Environment",
+            ),
+        }
+    }
+}
+
+/// Gets the persistent PATH entries of `scope`, as stored in the registry.
+///
+/// References such as `%SystemRoot%` are left unexpanded. Unlike [`path`], this
+/// does not reflect the current process, which inherited its PATH at startup.
+pub fn path_entries(scope: EnvScope) -> Result<Vec<PathBuf>> {
+    let (root, subkey) = scope.key();
+    let key = Key::open(root, subkey, Access::READ)?;
+    Ok(split_path(&read_raw_path(&key)?)
+        .map(PathBuf::from)
+        .collect())
+}
+
+/// Appends `dir` to the persistent PATH of `scope` if it isn't already present.
+///
+/// Entries are compared case-insensitively, ignoring trailing backslashes. The
+/// value is written back as `REG_EXPAND_SZ` and running applications are
+/// notified with `WM_SETTINGCHANGE`. Returns `Ok(false)` if nothing changed.
+///
+/// Changing the machine PATH without elevation fails with [`Error::AccessDenied`].
+/// An empty `dir`, or one containing `;`, fails with `ERROR_INVALID_PARAMETER`.
+pub fn add_to_path(scope: EnvScope, dir: impl AsRef<Path>) -> Result<bool> {
+    let dir = dir.as_ref().to_string_lossy();
+    check_path_entry(&dir)?;
+    let key = open_path_key(scope)?;
+    let raw = read_raw_path(&key)?;
+
+    if split_path(&raw).any(|entry| same_path_entry(entry, &dir)) {
+        return Ok(false);
+    }
+
+    let mut updated = raw.trim_end_matches(';').to_string();
+    if !updated.is_empty() {
+        updated.push(';');
+    }
+    updated.push_str(&dir);
+
+    write_path(scope, &key, updated)?;
+    Ok(true)
+}
+
+/// Removes every occurrence of `dir` from the persistent PATH of `scope`.
+///
+/// Uses the same matching, validation, write-back and notification as
+/// [`add_to_path`]. Returns `Ok(false)` if `dir` wasn't present.
+pub fn remove_from_path(scope: EnvScope, dir: impl AsRef<Path>) -> Result<bool> {
+    let dir = dir.as_ref().to_string_lossy();
+    check_path_entry(&dir)?;
+    let key = open_path_key(scope)?;
+    let raw = read_raw_path(&key)?;
+
+    let kept: Vec<&str> = split_path(&raw)
+        .filter(|entry| !same_path_entry(entry, &dir))
+        .collect();
+    if kept.len() == split_path(&raw).count() {
+        return Ok(false);
+    }
+
+    write_path(scope, &key, kept.join(";"))?;
+    Ok(true)
+}
+
+/// Rejects directories that can't be a single PATH entry.
+fn check_path_entry(dir: &str) -> Result<()> {
+    if dir.trim().is_empty() || dir.contains(';') {
+        return Err(Error::from_win32_code(ERROR_INVALID_PARAMETER.0));
+    }
+    Ok(())
+}
+
+fn open_path_key(scope: EnvScope) -> Result<Key> {
+    let (root, subkey) = scope.key();
+    Key::open(root, subkey, Access::READ.with(Access::SET_VALUE))
+        .map_err(|e| elevation_error(scope, e))
+}
+
+/// Reads the raw PATH value; a missing value is an empty PATH.
+///
+/// A `Path` value that isn't a string is an error rather than an empty PATH,
+/// which writing back would otherwise silently replace.
+fn read_raw_path(key: &Key) -> Result<String> {
+    let value = key.get_value_or("Path", Value::ExpandString(String::new()))?;
+    match value.as_string() {
+        Some(raw) => Ok(raw.to_string()),
+        None => Err(Error::custom(format!(
+            "PATH has unexpected registry type {:?}",
+            key.value_type("Path")?
+        ))),
+    }
+}
+
+fn write_path(scope: EnvScope, key: &Key, path: String) -> Result<()> {
+    key.set_value("Path", &Value::ExpandString(path))
+        .map_err(|e| elevation_error(scope, e))?;
+    broadcast_environment_change();
+    Ok(())
+}
+
+fn split_path(raw: &str) -> impl Iterator<Item = &str> {
+    raw.split(';').filter(|entry| !entry.trim().is_empty())
+}
+
+fn same_path_entry(a: &str, b: &str) -> bool {
+    let trim = |s: &str| s.trim().trim_end_matches(['\\', '/']).to_string();
+    equals_ignore_case(&trim(a), &trim(b))
+}
+
+fn elevation_error(scope: EnvScope, error: Error) -> Error {
+    match (&error, scope) {
+        (Error::Windows(e), EnvScope::Machine) if e.code() == ERROR_ACCESS_DENIED.to_hresult() => {
+            Error::access_denied("Changing the machine PATH requires elevation")
+        }
+        _ => error,
+    }
+}
+
+/// Tells top-level windows (Explorer in particular) to reload the environment.
+fn broadcast_environment_change() {
+    let area = WideString::new("Environment");
+    // SAFETY: The string outlives the call; SMTO_ABORTIFHUNG bounds the wait
+    // on unresponsive windows.
+    unsafe {
+        SendMessageTimeoutW(
+            HWND_BROADCAST,
+            WM_SETTINGCHANGE,
+            WPARAM(0),
+            LPARAM(area.as_ptr() as isize),
+            SMTO_ABORTIFHUNG,
+            5000,
+            None,
+        );
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -222,4 +381,46 @@ mod tests {
         assert!(exists("PATH"));
         assert!(!exists("NONEXISTENT_VAR_12345"));
     }
+
+    /// Takes `dir` back out of the user PATH even if the test fails midway.
+    struct UserPathGuard<'a>(&'a str);
+
+    impl Drop for UserPathGuard<'_> {
+        fn drop(&mut self) {
+            let _ = remove_from_path(EnvScope::User, self.0);
+        }
+    }
+
+    #[test]
+    fn test_user_path_add_remove() {
+        let dir = format!(r"C:\ErgonomicWindowsPathTest_{}", std::process::id());
+        let Ok(before) = path_entries(EnvScope::User) else {
+            return;
+        };
+        assert!(!before.iter().any(|p| p == Path::new(&dir)));
+
+        let _guard = UserPathGuard(&dir);
+        assert!(add_to_path(EnvScope::User, &dir).unwrap());
+        // Already present, in any case and with a trailing separator
+        assert!(!add_to_path(EnvScope::User, &dir).unwrap());
+        assert!(!add_to_path(EnvScope::User, format!("{}\\", dir.to_lowercase())).unwrap());
+
+        let added = path_entries(EnvScope::User).unwrap();
+        assert_eq!(added.iter().filter(|p| *p == Path::new(&dir)).count(), 1);
+
+        assert!(remove_from_path(EnvScope::User, &dir).unwrap());
+        assert!(!remove_from_path(EnvScope::User, &dir).unwrap());
+        assert_eq!(path_entries(EnvScope::User).unwrap(), before);
+    }
+
+    #[test]
+    fn test_path_entry_validation() {
+        let invalid = Some(ERROR_INVALID_PARAMETER.0);
+        for dir in ["", "  ", r"C:\one;C:	wo", r"C:	railing;"] {
+            let err = add_to_path(EnvScope::User, dir).unwrap_err();
+            assert_eq!(err.win32_error_code(), invalid, "{:?}", dir);
+            let err = remove_from_path(EnvScope::User, dir).unwrap_err();
+            assert_eq!(err.win32_error_code(), invalid, "{:?}", dir);
+        }
+    }
 }
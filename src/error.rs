@@ -3,11 +3,17 @@
 //! Provides ergonomic error types that wrap Windows error codes and convert them
 //! into idiomatic Rust `Result` types.
 
+use crate::handle::OwnedHandle;
+use std::io::ErrorKind;
 use thiserror::Error;
-use windows::core::Error as WinError;
+use windows::core::{Error as WinError, HRESULT};
+use windows::Win32::Foundation::{RtlNtStatusToDosError, BOOL, HANDLE, NTSTATUS};
 
 /// The main error type for this crate.
+///
+/// New variants may be added in minor releases, so matches need a wildcard arm.
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum Error {
     /// A Windows API error with its error code.
     #[error("Windows API error: {0}")]
@@ -48,9 +54,16 @@ pub enum Error {
     #[error("Access denied: {0}")]
     AccessDenied(String),
 
+    /// The operation is not supported on this system (e.g. an API missing on older Windows).
+    #[error("Unsupported: {0}")]
+    Unsupported(String),
+
     /// An I/O error occurred.
+    ///
+    /// Only I/O errors without a raw OS error end up here; the rest convert to
+    /// [`Error::Windows`].
     #[error("I/O error: {0}")]
-    Io(#[from] std::io::Error),
+    Io(std::io::Error),
 
     /// A custom error with a message.
     #[error("{0}")]
@@ -91,6 +104,11 @@ impl Error {
         Error::AccessDenied(msg.into())
     }
 
+    /// Creates a new unsupported operation error.
+    pub fn unsupported(msg: impl Into<String>) -> Self {
+        Error::Unsupported(msg.into())
+    }
+
     /// Creates a custom error with the given message.
     pub fn custom(msg: impl Into<String>) -> Self {
         Error::Custom(msg.into())
@@ -106,6 +124,25 @@ impl Error {
         Error::Windows(err)
     }
 
+    /// Creates an error from a raw Win32 error code, such as `ERROR_ACCESS_DENIED`.
+    pub fn from_win32_code(code: u32) -> Self {
+        Error::Windows(WinError::from(HRESULT::from_win32(code)))
+    }
+
+    /// Creates an error from a raw HRESULT.
+    pub fn from_hresult(hr: i32) -> Self {
+        Error::Windows(WinError::from(HRESULT(hr)))
+    }
+
+    /// Creates an error from an NTSTATUS, translated to the matching Win32 error code.
+    ///
+    /// Statuses without a Win32 equivalent become `ERROR_MR_MID_NOT_FOUND`.
+    pub fn from_ntstatus(status: i32) -> Self {
+        // SAFETY: RtlNtStatusToDosError is a pure lookup with no preconditions
+        let code = unsafe { RtlNtStatusToDosError(NTSTATUS(status)) };
+        Self::from_win32_code(code)
+    }
+
     /// Creates an I/O error with context.
     pub fn io_error(err: std::io::Error, context: &str) -> Self {
         Error::Custom(format!("{}: {}", context, err))
@@ -131,6 +168,54 @@ impl Error {
             _ => None,
         }
     }
+
+    /// Returns the closest matching `std::io::ErrorKind`.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Error::Windows(e) => std::io::Error::from_raw_os_error(os_error_code(e.code())).kind(),
+            Error::NullPointer { .. } | Error::InvalidHandle { .. } => ErrorKind::InvalidInput,
+            Error::StringConversion(_) => ErrorKind::InvalidData,
+            Error::BufferTooSmall { .. } => ErrorKind::InvalidInput,
+            Error::NotFound(_) => ErrorKind::NotFound,
+            Error::AccessDenied(_) => ErrorKind::PermissionDenied,
+            Error::Unsupported(_) => ErrorKind::Unsupported,
+            Error::Io(e) => e.kind(),
+            Error::Custom(_) => ErrorKind::Other,
+        }
+    }
+}
+
+/// Returns the Win32 error code wrapped in an HRESULT, or the HRESULT itself if it
+/// does not come from Win32.
+fn os_error_code(code: HRESULT) -> i32 {
+    const FACILITY_WIN32_PREFIX: u32 = 0x8007_0000;
+    let raw = code.0 as u32;
+    if raw & 0xFFFF_0000 == FACILITY_WIN32_PREFIX {
+        (raw & 0xFFFF) as i32
+    } else {
+        code.0
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        match err.raw_os_error() {
+            // Negative codes are already HRESULTs; positive ones are Win32 error codes.
+            Some(code) if code < 0 => Error::Windows(WinError::from(HRESULT(code))),
+            Some(code) => Error::Windows(WinError::from(HRESULT::from_win32(code as u32))),
+            None => Error::Io(err),
+        }
+    }
+}
+
+impl From<Error> for std::io::Error {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::Windows(e) => std::io::Error::from_raw_os_error(os_error_code(e.code())),
+            Error::Io(e) => e,
+            other => std::io::Error::new(other.kind(), other),
+        }
+    }
 }
 
 /// Gets the last Windows error as our Error type.
@@ -164,3 +249,132 @@ pub fn check_last_error() -> Result<()> {
         Err(Error::Windows(err))
     }
 }
+
+/// Converts a `BOOL` return into a `Result`, capturing the last error on `FALSE`.
+///
+/// Call it directly on the API's return value, before anything else can
+/// overwrite the thread's last error.
+pub fn bool_result(b: BOOL) -> Result<()> {
+    if b.as_bool() {
+        Ok(())
+    } else {
+        Err(last_error())
+    }
+}
+
+/// Passes a count or length through, capturing the last error if it is zero.
+///
+/// For APIs where zero means failure, such as `GetSystemDirectoryW`. APIs that
+/// can legitimately return zero need `SetLastError(0)` before the call and
+/// [`check_last_error`] after it instead.
+pub fn nonzero<T: Default + PartialEq>(v: T) -> Result<T> {
+    if v == T::default() {
+        Err(last_error())
+    } else {
+        Ok(v)
+    }
+}
+
+/// Takes ownership of a returned handle, capturing the last error if it is invalid.
+///
+/// Both null and `INVALID_HANDLE_VALUE` count as failure, since Win32 APIs use
+/// one or the other.
+pub fn handle_result(h: HANDLE) -> Result<OwnedHandle> {
+    if h.is_invalid() || h.0.is_null() {
+        Err(last_error())
+    } else {
+        // SAFETY: h is a valid handle just returned by an API, and the caller
+        // hands its ownership over
+        Ok(unsafe { OwnedHandle::new_unchecked(h) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use windows::Win32::Foundation::{ERROR_ACCESS_DENIED, ERROR_FILE_NOT_FOUND};
+
+    #[test]
+    fn test_into_io_error_preserves_code() {
+        let err = Error::from(WinError::from(ERROR_FILE_NOT_FOUND.to_hresult()));
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+
+        let io: std::io::Error = err.into();
+        assert_eq!(io.kind(), ErrorKind::NotFound);
+        assert_eq!(io.raw_os_error(), Some(ERROR_FILE_NOT_FOUND.0 as i32));
+    }
+
+    #[test]
+    fn test_from_io_error() {
+        let io = std::io::Error::from_raw_os_error(ERROR_ACCESS_DENIED.0 as i32);
+        let err = Error::from(io);
+        assert_eq!(err.raw_code(), ERROR_ACCESS_DENIED.to_hresult().0);
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+
+        let err = Error::from(std::io::Error::new(ErrorKind::WouldBlock, "busy"));
+        assert!(matches!(err, Error::Io(_)));
+        assert_eq!(err.kind(), ErrorKind::WouldBlock);
+    }
+
+    #[test]
+    fn test_from_raw_codes() {
+        let err = Error::from_win32_code(ERROR_ACCESS_DENIED.0);
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+        assert_eq!(err.raw_code(), ERROR_ACCESS_DENIED.to_hresult().0);
+
+        let err = Error::from_hresult(ERROR_FILE_NOT_FOUND.to_hresult().0);
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+
+        // STATUS_ACCESS_DENIED
+        let err = Error::from_ntstatus(0xC000_0022_u32 as i32);
+        assert_eq!(err.raw_code(), ERROR_ACCESS_DENIED.to_hresult().0);
+    }
+
+    #[test]
+    fn test_bool_result() {
+        use windows::Win32::Foundation::SetLastError;
+
+        assert!(bool_result(BOOL(1)).is_ok());
+        // SAFETY: SetLastError has no preconditions
+        unsafe { SetLastError(ERROR_ACCESS_DENIED) };
+        let err = bool_result(BOOL(0)).unwrap_err();
+        assert_eq!(err.win32_error_code(), Some(ERROR_ACCESS_DENIED.0));
+    }
+
+    #[test]
+    fn test_nonzero() {
+        use windows::Win32::Foundation::SetLastError;
+
+        assert_eq!(nonzero(42u32).unwrap(), 42);
+        assert_eq!(nonzero(usize::MAX).unwrap(), usize::MAX);
+        // SAFETY: SetLastError has no preconditions
+        unsafe { SetLastError(ERROR_FILE_NOT_FOUND) };
+        let err = nonzero(0u32).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_handle_result() {
+        use windows::Win32::Foundation::{SetLastError, INVALID_HANDLE_VALUE};
+        use windows::Win32::System::Threading::CreateEventW;
+
+        // SAFETY: SetLastError has no preconditions
+        unsafe { SetLastError(ERROR_ACCESS_DENIED) };
+        let err = handle_result(INVALID_HANDLE_VALUE).unwrap_err();
+        assert_eq!(err.win32_error_code(), Some(ERROR_ACCESS_DENIED.0));
+        unsafe { SetLastError(ERROR_ACCESS_DENIED) };
+        assert!(handle_result(HANDLE::default()).is_err());
+
+        // SAFETY: an unnamed event with default security has no preconditions
+        let event = unsafe { CreateEventW(None, true, false, None) }.unwrap();
+        let owned = handle_result(event).unwrap();
+        assert_eq!(owned.as_raw(), event);
+    }
+
+    #[test]
+    fn test_crate_error_into_io_error() {
+        let io: std::io::Error = Error::not_found("missing").into();
+        assert_eq!(io.kind(), ErrorKind::NotFound);
+        assert_eq!(io.raw_os_error(), None);
+    }
+}
@@ -2,19 +2,53 @@
 //!
 //! Provides ergonomic wrappers for Windows-specific file system operations.
 
-use crate::error::Result;
+use crate::error::{Error, Result};
 use crate::handle::OwnedHandle;
+use crate::security::{
+    access_check, SecurityDescriptor, SecurityInfo, Token, FILE_GENERIC_MAPPING, MAXIMUM_ALLOWED,
+};
 use crate::string::{from_wide, WideString};
+use crate::thread::{Event, WaitResult};
+use std::cell::{Cell, UnsafeCell};
+use std::ffi::OsString;
+use std::os::windows::ffi::OsStringExt;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
+use windows::Win32::Foundation::{
+    BOOLEAN, ERROR_FILE_NOT_FOUND, ERROR_INVALID_PARAMETER, ERROR_MORE_DATA, ERROR_NOTIFY_ENUM_DIR,
+    ERROR_NO_MORE_FILES, FILETIME, HANDLE, INVALID_HANDLE_VALUE,
+};
+use windows::Win32::Security::Authorization::{GetNamedSecurityInfoW, SE_FILE_OBJECT};
+use windows::Win32::Security::{PSECURITY_DESCRIPTOR, TOKEN_DUPLICATE, TOKEN_QUERY};
 use windows::Win32::Storage::FileSystem::{
-    CreateFileW, DeleteFileW, GetFileAttributesW, MoveFileExW, SetFileAttributesW, CREATE_ALWAYS,
-    CREATE_NEW, FILE_ACCESS_RIGHTS, FILE_ATTRIBUTE_ARCHIVE, FILE_ATTRIBUTE_DIRECTORY,
-    FILE_ATTRIBUTE_HIDDEN, FILE_ATTRIBUTE_NORMAL, FILE_ATTRIBUTE_READONLY, FILE_ATTRIBUTE_SYSTEM,
-    FILE_ATTRIBUTE_TEMPORARY, FILE_CREATION_DISPOSITION, FILE_FLAGS_AND_ATTRIBUTES,
-    FILE_GENERIC_READ, FILE_GENERIC_WRITE, FILE_SHARE_MODE, FILE_SHARE_READ, FILE_SHARE_WRITE,
+    CreateFileW, DeleteFileW, FileIdInfo, FindClose, FindExInfoBasic, FindExSearchNameMatch,
+    FindFirstFileExW, FindNextFileW, FlushFileBuffers, GetDriveTypeW, GetFileAttributesW,
+    GetFileInformationByHandle, GetFileInformationByHandleEx, GetFullPathNameW, GetLogicalDrives,
+    GetVolumeInformationW, GetVolumeNameForVolumeMountPointW, GetVolumePathNameW, MoveFileExW,
+    ReadDirectoryChangesW, SetEndOfFile, SetFileAttributesW, SetFilePointerEx, WriteFile,
+    BY_HANDLE_FILE_INFORMATION, COMPRESSION_FORMAT_DEFAULT, COMPRESSION_FORMAT_NONE, CREATE_ALWAYS,
+    CREATE_NEW, FILE_ACCESS_RIGHTS, FILE_ACTION_ADDED, FILE_ACTION_MODIFIED, FILE_ACTION_REMOVED,
+    FILE_ACTION_RENAMED_NEW_NAME, FILE_ACTION_RENAMED_OLD_NAME, FILE_ATTRIBUTE_ARCHIVE,
+    FILE_ATTRIBUTE_COMPRESSED, FILE_ATTRIBUTE_DIRECTORY, FILE_ATTRIBUTE_HIDDEN,
+    FILE_ATTRIBUTE_NORMAL, FILE_ATTRIBUTE_READONLY, FILE_ATTRIBUTE_REPARSE_POINT,
+    FILE_ATTRIBUTE_SYSTEM, FILE_ATTRIBUTE_TEMPORARY, FILE_BEGIN, FILE_CREATION_DISPOSITION,
+    FILE_FLAGS_AND_ATTRIBUTES, FILE_FLAG_BACKUP_SEMANTICS, FILE_FLAG_DELETE_ON_CLOSE,
+    FILE_FLAG_NO_BUFFERING, FILE_FLAG_OPEN_REPARSE_POINT, FILE_FLAG_OVERLAPPED,
+    FILE_FLAG_SEQUENTIAL_SCAN, FILE_FLAG_WRITE_THROUGH, FILE_GENERIC_READ, FILE_GENERIC_WRITE,
+    FILE_ID_INFO, FILE_LIST_DIRECTORY, FILE_NOTIFY_CHANGE, FILE_NOTIFY_CHANGE_ATTRIBUTES,
+    FILE_NOTIFY_CHANGE_DIR_NAME, FILE_NOTIFY_CHANGE_FILE_NAME, FILE_NOTIFY_CHANGE_LAST_WRITE,
+    FILE_NOTIFY_CHANGE_SECURITY, FILE_NOTIFY_CHANGE_SIZE, FILE_READ_ATTRIBUTES, FILE_SHARE_DELETE,
+    FILE_SHARE_MODE, FILE_SHARE_READ, FILE_SHARE_WRITE, FIND_FIRST_EX_LARGE_FETCH,
     INVALID_FILE_ATTRIBUTES, MOVEFILE_COPY_ALLOWED, MOVEFILE_REPLACE_EXISTING,
-    MOVEFILE_WRITE_THROUGH, MOVE_FILE_FLAGS, OPEN_ALWAYS, OPEN_EXISTING,
+    MOVEFILE_WRITE_THROUGH, MOVE_FILE_FLAGS, OPEN_ALWAYS, OPEN_EXISTING, WIN32_FIND_DATAW,
 };
+use windows::Win32::System::Ioctl::{
+    FILE_ALLOCATED_RANGE_BUFFER, FILE_SET_SPARSE_BUFFER, FILE_ZERO_DATA_INFORMATION,
+    FSCTL_GET_COMPRESSION, FSCTL_QUERY_ALLOCATED_RANGES, FSCTL_SET_COMPRESSION, FSCTL_SET_SPARSE,
+    FSCTL_SET_ZERO_DATA,
+};
+use windows::Win32::System::SystemServices::{IO_REPARSE_TAG_MOUNT_POINT, IO_REPARSE_TAG_SYMLINK};
+use windows::Win32::System::IO::{CancelIoEx, DeviceIoControl, GetOverlappedResult, OVERLAPPED};
 
 /// File attributes for Windows files.
 #[derive(Clone, Copy, Debug, Default)]
@@ -42,6 +76,13 @@ impl FileAttributes {
     /// The file is temporary.
     pub const TEMPORARY: Self = Self(FILE_ATTRIBUTE_TEMPORARY);
 
+    /// The file or directory is a reparse point (symbolic link, junction, etc.).
+    pub const REPARSE_POINT: Self = Self(FILE_ATTRIBUTE_REPARSE_POINT);
+
+    /// The file or directory is compressed. Set with [`set_compressed`], not
+    /// [`set_attributes`].
+    pub const COMPRESSED: Self = Self(FILE_ATTRIBUTE_COMPRESSED);
+
     /// Checks if this represents a directory.
     pub fn is_directory(&self) -> bool {
         (self.0 .0 & FILE_ATTRIBUTE_DIRECTORY.0) != 0
@@ -62,6 +103,16 @@ impl FileAttributes {
         (self.0 .0 & FILE_ATTRIBUTE_SYSTEM.0) != 0
     }
 
+    /// Checks if this file is a reparse point.
+    pub fn is_reparse_point(&self) -> bool {
+        (self.0 .0 & FILE_ATTRIBUTE_REPARSE_POINT.0) != 0
+    }
+
+    /// Checks if this file or directory is NTFS-compressed.
+    pub fn is_compressed(&self) -> bool {
+        (self.0 .0 & FILE_ATTRIBUTE_COMPRESSED.0) != 0
+    }
+
     /// Combines two sets of attributes.
     pub fn with(self, other: Self) -> Self {
         Self(FILE_FLAGS_AND_ATTRIBUTES(self.0 .0 | other.0 .0))
@@ -212,6 +263,207 @@ pub fn move_file_with_options(
     Ok(())
 }
 
+/// Replaces a file's contents atomically.
+///
+/// `data` is written to a temporary file next to `path`, flushed to disk, and
+/// then renamed over `path`. Readers see either the old contents or the new,
+/// never a partial write, and a crash at any point leaves `path` intact. The
+/// temporary file is removed if anything fails, but a crash before the rename
+/// leaves it behind as `.<name>.<pid>.<n>.tmp`; see
+/// [`AtomicWriter::remove_stale`].
+///
+/// The rename is only atomic within one volume, which is why the temporary
+/// file lives in the same directory; if `path` is itself a link to another
+/// volume, the link is replaced rather than followed. The new file takes the
+/// security and attributes the directory gives new files, not the old file's.
+///
+/// # Example
+///
+/// ```no_run
+/// use ergonomic_windows::fs::write_atomic;
+///
+/// write_atomic("settings.json", br#"{"theme": "dark"}"#)?;
+/// # Ok::<(), ergonomic_windows::error::Error>(())
+/// ```
+pub fn write_atomic(path: impl AsRef<Path>, data: &[u8]) -> Result<()> {
+    let mut writer = AtomicWriter::new(path)?;
+    std::io::Write::write_all(&mut writer, data)?;
+    writer.finish()
+}
+
+/// A streaming version of [`write_atomic`].
+///
+/// Writes go to a temporary file next to the target, which replaces the target
+/// only when [`finish`](Self::finish) is called. Dropping the writer without
+/// finishing discards everything written and leaves the target untouched.
+///
+/// If the process dies before finishing, the temporary file, named
+/// `.<name>.<pid>.<n>.tmp`, is orphaned next to the target. Call
+/// [`remove_stale`](Self::remove_stale), e.g. at startup, to clean such files up.
+///
+/// # Example
+///
+/// ```no_run
+/// use ergonomic_windows::fs::AtomicWriter;
+/// use std::io::Write;
+///
+/// let mut writer = AtomicWriter::new("data.csv")?;
+/// for row in 0..1000 {
+///     writeln!(writer, "{},{}", row, row * row)?;
+/// }
+/// writer.finish()?;
+/// # Ok::<(), ergonomic_windows::error::Error>(())
+/// ```
+pub struct AtomicWriter {
+    file: Option<OwnedHandle>,
+    temp: PathBuf,
+    target: PathBuf,
+}
+
+impl AtomicWriter {
+    /// Creates the temporary file for replacing `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the target's directory is missing or not writable.
+    pub fn new(path: impl AsRef<Path>) -> Result<Self> {
+        static NEXT_ID: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+        let target = path.as_ref().to_path_buf();
+        let name = target
+            .file_name()
+            .ok_or_else(|| Error::custom("atomic write target has no file name"))?;
+        let mut temp_name = OsString::from(".");
+        temp_name.push(name);
+        temp_name.push(format!(
+            ".{}.{}.tmp",
+            std::process::id(),
+            NEXT_ID.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+        ));
+        let temp = target.with_file_name(temp_name);
+
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .share_read(false)
+            .open(&temp)?;
+        Ok(Self {
+            file: Some(file),
+            temp,
+            target,
+        })
+    }
+
+    /// Removes temporary files orphaned next to `path` by writers that never
+    /// finished, e.g. because their process crashed. Returns how many were removed.
+    ///
+    /// Files from a process that is still running are skipped, and so are
+    /// files a live writer holds open, so this is safe to call while other
+    /// processes replace the same target.
+    pub fn remove_stale(path: impl AsRef<Path>) -> Result<usize> {
+        let target = path.as_ref();
+        let name = target
+            .file_name()
+            .ok_or_else(|| Error::custom("atomic write target has no file name"))?;
+        let prefix = format!(".{}.", name.to_string_lossy());
+        let dir = match target.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+
+        let mut removed = 0;
+        for entry in read_dir(dir)? {
+            let entry = entry?;
+            let Some(file_name) = entry.path().file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            // .<name>.<pid>.<n>.tmp
+            let Some((pid, id)) = file_name
+                .strip_prefix(prefix.as_str())
+                .and_then(|rest| rest.strip_suffix(".tmp"))
+                .and_then(|rest| rest.split_once('.'))
+            else {
+                continue;
+            };
+            let (Ok(pid), Ok(_)) = (pid.parse::<u32>(), id.parse::<u64>()) else {
+                continue;
+            };
+            if pid == std::process::id() || crate::process::Process::open_for_query(pid).is_ok() {
+                continue;
+            }
+            if delete_file(entry.path()).is_ok() {
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Returns the path of the temporary file being written.
+    pub fn temp_path(&self) -> &Path {
+        &self.temp
+    }
+
+    /// Flushes the data to disk and renames the temporary file over the target.
+    ///
+    /// On failure the temporary file is removed and the target is unchanged.
+    pub fn finish(mut self) -> Result<()> {
+        let result = self.commit();
+        if result.is_err() {
+            self.discard();
+        }
+        result
+    }
+
+    fn commit(&mut self) -> Result<()> {
+        if let Some(file) = self.file.take() {
+            // SAFETY: file is a valid handle opened for writing
+            unsafe { FlushFileBuffers(file.as_raw())? };
+        }
+        move_file_with_options(
+            &self.temp,
+            &self.target,
+            MoveOptions::new().replace().write_through(),
+        )?;
+        // Renamed away, so there is nothing left to clean up
+        self.temp = PathBuf::new();
+        Ok(())
+    }
+
+    /// Closes and deletes the temporary file, if any.
+    fn discard(&mut self) {
+        self.file = None;
+        if !self.temp.as_os_str().is_empty() {
+            let _ = delete_file(&self.temp);
+            self.temp = PathBuf::new();
+        }
+    }
+}
+
+impl std::io::Write for AtomicWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let file = self
+            .file
+            .as_ref()
+            .ok_or_else(|| std::io::Error::other("atomic writer already finished"))?;
+        let mut written = 0u32;
+        // SAFETY: file is a valid handle opened for writing
+        unsafe { WriteFile(file.as_raw(), Some(buf), Some(&mut written), None) }
+            .map_err(|e| std::io::Error::from(Error::from(e)))?;
+        Ok(written as usize)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        // Writes are unbuffered; `finish` flushes to disk.
+        Ok(())
+    }
+}
+
+impl Drop for AtomicWriter {
+    fn drop(&mut self) {
+        self.discard();
+    }
+}
+
 /// Options for opening files.
 pub struct OpenOptions {
     read: bool,
@@ -222,6 +474,7 @@ pub struct OpenOptions {
     share_read: bool,
     share_write: bool,
     attributes: FileAttributes,
+    flags: FILE_FLAGS_AND_ATTRIBUTES,
 }
 
 impl Default for OpenOptions {
@@ -242,6 +495,7 @@ impl OpenOptions {
             share_read: true,
             share_write: false,
             attributes: FileAttributes::NORMAL,
+            flags: FILE_FLAGS_AND_ATTRIBUTES(0),
         }
     }
 
@@ -293,6 +547,64 @@ impl OpenOptions {
         self
     }
 
+    /// Opens with backup semantics, which is required to open a directory handle.
+    ///
+    /// Also lets a caller holding `SeBackupPrivilege` or `SeRestorePrivilege`
+    /// bypass file security checks.
+    pub fn backup_semantics(self, enable: bool) -> Self {
+        self.flag(FILE_FLAG_BACKUP_SEMANTICS, enable)
+    }
+
+    /// Opens the handle for overlapped (asynchronous) I/O.
+    ///
+    /// Every read and write on the handle must then supply an `OVERLAPPED`.
+    pub fn overlapped(self, enable: bool) -> Self {
+        self.flag(FILE_FLAG_OVERLAPPED, enable)
+    }
+
+    /// Bypasses the system cache.
+    ///
+    /// Reads and writes must start at sector-aligned offsets, be a multiple of
+    /// the sector size in length, and use sector-aligned buffers, or they fail
+    /// with `ERROR_INVALID_PARAMETER`.
+    pub fn no_buffering(self, enable: bool) -> Self {
+        self.flag(FILE_FLAG_NO_BUFFERING, enable)
+    }
+
+    /// Writes go through the cache straight to disk instead of being deferred.
+    pub fn write_through(self, enable: bool) -> Self {
+        self.flag(FILE_FLAG_WRITE_THROUGH, enable)
+    }
+
+    /// Hints that the file will be read sequentially, to optimize caching.
+    pub fn sequential_scan(self, enable: bool) -> Self {
+        self.flag(FILE_FLAG_SEQUENTIAL_SCAN, enable)
+    }
+
+    /// Deletes the file once every handle to it has been closed.
+    ///
+    /// Other opens of the file fail unless they share delete access.
+    pub fn delete_on_close(self, enable: bool) -> Self {
+        self.flag(FILE_FLAG_DELETE_ON_CLOSE, enable)
+    }
+
+    fn flag(mut self, flag: FILE_FLAGS_AND_ATTRIBUTES, enable: bool) -> Self {
+        if enable {
+            self.flags |= flag;
+        } else {
+            self.flags.0 &= !flag.0;
+        }
+        self
+    }
+
+    /// Opens a directory handle with these options.
+    ///
+    /// This implies [`backup_semantics`](Self::backup_semantics). The directory
+    /// must already exist.
+    pub fn open_dir(self, path: impl AsRef<Path>) -> Result<OwnedHandle> {
+        self.backup_semantics(true).open(path)
+    }
+
     /// Opens the file with these options.
     ///
     /// # Errors
@@ -316,7 +628,7 @@ impl OpenOptions {
                 share_mode,
                 None,
                 creation,
-                self.attributes.0,
+                self.attributes.0 | self.flags,
                 None,
             )?
         };
@@ -359,6 +671,463 @@ impl OpenOptions {
     }
 }
 
+/// Sets the logical size of an open file, extending or truncating it.
+///
+/// This moves the file pointer to `size`. On a sparse file, extending does not
+/// allocate disk space for the new region.
+pub fn set_file_size(handle: &OwnedHandle, size: u64) -> Result<()> {
+    // SAFETY: handle is a valid open file handle with write access.
+    unsafe {
+        SetFilePointerEx(handle.as_raw(), size as i64, None, FILE_BEGIN)?;
+        SetEndOfFile(handle.as_raw())?;
+    }
+    Ok(())
+}
+
+/// Marks an open file as sparse (or not), so zeroed regions need no disk space.
+///
+/// Sparse files are an NTFS (and ReFS) feature; this fails on FAT volumes.
+pub fn set_sparse(handle: &OwnedHandle, sparse: bool) -> Result<()> {
+    let input = FILE_SET_SPARSE_BUFFER {
+        SetSparse: BOOLEAN(sparse as u8),
+    };
+    let mut bytes = 0u32;
+    // SAFETY: input is a valid FILE_SET_SPARSE_BUFFER of the size passed.
+    unsafe {
+        DeviceIoControl(
+            handle.as_raw(),
+            FSCTL_SET_SPARSE,
+            Some(&input as *const _ as *const _),
+            std::mem::size_of::<FILE_SET_SPARSE_BUFFER>() as u32,
+            None,
+            0,
+            Some(&mut bytes),
+            None,
+        )?;
+    }
+    Ok(())
+}
+
+/// Zeroes `len` bytes starting at `offset`.
+///
+/// On a sparse file the range is deallocated ("punched out") rather than written.
+/// A range ending past `i64::MAX` fails with `ERROR_INVALID_PARAMETER`.
+pub fn set_zero_data(handle: &OwnedHandle, offset: u64, len: u64) -> Result<()> {
+    let end = offset
+        .checked_add(len)
+        .and_then(|end| i64::try_from(end).ok())
+        .ok_or_else(|| Error::from_win32_code(ERROR_INVALID_PARAMETER.0))?;
+    let input = FILE_ZERO_DATA_INFORMATION {
+        FileOffset: offset as i64,
+        BeyondFinalZero: end,
+    };
+    let mut bytes = 0u32;
+    // SAFETY: input is a valid FILE_ZERO_DATA_INFORMATION of the size passed.
+    unsafe {
+        DeviceIoControl(
+            handle.as_raw(),
+            FSCTL_SET_ZERO_DATA,
+            Some(&input as *const _ as *const _),
+            std::mem::size_of::<FILE_ZERO_DATA_INFORMATION>() as u32,
+            None,
+            0,
+            Some(&mut bytes),
+            None,
+        )?;
+    }
+    Ok(())
+}
+
+/// Gets the `(offset, length)` ranges of an open file that are backed by disk space.
+///
+/// For a non-sparse file this is the whole file; for a sparse file, unallocated
+/// regions (which read as zeros) are omitted.
+pub fn allocated_ranges(handle: &OwnedHandle) -> Result<Vec<(u64, u64)>> {
+    const BATCH: usize = 64;
+    let entry_size = std::mem::size_of::<FILE_ALLOCATED_RANGE_BUFFER>();
+
+    let mut query = FILE_ALLOCATED_RANGE_BUFFER {
+        FileOffset: 0,
+        Length: Metadata::from_handle(handle)?.size as i64,
+    };
+    let mut output = [FILE_ALLOCATED_RANGE_BUFFER::default(); BATCH];
+    let mut ranges = Vec::new();
+
+    while query.Length > 0 {
+        let mut bytes = 0u32;
+        // SAFETY: query and output are valid buffers of the sizes passed.
+        let result = unsafe {
+            DeviceIoControl(
+                handle.as_raw(),
+                FSCTL_QUERY_ALLOCATED_RANGES,
+                Some(&query as *const _ as *const _),
+                entry_size as u32,
+                Some(output.as_mut_ptr().cast()),
+                (entry_size * BATCH) as u32,
+                Some(&mut bytes),
+                None,
+            )
+        };
+        let more = match result {
+            Ok(()) => false,
+            Err(e) if e.code() == ERROR_MORE_DATA.to_hresult() => true,
+            Err(e) => return Err(e.into()),
+        };
+
+        let count = bytes as usize / entry_size;
+        ranges.extend(
+            output[..count]
+                .iter()
+                .map(|r| (r.FileOffset as u64, r.Length as u64)),
+        );
+        if !more || count == 0 {
+            break;
+        }
+
+        // Continue after the last range returned
+        let last = output[count - 1];
+        let end = last.FileOffset + last.Length;
+        query.Length -= end - query.FileOffset;
+        query.FileOffset = end;
+    }
+
+    Ok(ranges)
+}
+
+/// Sends a control code to a device or file system driver, with typed buffers.
+///
+/// `input` and `output` are passed as pointers with their `size_of` as the
+/// buffer sizes. Returns the number of bytes the driver wrote to `output`.
+///
+/// Control codes are built by the `CTL_CODE` macro from a device type, function
+/// number, transfer method and required access. File system requests are the
+/// `FSCTL_*` codes and go to a file or volume handle; device requests are
+/// `IOCTL_*` codes (`IOCTL_DISK_*`, `IOCTL_STORAGE_*`, ...) and usually go to a
+/// volume or physical drive handle. Both are in `windows::Win32::System::Ioctl`.
+///
+/// Variable-length results fail with `ERROR_MORE_DATA` (partial data was
+/// returned; call again to continue) or `ERROR_INSUFFICIENT_BUFFER` (nothing
+/// useful was returned; retry with a bigger buffer). Use
+/// [`device_io_control_raw`] for those, growing the buffer until the call
+/// succeeds.
+///
+/// # Safety
+///
+/// `I` and `O` must be the structures `code` expects, and `O` must be valid for
+/// any bit pattern the driver writes (plain Win32 structures and integers are).
+/// Codes whose buffers contain pointers are only sound if those pointers are
+/// valid for the driver to use.
+pub unsafe fn device_io_control<I, O>(
+    handle: &OwnedHandle,
+    code: u32,
+    input: Option<&I>,
+    output: Option<&mut O>,
+) -> Result<u32> {
+    let in_size = input.map_or(0, |_| std::mem::size_of::<I>() as u32);
+    let out_size = output
+        .as_ref()
+        .map_or(0, |_| std::mem::size_of::<O>() as u32);
+    let mut bytes = 0u32;
+    DeviceIoControl(
+        handle.as_raw(),
+        code,
+        input.map(|i| i as *const I as *const _),
+        in_size,
+        output.map(|o| o as *mut O as *mut _),
+        out_size,
+        Some(&mut bytes),
+        None,
+    )?;
+    Ok(bytes)
+}
+
+/// Sends a control code to a device or file system driver, with byte buffers.
+///
+/// Returns the number of bytes written to `output`. See [`device_io_control`]
+/// for control code conventions and the retry pattern for variable-length output.
+///
+/// # Safety
+///
+/// Codes whose buffers contain pointers are only sound if those pointers are
+/// valid for the driver to use.
+pub unsafe fn device_io_control_raw(
+    handle: &OwnedHandle,
+    code: u32,
+    input: &[u8],
+    output: &mut [u8],
+) -> Result<u32> {
+    let mut bytes = 0u32;
+    DeviceIoControl(
+        handle.as_raw(),
+        code,
+        (!input.is_empty()).then(|| input.as_ptr().cast()),
+        input.len() as u32,
+        (!output.is_empty()).then(|| output.as_mut_ptr().cast()),
+        output.len() as u32,
+        Some(&mut bytes),
+        None,
+    )?;
+    Ok(bytes)
+}
+
+/// Enables or disables NTFS compression on an open file or directory.
+///
+/// The handle needs read and write access. Compressing a directory only sets the
+/// default for files created in it afterwards.
+pub fn set_compression(handle: &OwnedHandle, enable: bool) -> Result<()> {
+    let format = if enable {
+        COMPRESSION_FORMAT_DEFAULT
+    } else {
+        COMPRESSION_FORMAT_NONE
+    };
+    // SAFETY: FSCTL_SET_COMPRESSION takes a USHORT format and has no output
+    unsafe {
+        device_io_control::<u16, ()>(handle, FSCTL_SET_COMPRESSION, Some(&format.0), None)?;
+    }
+    Ok(())
+}
+
+/// Checks whether an open file or directory is compressed.
+pub fn is_compressed(handle: &OwnedHandle) -> Result<bool> {
+    let mut format = 0u16;
+    // SAFETY: FSCTL_GET_COMPRESSION has no input and writes a USHORT format
+    unsafe {
+        device_io_control::<(), u16>(handle, FSCTL_GET_COMPRESSION, None, Some(&mut format))?;
+    }
+    Ok(format != COMPRESSION_FORMAT_NONE.0)
+}
+
+/// Enables or disables NTFS compression on a file or directory by path.
+///
+/// See [`set_compression`] for the semantics. Check the current state with
+/// [`is_path_compressed`].
+pub fn set_compressed(path: impl AsRef<Path>, enable: bool) -> Result<()> {
+    let handle = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .backup_semantics(true)
+        .open(path)?;
+    set_compression(&handle, enable)
+}
+
+/// Checks whether a file or directory is compressed, by path.
+///
+/// The path counterpart of [`is_compressed`], which takes an open handle.
+pub fn is_path_compressed(path: impl AsRef<Path>) -> Result<bool> {
+    let handle = OpenOptions::new()
+        .read(true)
+        .backup_semantics(true)
+        .open(path)?;
+    is_compressed(&handle)
+}
+
+/// Gets the 128-bit ID of a file or directory, unique within its volume.
+///
+/// The ID stays the same across renames and moves within the volume, and is
+/// shared by all hard links to a file. Combine it with the volume (e.g. from
+/// [`volume_guid_path`]) to identify a file system-wide. On NTFS the upper 64
+/// bits are zero and the rest equals [`Metadata::identity`]'s file index; ReFS
+/// uses the full width.
+pub fn file_id(path: impl AsRef<Path>) -> Result<u128> {
+    let handle = open_for_metadata(path.as_ref(), FILE_FLAGS_AND_ATTRIBUTES(0))?;
+    let mut info = FILE_ID_INFO::default();
+    // SAFETY: info is a valid output buffer of the size passed
+    unsafe {
+        GetFileInformationByHandleEx(
+            handle.as_raw(),
+            FileIdInfo,
+            &mut info as *mut _ as *mut _,
+            std::mem::size_of::<FILE_ID_INFO>() as u32,
+        )?;
+    }
+    Ok(u128::from_le_bytes(info.FileId.Identifier))
+}
+
+/// Gets the volume GUID path of the volume containing `path`.
+///
+/// Returns a path such as `\\?\Volume{26a21bda-a627-11d7-9931-806e6f6e6963}\`,
+/// which names the volume independently of drive letters and mount points.
+pub fn volume_guid_path(path: impl AsRef<Path>) -> Result<String> {
+    let wide = WideString::from_path(path.as_ref());
+    // The mount point is a prefix of the full path, plus perhaps a trailing
+    // backslash; a relative input can be much shorter than either.
+    // SAFETY: wide is null-terminated; a missing buffer only queries the length
+    let full_len =
+        crate::error::nonzero(unsafe { GetFullPathNameW(wide.as_pcwstr(), None, None) })?;
+    let mut mount_point = vec![0u16; full_len as usize + 1];
+    // SAFETY: wide is null-terminated and mount_point is a writable buffer
+    unsafe {
+        GetVolumePathNameW(wide.as_pcwstr(), &mut mount_point)?;
+    }
+
+    // A volume GUID path is 49 characters plus the terminator
+    let mut volume = [0u16; 64];
+    // SAFETY: mount_point was null-terminated by GetVolumePathNameW
+    unsafe {
+        GetVolumeNameForVolumeMountPointW(
+            windows::core::PCWSTR(mount_point.as_ptr()),
+            &mut volume,
+        )?;
+    }
+    crate::string::from_wide_buffer(&volume)
+}
+
+/// Gets the access the current process would be granted to a file or
+/// directory, as a mask of `FILE_*` rights.
+///
+/// Reads the object's owner, group, DACL and integrity label with
+/// `GetNamedSecurityInfoW` and evaluates them against the process token with
+/// [`access_check`](crate::security::access_check), so nothing is opened and
+/// no-write-up labels are honored. Use it to warn about missing permissions
+/// up front; the actual open can still fail for other reasons, such as
+/// sharing violations or the read-only attribute.
+///
+/// # Example
+///
+/// ```no_run
+/// use ergonomic_windows::fs::effective_access;
+/// use windows::Win32::Storage::FileSystem::FILE_GENERIC_WRITE;
+///
+/// let access = effective_access("C:\\Windows\\notepad.exe")?;
+/// if access & FILE_GENERIC_WRITE.0 != FILE_GENERIC_WRITE.0 {
+///     println!("read-only for this user");
+/// }
+/// # Ok::<(), ergonomic_windows::error::Error>(())
+/// ```
+pub fn effective_access(path: impl AsRef<Path>) -> Result<u32> {
+    let wide = WideString::from_path(path.as_ref());
+    let mut raw = PSECURITY_DESCRIPTOR::default();
+    let info = SecurityInfo::OWNER
+        .with(SecurityInfo::GROUP)
+        .with(SecurityInfo::DACL)
+        .with(SecurityInfo::LABEL);
+    // SAFETY: wide is null-terminated; on success the descriptor is allocated
+    // with LocalAlloc and handed to from_local, which frees it
+    let descriptor = unsafe {
+        GetNamedSecurityInfoW(
+            wide.as_pcwstr(),
+            SE_FILE_OBJECT,
+            info.0,
+            None,
+            None,
+            None,
+            None,
+            &mut raw,
+        )
+        .ok()?;
+        SecurityDescriptor::from_local(raw)
+    };
+    let token = Token::current_process_with_access(TOKEN_QUERY | TOKEN_DUPLICATE)?;
+    let (_, granted) = access_check(&descriptor, &token, MAXIMUM_ALLOWED, &FILE_GENERIC_MAPPING)?;
+    Ok(granted)
+}
+
+/// The kind of a drive, as reported by `GetDriveTypeW`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DriveType {
+    /// The drive type cannot be determined.
+    Unknown,
+    /// The root path is invalid, e.g. no volume is mounted there.
+    NoRootDir,
+    /// Removable media, such as a USB stick or card reader.
+    Removable,
+    /// A fixed disk.
+    Fixed,
+    /// A network drive.
+    Remote,
+    /// A CD/DVD drive.
+    CdRom,
+    /// A RAM disk.
+    RamDisk,
+}
+
+impl DriveType {
+    fn from_raw(raw: u32) -> Self {
+        match raw {
+            1 => DriveType::NoRootDir,
+            2 => DriveType::Removable,
+            3 => DriveType::Fixed,
+            4 => DriveType::Remote,
+            5 => DriveType::CdRom,
+            6 => DriveType::RamDisk,
+            _ => DriveType::Unknown,
+        }
+    }
+}
+
+/// A drive letter with its type and label.
+#[derive(Debug, Clone)]
+pub struct DriveInfo {
+    /// The drive letter, such as `'C'`.
+    pub letter: char,
+    /// The root directory, such as `C:\`.
+    pub root: String,
+    /// The kind of drive.
+    pub drive_type: DriveType,
+    /// The volume label, if one is set and the volume is readable.
+    ///
+    /// Empty drives (e.g. a card reader without a card) have no label.
+    pub label: Option<String>,
+}
+
+/// Lists the drive letters currently in use, in alphabetical order.
+pub fn logical_drives() -> Vec<char> {
+    // SAFETY: GetLogicalDrives has no preconditions
+    let mask = unsafe { GetLogicalDrives() };
+    (0..26u8)
+        .filter(|bit| mask & (1 << bit) != 0)
+        .map(|bit| (b'A' + bit) as char)
+        .collect()
+}
+
+/// Gets the type of the drive with the given root directory, such as `C:\`.
+///
+/// The root needs a trailing backslash; without one the result is `NoRootDir`.
+pub fn drive_type(root: &str) -> DriveType {
+    let root_wide = WideString::new(root);
+    // SAFETY: root_wide is a valid null-terminated wide string
+    DriveType::from_raw(unsafe { GetDriveTypeW(root_wide.as_pcwstr()) })
+}
+
+/// Lists the drive letters in use with their types and labels.
+///
+/// Labels are best-effort: querying them never fails the listing.
+pub fn drives() -> Vec<DriveInfo> {
+    logical_drives()
+        .into_iter()
+        .map(|letter| {
+            let root = format!("{}:\\", letter);
+            DriveInfo {
+                letter,
+                drive_type: drive_type(&root),
+                label: volume_label(&root).filter(|label| !label.is_empty()),
+                root,
+            }
+        })
+        .collect()
+}
+
+/// Reads the label of the volume at `root`.
+fn volume_label(root: &str) -> Option<String> {
+    let root_wide = WideString::new(root);
+    let mut label = [0u16; 261];
+    // SAFETY: label is a valid buffer; the other outputs are not requested.
+    // Empty removable drives fail here instead of prompting, as
+    // SetErrorMode is left to the application.
+    unsafe {
+        GetVolumeInformationW(
+            root_wide.as_pcwstr(),
+            Some(&mut label),
+            None,
+            None,
+            None,
+            None,
+        )
+    }
+    .ok()?;
+    from_wide(&label).ok()
+}
+
 /// Gets the Windows system directory path (e.g., `C:\Windows\System32`).
 pub fn get_system_directory() -> Result<PathBuf> {
     use windows::Win32::System::SystemInformation::GetSystemDirectoryW;
@@ -366,11 +1135,7 @@ pub fn get_system_directory() -> Result<PathBuf> {
     let mut buffer = vec![0u16; 260]; // MAX_PATH
                                       // SAFETY: buffer is a valid mutable slice with sufficient capacity.
                                       // GetSystemDirectoryW writes at most buffer.len() characters.
-    let len = unsafe { GetSystemDirectoryW(Some(&mut buffer)) } as usize;
-
-    if len == 0 {
-        return Err(crate::error::last_error());
-    }
+    let len = crate::error::nonzero(unsafe { GetSystemDirectoryW(Some(&mut buffer)) })? as usize;
 
     buffer.truncate(len);
     let path_str = from_wide(&buffer)?;
@@ -383,11 +1148,7 @@ pub fn get_windows_directory() -> Result<PathBuf> {
 
     let mut buffer = vec![0u16; 260]; // MAX_PATH
                                       // SAFETY: buffer is a valid mutable slice with sufficient capacity.
-    let len = unsafe { GetWindowsDirectoryW(Some(&mut buffer)) } as usize;
-
-    if len == 0 {
-        return Err(crate::error::last_error());
-    }
+    let len = crate::error::nonzero(unsafe { GetWindowsDirectoryW(Some(&mut buffer)) })? as usize;
 
     buffer.truncate(len);
     let path_str = from_wide(&buffer)?;
@@ -400,17 +1161,737 @@ pub fn get_temp_directory() -> Result<PathBuf> {
 
     let mut buffer = vec![0u16; 260]; // MAX_PATH
                                       // SAFETY: buffer is a valid mutable slice with sufficient capacity.
-    let len = unsafe { GetTempPathW(Some(&mut buffer)) } as usize;
-
-    if len == 0 {
-        return Err(crate::error::last_error());
-    }
+    let len = crate::error::nonzero(unsafe { GetTempPathW(Some(&mut buffer)) })? as usize;
 
     buffer.truncate(len);
     let path_str = from_wide(&buffer)?;
     Ok(PathBuf::from(path_str))
 }
 
+/// Converts a `FILETIME` into a single 64-bit value.
+fn filetime_to_u64(ft: FILETIME) -> u64 {
+    ((ft.dwHighDateTime as u64) << 32) | (ft.dwLowDateTime as u64)
+}
+
+/// Metadata about a file or directory.
+///
+/// Times are file times (100-nanosecond intervals since January 1, 1601 UTC),
+/// matching [`crate::time::SystemTime::from_file_time`].
+#[derive(Debug, Clone, Copy)]
+pub struct Metadata {
+    /// File attributes.
+    pub attributes: FileAttributes,
+    /// File size in bytes.
+    pub size: u64,
+    /// Creation time.
+    pub creation_time: u64,
+    /// Last access time.
+    pub last_access_time: u64,
+    /// Last write time.
+    pub last_write_time: u64,
+    /// Serial number of the volume containing the file.
+    pub volume_serial_number: u32,
+    /// File index, unique per volume.
+    pub file_index: u64,
+    /// Number of hard links to the file.
+    pub number_of_links: u32,
+}
+
+impl Metadata {
+    /// Returns true if this is a directory.
+    pub fn is_dir(&self) -> bool {
+        self.attributes.is_directory()
+    }
+
+    /// Returns true if this is a regular file.
+    pub fn is_file(&self) -> bool {
+        !self.attributes.is_directory()
+    }
+
+    /// Returns the `(volume_serial_number, file_index)` pair identifying this file.
+    ///
+    /// Two paths referring to the same file (e.g. through a link) have equal identities.
+    pub fn identity(&self) -> (u32, u64) {
+        (self.volume_serial_number, self.file_index)
+    }
+
+    fn from_handle(handle: &OwnedHandle) -> Result<Self> {
+        let mut info = BY_HANDLE_FILE_INFORMATION::default();
+        // SAFETY: handle is a valid open file handle, info is a valid output parameter.
+        unsafe {
+            GetFileInformationByHandle(handle.as_raw(), &mut info)?;
+        }
+
+        Ok(Self {
+            attributes: FileAttributes(FILE_FLAGS_AND_ATTRIBUTES(info.dwFileAttributes)),
+            size: ((info.nFileSizeHigh as u64) << 32) | (info.nFileSizeLow as u64),
+            creation_time: filetime_to_u64(info.ftCreationTime),
+            last_access_time: filetime_to_u64(info.ftLastAccessTime),
+            last_write_time: filetime_to_u64(info.ftLastWriteTime),
+            volume_serial_number: info.dwVolumeSerialNumber,
+            file_index: ((info.nFileIndexHigh as u64) << 32) | (info.nFileIndexLow as u64),
+            number_of_links: info.nNumberOfLinks,
+        })
+    }
+}
+
+/// Opens a path for attribute queries only.
+fn open_for_metadata(path: &Path, flags: FILE_FLAGS_AND_ATTRIBUTES) -> Result<OwnedHandle> {
+    let wide = WideString::from_path(path);
+    // SAFETY: wide is a valid null-terminated wide string. FILE_FLAG_BACKUP_SEMANTICS
+    // is required to obtain a handle to a directory.
+    let handle = unsafe {
+        CreateFileW(
+            wide.as_pcwstr(),
+            FILE_READ_ATTRIBUTES.0,
+            FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE,
+            None,
+            OPEN_EXISTING,
+            FILE_FLAG_BACKUP_SEMANTICS | flags,
+            None,
+        )?
+    };
+    OwnedHandle::new(handle)
+}
+
+/// Gets the metadata of a file or directory, following symbolic links.
+///
+/// # Errors
+///
+/// Returns an error if the path does not exist or is not accessible.
+pub fn metadata(path: impl AsRef<Path>) -> Result<Metadata> {
+    let handle = open_for_metadata(path.as_ref(), FILE_FLAGS_AND_ATTRIBUTES(0))?;
+    Metadata::from_handle(&handle)
+}
+
+/// Gets the metadata of a file or directory without following symbolic links.
+///
+/// # Errors
+///
+/// Returns an error if the path does not exist or is not accessible.
+pub fn symlink_metadata(path: impl AsRef<Path>) -> Result<Metadata> {
+    let handle = open_for_metadata(path.as_ref(), FILE_FLAG_OPEN_REPARSE_POINT)?;
+    Metadata::from_handle(&handle)
+}
+
+/// An entry returned by [`read_dir`] or [`walk_dir`].
+#[derive(Debug, Clone)]
+pub struct DirEntry {
+    path: PathBuf,
+    attributes: FileAttributes,
+    size: u64,
+    reparse_tag: u32,
+}
+
+impl DirEntry {
+    fn from_find_data(parent: &Path, data: &WIN32_FIND_DATAW) -> Self {
+        let name_len = data
+            .cFileName
+            .iter()
+            .position(|&c| c == 0)
+            .unwrap_or(data.cFileName.len());
+        let name = OsString::from_wide(&data.cFileName[..name_len]);
+        let attributes = FileAttributes(FILE_FLAGS_AND_ATTRIBUTES(data.dwFileAttributes));
+
+        Self {
+            path: parent.join(name),
+            attributes,
+            size: ((data.nFileSizeHigh as u64) << 32) | (data.nFileSizeLow as u64),
+            // dwReserved0 holds the reparse tag when the entry is a reparse point.
+            reparse_tag: if attributes.is_reparse_point() {
+                data.dwReserved0
+            } else {
+                0
+            },
+        }
+    }
+
+    /// Returns the full path of this entry.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the file name of this entry.
+    pub fn file_name(&self) -> &std::ffi::OsStr {
+        self.path.file_name().unwrap_or_default()
+    }
+
+    /// Returns the attributes of this entry.
+    pub fn attributes(&self) -> FileAttributes {
+        self.attributes
+    }
+
+    /// Returns the file size in bytes (0 for directories).
+    pub fn len(&self) -> u64 {
+        self.size
+    }
+
+    /// Returns true if the file is empty.
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Returns true if this entry is a directory (or a link to one).
+    pub fn is_dir(&self) -> bool {
+        self.attributes.is_directory()
+    }
+
+    /// Returns true if this entry is a symbolic link or junction.
+    pub fn is_symlink(&self) -> bool {
+        self.reparse_tag == IO_REPARSE_TAG_SYMLINK || self.reparse_tag == IO_REPARSE_TAG_MOUNT_POINT
+    }
+
+    /// Queries the full metadata of this entry, following symbolic links.
+    pub fn metadata(&self) -> Result<Metadata> {
+        metadata(&self.path)
+    }
+}
+
+/// An iterator over the entries of a directory.
+///
+/// The special `.` and `..` entries are skipped.
+pub struct ReadDir {
+    handle: HANDLE,
+    parent: PathBuf,
+    first: Option<WIN32_FIND_DATAW>,
+}
+
+impl ReadDir {
+    fn close(&mut self) {
+        if self.handle != INVALID_HANDLE_VALUE {
+            // SAFETY: handle was returned by FindFirstFileExW and has not been closed yet.
+            unsafe {
+                let _ = FindClose(self.handle);
+            }
+            self.handle = INVALID_HANDLE_VALUE;
+        }
+    }
+}
+
+impl Iterator for ReadDir {
+    type Item = Result<DirEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let data = match self.first.take() {
+                Some(data) => data,
+                None => {
+                    if self.handle == INVALID_HANDLE_VALUE {
+                        return None;
+                    }
+                    let mut data = WIN32_FIND_DATAW::default();
+                    // SAFETY: handle is an open find handle, data is a valid output parameter.
+                    match unsafe { FindNextFileW(self.handle, &mut data) } {
+                        Ok(()) => data,
+                        Err(e) => {
+                            self.close();
+                            if e.code() == ERROR_NO_MORE_FILES.to_hresult() {
+                                return None;
+                            }
+                            return Some(Err(e.into()));
+                        }
+                    }
+                }
+            };
+
+            let name = &data.cFileName;
+            let is_dot = name[0] == b'.' as u16 && name[1] == 0;
+            let is_dot_dot = name[0] == b'.' as u16 && name[1] == b'.' as u16 && name[2] == 0;
+            if is_dot || is_dot_dot {
+                continue;
+            }
+
+            return Some(Ok(DirEntry::from_find_data(&self.parent, &data)));
+        }
+    }
+}
+
+impl Drop for ReadDir {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+/// Returns an iterator over the entries of a directory.
+///
+/// # Errors
+///
+/// Returns an error if the directory does not exist or is not accessible.
+pub fn read_dir(path: impl AsRef<Path>) -> Result<ReadDir> {
+    let parent = path.as_ref().to_path_buf();
+    let pattern = WideString::from_path(&parent.join("*"));
+    let mut data = WIN32_FIND_DATAW::default();
+
+    // SAFETY: pattern is a valid null-terminated wide string and data is a valid
+    // WIN32_FIND_DATAW output buffer for the FindExInfoBasic information level.
+    let result = unsafe {
+        FindFirstFileExW(
+            pattern.as_pcwstr(),
+            FindExInfoBasic,
+            &mut data as *mut _ as *mut _,
+            FindExSearchNameMatch,
+            None,
+            FIND_FIRST_EX_LARGE_FETCH,
+        )
+    };
+
+    match result {
+        Ok(handle) => Ok(ReadDir {
+            handle,
+            parent,
+            first: Some(data),
+        }),
+        // An empty volume root has no entries at all, not even `.` and `..`.
+        Err(e) if e.code() == ERROR_FILE_NOT_FOUND.to_hresult() && is_dir(&parent) => Ok(ReadDir {
+            handle: INVALID_HANDLE_VALUE,
+            parent,
+            first: None,
+        }),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// A recursive, depth-first directory walker created by [`walk_dir`].
+///
+/// Directories are yielded before their contents. The walk uses an explicit
+/// stack rather than recursion, so arbitrarily deep trees are safe to traverse.
+///
+/// # Example
+///
+/// ```no_run
+/// use ergonomic_windows::fs::walk_dir;
+///
+/// for entry in walk_dir(r"C:\Projects").max_depth(3).skip_hidden(true) {
+///     let entry = entry?;
+///     println!("{}", entry.path().display());
+/// }
+/// # Ok::<(), ergonomic_windows::error::Error>(())
+/// ```
+pub struct WalkDir {
+    root: PathBuf,
+    follow_links: bool,
+    max_depth: usize,
+    skip_hidden: bool,
+    started: bool,
+    /// Open directories, each with its identity when links are being followed.
+    stack: Vec<(ReadDir, Option<(u32, u64)>)>,
+    /// An error to yield after the entry that caused it.
+    pending_error: Option<Error>,
+}
+
+impl WalkDir {
+    /// Sets whether symbolic links and junctions to directories are descended into.
+    ///
+    /// When enabled, loops are detected by comparing each directory's
+    /// `(volume_serial_number, file_index)` against its ancestors, and reported as errors.
+    /// Defaults to `false`.
+    pub fn follow_links(mut self, follow: bool) -> Self {
+        self.follow_links = follow;
+        self
+    }
+
+    /// Sets the maximum depth to descend to.
+    ///
+    /// Entries directly inside the root have depth 1. Defaults to unlimited.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Sets whether hidden files are skipped.
+    ///
+    /// Hidden directories are pruned along with their contents. Defaults to `false`.
+    pub fn skip_hidden(mut self, skip: bool) -> Self {
+        self.skip_hidden = skip;
+        self
+    }
+
+    fn push_dir(&mut self, path: &Path) -> Result<()> {
+        let identity = if self.follow_links {
+            let id = metadata(path)?.identity();
+            if self.stack.iter().any(|(_, ancestor)| *ancestor == Some(id)) {
+                return Err(Error::custom(format!(
+                    "File system loop detected at {}",
+                    path.display()
+                )));
+            }
+            Some(id)
+        } else {
+            None
+        };
+
+        let dir = read_dir(path)?;
+        self.stack.push((dir, identity));
+        Ok(())
+    }
+}
+
+impl Iterator for WalkDir {
+    type Item = Result<DirEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(err) = self.pending_error.take() {
+            return Some(Err(err));
+        }
+
+        if !self.started {
+            self.started = true;
+            if self.max_depth == 0 {
+                return None;
+            }
+            let root = self.root.clone();
+            if let Err(e) = self.push_dir(&root) {
+                return Some(Err(e));
+            }
+        }
+
+        loop {
+            let depth = self.stack.len();
+            let (dir, _) = self.stack.last_mut()?;
+
+            let entry = match dir.next() {
+                None => {
+                    self.stack.pop();
+                    continue;
+                }
+                Some(Err(e)) => return Some(Err(e)),
+                Some(Ok(entry)) => entry,
+            };
+
+            if self.skip_hidden && entry.attributes().is_hidden() {
+                continue;
+            }
+
+            let descend = entry.is_dir()
+                && depth < self.max_depth
+                && (self.follow_links || !entry.attributes().is_reparse_point());
+            if descend {
+                if let Err(e) = self.push_dir(entry.path()) {
+                    self.pending_error = Some(e);
+                }
+            }
+
+            return Some(Ok(entry));
+        }
+    }
+}
+
+/// Recursively walks a directory tree, depth-first.
+///
+/// The root itself is not yielded. Use the builder methods on [`WalkDir`] to
+/// configure link following, depth limits, and hidden-file pruning.
+pub fn walk_dir(root: impl AsRef<Path>) -> WalkDir {
+    WalkDir {
+        root: root.as_ref().to_path_buf(),
+        follow_links: false,
+        max_depth: usize::MAX,
+        skip_hidden: false,
+        started: false,
+        stack: Vec::new(),
+        pending_error: None,
+    }
+}
+
+/// Size in bytes of the buffer that receives change records.
+///
+/// 64 KiB is the largest buffer `ReadDirectoryChangesW` accepts for network shares.
+const WATCH_BUFFER_SIZE: usize = 64 * 1024;
+
+/// The kinds of change reported by a [`DirWatcher`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WatchFilter(pub FILE_NOTIFY_CHANGE);
+
+impl WatchFilter {
+    /// A file was created, deleted, or renamed.
+    pub const FILE_NAME: Self = Self(FILE_NOTIFY_CHANGE_FILE_NAME);
+
+    /// A directory was created, deleted, or renamed.
+    pub const DIR_NAME: Self = Self(FILE_NOTIFY_CHANGE_DIR_NAME);
+
+    /// File attributes changed.
+    pub const ATTRIBUTES: Self = Self(FILE_NOTIFY_CHANGE_ATTRIBUTES);
+
+    /// A file size changed (reported when the data is flushed to disk).
+    pub const SIZE: Self = Self(FILE_NOTIFY_CHANGE_SIZE);
+
+    /// A last-write time changed.
+    pub const LAST_WRITE: Self = Self(FILE_NOTIFY_CHANGE_LAST_WRITE);
+
+    /// A security descriptor changed.
+    pub const SECURITY: Self = Self(FILE_NOTIFY_CHANGE_SECURITY);
+
+    /// Combines two filters.
+    pub fn with(self, other: Self) -> Self {
+        Self(FILE_NOTIFY_CHANGE(self.0 .0 | other.0 .0))
+    }
+}
+
+impl Default for WatchFilter {
+    /// Name changes and writes, which covers most "something changed" use cases.
+    fn default() -> Self {
+        Self::FILE_NAME.with(Self::DIR_NAME).with(Self::LAST_WRITE)
+    }
+}
+
+/// What happened to a file in a watched directory.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChangeAction {
+    /// The file was added to the directory.
+    Added,
+    /// The file was removed from the directory.
+    Removed,
+    /// The file was modified (contents, attributes, times, or security).
+    Modified,
+    /// The file was renamed; this is the old name.
+    RenamedFrom,
+    /// The file was renamed; this is the new name.
+    RenamedTo,
+    /// Too many changes occurred to be recorded; the directory should be rescanned.
+    RescanNeeded,
+}
+
+impl ChangeAction {
+    fn from_raw(action: u32) -> Option<Self> {
+        match action {
+            a if a == FILE_ACTION_ADDED.0 => Some(Self::Added),
+            a if a == FILE_ACTION_REMOVED.0 => Some(Self::Removed),
+            a if a == FILE_ACTION_MODIFIED.0 => Some(Self::Modified),
+            a if a == FILE_ACTION_RENAMED_OLD_NAME.0 => Some(Self::RenamedFrom),
+            a if a == FILE_ACTION_RENAMED_NEW_NAME.0 => Some(Self::RenamedTo),
+            _ => None,
+        }
+    }
+}
+
+/// A single change reported by a [`DirWatcher`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChangeEvent {
+    /// What happened.
+    pub action: ChangeAction,
+    /// Path of the affected file, relative to the watched directory.
+    ///
+    /// Empty for [`ChangeAction::RescanNeeded`].
+    pub path: PathBuf,
+}
+
+impl ChangeEvent {
+    fn rescan_needed() -> Self {
+        Self {
+            action: ChangeAction::RescanNeeded,
+            path: PathBuf::new(),
+        }
+    }
+}
+
+/// Overlapped state shared with the kernel while a read is outstanding.
+struct WatchState {
+    overlapped: OVERLAPPED,
+    buffer: [u32; WATCH_BUFFER_SIZE / 4],
+}
+
+/// Watches a directory for changes, created by [`watch`].
+///
+/// A read is always outstanding while the watcher is alive, so changes made
+/// between calls to [`DirWatcher::next_event`] are not lost.
+pub struct DirWatcher {
+    handle: OwnedHandle,
+    event: Event,
+    recursive: bool,
+    filter: WatchFilter,
+    state: Box<UnsafeCell<WatchState>>,
+    pending: Cell<bool>,
+}
+
+impl DirWatcher {
+    /// Waits for the next batch of changes.
+    ///
+    /// Returns an empty vector if the timeout elapses with no changes. If the
+    /// change buffer overflowed, a single [`ChangeAction::RescanNeeded`] event is
+    /// returned instead.
+    pub fn next_event(&self, timeout: Option<Duration>) -> Result<Vec<ChangeEvent>> {
+        if !self.pending.get() {
+            self.arm()?;
+        }
+
+        if self.event.wait_timeout(timeout)? == WaitResult::Timeout {
+            return Ok(Vec::new());
+        }
+
+        let state = self.state.get();
+        let mut bytes = 0u32;
+        // SAFETY: the event is signaled, so the read has completed and the kernel
+        // no longer writes to the state.
+        let result = unsafe {
+            GetOverlappedResult(
+                self.handle.as_raw(),
+                &(*state).overlapped,
+                &mut bytes,
+                false,
+            )
+        };
+        self.pending.set(false);
+
+        let events = match result {
+            // A successful read with no data means the buffer overflowed.
+            Ok(()) if bytes == 0 => vec![ChangeEvent::rescan_needed()],
+            Ok(()) => {
+                // SAFETY: the completed read wrote `bytes` bytes into the buffer.
+                let buffer = unsafe {
+                    std::slice::from_raw_parts(
+                        (*state).buffer.as_ptr().cast::<u8>(),
+                        (bytes as usize).min(WATCH_BUFFER_SIZE),
+                    )
+                };
+                parse_notifications(buffer)
+            }
+            Err(e) if e.code() == ERROR_NOTIFY_ENUM_DIR.to_hresult() => {
+                vec![ChangeEvent::rescan_needed()]
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        self.arm()?;
+        Ok(events)
+    }
+
+    /// Returns the raw directory handle.
+    pub fn as_raw(&self) -> HANDLE {
+        self.handle.as_raw()
+    }
+
+    /// Issues an overlapped read for the next batch of changes.
+    fn arm(&self) -> Result<()> {
+        let state = self.state.get();
+        // SAFETY: no read is outstanding, so nothing else accesses the state. The
+        // state is boxed and outlives the read, which is cancelled on drop.
+        unsafe {
+            (*state).overlapped = OVERLAPPED {
+                hEvent: self.event.as_raw(),
+                ..Default::default()
+            };
+            ReadDirectoryChangesW(
+                self.handle.as_raw(),
+                (*state).buffer.as_mut_ptr().cast(),
+                WATCH_BUFFER_SIZE as u32,
+                self.recursive,
+                self.filter.0,
+                None,
+                Some(&mut (*state).overlapped),
+                None,
+            )?;
+        }
+        self.pending.set(true);
+        Ok(())
+    }
+}
+
+impl Drop for DirWatcher {
+    fn drop(&mut self) {
+        if self.pending.get() {
+            let state = self.state.get();
+            let mut bytes = 0u32;
+            // SAFETY: the read must finish before the buffer is freed, so cancel it
+            // and wait for the cancellation to complete.
+            unsafe {
+                let _ = CancelIoEx(self.handle.as_raw(), Some(&(*state).overlapped));
+                let _ = GetOverlappedResult(
+                    self.handle.as_raw(),
+                    &(*state).overlapped,
+                    &mut bytes,
+                    true,
+                );
+            }
+        }
+    }
+}
+
+/// Parses a buffer of `FILE_NOTIFY_INFORMATION` records.
+fn parse_notifications(buffer: &[u8]) -> Vec<ChangeEvent> {
+    let read_u32 = |at: usize| -> Option<u32> {
+        let bytes = buffer.get(at..at + 4)?;
+        Some(u32::from_ne_bytes(bytes.try_into().ok()?))
+    };
+
+    let mut events = Vec::new();
+    let mut offset = 0;
+    while let (Some(next), Some(action), Some(name_len)) =
+        (read_u32(offset), read_u32(offset + 4), read_u32(offset + 8))
+    {
+        let name_start = offset + 12;
+        let Some(name_bytes) = buffer.get(name_start..name_start + name_len as usize) else {
+            break;
+        };
+        let name: Vec<u16> = name_bytes
+            .chunks_exact(2)
+            .map(|c| u16::from_ne_bytes([c[0], c[1]]))
+            .collect();
+
+        if let Some(action) = ChangeAction::from_raw(action) {
+            events.push(ChangeEvent {
+                action,
+                path: PathBuf::from(OsString::from_wide(&name)),
+            });
+        }
+
+        if next == 0 {
+            break;
+        }
+        offset += next as usize;
+    }
+    events
+}
+
+/// Starts watching a directory for changes.
+///
+/// Set `recursive` to also report changes in subdirectories.
+///
+/// # Example
+///
+/// ```no_run
+/// use ergonomic_windows::fs::{watch, WatchFilter};
+/// use std::time::Duration;
+///
+/// let watcher = watch(r"C:\Logs", false, WatchFilter::default())?;
+/// for event in watcher.next_event(Some(Duration::from_secs(1)))? {
+///     println!("{:?} {}", event.action, event.path.display());
+/// }
+/// # Ok::<(), ergonomic_windows::error::Error>(())
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if the directory does not exist or cannot be opened.
+pub fn watch(path: impl AsRef<Path>, recursive: bool, filter: WatchFilter) -> Result<DirWatcher> {
+    let wide = WideString::from_path(path.as_ref());
+    // SAFETY: wide is a valid null-terminated wide string. FILE_FLAG_BACKUP_SEMANTICS
+    // is required to open a directory.
+    let handle = unsafe {
+        CreateFileW(
+            wide.as_pcwstr(),
+            FILE_LIST_DIRECTORY.0,
+            FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE,
+            None,
+            OPEN_EXISTING,
+            FILE_FLAG_BACKUP_SEMANTICS | FILE_FLAG_OVERLAPPED,
+            None,
+        )?
+    };
+
+    let watcher = DirWatcher {
+        handle: OwnedHandle::new(handle)?,
+        event: Event::new_manual(false)?,
+        recursive,
+        filter,
+        state: Box::new(UnsafeCell::new(WatchState {
+            overlapped: OVERLAPPED::default(),
+            buffer: [0; WATCH_BUFFER_SIZE / 4],
+        })),
+        pending: Cell::new(false),
+    };
+    watcher.arm()?;
+    Ok(watcher)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -568,4 +2049,365 @@ mod tests {
             assert!(win_dir.to_string_lossy().len() < MAX_PATH_LEN);
         }
     }
+
+    // ============================================================================
+    // Directory Walking Tests
+    // ============================================================================
+
+    fn make_test_tree(name: &str) -> PathBuf {
+        let root = env::temp_dir().join(format!("{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("sub").join("deeper")).unwrap();
+        std::fs::create_dir_all(root.join("pruned")).unwrap();
+        std::fs::write(root.join("a.txt"), b"a").unwrap();
+        std::fs::write(root.join("b.txt"), b"bb").unwrap();
+        std::fs::write(root.join("sub").join("c.txt"), b"c").unwrap();
+        std::fs::write(root.join("sub").join("deeper").join("d.txt"), b"d").unwrap();
+        std::fs::write(root.join("pruned").join("e.txt"), b"e").unwrap();
+        std::fs::write(root.join("pruned").join("f.txt"), b"f").unwrap();
+        set_attributes(root.join("pruned"), FileAttributes::HIDDEN).unwrap();
+        root
+    }
+
+    fn remove_test_tree(root: &Path) {
+        let _ = set_attributes(root.join("pruned"), FileAttributes::NORMAL);
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_read_dir() {
+        let root = make_test_tree("fs_read_dir");
+
+        let mut names: Vec<String> = read_dir(&root)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["a.txt", "b.txt", "pruned", "sub"]);
+
+        remove_test_tree(&root);
+    }
+
+    #[test]
+    fn test_walk_dir_counts_files() {
+        let root = make_test_tree("fs_walk_all");
+
+        let files = walk_dir(&root)
+            .filter_map(|e| e.ok())
+            .filter(|e| !e.is_dir())
+            .count();
+        assert_eq!(files, 6);
+
+        remove_test_tree(&root);
+    }
+
+    #[test]
+    fn test_walk_dir_skip_hidden_prunes_subtree() {
+        let root = make_test_tree("fs_walk_hidden");
+
+        let entries: Vec<DirEntry> = walk_dir(&root)
+            .skip_hidden(true)
+            .map(|e| e.unwrap())
+            .collect();
+        assert_eq!(entries.iter().filter(|e| !e.is_dir()).count(), 4);
+        assert!(entries
+            .iter()
+            .all(|e| !e.path().starts_with(root.join("pruned"))));
+
+        remove_test_tree(&root);
+    }
+
+    #[test]
+    fn test_walk_dir_max_depth() {
+        let root = make_test_tree("fs_walk_depth");
+
+        assert_eq!(walk_dir(&root).max_depth(1).count(), 4);
+        assert_eq!(walk_dir(&root).max_depth(0).count(), 0);
+
+        let files = walk_dir(&root)
+            .max_depth(2)
+            .filter_map(|e| e.ok())
+            .filter(|e| !e.is_dir())
+            .count();
+        assert_eq!(files, 5);
+
+        remove_test_tree(&root);
+    }
+
+    #[test]
+    fn test_metadata_identity() {
+        let root = make_test_tree("fs_metadata");
+
+        let a = metadata(root.join("b.txt")).unwrap();
+        let b = metadata(root.join("sub").join("..").join("b.txt")).unwrap();
+        assert_eq!(a.size, 2);
+        assert!(a.is_file());
+        assert_eq!(a.identity(), b.identity());
+        assert!(metadata(&root).unwrap().is_dir());
+
+        remove_test_tree(&root);
+    }
+
+    #[test]
+    fn test_parse_notifications() {
+        fn record(next: u32, action: u32, name: &str) -> Vec<u8> {
+            let name: Vec<u16> = name.encode_utf16().collect();
+            let mut bytes = Vec::new();
+            bytes.extend_from_slice(&next.to_ne_bytes());
+            bytes.extend_from_slice(&action.to_ne_bytes());
+            bytes.extend_from_slice(&((name.len() * 2) as u32).to_ne_bytes());
+            for unit in name {
+                bytes.extend_from_slice(&unit.to_ne_bytes());
+            }
+            bytes
+        }
+
+        let mut buffer = record(24, FILE_ACTION_RENAMED_OLD_NAME.0, "old.txt");
+        buffer.resize(24, 0);
+        buffer.extend(record(0, FILE_ACTION_RENAMED_NEW_NAME.0, "new.txt"));
+
+        let events = parse_notifications(&buffer);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].action, ChangeAction::RenamedFrom);
+        assert_eq!(events[0].path, Path::new("old.txt"));
+        assert_eq!(events[1].action, ChangeAction::RenamedTo);
+        assert_eq!(events[1].path, Path::new("new.txt"));
+    }
+
+    #[test]
+    fn test_watch_added() {
+        let root = env::temp_dir().join(format!("fs_watch_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+
+        let watcher = watch(&root, false, WatchFilter::default()).unwrap();
+        std::fs::write(root.join("new.txt"), b"x").unwrap();
+
+        let mut added = false;
+        for _ in 0..10 {
+            let events = watcher
+                .next_event(Some(Duration::from_millis(500)))
+                .unwrap();
+            if events
+                .iter()
+                .any(|e| e.action == ChangeAction::Added && e.path == Path::new("new.txt"))
+            {
+                added = true;
+                break;
+            }
+        }
+        assert!(added);
+
+        drop(watcher);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_sparse_file() {
+        use windows::Win32::Storage::FileSystem::ReadFile;
+
+        let path = env::temp_dir().join(format!("fs_sparse_{}.bin", std::process::id()));
+        let handle = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)
+            .unwrap();
+
+        // Sparse files need NTFS or ReFS
+        if set_sparse(&handle, true).is_ok() {
+            const SIZE: u64 = 256 * 1024 * 1024;
+            set_file_size(&handle, SIZE).unwrap();
+            assert_eq!(Metadata::from_handle(&handle).unwrap().size, SIZE);
+
+            let allocated: u64 = allocated_ranges(&handle)
+                .unwrap()
+                .iter()
+                .map(|&(_, len)| len)
+                .sum();
+            assert!(allocated < SIZE / 16);
+
+            // Fill the first megabyte so there is something to deallocate
+            const DATA: usize = 1024 * 1024;
+            let data = vec![0xABu8; DATA];
+            let mut buffer = vec![0u8; DATA];
+            let mut transferred = 0u32;
+            unsafe {
+                SetFilePointerEx(handle.as_raw(), 0, None, FILE_BEGIN).unwrap();
+                WriteFile(handle.as_raw(), Some(&data), Some(&mut transferred), None).unwrap();
+            }
+            let total = |ranges: Vec<(u64, u64)>| ranges.iter().map(|&(_, len)| len).sum::<u64>();
+            assert!(total(allocated_ranges(&handle).unwrap()) >= DATA as u64);
+
+            set_zero_data(&handle, 0, SIZE).unwrap();
+            assert_eq!(total(allocated_ranges(&handle).unwrap()), 0);
+            assert_eq!(Metadata::from_handle(&handle).unwrap().size, SIZE);
+
+            unsafe {
+                SetFilePointerEx(handle.as_raw(), 0, None, FILE_BEGIN).unwrap();
+                ReadFile(
+                    handle.as_raw(),
+                    Some(&mut buffer),
+                    Some(&mut transferred),
+                    None,
+                )
+                .unwrap();
+            }
+            assert_eq!(transferred as usize, DATA);
+            assert!(buffer.iter().all(|&b| b == 0));
+        }
+
+        // Overflowing ranges are rejected before reaching the file system
+        let err = set_zero_data(&handle, u64::MAX, 2).unwrap_err();
+        assert_eq!(err.win32_error_code(), Some(ERROR_INVALID_PARAMETER.0));
+        assert!(set_zero_data(&handle, i64::MAX as u64, 1).is_err());
+
+        drop(handle);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_write_atomic() {
+        let path = env::temp_dir().join(format!("fs_atomic_{}.txt", std::process::id()));
+        write_atomic(&path, b"first").unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"first");
+        write_atomic(&path, b"second").unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"second");
+
+        // Dropping without finish simulates a crash before the rename
+        let mut writer = AtomicWriter::new(&path).unwrap();
+        std::io::Write::write_all(&mut writer, b"partial").unwrap();
+        let temp = writer.temp_path().to_path_buf();
+        assert!(temp.exists());
+        assert_eq!(temp.parent(), path.parent());
+        drop(writer);
+        assert!(!temp.exists());
+        assert_eq!(std::fs::read(&path).unwrap(), b"second");
+
+        // Orphans of dead processes are cleaned up, live writers' files are not
+        let name = path.file_name().unwrap().to_string_lossy().into_owned();
+        let orphan = path.with_file_name(format!(".{}.{}.0.tmp", name, u32::MAX - 3));
+        std::fs::write(&orphan, b"orphan").unwrap();
+        let live = AtomicWriter::new(&path).unwrap();
+        assert_eq!(AtomicWriter::remove_stale(&path).unwrap(), 1);
+        assert!(!orphan.exists());
+        assert!(live.temp_path().exists());
+        drop(live);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_open_dir() {
+        let handle = OpenOptions::new()
+            .read(true)
+            .share_write(true)
+            .open_dir(env::temp_dir())
+            .unwrap();
+        assert!(Metadata::from_handle(&handle).unwrap().is_dir());
+
+        // Without backup semantics a directory can't be opened
+        assert!(OpenOptions::new().read(true).open(env::temp_dir()).is_err());
+    }
+
+    #[test]
+    fn test_delete_on_close() {
+        let path = env::temp_dir().join(format!("fs_delete_on_close_{}.tmp", std::process::id()));
+        let handle = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .delete_on_close(true)
+            .sequential_scan(true)
+            .open(&path)
+            .unwrap();
+        assert!(path.exists());
+
+        drop(handle);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_compression_round_trip() {
+        let path = env::temp_dir().join(format!("fs_compress_{}.bin", std::process::id()));
+        let handle = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .delete_on_close(true)
+            .open(&path)
+            .unwrap();
+
+        // Compression needs NTFS
+        if set_compression(&handle, true).is_ok() {
+            assert!(is_compressed(&handle).unwrap());
+            set_compression(&handle, false).unwrap();
+            assert!(!is_compressed(&handle).unwrap());
+        }
+
+        // The raw form reports bytes returned
+        let mut out = [0u8; 2];
+        if let Ok(bytes) =
+            unsafe { device_io_control_raw(&handle, FSCTL_GET_COMPRESSION, &[], &mut out) }
+        {
+            assert_eq!(bytes, 2);
+        }
+    }
+
+    #[test]
+    fn test_effective_access() {
+        let path = env::temp_dir().join(format!("fs_access_{}.tmp", std::process::id()));
+        std::fs::write(&path, b"mine").unwrap();
+
+        let access = effective_access(&path).unwrap();
+        assert_eq!(access & FILE_GENERIC_READ.0, FILE_GENERIC_READ.0);
+        assert_eq!(access & FILE_GENERIC_WRITE.0, FILE_GENERIC_WRITE.0);
+
+        delete_file(&path).unwrap();
+        assert!(effective_access(&path).is_err());
+    }
+
+    #[test]
+    fn test_logical_drives() {
+        let letters = logical_drives();
+        let system_drive = std::env::var("SystemDrive").unwrap_or_else(|_| "C:".to_string());
+        let letter = system_drive.chars().next().unwrap().to_ascii_uppercase();
+        assert!(letters.contains(&letter));
+        assert_eq!(drive_type(&format!("{}\\", system_drive)), DriveType::Fixed);
+        assert_eq!(drive_type("not a root"), DriveType::NoRootDir);
+
+        let info = drives().into_iter().find(|d| d.letter == letter).unwrap();
+        assert_eq!(info.drive_type, DriveType::Fixed);
+        assert_eq!(info.root, format!("{}:\\", letter));
+    }
+
+    #[test]
+    fn test_file_id_survives_rename() {
+        let from = env::temp_dir().join(format!("fs_file_id_{}.tmp", std::process::id()));
+        let to = env::temp_dir().join(format!("fs_file_id_{}.moved", std::process::id()));
+        std::fs::write(&from, b"id").unwrap();
+
+        let id = file_id(&from).unwrap();
+        move_file(&from, &to).unwrap();
+        assert_eq!(file_id(&to).unwrap(), id);
+
+        // Compression needs NTFS
+        if set_compressed(&to, true).is_ok() {
+            assert!(get_attributes(&to).unwrap().is_compressed());
+            assert!(is_path_compressed(&to).unwrap());
+            set_compressed(&to, false).unwrap();
+            assert!(!get_attributes(&to).unwrap().is_compressed());
+            assert!(!is_path_compressed(&to).unwrap());
+        }
+
+        assert!(volume_guid_path(env::temp_dir())
+            .unwrap()
+            .starts_with("\\\\?\\Volume{"));
+        // A relative path is shorter than its mount point
+        assert_eq!(
+            volume_guid_path(".").unwrap(),
+            volume_guid_path(env::current_dir().unwrap()).unwrap()
+        );
+        delete_file(&to).unwrap();
+    }
 }
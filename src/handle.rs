@@ -4,8 +4,14 @@
 //! close the handle when dropped.
 
 use crate::error::{Error, Result};
+use crate::thread::WaitResult;
+use std::time::Duration;
 use windows::Win32::Foundation::{
     CloseHandle, DuplicateHandle, DUPLICATE_SAME_ACCESS, HANDLE, INVALID_HANDLE_VALUE,
+    WAIT_ABANDONED, WAIT_OBJECT_0, WAIT_TIMEOUT,
+};
+use windows::Win32::System::Threading::{
+    GetCurrentProcess, GetProcessHandleCount, WaitForSingleObject, INFINITE,
 };
 
 /// A safe wrapper around a Windows `HANDLE` that automatically closes when dropped.
@@ -212,10 +218,139 @@ impl HandleExt for HANDLE {
     }
 }
 
+/// Converts an optional timeout to milliseconds for the Win32 wait functions.
+///
+/// `None` means wait forever; finite timeouts are clamped below `INFINITE`.
+pub(crate) fn timeout_ms(timeout: Option<Duration>) -> u32 {
+    match timeout {
+        Some(d) => d.as_millis().min((INFINITE - 1) as u128) as u32,
+        None => INFINITE,
+    }
+}
+
+/// A kernel object that can be waited on, such as an event, mutex, thread, or process.
+///
+/// Implementors only provide the raw handle; [`wait`](Waitable::wait) is shared, so
+/// generic code can accept `&dyn Waitable`.
+///
+/// # Example
+///
+/// ```no_run
+/// use ergonomic_windows::handle::Waitable;
+/// use ergonomic_windows::thread::{Event, WaitResult};
+/// use std::time::Duration;
+///
+/// fn is_ready(object: &dyn Waitable) -> ergonomic_windows::error::Result<bool> {
+///     Ok(object.wait(Some(Duration::ZERO))? == WaitResult::Signaled)
+/// }
+///
+/// let event = Event::new_manual(true)?;
+/// assert!(is_ready(&event)?);
+/// # Ok::<(), ergonomic_windows::error::Error>(())
+/// ```
+pub trait Waitable {
+    /// Returns the raw handle to wait on.
+    fn raw_handle(&self) -> HANDLE;
+
+    /// Waits for the object to become signaled, or for the timeout to elapse.
+    ///
+    /// `None` waits forever. Waiting has the object's usual side effects: it
+    /// resets an auto-reset event, decrements a semaphore, and acquires a mutex,
+    /// which must then be released (prefer `Mutex::lock` for mutexes).
+    fn wait(&self, timeout: Option<Duration>) -> Result<WaitResult> {
+        // SAFETY: raw_handle returns a handle that is valid for the lifetime of self
+        let result = unsafe { WaitForSingleObject(self.raw_handle(), timeout_ms(timeout)) };
+
+        match result {
+            WAIT_OBJECT_0 => Ok(WaitResult::Signaled),
+            WAIT_TIMEOUT => Ok(WaitResult::Timeout),
+            WAIT_ABANDONED => Ok(WaitResult::Abandoned),
+            _ => Err(crate::error::last_error()),
+        }
+    }
+}
+
+impl Waitable for OwnedHandle {
+    fn raw_handle(&self) -> HANDLE {
+        self.handle
+    }
+}
+
+impl Waitable for BorrowedHandle<'_> {
+    fn raw_handle(&self) -> HANDLE {
+        self.handle
+    }
+}
+
+/// Returns the number of handles the current process has open.
+///
+/// Comparing the count before and after an operation shows whether it leaks
+/// handles; see [`guard_count_delta`].
+pub fn process_handle_count() -> Result<u32> {
+    let mut count = 0u32;
+    // SAFETY: GetCurrentProcess returns a pseudo-handle that needs no closing,
+    // and count is a valid output parameter
+    unsafe {
+        GetProcessHandleCount(GetCurrentProcess(), &mut count)?;
+    }
+    Ok(count)
+}
+
+/// Runs `f` and returns its result with the net change in the process's
+/// handle count across the call.
+///
+/// A positive delta after an operation that should clean up after itself
+/// points to a leak. The count covers the whole process, so handles opened
+/// or closed by other threads meanwhile show up too; use this in controlled
+/// tests, repeat the operation many times, and allow some slack.
+///
+/// # Errors
+///
+/// Returns an error if the handle count can't be read. If that happens
+/// before the call, `f` doesn't run; if after, its result is dropped.
+///
+/// # Example
+///
+/// ```no_run
+/// use ergonomic_windows::handle::guard_count_delta;
+/// use ergonomic_windows::thread::Event;
+///
+/// let ((), delta) = guard_count_delta(|| {
+///     for _ in 0..100 {
+///         let _event = Event::new_manual(false).unwrap();
+///     }
+/// })?;
+/// assert!(delta < 10);
+/// # Ok::<(), ergonomic_windows::error::Error>(())
+/// ```
+pub fn guard_count_delta<T>(f: impl FnOnce() -> T) -> Result<(T, i64)> {
+    let before = process_handle_count()?;
+    let result = f();
+    let after = process_handle_count()?;
+    Ok((result, after as i64 - before as i64))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_events_do_not_leak_handles() {
+        use crate::thread::Event;
+
+        assert!(process_handle_count().unwrap() > 0);
+
+        let (created, delta) = guard_count_delta(|| {
+            (0..1000)
+                .filter(|_| Event::new_manual(false).is_ok())
+                .count()
+        })
+        .unwrap();
+        assert_eq!(created, 1000);
+        // A leak would add 1000; the slack absorbs other tests' handles
+        assert!(delta.abs() < 100, "handle count changed by {}", delta);
+    }
+
     #[test]
     fn test_invalid_handle_rejected() {
         let result = OwnedHandle::new(INVALID_HANDLE_VALUE);
@@ -470,4 +605,29 @@ mod tests {
         let _ = std::fs::remove_file(&temp_path2);
         let _ = std::fs::remove_file(&temp_path3);
     }
+
+    fn wait_briefly(object: &dyn Waitable) -> WaitResult {
+        object.wait(Some(Duration::from_secs(5))).unwrap()
+    }
+
+    #[test]
+    fn test_waitable_dyn() {
+        let event = crate::thread::Event::new_manual(false).unwrap();
+        assert_eq!(
+            event.wait_timeout(Some(Duration::from_millis(10))).unwrap(),
+            WaitResult::Timeout
+        );
+        event.set().unwrap();
+        assert_eq!(wait_briefly(&event), WaitResult::Signaled);
+
+        let thread = crate::thread::Thread::spawn(|| 0).unwrap();
+        assert_eq!(wait_briefly(&thread), WaitResult::Signaled);
+    }
+
+    #[test]
+    fn test_timeout_ms() {
+        assert_eq!(timeout_ms(None), INFINITE);
+        assert_eq!(timeout_ms(Some(Duration::from_millis(1500))), 1500);
+        assert_eq!(timeout_ms(Some(Duration::MAX)), INFINITE - 1);
+    }
 }
@@ -13,6 +13,7 @@
 //! - **Windows**: Create windows and handle messages
 //! - **Threading**: Threads, mutexes, events, semaphores
 //! - **Memory**: Virtual memory, heaps, memory info
+//! - **Clipboard**: Text, file lists, and custom clipboard formats
 //! - **Console**: Console I/O, colors, cursor control
 //! - **Environment**: Environment variables
 //! - **Pipes**: Anonymous and named pipes for IPC
@@ -167,6 +168,7 @@ pub mod handle;
 pub mod string;
 
 // System modules
+pub mod clipboard;
 pub mod console;
 pub mod env;
 pub mod fs;
@@ -190,34 +192,60 @@ pub mod xaml;
 /// Prelude module for convenient imports.
 pub mod prelude {
     pub use crate::error::{Error, Result, ResultExt};
-    pub use crate::fs::{exists, is_dir, is_file, FileAttributes, OpenOptions};
+    pub use crate::fs::{
+        effective_access, exists, is_dir, is_file, write_atomic, AtomicWriter, FileAttributes,
+        OpenOptions,
+    };
     pub use crate::handle::{BorrowedHandle, HandleExt, OwnedHandle};
     pub use crate::process::{Command, Process, ProcessAccess};
-    pub use crate::registry::{Access, Key, RootKey, Value};
+    pub use crate::registry::{
+        Access, Key, RegKind, RegWatcher, RegistryTransaction, RootKey, Value, WatchOptions,
+    };
     pub use crate::string::{from_wide, from_wide_buffer, to_wide, WideString};
     pub use crate::window::{
-        ExStyle, Message, MessageHandler, ShowCommand, Style, Window, WindowBuilder,
+        capture_screen, capture_window, close_modal, register_device_notification,
+        register_power_notification, register_session_notification, run_modal, BackdropType,
+        CapturedImage, CornerPreference, DeviceBroadcast, DeviceChange, DeviceFilter, ExStyle,
+        Message, MessageHandler, ModalResult, PowerEvent, SessionChange, ShowCommand, Style,
+        Window, WindowBuilder,
     };
 
     // System modules
     pub use crate::console::{Color, Console, TextAttribute};
-    pub use crate::env::{expand as env_expand, get as env_get, set as env_set};
-    pub use crate::mem::{memory_status, MemoryStatus, Protection, VirtualMemory};
+    pub use crate::env::{
+        add_to_path, expand as env_expand, get as env_get, remove_from_path, set as env_set,
+        EnvScope,
+    };
+    pub use crate::mem::{
+        memory_status, Arena, GrowableBuffer, MemoryStatus, Protection, VirtualMemory,
+    };
     pub use crate::module::Library;
-    pub use crate::pipe::{AnonymousPipe, NamedPipeClient, NamedPipeServer};
-    pub use crate::security::{is_elevated, Token};
+    pub use crate::pipe::{
+        AnonymousPipe, ConcurrentPipeServer, NamedPipeClient, NamedPipeServer, PipeConnection,
+        PipeReader, PipeWriter,
+    };
+    pub use crate::security::{
+        access_check, enable_debug_privilege, is_admin, is_elevated, RestrictOptions,
+        SecurityDescriptor, SecurityInfo, Sid, Token,
+    };
     pub use crate::sysinfo::{system_summary, OsVersion, ProcessorInfo};
-    pub use crate::thread::{current_thread_id, sleep, Event, Mutex, Semaphore, Thread};
-    pub use crate::time::{tick_count, PerformanceCounter, Stopwatch, SystemTime};
+    pub use crate::thread::{
+        current_thread_id, register_wait, sleep, wait_any, Acquired, Event, Mutex, Semaphore,
+        Thread, WaitFlags,
+    };
+    pub use crate::time::{
+        tick_count, Deadline, PerformanceCounter, Scheduler, Stopwatch, SystemTime,
+    };
 
     // UI modules
     pub use crate::controls::{
-        init_common_controls, Button, ButtonStyle, ComboBox, Control, Edit, EditStyle, Label,
-        ListBox, ProgressBar, ProgressStyle, TextAlign,
+        init_common_controls, Button, ButtonStyle, CharFormat, ComboBox, Control, Edit, EditStyle,
+        Label, ListBox, ProgressBar, ProgressStyle, RichEdit, TabControl, TextAlign, UpDown,
     };
     pub use crate::d2d::{
-        Color as D2DColor, D2DFactory, DWriteFactory, ParagraphAlignment, RenderTarget, SolidBrush,
-        TextAlignment, TextFormat,
+        BitmapRenderTarget, Color as D2DColor, D2DBitmap, D2DCanvas, D2DFactory, DWriteFactory,
+        Device as D2DDevice, DrawOutcome, ImageFormat, InterpolationMode, ParagraphAlignment,
+        RenderTarget, SolidBrush, SwapChainTarget, TextAlignment, TextFormat, TextLayout,
     };
     pub use crate::webview::{WebView, WebViewBuilder};
     pub use crate::xaml::{
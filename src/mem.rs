@@ -4,14 +4,15 @@
 //! heap management, and memory information queries.
 
 use crate::error::{Error, Result};
+use std::cell::Cell;
 use std::ptr::NonNull;
 use windows::Win32::Foundation::HANDLE;
 use windows::Win32::System::Memory::{
     GetProcessHeap, HeapAlloc, HeapCreate, HeapDestroy, HeapFree, HeapReAlloc, HeapSize,
     VirtualAlloc, VirtualFree, VirtualLock, VirtualProtect, VirtualQuery, VirtualUnlock, HEAP_NONE,
-    MEMORY_BASIC_INFORMATION, MEM_COMMIT, MEM_DECOMMIT, MEM_RELEASE, MEM_RESERVE, PAGE_EXECUTE,
-    PAGE_EXECUTE_READ, PAGE_EXECUTE_READWRITE, PAGE_NOACCESS, PAGE_PROTECTION_FLAGS, PAGE_READONLY,
-    PAGE_READWRITE,
+    MEMORY_BASIC_INFORMATION, MEM_COMMIT, MEM_DECOMMIT, MEM_RELEASE, MEM_RESERVE, MEM_RESET,
+    PAGE_EXECUTE, PAGE_EXECUTE_READ, PAGE_EXECUTE_READWRITE, PAGE_NOACCESS, PAGE_PROTECTION_FLAGS,
+    PAGE_READONLY, PAGE_READWRITE,
 };
 use windows::Win32::System::SystemInformation::{
     GetSystemInfo, GlobalMemoryStatusEx, MEMORYSTATUSEX, SYSTEM_INFO,
@@ -417,6 +418,237 @@ impl Drop for Heap {
     }
 }
 
+/// A bump allocator over a reserved region of virtual memory.
+///
+/// The whole region is reserved up front but pages are only committed as
+/// allocations reach them, so a large limit costs address space, not memory.
+/// Allocating is a pointer bump; individual allocations are never freed, only
+/// all at once by [`reset`](Self::reset) or dropping the arena. This suits
+/// parsers and other workloads with many short-lived temporaries.
+///
+/// # Example
+///
+/// ```no_run
+/// use ergonomic_windows::mem::Arena;
+///
+/// let mut arena = Arena::with_reserved(16 * 1024 * 1024)?;
+/// let block = arena.alloc(256, 16)?;
+/// // SAFETY: the block is 256 writable bytes owned by the arena
+/// unsafe { block.as_ptr().write_bytes(0, 256) };
+/// arena.reset();
+/// # Ok::<(), ergonomic_windows::error::Error>(())
+/// ```
+pub struct Arena {
+    region: VirtualMemory,
+    used: Cell<usize>,
+    committed: Cell<usize>,
+    page_size: usize,
+}
+
+impl Arena {
+    /// Reserves `max` bytes of address space for the arena, committing none.
+    pub fn with_reserved(max: usize) -> Result<Self> {
+        Ok(Self {
+            region: VirtualMemory::reserve(max)?,
+            used: Cell::new(0),
+            committed: Cell::new(0),
+            page_size: system_info().page_size as usize,
+        })
+    }
+
+    /// Allocates `size` bytes aligned to `align`, committing pages as needed.
+    ///
+    /// The memory is readable and writable, and zeroed the first time its pages
+    /// are committed; after a [`reset`](Self::reset) it holds stale data. It
+    /// stays valid until the arena is reset or dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `align` is not a power of two, if the allocation
+    /// doesn't fit in the reserved region, or if committing fails.
+    pub fn alloc(&self, size: usize, align: usize) -> Result<NonNull<u8>> {
+        if !align.is_power_of_two() {
+            return Err(Error::custom("Alignment must be a power of two"));
+        }
+
+        let base = self.region.as_ptr() as usize;
+        let start = (base + self.used.get())
+            .checked_next_multiple_of(align)
+            .map(|address| address - base);
+        let end = start.and_then(|start| start.checked_add(size));
+        let (Some(start), Some(end)) = (start, end) else {
+            return Err(Error::custom("Arena allocation size overflows"));
+        };
+        if end > self.region.size() {
+            return Err(Error::custom(format!(
+                "Arena exhausted: {} bytes requested with {} of {} used",
+                size,
+                self.used.get(),
+                self.region.size()
+            )));
+        }
+
+        if end > self.committed.get() {
+            let committed = end.next_multiple_of(self.page_size).min(self.region.size());
+            self.region.commit(
+                self.committed.get(),
+                committed - self.committed.get(),
+                Protection::ReadWrite,
+            )?;
+            self.committed.set(committed);
+        }
+
+        self.used.set(end);
+        // SAFETY: start is within the region, whose base is non-null
+        Ok(unsafe { NonNull::new_unchecked(self.region.as_ptr().add(start)) })
+    }
+
+    /// Rewinds the arena so its memory can be reused.
+    ///
+    /// Committed pages stay committed, so refilling the arena doesn't commit
+    /// them again.
+    ///
+    /// The pointers returned by [`alloc`](Self::alloc) don't borrow the arena,
+    /// so they survive this call. They are dangling afterwards: the memory
+    /// they point to is handed out again by later allocations, and the caller
+    /// must not use any pointer obtained before the reset.
+    pub fn reset(&mut self) {
+        self.used.set(0);
+    }
+
+    /// Rewinds the arena and tells the system the contents of its committed
+    /// pages are no longer needed (`MEM_RESET`).
+    ///
+    /// The pages stay committed, but under memory pressure the system can
+    /// discard them instead of writing them to the page file. Their contents
+    /// are undefined afterwards. As with [`reset`](Self::reset), pointers
+    /// obtained before the call must not be used again.
+    pub fn reset_and_discard(&mut self) -> Result<()> {
+        self.used.set(0);
+        if self.committed.get() == 0 {
+            return Ok(());
+        }
+
+        // SAFETY: The committed range lies within the region we own. The
+        // protection argument is ignored for MEM_RESET.
+        let ptr = unsafe {
+            VirtualAlloc(
+                Some(self.region.as_ptr() as *const _),
+                self.committed.get(),
+                MEM_RESET,
+                PAGE_NOACCESS,
+            )
+        };
+        if ptr.is_null() {
+            return Err(crate::error::last_error());
+        }
+        Ok(())
+    }
+
+    /// Returns the number of bytes allocated, including alignment padding.
+    pub fn used(&self) -> usize {
+        self.used.get()
+    }
+
+    /// Returns the number of bytes committed so far.
+    pub fn committed(&self) -> usize {
+        self.committed.get()
+    }
+
+    /// Returns the size of the reserved region, the most the arena can hold.
+    pub fn capacity(&self) -> usize {
+        self.region.size()
+    }
+}
+
+/// A byte buffer over a reserved region that commits pages as it grows.
+///
+/// Unlike a `Vec<u8>`, growing never reallocates: the buffer's address is fixed
+/// for its whole life, so pointers into it stay valid. Only the pages that have
+/// been asked for cost memory; the rest of the reservation is address space.
+///
+/// # Example
+///
+/// ```no_run
+/// use ergonomic_windows::mem::GrowableBuffer;
+///
+/// let mut buffer = GrowableBuffer::with_max(64 * 1024 * 1024)?;
+/// buffer.ensure(100)?[..5].copy_from_slice(b"hello");
+/// let more = buffer.ensure(100_000)?;
+/// assert_eq!(&more[..5], b"hello");
+/// # Ok::<(), ergonomic_windows::error::Error>(())
+/// ```
+pub struct GrowableBuffer {
+    region: VirtualMemory,
+    committed: usize,
+    page_size: usize,
+}
+
+impl GrowableBuffer {
+    /// Reserves `max` bytes of address space for the buffer, committing none.
+    pub fn with_max(max: usize) -> Result<Self> {
+        Ok(Self {
+            region: VirtualMemory::reserve(max)?,
+            committed: 0,
+            page_size: system_info().page_size as usize,
+        })
+    }
+
+    /// Makes sure the first `len` bytes are committed and returns them.
+    ///
+    /// Commits whole pages, so [`committed`](Self::committed) can exceed `len`.
+    /// Newly committed pages are zeroed; bytes already committed keep their
+    /// contents.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `len` exceeds the reservation or committing fails.
+    pub fn ensure(&mut self, len: usize) -> Result<&mut [u8]> {
+        if len > self.region.size() {
+            return Err(Error::custom(format!(
+                "GrowableBuffer exhausted: {} bytes requested of {} reserved",
+                len,
+                self.region.size()
+            )));
+        }
+
+        if len > self.committed {
+            let committed = len.next_multiple_of(self.page_size).min(self.region.size());
+            self.region.commit(
+                self.committed,
+                committed - self.committed,
+                Protection::ReadWrite,
+            )?;
+            self.committed = committed;
+        }
+
+        // SAFETY: The first `committed` bytes are committed read-write, and
+        // `&mut self` guarantees no other borrow of them exists.
+        Ok(unsafe { std::slice::from_raw_parts_mut(self.region.as_ptr(), len) })
+    }
+
+    /// Returns the committed bytes.
+    pub fn as_slice(&self) -> &[u8] {
+        // SAFETY: The first `committed` bytes are committed and readable
+        unsafe { std::slice::from_raw_parts(self.region.as_ptr(), self.committed) }
+    }
+
+    /// Returns the base address of the buffer, which never changes.
+    pub fn as_ptr(&self) -> *mut u8 {
+        self.region.as_ptr()
+    }
+
+    /// Returns the number of bytes committed so far, a multiple of the page size.
+    pub fn committed(&self) -> usize {
+        self.committed
+    }
+
+    /// Returns the size of the reserved region, the most the buffer can grow to.
+    pub fn reserved(&self) -> usize {
+        self.region.size()
+    }
+}
+
 /// Global memory status information.
 #[derive(Debug, Clone)]
 pub struct MemoryStatus {
@@ -491,10 +723,143 @@ pub fn system_info() -> SystemMemoryInfo {
     }
 }
 
+/// Atomic operations on raw memory, such as a region shared between processes.
+///
+/// These compile to the same locked instructions as the `Interlocked*`
+/// family, so they interoperate with other processes (including C code using
+/// `InterlockedExchangeAdd64` and friends) that access the same memory.
+///
+/// Every pointer must be valid for reads and writes and 8-byte aligned, and
+/// must only ever be accessed atomically while other threads or processes
+/// may use it. Misaligned 64-bit atomics are not atomic on x86 and fault on ARM.
+pub mod atomic {
+    use std::sync::atomic::{AtomicI64, Ordering};
+
+    /// Adds `val` to `*ptr` and returns the previous value (`InterlockedExchangeAdd64`).
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be valid, 8-byte aligned, and only accessed atomically.
+    pub unsafe fn fetch_add_i64(ptr: *mut i64, val: i64) -> i64 {
+        // SAFETY: The caller guarantees ptr is valid and aligned
+        unsafe { AtomicI64::from_ptr(ptr) }.fetch_add(val, Ordering::SeqCst)
+    }
+
+    /// Stores `new` in `*ptr` if it equals `expected` (`InterlockedCompareExchange64`).
+    ///
+    /// Returns the value `*ptr` held before the operation; the exchange
+    /// happened if it equals `expected`.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be valid, 8-byte aligned, and only accessed atomically.
+    pub unsafe fn compare_exchange_i64(ptr: *mut i64, expected: i64, new: i64) -> i64 {
+        // SAFETY: The caller guarantees ptr is valid and aligned
+        match unsafe { AtomicI64::from_ptr(ptr) }.compare_exchange(
+            expected,
+            new,
+            Ordering::SeqCst,
+            Ordering::SeqCst,
+        ) {
+            Ok(previous) | Err(previous) => previous,
+        }
+    }
+
+    /// Reads `*ptr` with acquire ordering.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be valid, 8-byte aligned, and only accessed atomically.
+    pub unsafe fn load_i64(ptr: *const i64) -> i64 {
+        // SAFETY: The caller guarantees ptr is valid and aligned; a load never writes
+        unsafe { AtomicI64::from_ptr(ptr as *mut i64) }.load(Ordering::Acquire)
+    }
+
+    /// Writes `val` to `*ptr` with release ordering.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be valid, 8-byte aligned, and only accessed atomically.
+    pub unsafe fn store_i64(ptr: *mut i64, val: i64) {
+        // SAFETY: The caller guarantees ptr is valid and aligned
+        unsafe { AtomicI64::from_ptr(ptr) }.store(val, Ordering::Release)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_arena_commits_on_demand() {
+        let page = system_info().page_size as usize;
+        let mut arena = Arena::with_reserved(64 * page).unwrap();
+        assert_eq!(arena.committed(), 0);
+
+        let mut blocks = Vec::new();
+        for i in 0..(4 * page / 24) {
+            let block = arena.alloc(24, 8).unwrap();
+            assert_eq!(block.as_ptr() as usize % 8, 0);
+            unsafe { block.as_ptr().write_bytes(i as u8, 24) };
+            blocks.push(block);
+        }
+        // Several pages were committed, but not the whole reservation
+        assert!(arena.committed() >= 4 * page);
+        assert!(arena.committed() < arena.capacity());
+        for (i, block) in blocks.iter().enumerate() {
+            assert_eq!(unsafe { *block.as_ptr().add(23) }, i as u8);
+        }
+
+        let aligned = arena.alloc(1, 4096).unwrap();
+        assert_eq!(aligned.as_ptr() as usize % 4096, 0);
+        assert!(arena.alloc(8, 3).is_err());
+        assert!(arena.alloc(arena.capacity(), 1).is_err());
+
+        let first = blocks[0];
+        let committed = arena.committed();
+        arena.reset();
+        assert_eq!(arena.used(), 0);
+        let reused = arena.alloc(24, 8).unwrap();
+        assert_eq!(reused, first);
+        assert_eq!(arena.committed(), committed);
+
+        arena.reset_and_discard().unwrap();
+        assert_eq!(arena.alloc(24, 8).unwrap(), first);
+    }
+
+    #[test]
+    fn test_growable_buffer() {
+        let page = system_info().page_size as usize;
+        let mut buffer = GrowableBuffer::with_max(16 * page).unwrap();
+        assert_eq!(buffer.committed(), 0);
+        assert_eq!(buffer.reserved(), 16 * page);
+        let base = buffer.as_ptr();
+
+        buffer.ensure(10).unwrap().copy_from_slice(b"0123456789");
+        assert_eq!(buffer.committed(), page);
+
+        // Grow across several page boundaries, marking the start of each page
+        for pages in 2..=5 {
+            let slice = buffer.ensure(pages * page).unwrap();
+            assert_eq!(slice.len(), pages * page);
+            slice[(pages - 1) * page] = pages as u8;
+            assert_eq!(buffer.committed(), pages * page);
+            assert_eq!(buffer.as_ptr(), base);
+        }
+
+        let slice = buffer.ensure(5 * page).unwrap();
+        assert_eq!(&slice[..10], b"0123456789");
+        for pages in 2..=5 {
+            assert_eq!(slice[(pages - 1) * page], pages as u8);
+        }
+        assert_eq!(buffer.as_slice().len(), 5 * page);
+
+        // Shrinking requests leave the commitment alone
+        assert_eq!(buffer.ensure(1).unwrap(), b"0");
+        assert_eq!(buffer.committed(), 5 * page);
+        assert!(buffer.ensure(16 * page + 1).is_err());
+    }
+
     #[test]
     fn test_virtual_memory_alloc() {
         let mut mem = VirtualMemory::alloc(4096, Protection::ReadWrite).unwrap();
@@ -573,4 +938,30 @@ mod tests {
         assert!(!info.is_free);
         assert!(info.region_size >= 4096);
     }
+
+    #[test]
+    fn test_atomic_fetch_add_across_threads() {
+        let mem = VirtualMemory::alloc(4096, Protection::ReadWrite).unwrap();
+        let counter = mem.as_ptr() as usize;
+
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(move || {
+                    for _ in 0..1000 {
+                        unsafe { atomic::fetch_add_i64(counter as *mut i64, 1) };
+                    }
+                });
+            }
+        });
+
+        let counter = counter as *mut i64;
+        unsafe {
+            assert_eq!(atomic::load_i64(counter), 8000);
+            assert_eq!(atomic::compare_exchange_i64(counter, 1, 2), 8000);
+            assert_eq!(atomic::compare_exchange_i64(counter, 8000, 0), 8000);
+            assert_eq!(atomic::load_i64(counter), 0);
+            atomic::store_i64(counter, -5);
+            assert_eq!(atomic::load_i64(counter), -5);
+        }
+    }
 }
@@ -4,7 +4,9 @@
 
 use crate::error::{Error, Result};
 use crate::string::WideString;
+use std::marker::PhantomData;
 use std::path::Path;
+use std::sync::OnceLock;
 use windows::Win32::Foundation::{FreeLibrary, HMODULE};
 use windows::Win32::System::LibraryLoader::{
     GetModuleFileNameW, GetModuleHandleW, GetProcAddress, LoadLibraryExW, LoadLibraryW,
@@ -159,6 +161,105 @@ impl LoadFlags {
     }
 }
 
+/// A function export that may not exist on every version of Windows.
+///
+/// The DLL is loaded and the function looked up on first use; the result is
+/// cached, so later calls are a single atomic load. DLLs that aren't already
+/// loaded are loaded from System32 only and stay loaded for the life of the process.
+///
+/// Usually created through the [`resolve!`](crate::resolve) macro.
+pub struct OptionalApi<F> {
+    dll: &'static str,
+    name: &'static str,
+    address: OnceLock<Option<usize>>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Copy> OptionalApi<F> {
+    /// Creates a lazily resolved function export.
+    ///
+    /// # Safety
+    ///
+    /// `F` must be an `unsafe extern "system" fn` type matching the signature
+    /// of the exported function.
+    pub const unsafe fn new(dll: &'static str, name: &'static str) -> Self {
+        Self {
+            dll,
+            name,
+            address: OnceLock::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the function pointer, resolving it on first use.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Unsupported`] if the DLL or the export does not exist.
+    pub fn get(&self) -> Result<F> {
+        assert_eq!(std::mem::size_of::<F>(), std::mem::size_of::<usize>());
+
+        let address = *self
+            .address
+            .get_or_init(|| resolve_address(self.dll, self.name));
+
+        match address {
+            // SAFETY: F is a function pointer type matching the export, as promised in `new`
+            Some(address) => Ok(unsafe { std::mem::transmute_copy(&address) }),
+            None => Err(Error::unsupported(format!(
+                "{}!{} is not available on this system",
+                self.dll, self.name
+            ))),
+        }
+    }
+
+    /// Returns true if the function is available.
+    pub fn is_available(&self) -> bool {
+        self.get().is_ok()
+    }
+}
+
+/// Looks up an export, loading its DLL from System32 if necessary.
+fn resolve_address(dll: &str, name: &str) -> Option<usize> {
+    let library = Library::get(dll)
+        .or_else(|_| Library::load_with_flags(dll, LoadFlags::SEARCH_SYSTEM32))
+        .ok()?;
+
+    // SAFETY: The address is only reinterpreted by OptionalApi::get
+    let address = unsafe { library.get_proc::<usize>(name) }.ok();
+
+    // Cached pointers must stay valid, so never unload the DLL
+    std::mem::forget(library);
+    address
+}
+
+/// Resolves an optional function export, caching the result.
+///
+/// Evaluates to `Result<F>`, failing with [`Error::Unsupported`](crate::error::Error::Unsupported)
+/// when the DLL or function is missing. The function type must be an
+/// `unsafe extern "system" fn` matching the export's real signature.
+///
+/// # Example
+///
+/// ```ignore
+/// use ergonomic_windows::resolve;
+/// use windows::Win32::Foundation::HWND;
+///
+/// let get_dpi = resolve!("user32.dll", "GetDpiForWindow", unsafe extern "system" fn(HWND) -> u32)?;
+/// let dpi = unsafe { get_dpi(hwnd) };
+/// ```
+#[macro_export]
+macro_rules! resolve {
+    ($dll:expr, $name:expr, $ty:ty) => {{
+        const DLL: &str = $dll;
+        const NAME: &str = $name;
+        // SAFETY: The caller names the export's real signature in the macro invocation
+        static API: $crate::module::OptionalApi<$ty> =
+            unsafe { $crate::module::OptionalApi::new(DLL, NAME) };
+        API.get()
+    }};
+}
+
 /// Gets the path to the current executable.
 pub fn current_exe() -> Result<std::path::PathBuf> {
     Library::current()?.path()
@@ -216,4 +317,40 @@ mod tests {
         let dir = current_exe_dir().unwrap();
         assert!(dir.is_dir());
     }
+
+    #[test]
+    fn test_resolve_existing_function() {
+        let get_pid = crate::resolve!(
+            "kernel32.dll",
+            "GetCurrentProcessId",
+            unsafe extern "system" fn() -> u32
+        )
+        .unwrap();
+        assert_eq!(unsafe { get_pid() }, std::process::id());
+    }
+
+    #[test]
+    fn test_resolve_missing_function() {
+        let result = crate::resolve!(
+            "kernel32.dll",
+            "ThisFunctionDoesNotExist12345",
+            unsafe extern "system" fn() -> u32
+        );
+        assert!(matches!(result, Err(Error::Unsupported(_))));
+
+        let result = crate::resolve!(
+            "this_dll_does_not_exist_12345.dll",
+            "Anything",
+            unsafe extern "system" fn()
+        );
+        assert!(matches!(result, Err(Error::Unsupported(_))));
+    }
+
+    #[test]
+    fn test_optional_api_caches() {
+        static API: OptionalApi<unsafe extern "system" fn() -> u32> =
+            unsafe { OptionalApi::new("kernel32.dll", "GetCurrentThreadId") };
+        assert!(API.is_available());
+        assert_eq!(API.get().unwrap() as usize, API.get().unwrap() as usize);
+    }
 }
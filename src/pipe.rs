@@ -2,20 +2,31 @@
 //!
 //! Provides safe wrappers for Windows anonymous and named pipes.
 
-use crate::error::Result;
+use crate::error::{bool_result, handle_result, Error, Result};
 use crate::handle::OwnedHandle;
 use crate::string::WideString;
-use windows::Win32::Foundation::HANDLE;
+use crate::thread::{wait_any, Event, SharedEvent, Thread, WaitResult};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant};
+use windows::Win32::Foundation::{
+    SetHandleInformation, ERROR_BROKEN_PIPE, ERROR_IO_PENDING, ERROR_MORE_DATA, ERROR_NO_DATA,
+    ERROR_OPERATION_ABORTED, ERROR_PIPE_BUSY, ERROR_PIPE_CONNECTED, ERROR_PIPE_NOT_CONNECTED,
+    HANDLE, HANDLE_FLAGS, HANDLE_FLAG_INHERIT,
+};
 use windows::Win32::Storage::FileSystem::{
-    CreateFileW, ReadFile, WriteFile, FILE_FLAGS_AND_ATTRIBUTES, FILE_GENERIC_READ,
-    FILE_GENERIC_WRITE, FILE_SHARE_NONE, OPEN_EXISTING, PIPE_ACCESS_DUPLEX, PIPE_ACCESS_INBOUND,
-    PIPE_ACCESS_OUTBOUND,
+    CreateFileW, FlushFileBuffers, ReadFile, WriteFile, FILE_FLAGS_AND_ATTRIBUTES,
+    FILE_FLAG_OVERLAPPED, FILE_GENERIC_READ, FILE_GENERIC_WRITE, FILE_SHARE_NONE, OPEN_EXISTING,
+    PIPE_ACCESS_DUPLEX, PIPE_ACCESS_INBOUND, PIPE_ACCESS_OUTBOUND,
 };
 use windows::Win32::System::Pipes::{
-    ConnectNamedPipe, CreateNamedPipeW, CreatePipe, DisconnectNamedPipe, PeekNamedPipe,
-    SetNamedPipeHandleState, WaitNamedPipeW, NAMED_PIPE_MODE, PIPE_READMODE_BYTE,
-    PIPE_READMODE_MESSAGE, PIPE_TYPE_BYTE, PIPE_TYPE_MESSAGE, PIPE_WAIT,
+    ConnectNamedPipe, CreateNamedPipeW, CreatePipe, DisconnectNamedPipe,
+    GetNamedPipeClientProcessId, PeekNamedPipe, SetNamedPipeHandleState, WaitNamedPipeW,
+    NAMED_PIPE_MODE, PIPE_READMODE_BYTE, PIPE_READMODE_MESSAGE, PIPE_TYPE_BYTE, PIPE_TYPE_MESSAGE,
+    PIPE_UNLIMITED_INSTANCES, PIPE_WAIT,
 };
+use windows::Win32::System::IO::{CancelIoEx, GetOverlappedResult, OVERLAPPED};
 
 /// An anonymous pipe pair for parent-child process communication.
 pub struct AnonymousPipe {
@@ -48,6 +59,137 @@ impl AnonymousPipe {
             write: OwnedHandle::new(write_handle)?,
         })
     }
+
+    /// Creates a new anonymous pipe, choosing which end child processes inherit.
+    ///
+    /// Both ends are explicitly marked, so the end the child does not need is
+    /// never leaked into it. Typically only one end is inheritable: the write
+    /// end for a child's stdout, or the read end for its stdin.
+    pub fn pair(
+        read_inheritable: bool,
+        write_inheritable: bool,
+    ) -> Result<(PipeReader, PipeWriter)> {
+        Self::pair_with_buffer_size(read_inheritable, write_inheritable, 0)
+    }
+
+    /// Like [`pair`](Self::pair), with a suggested buffer size in bytes.
+    ///
+    /// A size of 0 uses the system default.
+    pub fn pair_with_buffer_size(
+        read_inheritable: bool,
+        write_inheritable: bool,
+        size: u32,
+    ) -> Result<(PipeReader, PipeWriter)> {
+        let pipe = Self::with_size(size)?;
+        set_inheritable(&pipe.read, read_inheritable)?;
+        set_inheritable(&pipe.write, write_inheritable)?;
+        Ok((
+            PipeReader { handle: pipe.read },
+            PipeWriter { handle: pipe.write },
+        ))
+    }
+}
+
+/// Sets or clears the inherit flag on a handle.
+fn set_inheritable(handle: &OwnedHandle, inheritable: bool) -> Result<()> {
+    let flags = if inheritable {
+        HANDLE_FLAG_INHERIT
+    } else {
+        HANDLE_FLAGS(0)
+    };
+    // SAFETY: handle is a valid pipe handle; only the inherit bit is touched
+    unsafe {
+        SetHandleInformation(handle.as_raw(), HANDLE_FLAG_INHERIT.0, flags)?;
+    }
+    Ok(())
+}
+
+/// The read end of an anonymous pipe.
+pub struct PipeReader {
+    handle: OwnedHandle,
+}
+
+impl PipeReader {
+    /// Reads data from the pipe.
+    ///
+    /// Returns 0 once the write end has been closed and the pipe is drained.
+    pub fn read(&self, buffer: &mut [u8]) -> Result<usize> {
+        let mut bytes_read = 0u32;
+        // SAFETY: ReadFile is safe with valid parameters
+        let result = unsafe {
+            ReadFile(
+                self.handle.as_raw(),
+                Some(buffer),
+                Some(&mut bytes_read),
+                None,
+            )
+        };
+        match result {
+            Ok(()) => Ok(bytes_read as usize),
+            Err(e) if e.code() == ERROR_BROKEN_PIPE.to_hresult() => Ok(0),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Returns the underlying handle, e.g. to pass as a child's stdin.
+    #[inline]
+    pub fn as_raw(&self) -> HANDLE {
+        self.handle.as_raw()
+    }
+
+    /// Consumes the reader and returns the owned handle.
+    pub fn into_handle(self) -> OwnedHandle {
+        self.handle
+    }
+}
+
+impl std::io::Read for PipeReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        PipeReader::read(self, buf).map_err(Into::into)
+    }
+}
+
+/// The write end of an anonymous pipe.
+pub struct PipeWriter {
+    handle: OwnedHandle,
+}
+
+impl PipeWriter {
+    /// Writes data to the pipe.
+    pub fn write(&self, data: &[u8]) -> Result<usize> {
+        let mut bytes_written = 0u32;
+        // SAFETY: WriteFile is safe with valid parameters
+        unsafe {
+            WriteFile(
+                self.handle.as_raw(),
+                Some(data),
+                Some(&mut bytes_written),
+                None,
+            )?;
+        }
+        Ok(bytes_written as usize)
+    }
+
+    /// Returns the underlying handle, e.g. to pass as a child's stdout.
+    #[inline]
+    pub fn as_raw(&self) -> HANDLE {
+        self.handle.as_raw()
+    }
+
+    /// Consumes the writer and returns the owned handle.
+    pub fn into_handle(self) -> OwnedHandle {
+        self.handle
+    }
+}
+
+impl std::io::Write for PipeWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        PipeWriter::write(self, buf).map_err(Into::into)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
 }
 
 /// Pipe access mode for named pipes.
@@ -100,6 +242,10 @@ impl PipeMode {
 pub struct NamedPipeServer {
     handle: OwnedHandle,
     name: String,
+    // Signals completion of the pipe's overlapped operations.
+    io_event: Event,
+    shutdown_event: Arc<SharedEvent>,
+    serving: Arc<AtomicBool>,
 }
 
 impl NamedPipeServer {
@@ -125,10 +271,11 @@ impl NamedPipeServer {
         let pipe_mode = NAMED_PIPE_MODE(mode.to_type_flags() | mode.to_read_flags() | PIPE_WAIT.0);
 
         // SAFETY: CreateNamedPipeW is safe with valid parameters
-        let handle = unsafe {
+        let handle = handle_result(unsafe {
             CreateNamedPipeW(
                 name_wide.as_pcwstr(),
-                access.to_flags(),
+                // Overlapped, so a shutdown can interrupt a wait for a client.
+                access.to_flags() | FILE_FLAG_OVERLAPPED,
                 pipe_mode,
                 max_instances,
                 out_buffer_size,
@@ -136,25 +283,34 @@ impl NamedPipeServer {
                 default_timeout,
                 None,
             )
-        };
-
-        if handle.is_invalid() {
-            return Err(crate::error::last_error());
-        }
+        })?;
 
         Ok(Self {
-            handle: OwnedHandle::new(handle)?,
+            handle,
             name: name.to_string(),
+            io_event: Event::new_manual(false)?,
+            shutdown_event: Arc::new(SharedEvent::new(Event::new_manual(false)?)),
+            serving: Arc::new(AtomicBool::new(false)),
         })
     }
 
     /// Waits for a client to connect.
     pub fn accept(&self) -> Result<()> {
-        // SAFETY: ConnectNamedPipe is safe with valid handle
-        unsafe {
-            ConnectNamedPipe(self.handle.as_raw(), None)?;
+        Ok(self.connect(false)?)
+    }
+
+    /// Waits for a client; a shutdown request cancels the wait with
+    /// `ERROR_OPERATION_ABORTED` if `cancellable`.
+    fn connect(&self, cancellable: bool) -> windows::core::Result<()> {
+        let (result, _) = self.overlapped(cancellable, |overlapped| {
+            // SAFETY: the handle is valid and overlapped outlives the operation
+            unsafe { ConnectNamedPipe(self.handle.as_raw(), Some(overlapped)) }
+        });
+        match result {
+            // The client connected between creation and this call
+            Err(e) if e.code() == ERROR_PIPE_CONNECTED.to_hresult() => Ok(()),
+            result => result,
         }
-        Ok(())
     }
 
     /// Disconnects from the current client.
@@ -168,31 +324,25 @@ impl NamedPipeServer {
 
     /// Reads data from the pipe.
     pub fn read(&self, buffer: &mut [u8]) -> Result<usize> {
-        let mut bytes_read = 0u32;
-        // SAFETY: ReadFile is safe with valid parameters
-        unsafe {
-            ReadFile(
-                self.handle.as_raw(),
-                Some(buffer),
-                Some(&mut bytes_read),
-                None,
-            )?;
-        }
+        let (result, bytes_read) = self.overlapped(false, |overlapped| {
+            // SAFETY: buffer and overlapped outlive the operation
+            unsafe { ReadFile(self.handle.as_raw(), Some(buffer), None, Some(overlapped)) }
+        });
+        result?;
         Ok(bytes_read as usize)
     }
 
     /// Writes data to the pipe.
     pub fn write(&self, data: &[u8]) -> Result<usize> {
-        let mut bytes_written = 0u32;
-        // SAFETY: WriteFile is safe with valid parameters
-        unsafe {
-            WriteFile(
-                self.handle.as_raw(),
-                Some(data),
-                Some(&mut bytes_written),
-                None,
-            )?;
-        }
+        self.send(data, false)
+    }
+
+    fn send(&self, data: &[u8], cancellable: bool) -> Result<usize> {
+        let (result, bytes_written) = self.overlapped(cancellable, |overlapped| {
+            // SAFETY: data and overlapped outlive the operation
+            unsafe { WriteFile(self.handle.as_raw(), Some(data), None, Some(overlapped)) }
+        });
+        result?;
         Ok(bytes_written as usize)
     }
 
@@ -220,6 +370,421 @@ impl NamedPipeServer {
     pub fn name(&self) -> &str {
         &self.name
     }
+
+    /// Reads one complete message from the connected client.
+    ///
+    /// In message mode, messages larger than the internal buffer are reassembled.
+    /// In byte mode, this returns whatever data a single read produces.
+    pub fn read_message(&self) -> Result<Vec<u8>> {
+        self.receive(false)
+    }
+
+    fn receive(&self, cancellable: bool) -> Result<Vec<u8>> {
+        let mut message = Vec::new();
+        let mut chunk = [0u8; 4096];
+
+        loop {
+            let (result, bytes_read) = self.overlapped(cancellable, |overlapped| {
+                // SAFETY: chunk and overlapped outlive the operation
+                unsafe {
+                    ReadFile(
+                        self.handle.as_raw(),
+                        Some(&mut chunk),
+                        None,
+                        Some(overlapped),
+                    )
+                }
+            });
+            message.extend_from_slice(&chunk[..bytes_read as usize]);
+
+            match result {
+                Ok(()) => return Ok(message),
+                // The rest of the message is still in the pipe
+                Err(e) if e.code() == ERROR_MORE_DATA.to_hresult() => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    /// Accepts a single client, answers one request with `handler`, and disconnects.
+    ///
+    /// Returns `Ok(false)` without calling the handler if a shutdown was requested.
+    pub fn serve_once(&self, mut handler: impl FnMut(&[u8]) -> Vec<u8>) -> Result<bool> {
+        if self.is_shutdown_requested()? {
+            return Ok(false);
+        }
+
+        match self.connect(true) {
+            Ok(()) => {}
+            Err(e) if e.code() == ERROR_OPERATION_ABORTED.to_hresult() => return Ok(false),
+            Err(e) => return Err(e.into()),
+        }
+
+        // A client may have connected just as the shutdown was requested
+        if self.is_shutdown_requested()? {
+            let _ = self.disconnect();
+            return Ok(false);
+        }
+
+        let result = self.read_message().and_then(|request| {
+            let response = handler(&request);
+            self.write(&response)?;
+            self.flush()
+        });
+
+        let _ = self.disconnect();
+        result.map(|()| true)
+    }
+
+    /// Runs a request/response loop until [`shutdown`](Self::shutdown) is called.
+    ///
+    /// Each iteration accepts a client, reads one request message, writes the
+    /// handler's response, and disconnects. Clients that hang up mid-request are
+    /// skipped; other errors stop the loop.
+    ///
+    /// A server instance handles one client at a time. For concurrency, create
+    /// several instances with the same name (raising `max_instances` in
+    /// [`with_options`](Self::with_options)) and serve each from its own thread.
+    pub fn serve(&self, mut handler: impl FnMut(&[u8]) -> Vec<u8>) -> Result<()> {
+        self.serving.store(true, Ordering::SeqCst);
+
+        let result = loop {
+            match self.serve_once(&mut handler) {
+                Ok(true) => continue,
+                Ok(false) => break Ok(()),
+                Err(e) if is_client_gone(&e) => continue,
+                Err(e) => break Err(e),
+            }
+        };
+
+        self.serving.store(false, Ordering::SeqCst);
+        result
+    }
+
+    /// Serves up to `max_instances` clients at once, each on its own worker thread.
+    ///
+    /// Creates `max_instances` duplex, message-mode instances of the pipe `name`
+    /// before returning. Every instance has a dedicated worker that accepts a
+    /// client, runs `handler` with the connection, and disconnects; the handler
+    /// may exchange as many messages as it likes. A panicking handler only drops
+    /// its own client.
+    ///
+    /// When every instance is busy, further clients see `ERROR_PIPE_BUSY`;
+    /// [`NamedPipeClient::connect_timeout`] waits for a free instance.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ergonomic_windows::pipe::NamedPipeServer;
+    /// use std::sync::Arc;
+    ///
+    /// let server = NamedPipeServer::serve_concurrent(
+    ///     r"\\.\pipe\echo",
+    ///     4,
+    ///     Arc::new(|conn: &mut ergonomic_windows::pipe::PipeConnection| {
+    ///         while let Ok(message) = conn.read_message() {
+    ///             if conn.write_message(&message).is_err() {
+    ///                 break;
+    ///             }
+    ///         }
+    ///     }),
+    /// )?;
+    ///
+    /// // ...
+    /// server.shutdown()?;
+    /// # Ok::<(), ergonomic_windows::error::Error>(())
+    /// ```
+    pub fn serve_concurrent<F>(
+        name: &str,
+        max_instances: u32,
+        handler: Arc<F>,
+    ) -> Result<ConcurrentPipeServer>
+    where
+        F: Fn(&mut PipeConnection) + Send + Sync + 'static,
+    {
+        if max_instances == 0 || max_instances > PIPE_UNLIMITED_INSTANCES {
+            return Err(Error::custom(format!(
+                "max_instances must be between 1 and {}",
+                PIPE_UNLIMITED_INSTANCES
+            )));
+        }
+
+        let mut server = ConcurrentPipeServer {
+            name: name.to_string(),
+            stop: Arc::new(SharedEvent::new(Event::new_manual(false)?)),
+            workers: Vec::with_capacity(max_instances as usize),
+        };
+
+        for _ in 0..max_instances {
+            // Each worker creates and owns its own instance (NamedPipeServer
+            // isn't Send) and reports back once it is listening.
+            let (ready_tx, ready_rx) = mpsc::channel();
+            let name = name.to_string();
+            let stop = Arc::clone(&server.stop);
+            let handler = Arc::clone(&handler);
+
+            let worker = Thread::spawn(move || {
+                let created = NamedPipeServer::with_options(
+                    &name,
+                    PipeAccess::Duplex,
+                    PipeMode::Message,
+                    max_instances,
+                    4096,
+                    4096,
+                    0,
+                );
+                let mut conn = match created {
+                    Ok(mut server) => {
+                        // Every instance stops on the shared event
+                        server.shutdown_event = stop;
+                        let _ = ready_tx.send(Ok(()));
+                        PipeConnection { server }
+                    }
+                    Err(e) => {
+                        let _ = ready_tx.send(Err(e));
+                        return 1;
+                    }
+                };
+
+                let mut backoff = ACCEPT_BACKOFF_MIN;
+                loop {
+                    match conn.server.connect(true) {
+                        Ok(()) => {
+                            backoff = ACCEPT_BACKOFF_MIN;
+                            let _ = panic::catch_unwind(AssertUnwindSafe(|| handler(&mut conn)));
+                            // Flushing waits for the client to read; skip it when stopping
+                            if !matches!(conn.server.is_shutdown_requested(), Ok(true)) {
+                                let _ = conn.server.flush();
+                            }
+                        }
+                        Err(e) if e.code() == ERROR_OPERATION_ABORTED.to_hresult() => break,
+                        Err(_) => {
+                            // Don't spin on a persistent failure; a shutdown still
+                            // ends the pause early.
+                            let stopped = conn
+                                .server
+                                .shutdown_event
+                                .event()
+                                .wait_timeout(Some(backoff));
+                            if matches!(stopped, Ok(WaitResult::Signaled)) {
+                                break;
+                            }
+                            backoff = (backoff * 2).min(ACCEPT_BACKOFF_MAX);
+                        }
+                    }
+                    let _ = conn.server.disconnect();
+                }
+                0
+            })?;
+            server.workers.push(worker);
+
+            match ready_rx.recv() {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => return Err(e),
+                Err(_) => return Err(Error::custom("pipe worker exited during startup")),
+            }
+        }
+
+        Ok(server)
+    }
+
+    /// Requests that [`serve`](Self::serve) stop before accepting the next client.
+    ///
+    /// A pending wait for a client in [`serve`](Self::serve) or
+    /// [`serve_once`](Self::serve_once) is cancelled, so a blocked server returns
+    /// promptly; [`accept`](Self::accept) is not affected. This doesn't wait for
+    /// the serve loop to exit. To stop a server running on another thread and
+    /// wait for it, use [`shutdown_handle`](Self::shutdown_handle).
+    pub fn shutdown(&self) -> Result<()> {
+        self.shutdown_event.event().set()
+    }
+
+    /// Returns a handle that can stop this server from another thread.
+    pub fn shutdown_handle(&self) -> PipeShutdown {
+        PipeShutdown {
+            event: Arc::clone(&self.shutdown_event),
+            serving: Arc::clone(&self.serving),
+        }
+    }
+
+    fn is_shutdown_requested(&self) -> Result<bool> {
+        Ok(self
+            .shutdown_event
+            .event()
+            .wait_timeout(Some(Duration::ZERO))?
+            == WaitResult::Signaled)
+    }
+
+    /// Runs one overlapped operation on the pipe to completion.
+    ///
+    /// If `cancellable`, a shutdown request cancels the operation, which then
+    /// fails with `ERROR_OPERATION_ABORTED`. The byte count is returned even on
+    /// failure, since a partial message read reports both.
+    fn overlapped(
+        &self,
+        cancellable: bool,
+        start: impl FnOnce(*mut OVERLAPPED) -> windows::core::Result<()>,
+    ) -> (windows::core::Result<()>, u32) {
+        let shutdown = self.shutdown_event.event();
+        if cancellable && matches!(self.is_shutdown_requested(), Ok(true)) {
+            return (Err(ERROR_OPERATION_ABORTED.to_hresult().into()), 0);
+        }
+
+        let mut overlapped = OVERLAPPED {
+            hEvent: self.io_event.as_raw(),
+            ..Default::default()
+        };
+        match start(&mut overlapped) {
+            Ok(()) => {}
+            Err(e) if e.code() == ERROR_IO_PENDING.to_hresult() => {
+                if cancellable
+                    && matches!(wait_any(&[&self.io_event, &shutdown], None), Ok(Some(1)))
+                {
+                    // Fails harmlessly if the operation completed meanwhile
+                    // SAFETY: overlapped identifies a pending operation on this handle
+                    let _ = unsafe { CancelIoEx(self.handle.as_raw(), Some(&overlapped)) };
+                }
+            }
+            // A partial message completes at once and still has a byte count
+            Err(e) if e.code() == ERROR_MORE_DATA.to_hresult() => {}
+            Err(e) => return (Err(e), 0),
+        }
+
+        let mut transferred = 0u32;
+        // SAFETY: waiting here keeps overlapped in place until the operation is done
+        let result = unsafe {
+            GetOverlappedResult(self.handle.as_raw(), &overlapped, &mut transferred, true)
+        };
+        (result, transferred)
+    }
+
+    fn flush(&self) -> Result<()> {
+        // SAFETY: FlushFileBuffers is safe with a valid handle
+        unsafe {
+            FlushFileBuffers(self.handle.as_raw())?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns true if the error means the client disconnected.
+fn is_client_gone(error: &Error) -> bool {
+    match error {
+        Error::Windows(e) => [ERROR_BROKEN_PIPE, ERROR_NO_DATA, ERROR_PIPE_NOT_CONNECTED]
+            .iter()
+            .any(|code| e.code() == code.to_hresult()),
+        _ => false,
+    }
+}
+
+/// A handle for stopping a [`NamedPipeServer`] from another thread.
+///
+/// Obtained from [`NamedPipeServer::shutdown_handle`].
+#[derive(Debug, Clone)]
+pub struct PipeShutdown {
+    event: Arc<SharedEvent>,
+    serving: Arc<AtomicBool>,
+}
+
+impl PipeShutdown {
+    /// Signals the server to stop and wakes it if it is waiting for a client.
+    ///
+    /// If the server is busy with a client, it stops once that request completes.
+    /// Blocks until the serve loop has exited.
+    pub fn shutdown(&self) -> Result<()> {
+        self.event.event().set()?;
+
+        while self.serving.load(Ordering::SeqCst) {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        Ok(())
+    }
+}
+
+/// A connected client of a [`NamedPipeServer::serve_concurrent`] server.
+///
+/// The pipe is duplex and in message mode. The connection is flushed and
+/// disconnected once the handler returns. Once the server is shutting down,
+/// reads and writes fail with `ERROR_OPERATION_ABORTED`, including ones
+/// already waiting on the client.
+pub struct PipeConnection {
+    server: NamedPipeServer,
+}
+
+impl PipeConnection {
+    /// Reads one complete message from the client.
+    pub fn read_message(&mut self) -> Result<Vec<u8>> {
+        self.server.receive(true)
+    }
+
+    /// Writes one message to the client.
+    pub fn write_message(&mut self, message: &[u8]) -> Result<()> {
+        let written = self.server.send(message, true)?;
+        if written != message.len() {
+            return Err(Error::custom(format!(
+                "pipe accepted {} of {} message bytes",
+                written,
+                message.len()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Returns the process ID of the connected client.
+    pub fn peer_pid(&self) -> Result<u32> {
+        let mut pid = 0u32;
+        // SAFETY: the handle is a valid server end, pid is a valid output parameter
+        unsafe {
+            GetNamedPipeClientProcessId(self.server.handle.as_raw(), &mut pid)?;
+        }
+        Ok(pid)
+    }
+}
+
+/// A running [`NamedPipeServer::serve_concurrent`] server.
+///
+/// Dropping it shuts the server down.
+pub struct ConcurrentPipeServer {
+    name: String,
+    stop: Arc<SharedEvent>,
+    workers: Vec<Thread>,
+}
+
+/// Bounds of the pause between failed accepts in a concurrent server worker.
+const ACCEPT_BACKOFF_MIN: Duration = Duration::from_millis(10);
+const ACCEPT_BACKOFF_MAX: Duration = Duration::from_secs(1);
+
+impl ConcurrentPipeServer {
+    /// Returns the pipe name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Stops accepting clients and waits for every worker to exit.
+    ///
+    /// Idle workers wake at once. Busy handlers see their pending and later
+    /// reads and writes fail, so a handler that returns on I/O errors finishes
+    /// promptly; one that keeps running without touching its connection still
+    /// delays the shutdown.
+    pub fn shutdown(mut self) -> Result<()> {
+        self.stop_workers()
+    }
+
+    fn stop_workers(&mut self) -> Result<()> {
+        self.stop.event().set()?;
+
+        for worker in self.workers.drain(..) {
+            worker.join()?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for ConcurrentPipeServer {
+    fn drop(&mut self) {
+        let _ = self.stop_workers();
+    }
 }
 
 /// A named pipe client.
@@ -234,35 +799,53 @@ impl NamedPipeClient {
     }
 
     /// Connects to a named pipe server with a timeout.
+    ///
+    /// With a timeout, this also waits out `ERROR_PIPE_BUSY` (every server
+    /// instance taken) until an instance frees up or the timeout elapses.
     pub fn connect_timeout(name: &str, timeout_ms: Option<u32>) -> Result<Self> {
         let name_wide = WideString::new(name);
+        let deadline = timeout_ms.map(|ms| Instant::now() + Duration::from_millis(ms.into()));
 
         // Wait for pipe to be available
         if let Some(timeout) = timeout_ms {
             // SAFETY: WaitNamedPipeW is safe with valid parameters
-            let result = unsafe { WaitNamedPipeW(name_wide.as_pcwstr(), timeout) };
-            if !result.as_bool() {
-                return Err(crate::error::last_error());
-            }
+            bool_result(unsafe { WaitNamedPipeW(name_wide.as_pcwstr(), timeout) })?;
         }
 
-        // Connect to the pipe
-        // SAFETY: CreateFileW is safe with valid parameters
-        let handle = unsafe {
-            CreateFileW(
-                name_wide.as_pcwstr(),
-                (FILE_GENERIC_READ | FILE_GENERIC_WRITE).0,
-                FILE_SHARE_NONE,
-                None,
-                OPEN_EXISTING,
-                FILE_FLAGS_AND_ATTRIBUTES(0),
-                None,
-            )?
-        };
+        loop {
+            // Connect to the pipe
+            // SAFETY: CreateFileW is safe with valid parameters
+            let result = unsafe {
+                CreateFileW(
+                    name_wide.as_pcwstr(),
+                    (FILE_GENERIC_READ | FILE_GENERIC_WRITE).0,
+                    FILE_SHARE_NONE,
+                    None,
+                    OPEN_EXISTING,
+                    FILE_FLAGS_AND_ATTRIBUTES(0),
+                    None,
+                )
+            };
 
-        Ok(Self {
-            handle: OwnedHandle::new(handle)?,
-        })
+            match (result, deadline) {
+                (Ok(handle), _) => {
+                    return Ok(Self {
+                        handle: OwnedHandle::new(handle)?,
+                    })
+                }
+                // Another client grabbed the free instance first; wait for the next one
+                (Err(e), Some(deadline)) if e.code() == ERROR_PIPE_BUSY.to_hresult() => {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    if remaining.is_zero() {
+                        return Err(e.into());
+                    }
+                    let remaining_ms = u32::try_from(remaining.as_millis()).unwrap_or(u32::MAX);
+                    // SAFETY: WaitNamedPipeW is safe with valid parameters
+                    bool_result(unsafe { WaitNamedPipeW(name_wide.as_pcwstr(), remaining_ms) })?;
+                }
+                (Err(e), _) => return Err(e.into()),
+            }
+        }
     }
 
     /// Sets the pipe to message mode.
@@ -345,6 +928,38 @@ mod tests {
         assert_eq!(&buffer[..read as usize], data);
     }
 
+    fn is_inheritable(handle: HANDLE) -> bool {
+        let mut flags = 0u32;
+        unsafe {
+            windows::Win32::Foundation::GetHandleInformation(handle, &mut flags).unwrap();
+        }
+        flags & HANDLE_FLAG_INHERIT.0 != 0
+    }
+
+    #[test]
+    fn test_pipe_pair_transfers_bytes() {
+        use std::io::{Read, Write};
+
+        let (mut reader, mut writer) = AnonymousPipe::pair(false, false).unwrap();
+        writer.write_all(b"Hello, pair!").unwrap();
+        drop(writer);
+
+        let mut received = Vec::new();
+        reader.read_to_end(&mut received).unwrap();
+        assert_eq!(received, b"Hello, pair!");
+    }
+
+    #[test]
+    fn test_pipe_pair_inheritance() {
+        let (reader, writer) = AnonymousPipe::pair(false, true).unwrap();
+        assert!(!is_inheritable(reader.as_raw()));
+        assert!(is_inheritable(writer.as_raw()));
+
+        let (reader, writer) = AnonymousPipe::pair_with_buffer_size(true, false, 4096).unwrap();
+        assert!(is_inheritable(reader.as_raw()));
+        assert!(!is_inheritable(writer.as_raw()));
+    }
+
     #[test]
     fn test_unique_pipe_name() {
         let name1 = unique_pipe_name("test");
@@ -354,4 +969,129 @@ mod tests {
         // Names should be different (different timestamps)
         // But they might be the same if called too fast, so we just check format
     }
+
+    #[test]
+    fn test_serve_echo_round_trip() {
+        let name = unique_pipe_name("serve_echo");
+        let server = NamedPipeServer::new(&name, PipeAccess::Duplex, PipeMode::Message).unwrap();
+        let shutdown = server.shutdown_handle();
+
+        let client_name = name.clone();
+        let client = std::thread::spawn(move || {
+            let mut responses = Vec::new();
+            for request in ["hello", "world"] {
+                let client = NamedPipeClient::connect_timeout(&client_name, Some(5000)).unwrap();
+                client.set_message_mode().unwrap();
+                client.write(request.as_bytes()).unwrap();
+
+                let mut buffer = [0u8; 64];
+                let n = client.read(&mut buffer).unwrap();
+                responses.push(String::from_utf8_lossy(&buffer[..n]).into_owned());
+            }
+            shutdown.shutdown().unwrap();
+            responses
+        });
+
+        server
+            .serve(|request| {
+                let mut response = b"echo: ".to_vec();
+                response.extend_from_slice(request);
+                response
+            })
+            .unwrap();
+
+        assert_eq!(client.join().unwrap(), ["echo: hello", "echo: world"]);
+    }
+
+    #[test]
+    fn test_serve_once_after_shutdown() {
+        let name = unique_pipe_name("serve_once");
+        let server = NamedPipeServer::new(&name, PipeAccess::Duplex, PipeMode::Message).unwrap();
+        server.shutdown().unwrap();
+        assert!(!server.serve_once(|r| r.to_vec()).unwrap());
+    }
+
+    #[test]
+    fn test_serve_concurrent_echo() {
+        use std::sync::Barrier;
+
+        let name = unique_pipe_name("serve_concurrent");
+        let server = NamedPipeServer::serve_concurrent(
+            &name,
+            3,
+            Arc::new(|conn: &mut PipeConnection| {
+                let pid = conn.peer_pid().unwrap_or(0);
+                while let Ok(request) = conn.read_message() {
+                    let mut response = format!("{}: ", pid).into_bytes();
+                    response.extend_from_slice(&request);
+                    if conn.write_message(&response).is_err() {
+                        break;
+                    }
+                }
+            }),
+        )
+        .unwrap();
+
+        // All three clients stay connected until each has its reply, so the
+        // server must be handling them at the same time.
+        let barrier = Arc::new(Barrier::new(3));
+        let clients: Vec<_> = (0..3)
+            .map(|i| {
+                let name = name.clone();
+                let barrier = Arc::clone(&barrier);
+                std::thread::spawn(move || {
+                    let client = NamedPipeClient::connect_timeout(&name, Some(5000)).unwrap();
+                    client.set_message_mode().unwrap();
+                    client.write(format!("client {}", i).as_bytes()).unwrap();
+
+                    let mut buffer = [0u8; 64];
+                    let n = client.read(&mut buffer).unwrap();
+                    barrier.wait();
+                    String::from_utf8_lossy(&buffer[..n]).into_owned()
+                })
+            })
+            .collect();
+
+        for (i, client) in clients.into_iter().enumerate() {
+            assert_eq!(
+                client.join().unwrap(),
+                format!("{}: client {}", std::process::id(), i)
+            );
+        }
+
+        server.shutdown().unwrap();
+    }
+
+    #[test]
+    fn test_serve_concurrent_shutdown_interrupts_blocked_handler() {
+        let name = unique_pipe_name("serve_concurrent_blocked");
+        let (started_tx, started_rx) = mpsc::channel();
+        let started_tx = std::sync::Mutex::new(started_tx);
+        let server = NamedPipeServer::serve_concurrent(
+            &name,
+            1,
+            Arc::new(move |conn: &mut PipeConnection| {
+                let _ = started_tx.lock().unwrap().send(());
+                // The client never sends anything, so only the shutdown ends this
+                let _ = conn.read_message();
+            }),
+        )
+        .unwrap();
+
+        let _client = NamedPipeClient::connect_timeout(&name, Some(5000)).unwrap();
+        started_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+
+        let start = Instant::now();
+        server.shutdown().unwrap();
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_serve_concurrent_rejects_zero_instances() {
+        let name = unique_pipe_name("serve_concurrent_zero");
+        assert!(
+            NamedPipeServer::serve_concurrent(&name, 0, Arc::new(|_: &mut PipeConnection| {}))
+                .is_err()
+        );
+    }
 }
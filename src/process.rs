@@ -3,16 +3,37 @@
 //! Provides ergonomic wrappers for creating, managing, and querying Windows processes.
 
 use crate::error::{Error, Result};
-use crate::handle::OwnedHandle;
-use crate::string::{to_wide, WideString};
+use crate::handle::{OwnedHandle, Waitable};
+use crate::pipe::{AnonymousPipe, PipeReader, PipeWriter};
+use crate::security::Token;
+use crate::string::{pack_multi_wide, to_wide, unpack_multi, WideString};
+use crate::thread::{Thread, WaitResult};
+use crate::window::ShowCommand;
 use std::borrow::Cow;
+use std::path::PathBuf;
 use std::time::Duration;
-use windows::Win32::Foundation::{CloseHandle, HANDLE, WAIT_OBJECT_0, WAIT_TIMEOUT};
+use windows::Win32::Foundation::{
+    CloseHandle, DuplicateHandle, BOOL, DUPLICATE_SAME_ACCESS, ERROR_INSUFFICIENT_BUFFER, FILETIME,
+    HANDLE, HWND, RECT, WAIT_FAILED, WAIT_TIMEOUT,
+};
+use windows::Win32::System::Console::{
+    GetStdHandle, STD_ERROR_HANDLE, STD_INPUT_HANDLE, STD_OUTPUT_HANDLE,
+};
+use windows::Win32::System::Diagnostics::Debug::ReadProcessMemory;
+use windows::Win32::System::ProcessStatus::{GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS};
 use windows::Win32::System::Threading::{
-    CreateProcessW, GetExitCodeProcess, OpenProcess, TerminateProcess, WaitForSingleObject,
-    CREATE_NEW_CONSOLE, CREATE_NO_WINDOW, CREATE_UNICODE_ENVIRONMENT, PROCESS_CREATION_FLAGS,
-    PROCESS_INFORMATION, PROCESS_QUERY_INFORMATION, PROCESS_TERMINATE, STARTUPINFOW,
+    CreateProcessAsUserW, CreateProcessW, DeleteProcThreadAttributeList, GetCurrentProcess,
+    GetExitCodeProcess, GetProcessAffinityMask, GetProcessTimes, InitializeProcThreadAttributeList,
+    IsWow64Process, OpenProcess, QueryFullProcessImageNameW, SetProcessAffinityMask,
+    TerminateProcess, UpdateProcThreadAttribute, WaitForInputIdle, CREATE_NEW_CONSOLE,
+    CREATE_NO_WINDOW, CREATE_UNICODE_ENVIRONMENT, EXTENDED_STARTUPINFO_PRESENT,
+    LPPROC_THREAD_ATTRIBUTE_LIST, PROCESS_CREATION_FLAGS, PROCESS_INFORMATION, PROCESS_NAME_WIN32,
+    PROCESS_QUERY_INFORMATION, PROCESS_QUERY_LIMITED_INFORMATION, PROCESS_SET_INFORMATION,
+    PROCESS_SUSPEND_RESUME, PROCESS_TERMINATE, PROCESS_VM_READ, PROC_THREAD_ATTRIBUTE_HANDLE_LIST,
+    STARTF_USEPOSITION, STARTF_USESHOWWINDOW, STARTF_USESIZE, STARTF_USESTDHANDLES, STARTUPINFOEXW,
+    STARTUPINFOW,
 };
+use windows::Win32::UI::WindowsAndMessaging::{GetWindow, GetWindowRect, GW_OWNER};
 
 /// Represents a running or completed process.
 pub struct Process {
@@ -42,6 +63,27 @@ impl Process {
         })
     }
 
+    /// Opens an existing process with the least access needed for queries.
+    ///
+    /// Tries [`ProcessAccess::QUERY_LIMITED`] first, which is granted for more
+    /// processes (including many elevated or protected ones), and falls back to
+    /// [`ProcessAccess::QUERY`] only if that fails.
+    ///
+    /// Limited query access is enough for [`image_path`](Self::image_path),
+    /// [`times`](Self::times), [`exit_code`](Self::exit_code) and
+    /// [`affinity_mask`](Self::affinity_mask). [`memory_info`](Self::memory_info)
+    /// also needs `PROCESS_VM_READ`, waiting needs `SYNCHRONIZE`, and changing the
+    /// process needs `TERMINATE` or `SET_INFORMATION`; use [`open`](Self::open)
+    /// for those.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error from the limited attempt if both attempts fail.
+    pub fn open_for_query(pid: u32) -> Result<Self> {
+        Self::open(pid, ProcessAccess::QUERY_LIMITED)
+            .or_else(|e| Self::open(pid, ProcessAccess::QUERY).map_err(|_| e))
+    }
+
     /// Returns the process ID.
     pub fn pid(&self) -> u32 {
         self.pid
@@ -64,18 +106,9 @@ impl Process {
     /// Returns `Ok(exit_code)` if the process exits within the timeout,
     /// or an error if the timeout expires or waiting fails.
     pub fn wait_timeout(&self, timeout: Option<Duration>) -> Result<u32> {
-        let timeout_ms = timeout
-            .map(|d| d.as_millis() as u32)
-            .unwrap_or(windows::Win32::System::Threading::INFINITE);
-
-        // SAFETY: self.handle is a valid process handle that we own.
-        // WaitForSingleObject is safe to call on any valid handle.
-        let result = unsafe { WaitForSingleObject(self.handle.as_raw(), timeout_ms) };
-
-        match result {
-            WAIT_OBJECT_0 => self.exit_code(),
-            WAIT_TIMEOUT => Err(Error::custom("Wait timed out")),
-            _ => Err(Error::custom("Wait failed")),
+        match Waitable::wait(self, timeout)? {
+            WaitResult::Timeout => Err(Error::custom("Wait timed out")),
+            _ => self.exit_code(),
         }
     }
 
@@ -83,14 +116,9 @@ impl Process {
     ///
     /// Returns `Ok(Some(exit_code))` if exited, `Ok(None)` if still running.
     pub fn try_wait(&self) -> Result<Option<u32>> {
-        // SAFETY: self.handle is a valid process handle that we own.
-        // A timeout of 0 makes this a non-blocking check.
-        let result = unsafe { WaitForSingleObject(self.handle.as_raw(), 0) };
-
-        match result {
-            WAIT_OBJECT_0 => Ok(Some(self.exit_code()?)),
-            WAIT_TIMEOUT => Ok(None),
-            _ => Err(Error::custom("Wait failed")),
+        match Waitable::wait(self, Some(Duration::ZERO))? {
+            WaitResult::Timeout => Ok(None),
+            _ => Ok(Some(self.exit_code()?)),
         }
     }
 
@@ -107,6 +135,93 @@ impl Process {
         Ok(exit_code)
     }
 
+    /// Gets the full path of the process executable.
+    ///
+    /// Requires `PROCESS_QUERY_LIMITED_INFORMATION` access.
+    pub fn image_path(&self) -> Result<PathBuf> {
+        let mut buffer = vec![0u16; 512];
+        loop {
+            let mut size = buffer.len() as u32;
+            // SAFETY: buffer is valid for size UTF-16 units
+            let result = unsafe {
+                QueryFullProcessImageNameW(
+                    self.handle.as_raw(),
+                    PROCESS_NAME_WIN32,
+                    windows::core::PWSTR(buffer.as_mut_ptr()),
+                    &mut size,
+                )
+            };
+            match result {
+                Ok(()) => {
+                    return Ok(PathBuf::from(crate::string::from_wide(
+                        &buffer[..size as usize],
+                    )?))
+                }
+                // Paths can exceed MAX_PATH; grow up to the 32K UNICODE_STRING limit
+                Err(e)
+                    if e.code() == ERROR_INSUFFICIENT_BUFFER.to_hresult()
+                        && buffer.len() < 32 * 1024 =>
+                {
+                    buffer.resize(buffer.len() * 4, 0)
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    /// Gets the process creation/exit times and CPU usage.
+    ///
+    /// Requires `PROCESS_QUERY_LIMITED_INFORMATION` access.
+    pub fn times(&self) -> Result<ProcessTimes> {
+        let mut creation = FILETIME::default();
+        let mut exit = FILETIME::default();
+        let mut kernel = FILETIME::default();
+        let mut user = FILETIME::default();
+        // SAFETY: all four are valid output parameters
+        unsafe {
+            GetProcessTimes(
+                self.handle.as_raw(),
+                &mut creation,
+                &mut exit,
+                &mut kernel,
+                &mut user,
+            )?;
+        }
+
+        let ticks = |ft: FILETIME| ((ft.dwHighDateTime as u64) << 32) | (ft.dwLowDateTime as u64);
+        Ok(ProcessTimes {
+            creation_time: ticks(creation),
+            exit_time: ticks(exit),
+            kernel_time: Duration::from_nanos(ticks(kernel) * 100),
+            user_time: Duration::from_nanos(ticks(user) * 100),
+        })
+    }
+
+    /// Gets the process working set and pagefile usage.
+    ///
+    /// Requires `PROCESS_QUERY_LIMITED_INFORMATION | PROCESS_VM_READ` access
+    /// (or `PROCESS_QUERY_INFORMATION | PROCESS_VM_READ`, as in
+    /// [`ProcessAccess::QUERY_AND_READ`]). A handle from
+    /// [`open_for_query`](Self::open_for_query) lacks `PROCESS_VM_READ`.
+    pub fn memory_info(&self) -> Result<ProcessMemoryInfo> {
+        let mut counters = PROCESS_MEMORY_COUNTERS {
+            cb: std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32,
+            ..Default::default()
+        };
+        // SAFETY: counters is a valid output parameter with cb set
+        unsafe {
+            GetProcessMemoryInfo(self.handle.as_raw(), &mut counters, counters.cb)?;
+        }
+
+        Ok(ProcessMemoryInfo {
+            working_set: counters.WorkingSetSize,
+            peak_working_set: counters.PeakWorkingSetSize,
+            pagefile_usage: counters.PagefileUsage,
+            peak_pagefile_usage: counters.PeakPagefileUsage,
+            page_fault_count: counters.PageFaultCount,
+        })
+    }
+
     /// Terminates the process.
     ///
     /// # Arguments
@@ -126,12 +241,334 @@ impl Process {
         Ok(())
     }
 
+    /// Suspends every thread in the process.
+    ///
+    /// Uses `NtSuspendProcess` from ntdll, which freezes all threads at once and
+    /// so can't race with the process creating new ones, as suspending threads
+    /// one by one can. Suspensions nest: each call needs a matching
+    /// [`resume`](Self::resume). The handle needs `PROCESS_SUSPEND_RESUME` access
+    /// (see [`ProcessAccess::SUSPEND_RESUME`]).
+    ///
+    /// # Warning
+    ///
+    /// The process is frozen wherever its threads happen to be, possibly while
+    /// holding a lock. If the caller then waits on something the target holds,
+    /// such as a shared mutex or a pipe it would drain, both deadlock.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if ntdll doesn't export `NtSuspendProcess`, or with the
+    /// translated NTSTATUS if the call fails.
+    pub fn suspend(&self) -> Result<()> {
+        let suspend = crate::resolve!("ntdll.dll", "NtSuspendProcess", NtProcessFn)?;
+        self.call_nt(suspend)
+    }
+
+    /// Resumes every thread in a process suspended with [`suspend`](Self::suspend).
+    ///
+    /// Uses `NtResumeProcess` from ntdll and needs `PROCESS_SUSPEND_RESUME` access.
+    pub fn resume(&self) -> Result<()> {
+        let resume = crate::resolve!("ntdll.dll", "NtResumeProcess", NtProcessFn)?;
+        self.call_nt(resume)
+    }
+
+    /// Calls an ntdll function that takes just a process handle.
+    fn call_nt(&self, f: NtProcessFn) -> Result<()> {
+        // SAFETY: f has the NtProcessFn signature and the handle is valid
+        let status = unsafe { f(self.handle.as_raw()) };
+        if status.is_err() {
+            return Err(Error::from_ntstatus(status.0));
+        }
+        Ok(())
+    }
+
     /// Checks if the process is still running.
     pub fn is_running(&self) -> Result<bool> {
         Ok(self.try_wait()?.is_none())
     }
+
+    /// Waits until the process has finished its initialization and is waiting
+    /// for user input with no input pending.
+    ///
+    /// This is the usual preamble before sending input to, or searching for the
+    /// windows of, a freshly launched GUI application.
+    ///
+    /// Returns `Ok(true)` if the process became idle, or `Ok(false)` if the timeout elapsed.
+    ///
+    /// # Errors
+    ///
+    /// Only GUI processes with a message queue can become input-idle. For console
+    /// applications (or processes that never create a message queue) this returns
+    /// an error immediately instead of waiting.
+    pub fn wait_for_input_idle(&self, timeout: Duration) -> Result<bool> {
+        let timeout_ms = timeout.as_millis().min(u32::MAX as u128 - 1) as u32;
+
+        // SAFETY: self.handle is a valid process handle that we own.
+        let result = unsafe { WaitForInputIdle(self.handle.as_raw(), timeout_ms) };
+
+        match result {
+            0 => Ok(true),
+            r if r == WAIT_TIMEOUT.0 => Ok(false),
+            r if r == WAIT_FAILED.0 => Err(crate::error::last_error()),
+            _ => Err(Error::custom("WaitForInputIdle failed")),
+        }
+    }
+
+    /// Gets the visible top-level windows of the process, in Z order.
+    ///
+    /// Child windows are not included. See [`main_window`](Self::main_window)
+    /// for picking the one the user thinks of as the application.
+    pub fn windows(&self) -> Vec<HWND> {
+        crate::window::enumerate()
+            .into_iter()
+            .filter(|info| info.pid == self.pid && info.visible)
+            .map(|info| info.hwnd)
+            .collect()
+    }
+
+    /// Guesses the main window of the process.
+    ///
+    /// Windows has no notion of a main window, so this picks the largest of the
+    /// process's visible top-level windows that have a title and no owner
+    /// (owned windows are dialogs and tool windows). The guess can be wrong: a
+    /// splash screen may be the only candidate while the application starts,
+    /// and applications with several document windows have no single answer.
+    /// After launching an application, [`wait_for_input_idle`](Self::wait_for_input_idle)
+    /// first to give it a chance to create its window.
+    ///
+    /// Returns `None` if the process has no such window.
+    pub fn main_window(&self) -> Option<HWND> {
+        crate::window::enumerate()
+            .into_iter()
+            .filter(|info| info.pid == self.pid && info.visible && !info.title.is_empty())
+            .filter(|info| {
+                // SAFETY: GetWindow accepts any handle and fails for stale ones
+                unsafe { GetWindow(info.hwnd, GW_OWNER) }.is_err()
+            })
+            .max_by_key(|info| {
+                let mut rect = RECT::default();
+                // SAFETY: rect is a valid output parameter; stale windows count as empty
+                let _ = unsafe { GetWindowRect(info.hwnd, &mut rect) };
+                (rect.right - rect.left).max(0) as i64 * (rect.bottom - rect.top).max(0) as i64
+            })
+            .map(|info| info.hwnd)
+    }
+
+    /// Gets the process affinity mask and the system affinity mask.
+    ///
+    /// Returns `(process_mask, system_mask)`, where each set bit is a logical
+    /// processor the process may run on, or that is configured on the system.
+    ///
+    /// Masks only cover the 64 processors of a single processor group. On systems
+    /// with more than one group, both masks may be zero if the process spans groups.
+    pub fn affinity_mask(&self) -> Result<(usize, usize)> {
+        let mut process_mask = 0usize;
+        let mut system_mask = 0usize;
+        // SAFETY: self.handle is a valid process handle; both masks are valid output parameters.
+        unsafe {
+            GetProcessAffinityMask(self.handle.as_raw(), &mut process_mask, &mut system_mask)?;
+        }
+        Ok((process_mask, system_mask))
+    }
+
+    /// Restricts the process to the logical processors set in `mask`.
+    ///
+    /// The mask must be a subset of the system mask from [`affinity_mask`](Self::affinity_mask)
+    /// and, like it, only addresses processors in a single processor group (at most 64).
+    /// The handle needs `PROCESS_SET_INFORMATION` access (see [`ProcessAccess::SET_INFORMATION`]).
+    pub fn set_affinity_mask(&self, mask: usize) -> Result<()> {
+        // SAFETY: self.handle is a valid process handle.
+        unsafe {
+            SetProcessAffinityMask(self.handle.as_raw(), mask)?;
+        }
+        Ok(())
+    }
+
+    /// Reads the environment variables of the process.
+    ///
+    /// The block is read from the target's PEB, so it reflects changes the
+    /// process made to its own environment. Requires `PROCESS_QUERY_INFORMATION`
+    /// and `PROCESS_VM_READ` access (see [`ProcessAccess::QUERY_AND_READ`]).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Unsupported`] if the target's bitness differs from the
+    /// caller's, since the PEB layout differs between them.
+    pub fn environment(&self) -> Result<Vec<(String, String)>> {
+        // Offsets of PEB.ProcessParameters and of Environment and EnvironmentSize
+        // in RTL_USER_PROCESS_PARAMETERS
+        #[cfg(target_pointer_width = "64")]
+        const OFFSETS: (usize, usize, usize) = (0x20, 0x80, 0x3F0);
+        #[cfg(target_pointer_width = "32")]
+        const OFFSETS: (usize, usize, usize) = (0x10, 0x48, 0x290);
+        // Guards against reading a corrupt size
+        const MAX_ENVIRONMENT: usize = 64 * 1024 * 1024;
+
+        if is_wow64(current_process())? != is_wow64(self.handle.as_raw())? {
+            return Err(Error::unsupported(
+                "Cannot read the PEB of a process with a different bitness",
+            ));
+        }
+
+        let (parameters_offset, environment_offset, size_offset) = OFFSETS;
+        let peb = self.peb_address()?;
+        let parameters = self.read_usize(peb + parameters_offset)?;
+        let environment = self.read_usize(parameters + environment_offset)?;
+        let size = self.read_usize(parameters + size_offset)?;
+        if environment == 0 || size > MAX_ENVIRONMENT {
+            return Err(Error::custom("Process has no readable environment block"));
+        }
+
+        let mut block = vec![0u16; size / 2];
+        // SAFETY: block is valid for size / 2 UTF-16 units
+        let bytes = unsafe {
+            std::slice::from_raw_parts_mut(block.as_mut_ptr() as *mut u8, block.len() * 2)
+        };
+        self.read_memory(environment, bytes)?;
+
+        Ok(parse_environment_block(&block))
+    }
+
+    /// Gets the address of the process's PEB.
+    fn peb_address(&self) -> Result<usize> {
+        /// Native PROCESS_BASIC_INFORMATION layout.
+        #[repr(C)]
+        #[derive(Default)]
+        struct ProcessBasicInformation {
+            exit_status: i32,
+            peb_base_address: usize,
+            affinity_mask: usize,
+            base_priority: i32,
+            unique_process_id: usize,
+            inherited_from_unique_process_id: usize,
+        }
+        type NtQueryInformationProcessFn =
+            unsafe extern "system" fn(
+                HANDLE,
+                i32,
+                *mut std::ffi::c_void,
+                u32,
+                *mut u32,
+            ) -> windows::Win32::Foundation::NTSTATUS;
+        const PROCESS_BASIC_INFORMATION_CLASS: i32 = 0;
+
+        let query = crate::resolve!(
+            "ntdll.dll",
+            "NtQueryInformationProcess",
+            NtQueryInformationProcessFn
+        )?;
+
+        let mut info = ProcessBasicInformation::default();
+        let mut returned = 0u32;
+        // SAFETY: info is a writable buffer of the size passed
+        let status = unsafe {
+            query(
+                self.handle.as_raw(),
+                PROCESS_BASIC_INFORMATION_CLASS,
+                &mut info as *mut _ as *mut _,
+                std::mem::size_of::<ProcessBasicInformation>() as u32,
+                &mut returned,
+            )
+        };
+        if status.is_err() {
+            return Err(Error::from_ntstatus(status.0));
+        }
+        Ok(info.peb_base_address)
+    }
+
+    /// Reads exactly `buffer.len()` bytes from the process at `address`.
+    fn read_memory(&self, address: usize, buffer: &mut [u8]) -> Result<()> {
+        // SAFETY: buffer is valid for buffer.len() bytes; the source address is
+        // in the other process and validated by ReadProcessMemory.
+        unsafe {
+            ReadProcessMemory(
+                self.handle.as_raw(),
+                address as *const _,
+                buffer.as_mut_ptr() as *mut _,
+                buffer.len(),
+                None,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Reads a pointer-sized value from the process.
+    fn read_usize(&self, address: usize) -> Result<usize> {
+        let mut bytes = [0u8; std::mem::size_of::<usize>()];
+        self.read_memory(address, &mut bytes)?;
+        Ok(usize::from_ne_bytes(bytes))
+    }
+}
+
+/// Returns true if the process runs under WOW64.
+fn is_wow64(process: HANDLE) -> Result<bool> {
+    let mut wow64 = BOOL::default();
+    // SAFETY: wow64 is a valid output parameter
+    unsafe { IsWow64Process(process, &mut wow64)? };
+    Ok(wow64.as_bool())
+}
+
+/// Parses a `KEY=VALUE\0...\0\0` environment block.
+///
+/// Keys may start with `=` (e.g. the hidden `=C:` per-drive directories),
+/// so the separator is searched for after the first character.
+fn parse_environment_block(block: &[u16]) -> Vec<(String, String)> {
+    // The block may be followed by padding; it ends at the first empty entry
+    let end = block
+        .windows(2)
+        .position(|pair| pair == [0, 0])
+        .map_or(block.len(), |i| i + 2);
+    unpack_multi(&block[..end])
+        .into_iter()
+        .map(
+            |entry| match entry.char_indices().skip(1).find(|&(_, c)| c == '=') {
+                Some((i, _)) => (entry[..i].to_string(), entry[i + 1..].to_string()),
+                None => (entry, String::new()),
+            },
+        )
+        .collect()
+}
+
+/// Process timing information returned by [`Process::times`].
+///
+/// Creation and exit times are file times (100-nanosecond intervals since
+/// January 1, 1601 UTC), matching [`crate::time::SystemTime::from_file_time`].
+#[derive(Debug, Clone, Copy)]
+pub struct ProcessTimes {
+    /// When the process was created.
+    pub creation_time: u64,
+    /// When the process exited, or 0 if it is still running.
+    pub exit_time: u64,
+    /// CPU time spent in kernel mode.
+    pub kernel_time: Duration,
+    /// CPU time spent in user mode.
+    pub user_time: Duration,
 }
 
+/// Process memory usage returned by [`Process::memory_info`].
+#[derive(Debug, Clone, Copy)]
+pub struct ProcessMemoryInfo {
+    /// Current working set size in bytes.
+    pub working_set: usize,
+    /// Peak working set size in bytes.
+    pub peak_working_set: usize,
+    /// Private bytes committed for the process.
+    pub pagefile_usage: usize,
+    /// Peak private bytes committed for the process.
+    pub peak_pagefile_usage: usize,
+    /// Number of page faults.
+    pub page_fault_count: u32,
+}
+
+impl Waitable for Process {
+    fn raw_handle(&self) -> HANDLE {
+        self.handle.as_raw()
+    }
+}
+
+/// Signature of `NtSuspendProcess` and `NtResumeProcess`.
+type NtProcessFn = unsafe extern "system" fn(HANDLE) -> windows::Win32::Foundation::NTSTATUS;
+
 /// Process access rights for opening existing processes.
 #[derive(Clone, Copy, Debug)]
 pub struct ProcessAccess(pub windows::Win32::System::Threading::PROCESS_ACCESS_RIGHTS);
@@ -143,9 +580,26 @@ impl ProcessAccess {
     /// Access to query process information.
     pub const QUERY: Self = Self(PROCESS_QUERY_INFORMATION);
 
+    /// Access to query a limited set of process information.
+    ///
+    /// Granted for more processes than [`QUERY`](Self::QUERY), such as elevated ones.
+    pub const QUERY_LIMITED: Self = Self(PROCESS_QUERY_LIMITED_INFORMATION);
+
     /// Access to terminate the process.
     pub const TERMINATE: Self = Self(PROCESS_TERMINATE);
 
+    /// Access to set process information such as the affinity mask.
+    pub const SET_INFORMATION: Self = Self(PROCESS_SET_INFORMATION);
+
+    /// Access to suspend and resume the process.
+    pub const SUSPEND_RESUME: Self = Self(PROCESS_SUSPEND_RESUME);
+
+    /// Access to query information and read memory, e.g. for [`Process::environment`].
+    pub const QUERY_AND_READ: Self =
+        Self(windows::Win32::System::Threading::PROCESS_ACCESS_RIGHTS(
+            PROCESS_QUERY_INFORMATION.0 | PROCESS_VM_READ.0,
+        ));
+
     /// Access to query information and terminate.
     pub const QUERY_AND_TERMINATE: Self =
         Self(windows::Win32::System::Threading::PROCESS_ACCESS_RIGHTS(
@@ -153,6 +607,17 @@ impl ProcessAccess {
         ));
 }
 
+/// The result of [`Command::output`].
+#[derive(Debug, Clone)]
+pub struct Output {
+    /// The process exit code.
+    pub exit_code: u32,
+    /// Everything the process wrote to standard output.
+    pub stdout: Vec<u8>,
+    /// Everything the process wrote to standard error.
+    pub stderr: Vec<u8>,
+}
+
 /// Builder for creating new processes.
 pub struct Command {
     program: String,
@@ -160,6 +625,11 @@ pub struct Command {
     current_dir: Option<String>,
     creation_flags: PROCESS_CREATION_FLAGS,
     env: Option<Vec<(String, String)>>,
+    stdin: Option<Vec<u8>>,
+    show_window: Option<ShowCommand>,
+    window_position: Option<(i32, i32)>,
+    window_size: Option<(u32, u32)>,
+    desktop: Option<String>,
 }
 
 impl Command {
@@ -171,6 +641,11 @@ impl Command {
             current_dir: None,
             creation_flags: PROCESS_CREATION_FLAGS(0),
             env: None,
+            stdin: None,
+            show_window: None,
+            window_position: None,
+            window_size: None,
+            desktop: None,
         }
     }
 
@@ -216,12 +691,180 @@ impl Command {
         self
     }
 
+    /// Feeds `data` to the process as its standard input.
+    ///
+    /// The child reads from an anonymous pipe. A helper thread writes `data` to
+    /// the other end and then closes it, so the child sees end-of-file after the
+    /// last byte; writing from a separate thread means a child that produces
+    /// output before consuming all its input cannot deadlock against the parent.
+    /// If the child exits without reading everything, the rest is discarded.
+    ///
+    /// Standard output and error stay attached to the parent's.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ergonomic_windows::process::Command;
+    ///
+    /// let status = Command::new("findstr")
+    ///     .arg("banana")
+    ///     .stdin_bytes(b"apple\nbanana\n")
+    ///     .run()?;
+    /// assert_eq!(status, 0);
+    /// # Ok::<(), ergonomic_windows::error::Error>(())
+    /// ```
+    pub fn stdin_bytes(mut self, data: impl Into<Vec<u8>>) -> Self {
+        self.stdin = Some(data.into());
+        self
+    }
+
+    /// Sets how the child's first window is shown (`STARTF_USESHOWWINDOW`).
+    ///
+    /// Like the other window options, this is only a request passed through
+    /// `STARTUPINFO`: it affects GUI programs that honor it, typically by
+    /// calling `ShowWindow` with `SW_SHOWDEFAULT` for their main window.
+    /// Console programs get it applied to a newly created console window.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ergonomic_windows::process::Command;
+    /// use ergonomic_windows::window::ShowCommand;
+    ///
+    /// let process = Command::new("notepad.exe")
+    ///     .window_show(ShowCommand::MINIMIZE)
+    ///     .spawn()?;
+    /// # Ok::<(), ergonomic_windows::error::Error>(())
+    /// ```
+    pub fn window_show(mut self, show: ShowCommand) -> Self {
+        self.show_window = Some(show);
+        self
+    }
+
+    /// Sets the position of the child's first window in screen coordinates
+    /// (`STARTF_USEPOSITION`).
+    ///
+    /// Only honored by programs that create their window at `CW_USEDEFAULT`.
+    pub fn window_position(mut self, x: i32, y: i32) -> Self {
+        self.window_position = Some((x, y));
+        self
+    }
+
+    /// Sets the size of the child's first window in pixels (`STARTF_USESIZE`).
+    ///
+    /// Only honored by programs that create their window at `CW_USEDEFAULT`.
+    pub fn window_size(mut self, width: u32, height: u32) -> Self {
+        self.window_size = Some((width, height));
+        self
+    }
+
+    /// Starts the process on the named desktop, such as `"winsta0\\default"`.
+    ///
+    /// A name without a window station refers to a desktop in the caller's
+    /// window station. The caller needs access to the desktop; the child's
+    /// windows only appear to users on that desktop.
+    pub fn desktop(mut self, name: impl Into<String>) -> Self {
+        self.desktop = Some(name.into());
+        self
+    }
+
     /// Spawns the process.
     ///
     /// # Errors
     ///
     /// Returns an error if the process cannot be created (e.g., program not found).
     pub fn spawn(self) -> Result<Process> {
+        self.spawn_inner(false, None).map(|(process, _)| process)
+    }
+
+    /// Spawns the process with `token` as its primary token (`CreateProcessAsUserW`).
+    ///
+    /// Pair it with [`Token::create_restricted`] to run a child with fewer
+    /// rights than the caller. The token must be a primary token opened with
+    /// `TOKEN_QUERY`, `TOKEN_DUPLICATE` and `TOKEN_ASSIGN_PRIMARY` access. A
+    /// token for another user, rather than a restricted copy of the caller's
+    /// own, also needs `SeAssignPrimaryTokenPrivilege`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ergonomic_windows::process::Command;
+    /// use ergonomic_windows::security::{RestrictOptions, Token};
+    /// use windows::Win32::Security::{TOKEN_ASSIGN_PRIMARY, TOKEN_DUPLICATE, TOKEN_QUERY};
+    ///
+    /// let token = Token::current_process_with_access(
+    ///     TOKEN_QUERY | TOKEN_DUPLICATE | TOKEN_ASSIGN_PRIMARY,
+    /// )?;
+    /// let restricted = token.create_restricted(RestrictOptions::new().disable_max_privilege(true))?;
+    /// let process = Command::new("cmd").args(["/c", "whoami /priv"]).spawn_as_user(&restricted)?;
+    /// process.wait()?;
+    /// # Ok::<(), ergonomic_windows::error::Error>(())
+    /// ```
+    pub fn spawn_as_user(self, token: &Token) -> Result<Process> {
+        self.spawn_inner(false, Some(token))
+            .map(|(process, _)| process)
+    }
+
+    /// Spawns the process and collects its standard output and error.
+    ///
+    /// Both streams are redirected to anonymous pipes. Standard error is read
+    /// on a helper thread while standard output is read on this one, so a
+    /// child that fills one pipe while the parent waits on the other cannot
+    /// deadlock. Returns once the child has exited and both pipes reached
+    /// end-of-file; a grandchild that inherited the pipes keeps them open
+    /// until it exits too.
+    ///
+    /// Standard input comes from [`stdin_bytes`](Self::stdin_bytes) if set,
+    /// and from the parent's otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ergonomic_windows::process::Command;
+    ///
+    /// let output = Command::new("cmd").args(["/c", "echo hi"]).output()?;
+    /// assert_eq!(output.exit_code, 0);
+    /// assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "hi");
+    /// # Ok::<(), ergonomic_windows::error::Error>(())
+    /// ```
+    pub fn output(self) -> Result<Output> {
+        let (process, pipes) = self.spawn_inner(true, None)?;
+        let (mut stdout_reader, stderr_reader) =
+            pipes.ok_or_else(|| Error::custom("Output pipes were not created"))?;
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        // stderr is drained on a second thread so a child filling one pipe
+        // can't deadlock against us reading the other
+        let stderr_reader = ThreadPipe(stderr_reader);
+        crate::thread::scope(|s| -> Result<()> {
+            let stderr = &mut stderr;
+            s.spawn(move || {
+                let mut reader = stderr_reader.into_inner();
+                // A read error ends the stream early; stdout errors are reported instead.
+                let _ = std::io::Read::read_to_end(&mut reader, stderr);
+                0
+            })?;
+            std::io::Read::read_to_end(&mut stdout_reader, &mut stdout)
+                .map_err(|e| Error::io_error(e, "reading child stdout"))?;
+            Ok(())
+        })?;
+
+        let exit_code = process.wait()?;
+        Ok(Output {
+            exit_code,
+            stdout,
+            stderr,
+        })
+    }
+
+    /// Spawns the process, redirecting stdout and stderr to pipes if `capture`
+    /// is set and returning their read ends.
+    fn spawn_inner(
+        self,
+        capture: bool,
+        token: Option<&Token>,
+    ) -> Result<(Process, Option<(PipeReader, PipeReader)>)> {
         let command_line = self.build_command_line();
         let mut command_line_wide = to_wide(&command_line);
 
@@ -229,52 +872,128 @@ impl Command {
 
         let env_block = self.build_env_block();
 
-        let startup_info = STARTUPINFOW {
-            cb: std::mem::size_of::<STARTUPINFOW>() as u32,
+        let mut desktop_wide = self.desktop.as_deref().map(to_wide);
+        let mut startup_info = self.build_startup_info(desktop_wide.as_mut());
+
+        // Pipe ends are created non-inheritable; the child gets inheritable
+        // duplicates of its own ends only, made while SPAWN_LOCK is held.
+        let stdin_pipe = match &self.stdin {
+            Some(_) => Some(AnonymousPipe::pair(false, false)?),
+            None => None,
+        };
+        let output_pipes = if capture {
+            Some((
+                AnonymousPipe::pair(false, false)?,
+                AnonymousPipe::pair(false, false)?,
+            ))
+        } else {
+            None
+        };
+        let inherit_handles = stdin_pipe.is_some() || output_pipes.is_some();
+
+        let spawn_guard = inherit_handles.then(|| {
+            SPAWN_LOCK
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+        });
+        let mut child_stdio = Vec::new();
+        let mut attributes = None;
+        if inherit_handles {
+            // SAFETY: GetStdHandle has no preconditions
+            let parent = |kind| unsafe { GetStdHandle(kind).unwrap_or_default() };
+            let stdin = match &stdin_pipe {
+                Some((reader, _)) => reader.as_raw(),
+                None => parent(STD_INPUT_HANDLE),
+            };
+            let (stdout, stderr) = match &output_pipes {
+                Some(((_, out), (_, err))) => (out.as_raw(), err.as_raw()),
+                None => (parent(STD_OUTPUT_HANDLE), parent(STD_ERROR_HANDLE)),
+            };
+            child_stdio = [stdin, stdout, stderr]
+                .into_iter()
+                .map(inheritable_copy)
+                .collect::<Result<Vec<_>>>()?;
+            let raw =
+                |h: &Option<OwnedHandle>| h.as_ref().map_or(HANDLE::default(), |h| h.as_raw());
+
+            startup_info.dwFlags |= STARTF_USESTDHANDLES;
+            startup_info.hStdInput = raw(&child_stdio[0]);
+            startup_info.hStdOutput = raw(&child_stdio[1]);
+            startup_info.hStdError = raw(&child_stdio[2]);
+            attributes = Some(HandleListAttribute::new(
+                child_stdio
+                    .iter()
+                    .flatten()
+                    .map(OwnedHandle::as_raw)
+                    .collect(),
+            )?);
+        }
+
+        let mut startup_info_ex = STARTUPINFOEXW {
+            StartupInfo: startup_info,
             ..Default::default()
         };
+        let mut creation_flags = self.creation_flags;
+        if let Some(attributes) = &attributes {
+            startup_info_ex.StartupInfo.cb = std::mem::size_of::<STARTUPINFOEXW>() as u32;
+            startup_info_ex.lpAttributeList = attributes.as_raw();
+            creation_flags.0 |= EXTENDED_STARTUPINFO_PRESENT.0;
+        }
 
         let mut process_info = PROCESS_INFORMATION::default();
 
-        let creation_flags = if env_block.is_some() {
-            PROCESS_CREATION_FLAGS(self.creation_flags.0 | CREATE_UNICODE_ENVIRONMENT.0)
-        } else {
-            self.creation_flags
-        };
+        if env_block.is_some() {
+            creation_flags.0 |= CREATE_UNICODE_ENVIRONMENT.0;
+        }
 
-        // SAFETY: All pointers passed to CreateProcessW are valid:
+        let command_line = windows::core::PWSTR(command_line_wide.as_mut_ptr());
+        let environment = env_block.as_ref().map(|e| e.as_ptr() as *const _);
+        let current_dir = current_dir_wide
+            .as_ref()
+            .map_or(windows::core::PCWSTR::null(), |dir| dir.as_pcwstr());
+
+        // SAFETY: All pointers passed to CreateProcessW/CreateProcessAsUserW are valid:
         // - command_line_wide is a valid mutable buffer (CreateProcessW may modify it)
         // - env_block is either None or points to a valid double-null-terminated block
-        // - current_dir_wide is either None or a valid null-terminated string
-        // - startup_info and process_info are valid stack-allocated structs
-        unsafe {
-            match &current_dir_wide {
-                Some(dir) => CreateProcessW(
+        // - current_dir is null or points into current_dir_wide, which outlives the call
+        // - startup_info.lpDesktop is null or points into desktop_wide, which outlives the call
+        // - startup_info_ex and process_info are valid stack-allocated structs; its
+        //   attribute list, if any, is initialized and outlives the call
+        // - token, if any, is a valid token handle borrowed for the call
+        let created = unsafe {
+            match token {
+                Some(token) => CreateProcessAsUserW(
+                    token.as_raw(),
                     None,
-                    windows::core::PWSTR(command_line_wide.as_mut_ptr()),
+                    command_line,
                     None,
                     None,
-                    false,
+                    inherit_handles,
                     creation_flags,
-                    env_block.as_ref().map(|e| e.as_ptr() as *const _),
-                    dir.as_pcwstr(),
-                    &startup_info,
+                    environment,
+                    current_dir,
+                    &startup_info_ex.StartupInfo,
                     &mut process_info,
-                )?,
+                ),
                 None => CreateProcessW(
                     None,
-                    windows::core::PWSTR(command_line_wide.as_mut_ptr()),
+                    command_line,
                     None,
                     None,
-                    false,
+                    inherit_handles,
                     creation_flags,
-                    env_block.as_ref().map(|e| e.as_ptr() as *const _),
-                    None,
-                    &startup_info,
+                    environment,
+                    current_dir,
+                    &startup_info_ex.StartupInfo,
                     &mut process_info,
-                )?,
-            };
-        }
+                ),
+            }
+        };
+        // The child has its own copies now; close ours before other spawns may run.
+        drop(attributes);
+        drop(child_stdio);
+        drop(spawn_guard);
+        created?;
 
         // Close the thread handle immediately - we don't need it.
         // SAFETY: process_info.hThread is a valid handle returned by CreateProcessW.
@@ -285,10 +1004,20 @@ impl Command {
             }
         }
 
-        Ok(Process {
+        let process = Process {
             handle: OwnedHandle::new(process_info.hProcess)?,
             pid: process_info.dwProcessId,
-        })
+        };
+
+        if let (Some((reader, writer)), Some(data)) = (stdin_pipe, self.stdin) {
+            drop(reader);
+            feed_stdin(writer, data)?;
+        }
+
+        // Drop our write ends too, or the reads never see end-of-file.
+        let readers = output_pipes.map(|((out, _), (err, _))| (out, err));
+
+        Ok((process, readers))
     }
 
     /// Spawns the process and waits for it to complete.
@@ -297,6 +1026,35 @@ impl Command {
         process.wait()
     }
 
+    /// Fills in the window and desktop fields of `STARTUPINFOW`.
+    ///
+    /// `desktop` must be the null-terminated wide form of `self.desktop` and
+    /// outlive the returned struct, which points into it.
+    fn build_startup_info(&self, desktop: Option<&mut Vec<u16>>) -> STARTUPINFOW {
+        let mut startup_info = STARTUPINFOW {
+            cb: std::mem::size_of::<STARTUPINFOW>() as u32,
+            ..Default::default()
+        };
+        if let Some(show) = self.show_window {
+            startup_info.dwFlags |= STARTF_USESHOWWINDOW;
+            startup_info.wShowWindow = show.0 .0 as u16;
+        }
+        if let Some((x, y)) = self.window_position {
+            startup_info.dwFlags |= STARTF_USEPOSITION;
+            startup_info.dwX = x as u32;
+            startup_info.dwY = y as u32;
+        }
+        if let Some((width, height)) = self.window_size {
+            startup_info.dwFlags |= STARTF_USESIZE;
+            startup_info.dwXSize = width;
+            startup_info.dwYSize = height;
+        }
+        if let Some(desktop) = desktop {
+            startup_info.lpDesktop = windows::core::PWSTR(desktop.as_mut_ptr());
+        }
+        startup_info
+    }
+
     fn build_command_line(&self) -> String {
         // Pre-calculate total length to minimize allocations.
         // Each arg needs at most: original length + 2 (quotes) + 1 (space separator)
@@ -315,17 +1073,131 @@ impl Command {
 
     fn build_env_block(&self) -> Option<Vec<u16>> {
         let env = self.env.as_ref()?;
-        let mut block = Vec::new();
+        let entries: Vec<String> = env
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect();
+        Some(pack_multi_wide(
+            entries.iter().map(|entry| entry.encode_utf16()),
+        ))
+    }
+}
 
-        for (key, value) in env {
-            let entry = format!("{}={}", key, value);
-            block.extend(entry.encode_utf16());
-            block.push(0);
-        }
-        block.push(0); // Double null terminator
+/// Held while this crate's spawns have inheritable handles open, so a
+/// concurrent spawn can't pick up another child's pipe ends and keep them
+/// open. Other code in the process that creates children with inheritance
+/// enabled and no handle list can still inherit them during that window.
+static SPAWN_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
 
-        Some(block)
+/// Makes an inheritable duplicate of `handle` for a child's standard stream.
+///
+/// Returns `None` for a null or invalid handle, such as a missing console
+/// stream in a GUI process.
+fn inheritable_copy(handle: HANDLE) -> Result<Option<OwnedHandle>> {
+    if handle.is_invalid() || handle.0.is_null() {
+        return Ok(None);
     }
+    let mut copy = HANDLE::default();
+    // SAFETY: handle is a valid handle in this process; the copy is owned below
+    unsafe {
+        let process = GetCurrentProcess();
+        DuplicateHandle(
+            process,
+            handle,
+            process,
+            &mut copy,
+            0,
+            true,
+            DUPLICATE_SAME_ACCESS,
+        )?;
+    }
+    Ok(Some(OwnedHandle::new(copy)?))
+}
+
+/// A `PROC_THREAD_ATTRIBUTE_LIST` limiting inheritance to a list of handles.
+struct HandleListAttribute {
+    // usize storage keeps the opaque list pointer-aligned.
+    buffer: Vec<usize>,
+    // Referenced by the list until it is deleted.
+    handles: Vec<HANDLE>,
+}
+
+impl HandleListAttribute {
+    fn new(handles: Vec<HANDLE>) -> Result<Self> {
+        let mut size = 0usize;
+        // SAFETY: A null list with a size pointer only queries the required size,
+        // failing with ERROR_INSUFFICIENT_BUFFER
+        let _ = unsafe {
+            InitializeProcThreadAttributeList(
+                LPPROC_THREAD_ATTRIBUTE_LIST::default(),
+                1,
+                0,
+                &mut size,
+            )
+        };
+        let mut buffer = vec![0usize; size.div_ceil(std::mem::size_of::<usize>())];
+        let raw = LPPROC_THREAD_ATTRIBUTE_LIST(buffer.as_mut_ptr() as *mut _);
+        // SAFETY: buffer holds at least `size` bytes
+        unsafe { InitializeProcThreadAttributeList(raw, 1, 0, &mut size)? };
+        // From here on, dropping `list` deletes the initialized list.
+        let list = Self { buffer, handles };
+        // SAFETY: the list is initialized and `handles` lives as long as it does
+        unsafe {
+            UpdateProcThreadAttribute(
+                list.as_raw(),
+                0,
+                PROC_THREAD_ATTRIBUTE_HANDLE_LIST as usize,
+                Some(list.handles.as_ptr() as *const _),
+                std::mem::size_of_val(list.handles.as_slice()),
+                None,
+                None,
+            )?
+        };
+        Ok(list)
+    }
+
+    fn as_raw(&self) -> LPPROC_THREAD_ATTRIBUTE_LIST {
+        LPPROC_THREAD_ATTRIBUTE_LIST(self.buffer.as_ptr() as *mut _)
+    }
+}
+
+impl Drop for HandleListAttribute {
+    fn drop(&mut self) {
+        // SAFETY: the list was initialized in new and is deleted only here
+        unsafe { DeleteProcThreadAttributeList(self.as_raw()) };
+    }
+}
+
+/// A pipe end being moved to a helper thread.
+///
+/// `HANDLE` wraps a raw pointer and so isn't `Send`, although kernel handles
+/// work from any thread. If the thread fails to start, the closure is dropped
+/// and the pipe end with it, so the handle is still closed.
+struct ThreadPipe<T>(T);
+
+// SAFETY: the pipe end owns its handle, which is only used by one thread at a time
+unsafe impl Send for ThreadPipe<PipeReader> {}
+// SAFETY: as above
+unsafe impl Send for ThreadPipe<PipeWriter> {}
+
+impl<T> ThreadPipe<T> {
+    /// Unwraps the pipe end; taking `self` makes closures capture the whole wrapper.
+    fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+/// Writes `data` to a child's stdin pipe on a helper thread, then closes it.
+fn feed_stdin(writer: PipeWriter, data: Vec<u8>) -> Result<()> {
+    let writer = ThreadPipe(writer);
+    let thread = Thread::spawn(move || {
+        let mut writer = writer.into_inner();
+        // A child that exits early breaks the pipe; the unread rest is dropped.
+        let _ = std::io::Write::write_all(&mut writer, &data);
+        0
+    });
+    // The thread ends on its own once the data is written or the child exits.
+    thread.map(drop)
 }
 
 /// Quotes a command-line argument if necessary.
@@ -414,6 +1286,74 @@ pub fn current_process() -> HANDLE {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use windows::Win32::UI::WindowsAndMessaging::SW_MINIMIZE;
+
+    #[test]
+    fn test_open_for_query() {
+        let process = Process::open_for_query(current_pid()).unwrap();
+        let path = process.image_path().unwrap();
+        assert!(path.is_absolute());
+        assert_eq!(path, std::env::current_exe().unwrap());
+
+        let times = process.times().unwrap();
+        assert!(times.creation_time > 0);
+        assert_eq!(times.exit_time, 0);
+
+        let process = Process::open(current_pid(), ProcessAccess::QUERY_AND_READ).unwrap();
+        assert!(process.memory_info().unwrap().working_set > 0);
+    }
+
+    #[test]
+    fn test_affinity_mask() {
+        let process = Process::open(current_pid(), ProcessAccess::QUERY).unwrap();
+        let (process_mask, system_mask) = process.affinity_mask().unwrap();
+        assert_eq!(process_mask & !system_mask, 0);
+    }
+
+    #[test]
+    fn test_environment_of_current_process() {
+        let process = Process::open(current_pid(), ProcessAccess::QUERY_AND_READ).unwrap();
+        let environment = process.environment().unwrap();
+        assert!(environment
+            .iter()
+            .any(|(key, value)| key.eq_ignore_ascii_case("PATH") && !value.is_empty()));
+    }
+
+    #[test]
+    fn test_parse_environment_block() {
+        let block: Vec<u16> = "=C:=C:\\dir\0A=1\0EMPTY=\0\0garbage"
+            .encode_utf16()
+            .collect();
+        assert_eq!(
+            parse_environment_block(&block),
+            vec![
+                ("=C:".to_string(), "C:\\dir".to_string()),
+                ("A".to_string(), "1".to_string()),
+                ("EMPTY".to_string(), String::new()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_windows_of_current_process() {
+        use crate::window::{DefaultHandler, ShowCommand, WindowBuilder};
+
+        // Window creation may fail in headless environments
+        let Ok(window) = WindowBuilder::new()
+            .class_name(format!("ErgonomicProcessWindowTest_{}", current_pid()))
+            .title("Process window test")
+            .size(320, 240)
+            .build(DefaultHandler)
+        else {
+            return;
+        };
+        window.show(ShowCommand::SHOW);
+
+        let process = Process::open_for_query(current_pid()).unwrap();
+        assert!(process.windows().contains(&window.hwnd()));
+        // Other tests may have larger windows open, so only check one is found
+        assert!(process.main_window().is_some());
+    }
 
     #[test]
     fn test_quote_arg() {
@@ -463,6 +1403,165 @@ mod tests {
         assert_eq!(exit_code.unwrap(), 42);
     }
 
+    #[test]
+    fn test_stdin_bytes() {
+        // findstr exits with 0 when a line matches and 1 when none does
+        let found = Command::new("findstr.exe")
+            .arg("banana")
+            .stdin_bytes(b"apple\nbanana\ncherry\n")
+            .no_window()
+            .run()
+            .unwrap();
+        assert_eq!(found, 0);
+
+        let missing = Command::new("findstr.exe")
+            .arg("durian")
+            .stdin_bytes(b"apple\nbanana\ncherry\n")
+            .no_window()
+            .run()
+            .unwrap();
+        assert_eq!(missing, 1);
+
+        // The child must have read exactly the bytes we fed it
+        let output = Command::new("findstr.exe")
+            .arg("/n")
+            .arg("^")
+            .stdin_bytes(b"apple\nbanana\ncherry\n")
+            .no_window()
+            .output()
+            .unwrap();
+        assert_eq!(output.exit_code, 0);
+        let lines: Vec<String> = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(|line| line.trim_end().to_string())
+            .collect();
+        assert_eq!(lines, ["1:apple", "2:banana", "3:cherry"]);
+    }
+
+    #[test]
+    fn test_output_captures_stdout() {
+        let output = Command::new("cmd.exe")
+            .args(["/c", "echo hi"])
+            .output()
+            .unwrap();
+        assert_eq!(output.exit_code, 0);
+        assert!(String::from_utf8_lossy(&output.stdout).contains("hi"));
+        assert!(output.stderr.is_empty());
+    }
+
+    #[test]
+    fn test_output_captures_stderr_and_exit_code() {
+        let output = Command::new("cmd.exe")
+            .args(["/c", "echo oops 1>&2 & exit 3"])
+            .output()
+            .unwrap();
+        assert_eq!(output.exit_code, 3);
+        assert!(String::from_utf8_lossy(&output.stderr).contains("oops"));
+    }
+
+    #[test]
+    fn test_output_large_streams_do_not_deadlock() {
+        // Both streams exceed the default pipe buffer
+        let output = Command::new("cmd.exe")
+            .args([
+                "/c",
+                "for /l %i in (1,1,3000) do @(echo out %i & echo err %i 1>&2)",
+            ])
+            .output()
+            .unwrap();
+        assert_eq!(output.exit_code, 0);
+        assert!(output.stdout.len() > 4096 * 4);
+        assert!(output.stderr.len() > 4096 * 4);
+    }
+
+    #[test]
+    fn test_output_with_stdin_bytes() {
+        let output = Command::new("findstr")
+            .arg("banana")
+            .stdin_bytes(b"apple\nbanana\n".to_vec())
+            .output()
+            .unwrap();
+        assert_eq!(output.exit_code, 0);
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "banana");
+    }
+
+    #[test]
+    fn test_stdin_bytes_larger_than_pipe_buffer() {
+        // Far more than the pipe holds, so the writer must block on the child
+        let mut data = "filler line\n".repeat(100_000).into_bytes();
+        data.extend_from_slice(b"needle\n");
+        let output = Command::new("findstr.exe")
+            .arg("/n")
+            .arg("/c:needle")
+            .stdin_bytes(data)
+            .no_window()
+            .output()
+            .unwrap();
+        assert_eq!(output.exit_code, 0);
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout).trim_end(),
+            "100001:needle"
+        );
+    }
+
+    #[test]
+    fn test_startup_info_window_fields() {
+        let command = Command::new("notepad.exe")
+            .window_show(ShowCommand::MINIMIZE)
+            .window_position(-100, 50)
+            .window_size(640, 480);
+        let info = command.build_startup_info(None);
+        assert_eq!(
+            info.dwFlags,
+            STARTF_USESHOWWINDOW | STARTF_USEPOSITION | STARTF_USESIZE
+        );
+        assert_eq!(info.wShowWindow, SW_MINIMIZE.0 as u16);
+        assert_eq!((info.dwX as i32, info.dwY as i32), (-100, 50));
+        assert_eq!((info.dwXSize, info.dwYSize), (640, 480));
+        assert!(info.lpDesktop.is_null());
+
+        let mut desktop = to_wide("winsta0\\default");
+        let info = Command::new("cmd.exe").build_startup_info(Some(&mut desktop));
+        assert_eq!(info.dwFlags.0, 0);
+        assert_eq!(info.lpDesktop.0, desktop.as_mut_ptr());
+    }
+
+    #[test]
+    fn test_spawn_minimized_on_default_desktop() {
+        // The new console window honors the STARTUPINFO window fields
+        let exit_code = Command::new("cmd.exe")
+            .args(["/c", "exit 5"])
+            .new_console()
+            .window_show(ShowCommand::MINIMIZE)
+            .window_position(0, 0)
+            .window_size(320, 200)
+            .desktop("winsta0\\default")
+            .run()
+            .unwrap();
+        assert_eq!(exit_code, 5);
+    }
+
+    #[test]
+    fn test_spawn_as_user_with_restricted_token() {
+        use crate::security::RestrictOptions;
+        use windows::Win32::Security::{TOKEN_ASSIGN_PRIMARY, TOKEN_DUPLICATE, TOKEN_QUERY};
+
+        let token = Token::current_process_with_access(
+            TOKEN_QUERY | TOKEN_DUPLICATE | TOKEN_ASSIGN_PRIMARY,
+        )
+        .unwrap();
+        let restricted = token
+            .create_restricted(RestrictOptions::new().disable_max_privilege(true))
+            .unwrap();
+
+        let process = Command::new("cmd.exe")
+            .args(["/c", "exit 7"])
+            .no_window()
+            .spawn_as_user(&restricted)
+            .unwrap();
+        assert_eq!(process.wait().unwrap(), 7);
+    }
+
     #[test]
     fn test_spawn_nonexistent_program() {
         // Spawning a nonexistent program should fail
@@ -554,6 +1653,56 @@ mod tests {
         let _ = process.terminate(1);
     }
 
+    #[test]
+    fn test_suspend_resume() {
+        let process = Command::new("cmd.exe")
+            .arg("/c")
+            .arg("timeout /t 1 /nobreak > nul")
+            .no_window()
+            .spawn()
+            .unwrap();
+
+        process.suspend().unwrap();
+        // Frozen, it can't finish even after its timeout has elapsed
+        assert!(process
+            .wait_timeout(Some(Duration::from_millis(1500)))
+            .is_err());
+
+        process.resume().unwrap();
+        let result = process.wait_timeout(Some(Duration::from_secs(10)));
+        if result.is_err() {
+            let _ = process.terminate(1);
+        }
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_wait_for_input_idle_console_process() {
+        // Console applications have no message queue, so this fails immediately
+        let process = Command::new("cmd.exe")
+            .arg("/c")
+            .arg("timeout /t 2 /nobreak > nul")
+            .no_window()
+            .spawn()
+            .unwrap();
+
+        let result = process.wait_for_input_idle(Duration::from_secs(5));
+        assert!(result.is_err());
+
+        let _ = process.terminate(1);
+    }
+
+    #[test]
+    #[ignore = "launches a GUI application"]
+    fn test_wait_for_input_idle_gui_process() {
+        let process = Command::new("notepad.exe").spawn().unwrap();
+
+        let result = process.wait_for_input_idle(Duration::from_secs(10));
+        assert!(matches!(result, Ok(true)));
+
+        let _ = process.terminate(0);
+    }
+
     #[test]
     fn test_is_running() {
         let process = Command::new("cmd.exe")
@@ -3,18 +3,35 @@
 //! Provides ergonomic wrappers for reading and writing Windows Registry keys and values.
 
 use crate::error::{Error, Result};
-use crate::string::{from_wide, to_wide, WideString};
+use crate::handle::{OwnedHandle, Waitable};
+use crate::security::{SecurityDescriptor, SecurityInfo};
+use crate::string::{from_wide, pack_multi_wide, split_multi, to_wide, WideString};
+use crate::thread::WaitResult;
+use std::rc::Rc;
+use std::time::Duration;
 use windows::Win32::Foundation::{
-    ERROR_MORE_DATA, ERROR_NO_MORE_ITEMS, ERROR_SUCCESS, WIN32_ERROR,
+    ERROR_FILE_NOT_FOUND, ERROR_INSUFFICIENT_BUFFER, ERROR_MORE_DATA, ERROR_NO_MORE_ITEMS,
+    ERROR_SUCCESS, HANDLE, WIN32_ERROR,
+};
+use windows::Win32::Security::PSECURITY_DESCRIPTOR;
+use windows::Win32::Storage::FileSystem::{
+    CommitTransaction, CreateTransaction, RollbackTransaction,
 };
 use windows::Win32::System::Registry::{
-    RegCloseKey, RegCreateKeyExW, RegDeleteKeyW, RegDeleteValueW, RegEnumKeyExW, RegEnumValueW,
-    RegOpenKeyExW, RegQueryValueExW, RegSetValueExW, HKEY, HKEY_CLASSES_ROOT, HKEY_CURRENT_CONFIG,
-    HKEY_CURRENT_USER, HKEY_LOCAL_MACHINE, HKEY_USERS, KEY_ALL_ACCESS, KEY_CREATE_SUB_KEY,
-    KEY_ENUMERATE_SUB_KEYS, KEY_QUERY_VALUE, KEY_READ, KEY_SET_VALUE, KEY_WOW64_32KEY,
-    KEY_WOW64_64KEY, KEY_WRITE, REG_BINARY, REG_DWORD, REG_EXPAND_SZ, REG_MULTI_SZ,
-    REG_OPTION_NON_VOLATILE, REG_QWORD, REG_SAM_FLAGS, REG_SZ, REG_VALUE_TYPE,
+    RegCloseKey, RegCreateKeyExW, RegCreateKeyTransactedW, RegDeleteKeyTransactedW, RegDeleteKeyW,
+    RegDeleteTreeW, RegDeleteValueW, RegEnumKeyExW, RegEnumValueW, RegGetKeySecurity,
+    RegNotifyChangeKeyValue, RegOpenKeyExW, RegOpenKeyTransactedW, RegQueryInfoKeyW,
+    RegQueryValueExW, RegSetKeySecurity, RegSetValueExW, HKEY, HKEY_CLASSES_ROOT,
+    HKEY_CURRENT_CONFIG, HKEY_CURRENT_USER, HKEY_LOCAL_MACHINE, HKEY_USERS, KEY_ALL_ACCESS,
+    KEY_CREATE_SUB_KEY, KEY_ENUMERATE_SUB_KEYS, KEY_QUERY_VALUE, KEY_READ, KEY_SET_VALUE,
+    KEY_WOW64_32KEY, KEY_WOW64_64KEY, KEY_WRITE, REG_BINARY, REG_DWORD, REG_DWORD_BIG_ENDIAN,
+    REG_EXPAND_SZ, REG_FULL_RESOURCE_DESCRIPTOR, REG_LINK, REG_MULTI_SZ, REG_NONE,
+    REG_NOTIFY_CHANGE_ATTRIBUTES, REG_NOTIFY_CHANGE_LAST_SET, REG_NOTIFY_CHANGE_NAME,
+    REG_NOTIFY_CHANGE_SECURITY, REG_NOTIFY_FILTER, REG_NOTIFY_THREAD_AGNOSTIC,
+    REG_OPTION_NON_VOLATILE, REG_QWORD, REG_RESOURCE_LIST, REG_RESOURCE_REQUIREMENTS_LIST,
+    REG_SAM_FLAGS, REG_SZ, REG_VALUE_TYPE,
 };
+use windows::Win32::System::Threading::CreateEventW;
 
 /// Helper to convert WIN32_ERROR to Result
 fn check_error(err: WIN32_ERROR) -> Result<()> {
@@ -25,6 +42,87 @@ fn check_error(err: WIN32_ERROR) -> Result<()> {
     }
 }
 
+/// Parses raw registry data of the given type.
+fn parse_value(value_type: REG_VALUE_TYPE, buffer: Vec<u8>) -> Result<Value> {
+    match value_type {
+        REG_SZ | REG_EXPAND_SZ => {
+            let wide: Vec<u16> = buffer
+                .chunks_exact(2)
+                .map(|c| u16::from_le_bytes([c[0], c[1]]))
+                .collect();
+            let s = from_wide(&wide)?;
+            if value_type == REG_SZ {
+                Ok(Value::String(s))
+            } else {
+                Ok(Value::ExpandString(s))
+            }
+        }
+        REG_MULTI_SZ => {
+            let wide: Vec<u16> = buffer
+                .chunks_exact(2)
+                .map(|c| u16::from_le_bytes([c[0], c[1]]))
+                .collect();
+            Ok(Value::MultiString(parse_multi_string(&wide)?))
+        }
+        REG_DWORD => {
+            if buffer.len() >= 4 {
+                let value = u32::from_le_bytes([buffer[0], buffer[1], buffer[2], buffer[3]]);
+                Ok(Value::Dword(value))
+            } else {
+                Err(Error::custom("Invalid DWORD size"))
+            }
+        }
+        REG_DWORD_BIG_ENDIAN => {
+            if buffer.len() >= 4 {
+                let value = u32::from_be_bytes([buffer[0], buffer[1], buffer[2], buffer[3]]);
+                Ok(Value::Dword(value))
+            } else {
+                Err(Error::custom("Invalid DWORD size"))
+            }
+        }
+        REG_QWORD => {
+            if buffer.len() >= 8 {
+                let value = u64::from_le_bytes([
+                    buffer[0], buffer[1], buffer[2], buffer[3], buffer[4], buffer[5], buffer[6],
+                    buffer[7],
+                ]);
+                Ok(Value::Qword(value))
+            } else {
+                Err(Error::custom("Invalid QWORD size"))
+            }
+        }
+        REG_BINARY => Ok(Value::Binary(buffer)),
+        _ => Ok(Value::Unknown {
+            type_code: value_type.0,
+            data: buffer,
+        }),
+    }
+}
+
+/// Splits `REG_MULTI_SZ` data into its strings.
+///
+/// Each string is null-terminated and the list ends with one more null, so
+/// `["a", "", "b"]` is `a\0\0b\0\0` and `[""]` is `\0\0`. Only that final null
+/// is the list terminator; any other empty string is a real element. An empty
+/// list is a lone `\0` (or no data at all). Data written by other tools may
+/// lack the terminators, in which case the last string ends with the data.
+fn parse_multi_string(wide: &[u16]) -> Result<Vec<String>> {
+    // Unlike string::unpack_multi, registry data keeps `\0\0` for `[""]`,
+    // since the empty list is stored as a lone `\0`
+    if wide == [0, 0] {
+        return Ok(vec![String::new()]);
+    }
+    split_multi(wide).into_iter().map(from_wide).collect()
+}
+
+/// Packs strings as `REG_MULTI_SZ` data, storing the empty list as a lone `\0`.
+fn pack_multi_string(strings: &[String]) -> Vec<u16> {
+    if strings.is_empty() {
+        return vec![0];
+    }
+    pack_multi_wide(strings.iter().map(|s| s.encode_utf16()))
+}
+
 /// Predefined registry root keys.
 #[derive(Clone, Copy, Debug)]
 pub struct RootKey(pub HKEY);
@@ -94,11 +192,24 @@ pub enum Value {
     /// A multi-string value (REG_MULTI_SZ).
     MultiString(Vec<String>),
     /// A 32-bit integer (REG_DWORD).
+    ///
+    /// `REG_DWORD_BIG_ENDIAN` data is also read into this variant; writing it
+    /// back stores a little-endian `REG_DWORD`.
     Dword(u32),
     /// A 64-bit integer (REG_QWORD).
     Qword(u64),
     /// Binary data (REG_BINARY).
     Binary(Vec<u8>),
+    /// Data of a type without its own variant, such as `REG_NONE` or
+    /// `REG_LINK`, kept as the raw type code and bytes.
+    ///
+    /// Writing this variant stores `data` with `type_code` unchanged.
+    Unknown {
+        /// The `REG_*` type code.
+        type_code: u32,
+        /// The unparsed data.
+        data: Vec<u8>,
+    },
 }
 
 impl Value {
@@ -122,6 +233,11 @@ impl Value {
         Value::Binary(data.into())
     }
 
+    /// Creates a multi-string value.
+    pub fn multi_string(strings: impl IntoIterator<Item = String>) -> Self {
+        Value::MultiString(strings.into_iter().collect())
+    }
+
     /// Gets the value as a string, if it is one.
     pub fn as_string(&self) -> Option<&str> {
         match self {
@@ -146,6 +262,14 @@ impl Value {
         }
     }
 
+    /// Gets the value as a list of strings, if it is one.
+    pub fn as_multi_string(&self) -> Option<&[String]> {
+        match self {
+            Value::MultiString(v) => Some(v),
+            _ => None,
+        }
+    }
+
     /// Gets the value as binary data, if it is one.
     pub fn as_binary(&self) -> Option<&[u8]> {
         match self {
@@ -155,10 +279,84 @@ impl Value {
     }
 }
 
+/// The type of a registry value, mirroring the `REG_*` constants.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RegKind {
+    /// No defined type (REG_NONE).
+    None,
+    /// A string (REG_SZ).
+    String,
+    /// A string with unexpanded environment references (REG_EXPAND_SZ).
+    ExpandString,
+    /// Binary data (REG_BINARY).
+    Binary,
+    /// A little-endian 32-bit integer (REG_DWORD).
+    Dword,
+    /// A big-endian 32-bit integer (REG_DWORD_BIG_ENDIAN).
+    DwordBigEndian,
+    /// A symbolic link to another key (REG_LINK).
+    Link,
+    /// A list of strings (REG_MULTI_SZ).
+    MultiString,
+    /// A device driver resource list (REG_RESOURCE_LIST).
+    ResourceList,
+    /// A hardware resource descriptor (REG_FULL_RESOURCE_DESCRIPTOR).
+    FullResourceDescriptor,
+    /// A resource requirements list (REG_RESOURCE_REQUIREMENTS_LIST).
+    ResourceRequirementsList,
+    /// A little-endian 64-bit integer (REG_QWORD).
+    Qword,
+    /// A type code with no predefined constant.
+    Other(u32),
+}
+
+impl RegKind {
+    /// Converts a raw `REG_*` type code.
+    pub fn from_raw(raw: u32) -> Self {
+        match REG_VALUE_TYPE(raw) {
+            REG_NONE => RegKind::None,
+            REG_SZ => RegKind::String,
+            REG_EXPAND_SZ => RegKind::ExpandString,
+            REG_BINARY => RegKind::Binary,
+            REG_DWORD => RegKind::Dword,
+            REG_DWORD_BIG_ENDIAN => RegKind::DwordBigEndian,
+            REG_LINK => RegKind::Link,
+            REG_MULTI_SZ => RegKind::MultiString,
+            REG_RESOURCE_LIST => RegKind::ResourceList,
+            REG_FULL_RESOURCE_DESCRIPTOR => RegKind::FullResourceDescriptor,
+            REG_RESOURCE_REQUIREMENTS_LIST => RegKind::ResourceRequirementsList,
+            REG_QWORD => RegKind::Qword,
+            _ => RegKind::Other(raw),
+        }
+    }
+
+    /// Returns the raw `REG_*` type code.
+    pub fn to_raw(self) -> u32 {
+        match self {
+            RegKind::None => REG_NONE.0,
+            RegKind::String => REG_SZ.0,
+            RegKind::ExpandString => REG_EXPAND_SZ.0,
+            RegKind::Binary => REG_BINARY.0,
+            RegKind::Dword => REG_DWORD.0,
+            RegKind::DwordBigEndian => REG_DWORD_BIG_ENDIAN.0,
+            RegKind::Link => REG_LINK.0,
+            RegKind::MultiString => REG_MULTI_SZ.0,
+            RegKind::ResourceList => REG_RESOURCE_LIST.0,
+            RegKind::FullResourceDescriptor => REG_FULL_RESOURCE_DESCRIPTOR.0,
+            RegKind::ResourceRequirementsList => REG_RESOURCE_REQUIREMENTS_LIST.0,
+            RegKind::Qword => REG_QWORD.0,
+            RegKind::Other(raw) => raw,
+        }
+    }
+}
+
 /// An opened registry key.
 pub struct Key {
     hkey: HKEY,
     owned: bool,
+    // Set for keys opened through a RegistryTransaction; keeps the
+    // transaction handle open and routes subkey calls through it.
+    transaction: Option<Rc<OwnedHandle>>,
 }
 
 impl Key {
@@ -168,17 +366,7 @@ impl Key {
     ///
     /// Returns an error if the key does not exist or access is denied.
     pub fn open(root: RootKey, path: &str, access: Access) -> Result<Self> {
-        let path_wide = WideString::new(path);
-        let mut hkey = HKEY::default();
-
-        // SAFETY: All parameters are valid:
-        // - root.0 is a valid predefined root key handle
-        // - path_wide is a valid null-terminated wide string
-        // - hkey is a valid output parameter
-        let err = unsafe { RegOpenKeyExW(root.0, path_wide.as_pcwstr(), 0, access.0, &mut hkey) };
-        check_error(err)?;
-
-        Ok(Self { hkey, owned: true })
+        Self::open_in(root.0, path, access, None)
     }
 
     /// Creates or opens a registry key.
@@ -189,30 +377,7 @@ impl Key {
     ///
     /// Returns an error if the key cannot be created or access is denied.
     pub fn create(root: RootKey, path: &str, access: Access) -> Result<Self> {
-        let path_wide = WideString::new(path);
-        let mut hkey = HKEY::default();
-
-        // SAFETY: All parameters are valid:
-        // - root.0 is a valid predefined root key handle
-        // - path_wide is a valid null-terminated wide string
-        // - REG_OPTION_NON_VOLATILE is a valid option
-        // - hkey is a valid output parameter
-        let err = unsafe {
-            RegCreateKeyExW(
-                root.0,
-                path_wide.as_pcwstr(),
-                0,
-                None,
-                REG_OPTION_NON_VOLATILE,
-                access.0,
-                None,
-                &mut hkey,
-                None,
-            )
-        };
-        check_error(err)?;
-
-        Ok(Self { hkey, owned: true })
+        Self::create_in(root.0, path, access, None)
     }
 
     /// Opens a subkey of this key.
@@ -221,15 +386,7 @@ impl Key {
     ///
     /// Returns an error if the subkey does not exist or access is denied.
     pub fn open_subkey(&self, path: &str, access: Access) -> Result<Self> {
-        let path_wide = WideString::new(path);
-        let mut hkey = HKEY::default();
-
-        // SAFETY: self.hkey is a valid handle we own, path_wide is valid.
-        let err =
-            unsafe { RegOpenKeyExW(self.hkey, path_wide.as_pcwstr(), 0, access.0, &mut hkey) };
-        check_error(err)?;
-
-        Ok(Self { hkey, owned: true })
+        Self::open_in(self.hkey, path, access, self.transaction.clone())
     }
 
     /// Creates or opens a subkey of this key.
@@ -238,26 +395,98 @@ impl Key {
     ///
     /// Returns an error if the subkey cannot be created or access is denied.
     pub fn create_subkey(&self, path: &str, access: Access) -> Result<Self> {
+        Self::create_in(self.hkey, path, access, self.transaction.clone())
+    }
+
+    /// Opens `path` under `parent`, inside `transaction` if there is one.
+    fn open_in(
+        parent: HKEY,
+        path: &str,
+        access: Access,
+        transaction: Option<Rc<OwnedHandle>>,
+    ) -> Result<Self> {
         let path_wide = WideString::new(path);
         let mut hkey = HKEY::default();
 
-        // SAFETY: self.hkey is a valid handle we own, path_wide is valid.
+        // SAFETY: All parameters are valid:
+        // - parent is a valid open or predefined key handle
+        // - path_wide is a valid null-terminated wide string
+        // - hkey is a valid output parameter
+        // - the transaction handle, if any, is open for the duration of the call
         let err = unsafe {
-            RegCreateKeyExW(
-                self.hkey,
-                path_wide.as_pcwstr(),
-                0,
-                None,
-                REG_OPTION_NON_VOLATILE,
-                access.0,
-                None,
-                &mut hkey,
-                None,
-            )
+            match &transaction {
+                Some(tx) => RegOpenKeyTransactedW(
+                    parent,
+                    path_wide.as_pcwstr(),
+                    0,
+                    access.0,
+                    &mut hkey,
+                    tx.as_raw(),
+                    None,
+                ),
+                None => RegOpenKeyExW(parent, path_wide.as_pcwstr(), 0, access.0, &mut hkey),
+            }
+        };
+        check_error(err)?;
+
+        Ok(Self {
+            hkey,
+            owned: true,
+            transaction,
+        })
+    }
+
+    /// Creates or opens `path` under `parent`, inside `transaction` if there is one.
+    fn create_in(
+        parent: HKEY,
+        path: &str,
+        access: Access,
+        transaction: Option<Rc<OwnedHandle>>,
+    ) -> Result<Self> {
+        let path_wide = WideString::new(path);
+        let mut hkey = HKEY::default();
+
+        // SAFETY: All parameters are valid:
+        // - parent is a valid open or predefined key handle
+        // - path_wide is a valid null-terminated wide string
+        // - REG_OPTION_NON_VOLATILE is a valid option
+        // - hkey is a valid output parameter
+        // - the transaction handle, if any, is open for the duration of the call
+        let err = unsafe {
+            match &transaction {
+                Some(tx) => RegCreateKeyTransactedW(
+                    parent,
+                    path_wide.as_pcwstr(),
+                    0,
+                    None,
+                    REG_OPTION_NON_VOLATILE,
+                    access.0,
+                    None,
+                    &mut hkey,
+                    None,
+                    tx.as_raw(),
+                    None,
+                ),
+                None => RegCreateKeyExW(
+                    parent,
+                    path_wide.as_pcwstr(),
+                    0,
+                    None,
+                    REG_OPTION_NON_VOLATILE,
+                    access.0,
+                    None,
+                    &mut hkey,
+                    None,
+                ),
+            }
         };
         check_error(err)?;
 
-        Ok(Self { hkey, owned: true })
+        Ok(Self {
+            hkey,
+            owned: true,
+            transaction,
+        })
     }
 
     /// Deletes a subkey and all its values.
@@ -266,14 +495,147 @@ impl Key {
     ///
     /// Returns an error if the subkey does not exist or access is denied.
     pub fn delete_subkey(&self, name: &str) -> Result<()> {
+        let name_wide = WideString::new(name);
+        // SAFETY: self.hkey is a valid handle, name_wide is valid, and the
+        // transaction handle, if any, is kept open by self.
+        let err = unsafe {
+            match &self.transaction {
+                Some(tx) => RegDeleteKeyTransactedW(
+                    self.hkey,
+                    name_wide.as_pcwstr(),
+                    0,
+                    0,
+                    tx.as_raw(),
+                    None,
+                ),
+                None => RegDeleteKeyW(self.hkey, name_wide.as_pcwstr()),
+            }
+        };
+        check_error(err)
+    }
+
+    /// Deletes a subkey together with all of its descendants and values.
+    ///
+    /// Wraps `RegDeleteTreeW`. If the subkey does not exist, the Windows error
+    /// (`ERROR_FILE_NOT_FOUND`) is returned unchanged. An empty `name` is
+    /// rejected rather than clearing this key's own contents.
+    ///
+    /// The deletion is not atomic: if it fails partway, for example because a
+    /// descendant denies `DELETE` access, the keys already removed stay removed.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ergonomic_windows::registry::{Access, Key, RootKey};
+    ///
+    /// let software = Key::open(RootKey::CURRENT_USER, "Software", Access::ALL)?;
+    /// software.delete_subkey_recursive("MyApp")?;
+    /// # Ok::<(), ergonomic_windows::error::Error>(())
+    /// ```
+    pub fn delete_subkey_recursive(&self, name: &str) -> Result<()> {
+        if name.is_empty() {
+            return Err(Error::custom("Subkey name must not be empty"));
+        }
         let name_wide = WideString::new(name);
         // SAFETY: self.hkey is a valid handle, name_wide is valid.
-        let err = unsafe { RegDeleteKeyW(self.hkey, name_wide.as_pcwstr()) };
+        let err = unsafe { RegDeleteTreeW(self.hkey, name_wide.as_pcwstr()) };
+        check_error(err)
+    }
+
+    /// Renames a subkey of this key, keeping its values and descendants.
+    ///
+    /// Uses `RegRenameKey`, which is resolved at run time since it is only
+    /// documented from Windows 10; on systems without it this returns
+    /// [`Error::Unsupported`]. The key needs `KEY_WRITE` access. Fails if
+    /// `old_name` does not exist or `new_name` already does.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ergonomic_windows::registry::{Access, Key, RootKey};
+    ///
+    /// let key = Key::open(RootKey::CURRENT_USER, "Software\\MyApp", Access::ALL)?;
+    /// key.rename_subkey("Profile.new", "Profile")?;
+    /// # Ok::<(), ergonomic_windows::error::Error>(())
+    /// ```
+    pub fn rename_subkey(&self, old_name: &str, new_name: &str) -> Result<()> {
+        type RegRenameKeyFn = unsafe extern "system" fn(
+            HKEY,
+            windows::core::PCWSTR,
+            windows::core::PCWSTR,
+        ) -> WIN32_ERROR;
+        let rename = crate::resolve!("advapi32.dll", "RegRenameKey", RegRenameKeyFn)?;
+
+        let old_wide = WideString::new(old_name);
+        let new_wide = WideString::new(new_name);
+        // SAFETY: self.hkey is a valid handle and both names are valid
+        // null-terminated strings.
+        let err = unsafe { rename(self.hkey, old_wide.as_pcwstr(), new_wide.as_pcwstr()) };
         check_error(err)
     }
 
     /// Gets a value from this key.
+    ///
+    /// Types without a dedicated [`Value`] variant come back as
+    /// [`Value::Unknown`].
     pub fn get_value(&self, name: &str) -> Result<Value> {
+        let (value_type, buffer) = self.get_value_raw(name)?;
+        parse_value(REG_VALUE_TYPE(value_type), buffer)
+    }
+
+    /// Gets the type of a value without reading its data.
+    ///
+    /// A missing value yields the same `ERROR_FILE_NOT_FOUND` error as
+    /// [`get_value`](Self::get_value).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ergonomic_windows::registry::{Access, Key, RegKind, RootKey};
+    ///
+    /// let key = Key::open(RootKey::CURRENT_USER, "Environment", Access::READ)?;
+    /// for name in key.values()? {
+    ///     if key.value_type(&name)? == RegKind::ExpandString {
+    ///         println!("{} needs expanding", name);
+    ///     }
+    /// }
+    /// # Ok::<(), ergonomic_windows::error::Error>(())
+    /// ```
+    pub fn value_type(&self, name: &str) -> Result<RegKind> {
+        let name_wide = WideString::new(name);
+        let mut value_type = REG_VALUE_TYPE::default();
+        // SAFETY: self.hkey is a valid handle; no data buffer is passed, so
+        // only the type is written
+        let err = unsafe {
+            RegQueryValueExW(
+                self.hkey,
+                name_wide.as_pcwstr(),
+                None,
+                Some(&mut value_type),
+                None,
+                None,
+            )
+        };
+        check_error(err)?;
+        Ok(RegKind::from_raw(value_type.0))
+    }
+
+    /// Gets a value's `REG_*` type code and unparsed data.
+    ///
+    /// Useful for types [`get_value`](Self::get_value) doesn't decode, or to
+    /// read data exactly as stored (string data keeps its terminators).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ergonomic_windows::registry::{Access, Key, RootKey};
+    ///
+    /// let key = Key::open(RootKey::CURRENT_USER, "Software\\MyApp", Access::READ)?;
+    /// let (type_code, data) = key.get_value_raw("Setting")?;
+    /// println!("type {} with {} bytes", type_code, data.len());
+    /// # Ok::<(), ergonomic_windows::error::Error>(())
+    /// ```
+    pub fn get_value_raw(&self, name: &str) -> Result<(u32, Vec<u8>)> {
         let name_wide = WideString::new(name);
         let mut value_type = REG_VALUE_TYPE::default();
         let mut size = 0u32;
@@ -312,61 +674,17 @@ impl Key {
         buffer.truncate(size as usize);
         buffer.shrink_to_fit(); // Release excess capacity
 
-        // Parse the value based on type
-        match value_type {
-            REG_SZ | REG_EXPAND_SZ => {
-                let wide: Vec<u16> = buffer
-                    .chunks_exact(2)
-                    .map(|c| u16::from_le_bytes([c[0], c[1]]))
-                    .collect();
-                let s = from_wide(&wide)?;
-                if value_type == REG_SZ {
-                    Ok(Value::String(s))
-                } else {
-                    Ok(Value::ExpandString(s))
-                }
-            }
-            REG_MULTI_SZ => {
-                let wide: Vec<u16> = buffer
-                    .chunks_exact(2)
-                    .map(|c| u16::from_le_bytes([c[0], c[1]]))
-                    .collect();
-                let mut strings = Vec::new();
-                let mut start = 0;
-                for (i, &c) in wide.iter().enumerate() {
-                    if c == 0 {
-                        if i > start {
-                            strings.push(from_wide(&wide[start..i])?);
-                        }
-                        start = i + 1;
-                    }
-                }
-                Ok(Value::MultiString(strings))
-            }
-            REG_DWORD => {
-                if buffer.len() >= 4 {
-                    let value = u32::from_le_bytes([buffer[0], buffer[1], buffer[2], buffer[3]]);
-                    Ok(Value::Dword(value))
-                } else {
-                    Err(Error::custom("Invalid DWORD size"))
-                }
-            }
-            REG_QWORD => {
-                if buffer.len() >= 8 {
-                    let value = u64::from_le_bytes([
-                        buffer[0], buffer[1], buffer[2], buffer[3], buffer[4], buffer[5],
-                        buffer[6], buffer[7],
-                    ]);
-                    Ok(Value::Qword(value))
-                } else {
-                    Err(Error::custom("Invalid QWORD size"))
-                }
-            }
-            REG_BINARY => Ok(Value::Binary(buffer)),
-            _ => Err(Error::custom(format!(
-                "Unsupported registry type: {:?}",
-                value_type
-            ))),
+        Ok((value_type.0, buffer))
+    }
+
+    /// Gets a value, or `default` if it does not exist.
+    ///
+    /// Only `ERROR_FILE_NOT_FOUND` is mapped to `default`; other errors such as
+    /// access denied are returned.
+    pub fn get_value_or(&self, name: &str, default: Value) -> Result<Value> {
+        match self.get_value(name) {
+            Err(Error::Windows(e)) if e.code() == ERROR_FILE_NOT_FOUND.to_hresult() => Ok(default),
+            result => result,
         }
     }
 
@@ -386,18 +704,14 @@ impl Key {
                 (REG_EXPAND_SZ, bytes)
             }
             Value::MultiString(strings) => {
-                let mut wide = Vec::new();
-                for s in strings {
-                    wide.extend(s.encode_utf16());
-                    wide.push(0);
-                }
-                wide.push(0); // Double null terminator
+                let wide = pack_multi_string(strings);
                 let bytes: Vec<u8> = wide.iter().flat_map(|&w| w.to_le_bytes()).collect();
                 (REG_MULTI_SZ, bytes)
             }
             Value::Dword(v) => (REG_DWORD, v.to_le_bytes().to_vec()),
             Value::Qword(v) => (REG_QWORD, v.to_le_bytes().to_vec()),
             Value::Binary(data) => (REG_BINARY, data.clone()),
+            Value::Unknown { type_code, data } => (REG_VALUE_TYPE(*type_code), data.clone()),
         };
 
         let err =
@@ -414,30 +728,109 @@ impl Key {
 
     /// Enumerates the subkeys of this key.
     pub fn subkeys(&self) -> Result<Vec<String>> {
+        self.iter_subkeys().collect()
+    }
+
+    /// Lazily enumerates the subkeys of this key, one `RegEnumKeyExW` call
+    /// per item.
+    ///
+    /// Adding or removing subkeys while iterating can skip or repeat names.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ergonomic_windows::registry::{Access, Key, RootKey};
+    ///
+    /// let key = Key::open(RootKey::LOCAL_MACHINE, "SOFTWARE", Access::READ)?;
+    /// let first: Vec<String> = key.iter_subkeys().take(5).collect::<Result<_, _>>()?;
+    /// # Ok::<(), ergonomic_windows::error::Error>(())
+    /// ```
+    pub fn iter_subkeys(&self) -> SubkeyIter<'_> {
+        SubkeyIter {
+            key: self,
+            index: 0,
+            name_buffer: vec![0u16; 256],
+            done: false,
+        }
+    }
+
+    /// Enumerates the values of this key.
+    pub fn values(&self) -> Result<Vec<String>> {
+        self.iter_values().collect()
+    }
+
+    /// Lazily enumerates the value names of this key, one `RegEnumValueW`
+    /// call per item.
+    ///
+    /// Adding or removing values while iterating can skip or repeat names.
+    pub fn iter_values(&self) -> ValueIter<'_> {
+        ValueIter {
+            key: self,
+            index: 0,
+            name_buffer: vec![0u16; 256],
+            done: false,
+        }
+    }
+
+    /// Enumerates the values of this key along with their data.
+    ///
+    /// The name and data buffers are sized once from the key's maximum value
+    /// lengths and reused for every value.
+    pub fn entries(&self) -> Result<Vec<(String, Value)>> {
+        let mut max_name_len = 0u32;
+        let mut max_data_len = 0u32;
+        // SAFETY: self.hkey is a valid handle, outputs are valid pointers.
+        let err = unsafe {
+            RegQueryInfoKeyW(
+                self.hkey,
+                windows::core::PWSTR::null(),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some(&mut max_name_len),
+                Some(&mut max_data_len),
+                None,
+                None,
+            )
+        };
+        check_error(err)?;
+
         let mut result = Vec::new();
         let mut index = 0u32;
-        let mut name_buffer = vec![0u16; 256];
+        let mut name_buffer = vec![0u16; max_name_len as usize + 1];
+        let mut data_buffer = vec![0u8; max_data_len as usize];
 
         loop {
             let mut name_len = name_buffer.len() as u32;
+            let mut data_len = data_buffer.len() as u32;
+            let mut value_type = 0u32;
 
+            // SAFETY: self.hkey is a valid handle, buffers and lengths match.
             let err = unsafe {
-                RegEnumKeyExW(
+                RegEnumValueW(
                     self.hkey,
                     index,
                     windows::core::PWSTR(name_buffer.as_mut_ptr()),
                     &mut name_len,
                     None,
-                    windows::core::PWSTR::null(),
-                    None,
-                    None,
+                    Some(&mut value_type),
+                    Some(data_buffer.as_mut_ptr()),
+                    Some(&mut data_len),
                 )
             };
 
             if err == ERROR_SUCCESS {
                 let name = from_wide(&name_buffer[..name_len as usize])?;
-                result.push(name);
+                let data = data_buffer[..data_len as usize].to_vec();
+                result.push((name, parse_value(REG_VALUE_TYPE(value_type), data)?));
                 index += 1;
+            } else if err == ERROR_MORE_DATA {
+                // A value grew since the key was queried; retry with larger buffers.
+                name_buffer.resize(name_buffer.len() * 2, 0);
+                data_buffer.resize((data_len as usize).max(data_buffer.len() * 2), 0);
             } else if err == ERROR_NO_MORE_ITEMS {
                 break;
             } else {
@@ -448,20 +841,301 @@ impl Key {
         Ok(result)
     }
 
-    /// Enumerates the values of this key.
-    pub fn values(&self) -> Result<Vec<String>> {
-        let mut result = Vec::new();
-        let mut index = 0u32;
-        let mut name_buffer = vec![0u16; 256];
+    /// Starts watching this key for changes.
+    ///
+    /// The key must have been opened with `KEY_NOTIFY` access, which
+    /// [`Access::READ`] includes.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ergonomic_windows::registry::{Access, Key, RootKey, WatchOptions};
+    ///
+    /// let key = Key::open(RootKey::CURRENT_USER, "Software\\MyApp", Access::READ)?;
+    /// let watcher = key.watch(WatchOptions::new().subtree(true))?;
+    /// loop {
+    ///     watcher.wait()?;
+    ///     println!("settings changed");
+    /// }
+    /// # Ok::<(), ergonomic_windows::error::Error>(())
+    /// ```
+    pub fn watch(&self, options: WatchOptions) -> Result<RegWatcher<'_>> {
+        // SAFETY: CreateEventW is safe with these parameters
+        let event = unsafe { CreateEventW(None, false, false, None)? };
+        let watcher = RegWatcher {
+            key: self,
+            event: OwnedHandle::new(event)?,
+            options,
+        };
+        watcher.rearm()?;
+        Ok(watcher)
+    }
 
+    /// Reads the requested parts of this key's security descriptor.
+    ///
+    /// The key needs `READ_CONTROL` access, which [`Access::READ`] includes.
+    /// Reading [`SecurityInfo::SACL`] also requires `SeSecurityPrivilege` to be
+    /// enabled and the key to be opened with `ACCESS_SYSTEM_SECURITY`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ergonomic_windows::registry::{Access, Key, RootKey};
+    /// use ergonomic_windows::security::SecurityInfo;
+    ///
+    /// let key = Key::open(RootKey::LOCAL_MACHINE, "SOFTWARE\\Microsoft", Access::READ)?;
+    /// println!("{}", key.get_security(SecurityInfo::DACL)?.to_sddl(SecurityInfo::DACL)?);
+    /// # Ok::<(), ergonomic_windows::error::Error>(())
+    /// ```
+    pub fn get_security(&self, info: SecurityInfo) -> Result<SecurityDescriptor> {
+        let mut size = 0u32;
+        let mut buffer: Vec<u32> = Vec::new();
         loop {
-            let mut name_len = name_buffer.len() as u32;
+            // SAFETY: buffer holds `size` bytes; the first call passes a null
+            // descriptor to learn the size
+            let err = unsafe {
+                RegGetKeySecurity(
+                    self.hkey,
+                    info.0,
+                    PSECURITY_DESCRIPTOR(buffer.as_mut_ptr() as *mut _),
+                    &mut size,
+                )
+            };
+            if err == ERROR_INSUFFICIENT_BUFFER {
+                // The descriptor can change between calls; retry with the new size.
+                buffer.resize((size as usize).div_ceil(4), 0);
+                continue;
+            }
+            check_error(err)?;
+            return Ok(SecurityDescriptor::from_buffer(buffer));
+        }
+    }
+
+    /// Replaces the requested parts of this key's security descriptor.
+    ///
+    /// Writing the DACL needs `WRITE_DAC` access and the owner needs
+    /// `WRITE_OWNER`; [`Access::ALL`] includes both. Setting an explicit DACL,
+    /// e.g. `D:P(A;CI;KR;;;BU)(A;CI;KA;;;BA)(A;CI;KA;;;SY)`, lets provisioning
+    /// code make a key readable but not writable by standard users.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ergonomic_windows::registry::{Access, Key, RootKey};
+    /// use ergonomic_windows::security::{SecurityDescriptor, SecurityInfo};
+    ///
+    /// let key = Key::create(RootKey::LOCAL_MACHINE, "SOFTWARE\\MyApp", Access::ALL)?;
+    /// let sd = SecurityDescriptor::from_sddl("D:P(A;CI;KR;;;BU)(A;CI;KA;;;BA)(A;CI;KA;;;SY)")?;
+    /// key.set_security(SecurityInfo::DACL, &sd)?;
+    /// # Ok::<(), ergonomic_windows::error::Error>(())
+    /// ```
+    pub fn set_security(&self, info: SecurityInfo, descriptor: &SecurityDescriptor) -> Result<()> {
+        // SAFETY: self.hkey is valid and descriptor holds a valid security descriptor
+        let err = unsafe { RegSetKeySecurity(self.hkey, info.0, descriptor.as_raw()) };
+        check_error(err)
+    }
+
+    /// Returns the raw HKEY handle.
+    pub fn as_raw(&self) -> HKEY {
+        self.hkey
+    }
+}
+
+/// Which changes a [`RegWatcher`] reports.
+///
+/// The default watches for added or removed subkeys and changed values on
+/// the key itself.
+#[derive(Debug, Clone, Copy)]
+pub struct WatchOptions {
+    filter: REG_NOTIFY_FILTER,
+    subtree: bool,
+}
+
+impl WatchOptions {
+    /// Creates options watching names and values, without the subtree.
+    pub fn new() -> Self {
+        Self {
+            filter: REG_NOTIFY_CHANGE_NAME | REG_NOTIFY_CHANGE_LAST_SET,
+            subtree: false,
+        }
+    }
+
+    /// Reports subkeys being added or deleted (`REG_NOTIFY_CHANGE_NAME`).
+    pub fn names(self, watch: bool) -> Self {
+        self.flag(REG_NOTIFY_CHANGE_NAME, watch)
+    }
+
+    /// Reports values being added, changed or deleted
+    /// (`REG_NOTIFY_CHANGE_LAST_SET`).
+    pub fn values(self, watch: bool) -> Self {
+        self.flag(REG_NOTIFY_CHANGE_LAST_SET, watch)
+    }
+
+    /// Reports key attribute changes (`REG_NOTIFY_CHANGE_ATTRIBUTES`).
+    pub fn attributes(self, watch: bool) -> Self {
+        self.flag(REG_NOTIFY_CHANGE_ATTRIBUTES, watch)
+    }
+
+    /// Reports security descriptor changes (`REG_NOTIFY_CHANGE_SECURITY`).
+    pub fn security(self, watch: bool) -> Self {
+        self.flag(REG_NOTIFY_CHANGE_SECURITY, watch)
+    }
+
+    /// Also reports changes in every descendant key.
+    pub fn subtree(mut self, watch: bool) -> Self {
+        self.subtree = watch;
+        self
+    }
+
+    fn flag(mut self, flag: REG_NOTIFY_FILTER, set: bool) -> Self {
+        if set {
+            self.filter |= flag;
+        } else {
+            self.filter = REG_NOTIFY_FILTER(self.filter.0 & !flag.0);
+        }
+        self
+    }
+}
+
+impl Default for WatchOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Watches a registry key for changes, from [`Key::watch`].
+///
+/// Windows signals the watcher once per registration, so [`wait`](Self::wait)
+/// and [`wait_timeout`](Self::wait_timeout) register again after each change;
+/// several changes in quick succession may wake the watcher only once. The
+/// watcher reports that something changed, not what: re-read the key to find
+/// out.
+pub struct RegWatcher<'a> {
+    key: &'a Key,
+    event: OwnedHandle,
+    options: WatchOptions,
+}
+
+impl RegWatcher<'_> {
+    /// Blocks until the key changes.
+    pub fn wait(&self) -> Result<()> {
+        self.wait_timeout_inner(None).map(|_| ())
+    }
+
+    /// Blocks until the key changes or the timeout elapses.
+    ///
+    /// Returns `true` if a change was reported.
+    pub fn wait_timeout(&self, timeout: Duration) -> Result<bool> {
+        self.wait_timeout_inner(Some(timeout))
+    }
+
+    /// Returns the auto-reset event that is signaled on a change.
+    ///
+    /// Use it to wait on the watcher together with other objects. After the
+    /// event fires, call [`rearm`](Self::rearm) to be notified of the next
+    /// change.
+    pub fn event_handle(&self) -> HANDLE {
+        self.event.as_raw()
+    }
+
+    /// Registers for the next change notification.
+    ///
+    /// Only needed after waiting on [`event_handle`](Self::event_handle)
+    /// directly; the `wait` methods do this themselves.
+    pub fn rearm(&self) -> Result<()> {
+        // SAFETY: the key and event handles are valid for the lifetime of self.
+        // The thread-agnostic flag keeps the registration alive if this thread exits.
+        let err = unsafe {
+            RegNotifyChangeKeyValue(
+                self.key.hkey,
+                self.options.subtree,
+                self.options.filter | REG_NOTIFY_THREAD_AGNOSTIC,
+                self.event.as_raw(),
+                true,
+            )
+        };
+        check_error(err)
+    }
+
+    fn wait_timeout_inner(&self, timeout: Option<Duration>) -> Result<bool> {
+        match self.event.wait(timeout)? {
+            WaitResult::Timeout => Ok(false),
+            _ => {
+                self.rearm()?;
+                Ok(true)
+            }
+        }
+    }
+}
+
+/// Iterator over the subkey names of a [`Key`], from [`Key::iter_subkeys`].
+pub struct SubkeyIter<'a> {
+    key: &'a Key,
+    index: u32,
+    name_buffer: Vec<u16>,
+    done: bool,
+}
+
+impl Iterator for SubkeyIter<'_> {
+    type Item = Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        loop {
+            let mut name_len = self.name_buffer.len() as u32;
+            // SAFETY: the key handle is valid and name_len matches the buffer.
+            let err = unsafe {
+                RegEnumKeyExW(
+                    self.key.hkey,
+                    self.index,
+                    windows::core::PWSTR(self.name_buffer.as_mut_ptr()),
+                    &mut name_len,
+                    None,
+                    windows::core::PWSTR::null(),
+                    None,
+                    None,
+                )
+            };
+            if err == ERROR_MORE_DATA {
+                let len = self.name_buffer.len() * 2;
+                self.name_buffer.resize(len, 0);
+                continue;
+            }
+            return finish_enum(
+                err,
+                &self.name_buffer[..name_len as usize],
+                &mut self.index,
+                &mut self.done,
+            );
+        }
+    }
+}
+
+/// Iterator over the value names of a [`Key`], from [`Key::iter_values`].
+pub struct ValueIter<'a> {
+    key: &'a Key,
+    index: u32,
+    name_buffer: Vec<u16>,
+    done: bool,
+}
+
+impl Iterator for ValueIter<'_> {
+    type Item = Result<String>;
 
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        loop {
+            let mut name_len = self.name_buffer.len() as u32;
+            // SAFETY: the key handle is valid and name_len matches the buffer.
             let err = unsafe {
                 RegEnumValueW(
-                    self.hkey,
-                    index,
-                    windows::core::PWSTR(name_buffer.as_mut_ptr()),
+                    self.key.hkey,
+                    self.index,
+                    windows::core::PWSTR(self.name_buffer.as_mut_ptr()),
                     &mut name_len,
                     None,
                     None,
@@ -469,24 +1143,39 @@ impl Key {
                     None,
                 )
             };
-
-            if err == ERROR_SUCCESS {
-                let name = from_wide(&name_buffer[..name_len as usize])?;
-                result.push(name);
-                index += 1;
-            } else if err == ERROR_NO_MORE_ITEMS {
-                break;
-            } else {
-                return Err(Error::Windows(windows::core::Error::from(err)));
+            if err == ERROR_MORE_DATA {
+                let len = self.name_buffer.len() * 2;
+                self.name_buffer.resize(len, 0);
+                continue;
             }
+            return finish_enum(
+                err,
+                &self.name_buffer[..name_len as usize],
+                &mut self.index,
+                &mut self.done,
+            );
         }
-
-        Ok(result)
     }
+}
 
-    /// Returns the raw HKEY handle.
-    pub fn as_raw(&self) -> HKEY {
-        self.hkey
+/// Turns the result of one enumeration call into an iterator item, advancing
+/// past a successful entry and stopping after the last one or an error.
+fn finish_enum(
+    err: WIN32_ERROR,
+    name: &[u16],
+    index: &mut u32,
+    done: &mut bool,
+) -> Option<Result<String>> {
+    if err == ERROR_SUCCESS {
+        *index += 1;
+        Some(from_wide(name))
+    } else {
+        *done = true;
+        if err == ERROR_NO_MORE_ITEMS {
+            None
+        } else {
+            Some(Err(Error::Windows(windows::core::Error::from(err))))
+        }
     }
 }
 
@@ -502,6 +1191,92 @@ impl Drop for Key {
     }
 }
 
+/// A kernel transaction (KTM) grouping registry changes so they apply all
+/// together or not at all.
+///
+/// Keys from [`open_key`](Self::open_key) and [`create_key`](Self::create_key)
+/// belong to the transaction, as do subkeys opened, created or deleted through
+/// them and every value written with [`Key::set_value`] or removed with
+/// [`Key::delete_value`]. None of it is visible to other handles until
+/// [`commit`](Self::commit). Dropping the transaction and all its keys
+/// without committing rolls everything back.
+///
+/// Microsoft discourages new use of KTM and may remove it in a future
+/// Windows release, but it remains functional on current versions. Recursive
+/// deletes and [`Key::rename_subkey`] are not transacted.
+///
+/// # Example
+///
+/// ```no_run
+/// use ergonomic_windows::registry::{Access, RegistryTransaction, RootKey, Value};
+///
+/// let tx = RegistryTransaction::new()?;
+/// let app = tx.create_key(RootKey::CURRENT_USER, "Software\\MyApp", Access::ALL)?;
+/// app.set_value("Version", &Value::dword(2))?;
+/// let paths = app.create_subkey("Paths", Access::ALL)?;
+/// paths.set_value("Data", &Value::string("C:\\Data"))?;
+/// drop((app, paths));
+/// tx.commit()?;
+/// # Ok::<(), ergonomic_windows::error::Error>(())
+/// ```
+pub struct RegistryTransaction {
+    handle: Rc<OwnedHandle>,
+}
+
+impl RegistryTransaction {
+    /// Starts a new transaction with no timeout.
+    pub fn new() -> Result<Self> {
+        // SAFETY: null attributes and unit of work are allowed; a zero
+        // timeout means the transaction never times out.
+        let handle = unsafe {
+            CreateTransaction(
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                0,
+                0,
+                0,
+                0,
+                windows::core::PCWSTR::null(),
+            )?
+        };
+        Ok(Self {
+            handle: Rc::new(OwnedHandle::new(handle)?),
+        })
+    }
+
+    /// Opens an existing key inside the transaction.
+    pub fn open_key(&self, root: RootKey, path: &str, access: Access) -> Result<Key> {
+        Key::open_in(root.0, path, access, Some(self.handle.clone()))
+    }
+
+    /// Creates or opens a key inside the transaction.
+    pub fn create_key(&self, root: RootKey, path: &str, access: Access) -> Result<Key> {
+        Key::create_in(root.0, path, access, Some(self.handle.clone()))
+    }
+
+    /// Applies every change made in the transaction atomically.
+    ///
+    /// Keys still open from the transaction can no longer be used for
+    /// changes afterwards.
+    pub fn commit(self) -> Result<()> {
+        // SAFETY: the transaction handle is valid
+        unsafe { CommitTransaction(self.handle.as_raw())? };
+        Ok(())
+    }
+
+    /// Discards every change made in the transaction.
+    pub fn rollback(self) -> Result<()> {
+        // SAFETY: the transaction handle is valid
+        unsafe { RollbackTransaction(self.handle.as_raw())? };
+        Ok(())
+    }
+
+    /// Returns the raw transaction handle.
+    pub fn as_raw(&self) -> HANDLE {
+        self.handle.as_raw()
+    }
+}
+
 /// Convenience function to read a string value from the registry.
 pub fn get_string(root: RootKey, path: &str, name: &str) -> Result<String> {
     let key = Key::open(root, path, Access::READ)?;
@@ -532,6 +1307,142 @@ pub fn set_dword(root: RootKey, path: &str, name: &str, value: u32) -> Result<()
     key.set_value(name, &Value::Dword(value))
 }
 
+/// How one field of a settings struct is stored as a named registry value.
+///
+/// A slice of these declares a struct's persistence once; see [`Settings`].
+/// The accessors are plain function pointers, so non-capturing closures work.
+pub enum RegField<T> {
+    /// A `u32` field stored as `REG_DWORD`.
+    Dword {
+        /// The value name.
+        name: &'static str,
+        /// Reads the field.
+        get: fn(&T) -> u32,
+        /// Writes the field.
+        set: fn(&mut T, u32),
+    },
+    /// A `String` field stored as `REG_SZ`.
+    String {
+        /// The value name.
+        name: &'static str,
+        /// Reads the field.
+        get: fn(&T) -> String,
+        /// Writes the field.
+        set: fn(&mut T, String),
+    },
+    /// A `bool` field stored as a `REG_DWORD` of 0 or 1.
+    Bool {
+        /// The value name.
+        name: &'static str,
+        /// Reads the field.
+        get: fn(&T) -> bool,
+        /// Writes the field.
+        set: fn(&mut T, bool),
+    },
+}
+
+impl<T> RegField<T> {
+    /// Maps a `u32` field to a `REG_DWORD` value.
+    pub fn dword(name: &'static str, get: fn(&T) -> u32, set: fn(&mut T, u32)) -> Self {
+        RegField::Dword { name, get, set }
+    }
+
+    /// Maps a `String` field to a `REG_SZ` value.
+    pub fn string(name: &'static str, get: fn(&T) -> String, set: fn(&mut T, String)) -> Self {
+        RegField::String { name, get, set }
+    }
+
+    /// Maps a `bool` field to a `REG_DWORD` value.
+    pub fn bool(name: &'static str, get: fn(&T) -> bool, set: fn(&mut T, bool)) -> Self {
+        RegField::Bool { name, get, set }
+    }
+
+    /// Returns the registry value name.
+    pub fn name(&self) -> &'static str {
+        match self {
+            RegField::Dword { name, .. }
+            | RegField::String { name, .. }
+            | RegField::Bool { name, .. } => name,
+        }
+    }
+}
+
+/// Loads and saves settings structs through a [`RegField`] mapping table.
+///
+/// # Example
+///
+/// ```no_run
+/// use ergonomic_windows::registry::{Access, Key, RegField, RootKey, Settings};
+///
+/// #[derive(Default)]
+/// struct Prefs {
+///     width: u32,
+///     theme: String,
+///     autosave: bool,
+/// }
+///
+/// fn fields() -> [RegField<Prefs>; 3] {
+///     [
+///         RegField::dword("Width", |p| p.width, |p, v| p.width = v),
+///         RegField::string("Theme", |p| p.theme.clone(), |p, v| p.theme = v),
+///         RegField::bool("AutoSave", |p| p.autosave, |p, v| p.autosave = v),
+///     ]
+/// }
+///
+/// let key = Key::create(RootKey::CURRENT_USER, r"Software\MyApp", Access::ALL)?;
+/// let mut prefs = Prefs::default();
+/// Settings::load_into(&key, &mut prefs, &fields())?;
+/// prefs.width = 1024;
+/// Settings::save_from(&key, &prefs, &fields())?;
+/// # Ok::<(), ergonomic_windows::error::Error>(())
+/// ```
+pub struct Settings;
+
+impl Settings {
+    /// Reads each mapped value from `key` into `target`.
+    ///
+    /// Missing values leave the field untouched, so start from the struct's
+    /// defaults. A value of the wrong type is an error.
+    pub fn load_into<T>(key: &Key, target: &mut T, fields: &[RegField<T>]) -> Result<()> {
+        for field in fields {
+            let value = match key.get_value(field.name()) {
+                Err(Error::Windows(e)) if e.code() == ERROR_FILE_NOT_FOUND.to_hresult() => continue,
+                result => result?,
+            };
+            let mismatch = || {
+                Error::custom(format!(
+                    "Registry value {} has an unexpected type",
+                    field.name()
+                ))
+            };
+
+            match field {
+                RegField::Dword { set, .. } => set(target, value.as_dword().ok_or_else(mismatch)?),
+                RegField::Bool { set, .. } => {
+                    set(target, value.as_dword().ok_or_else(mismatch)? != 0)
+                }
+                RegField::String { set, .. } => {
+                    set(target, value.as_string().ok_or_else(mismatch)?.to_string())
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes each mapped field of `source` to `key`.
+    pub fn save_from<T>(key: &Key, source: &T, fields: &[RegField<T>]) -> Result<()> {
+        for field in fields {
+            let value = match field {
+                RegField::Dword { get, .. } => Value::Dword(get(source)),
+                RegField::Bool { get, .. } => Value::Dword(get(source) as u32),
+                RegField::String { get, .. } => Value::String(get(source)),
+            };
+            key.set_value(field.name(), &value)?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -570,6 +1481,33 @@ mod tests {
     // Empty Registry Value Tests
     // ============================================================================
 
+    #[test]
+    fn test_key_security() {
+        let test_key = get_unique_test_key();
+        cleanup_test_key_path(&test_key);
+
+        if let Ok(key) = Key::create(RootKey::CURRENT_USER, &test_key, Access::ALL) {
+            let sddl = key
+                .get_security(SecurityInfo::DACL)
+                .unwrap()
+                .to_sddl(SecurityInfo::DACL)
+                .unwrap();
+            assert!(sddl.starts_with("D:"), "unexpected SDDL: {}", sddl);
+
+            // Grant only the owner access; the key stays usable for the test.
+            let owner_only = SecurityDescriptor::from_sddl("D:P(A;;KA;;;OW)").unwrap();
+            key.set_security(SecurityInfo::DACL, &owner_only).unwrap();
+            let sddl = key
+                .get_security(SecurityInfo::DACL)
+                .unwrap()
+                .to_sddl(SecurityInfo::DACL)
+                .unwrap();
+            assert!(sddl.starts_with("D:P"), "unexpected SDDL: {}", sddl);
+        }
+
+        cleanup_test_key_path(&test_key);
+    }
+
     #[test]
     fn test_empty_string_value() {
         let test_key = get_unique_test_key();
@@ -651,6 +1589,47 @@ mod tests {
         cleanup_test_key_path(&test_key);
     }
 
+    #[test]
+    fn test_parse_multi_string_terminators() {
+        let wide = |s: &str| s.encode_utf16().collect::<Vec<u16>>();
+
+        assert_eq!(parse_multi_string(&[]).unwrap(), Vec::<String>::new());
+        assert_eq!(parse_multi_string(&[0]).unwrap(), Vec::<String>::new());
+        assert_eq!(parse_multi_string(&[0, 0]).unwrap(), [""]);
+        assert_eq!(
+            parse_multi_string(&wide("a\0\0b\0\0")).unwrap(),
+            ["a", "", "b"]
+        );
+        // Missing list terminator, and missing string terminator
+        assert_eq!(parse_multi_string(&wide("a\0b\0")).unwrap(), ["a", "b"]);
+        assert_eq!(parse_multi_string(&wide("a\0b")).unwrap(), ["a", "b"]);
+    }
+
+    #[test]
+    fn test_multi_string_empty_elements_round_trip() {
+        let test_key = get_unique_test_key();
+
+        cleanup_test_key_path(&test_key);
+
+        if let Ok(key) = Key::create(RootKey::CURRENT_USER, &test_key, Access::ALL) {
+            let cases: [Vec<String>; 3] = [
+                vec!["a".into(), "".into(), "b".into()],
+                vec!["".into()],
+                vec![],
+            ];
+            for strings in cases {
+                key.set_value("multi", &Value::MultiString(strings.clone()))
+                    .unwrap();
+                match key.get_value("multi").unwrap() {
+                    Value::MultiString(v) => assert_eq!(v, strings),
+                    other => panic!("Expected MultiString, got: {:?}", other),
+                }
+            }
+        }
+
+        cleanup_test_key_path(&test_key);
+    }
+
     #[test]
     fn test_empty_multi_string_value() {
         let test_key = get_unique_test_key();
@@ -705,6 +1684,107 @@ mod tests {
         cleanup_test_key_path(&test_key);
     }
 
+    #[test]
+    fn test_raw_and_unknown_value() {
+        let test_key = get_unique_test_key();
+        cleanup_test_key_path(&test_key);
+
+        if let Ok(key) = Key::create(RootKey::CURRENT_USER, &test_key, Access::ALL) {
+            let none = Value::Unknown {
+                type_code: REG_NONE.0,
+                data: vec![1, 2, 3],
+            };
+            key.set_value("none_test", &none).unwrap();
+
+            let (type_code, data) = key.get_value_raw("none_test").unwrap();
+            assert_eq!(type_code, REG_NONE.0);
+            assert_eq!(data, vec![1, 2, 3]);
+
+            match key.get_value("none_test").unwrap() {
+                Value::Unknown { type_code, data } => {
+                    assert_eq!(type_code, REG_NONE.0);
+                    assert_eq!(data, vec![1, 2, 3]);
+                }
+                other => panic!("Expected Unknown, got {:?}", other),
+            }
+
+            key.set_value("dword_raw", &Value::Dword(7)).unwrap();
+            assert_eq!(
+                key.get_value_raw("dword_raw").unwrap(),
+                (REG_DWORD.0, vec![7, 0, 0, 0])
+            );
+        }
+
+        cleanup_test_key_path(&test_key);
+    }
+
+    #[test]
+    fn test_big_endian_dword_value() {
+        let test_key = get_unique_test_key();
+        cleanup_test_key_path(&test_key);
+
+        if let Ok(key) = Key::create(RootKey::CURRENT_USER, &test_key, Access::ALL) {
+            let raw = Value::Unknown {
+                type_code: REG_DWORD_BIG_ENDIAN.0,
+                data: vec![0x12, 0x34, 0x56, 0x78],
+            };
+            key.set_value("dword_be", &raw).unwrap();
+
+            assert_eq!(
+                key.get_value("dword_be").unwrap().as_dword(),
+                Some(0x1234_5678)
+            );
+            assert_eq!(
+                key.get_value_raw("dword_be").unwrap(),
+                (REG_DWORD_BIG_ENDIAN.0, vec![0x12, 0x34, 0x56, 0x78])
+            );
+        }
+
+        cleanup_test_key_path(&test_key);
+    }
+
+    #[test]
+    fn test_value_type() {
+        let test_key = get_unique_test_key();
+        cleanup_test_key_path(&test_key);
+
+        if let Ok(key) = Key::create(RootKey::CURRENT_USER, &test_key, Access::ALL) {
+            key.set_value("d", &Value::Dword(1)).unwrap();
+            key.set_value("s", &Value::string("x")).unwrap();
+            key.set_value("m", &Value::MultiString(vec!["a".into()]))
+                .unwrap();
+            key.set_value(
+                "o",
+                &Value::Unknown {
+                    type_code: 0x1234,
+                    data: vec![],
+                },
+            )
+            .unwrap();
+
+            assert_eq!(key.value_type("d").unwrap(), RegKind::Dword);
+            assert_eq!(key.value_type("s").unwrap(), RegKind::String);
+            assert_eq!(key.value_type("m").unwrap(), RegKind::MultiString);
+            assert_eq!(key.value_type("o").unwrap(), RegKind::Other(0x1234));
+
+            let err = key.value_type("missing").unwrap_err();
+            assert!(
+                matches!(&err, Error::Windows(e) if e.code() == ERROR_FILE_NOT_FOUND.to_hresult())
+            );
+        }
+
+        cleanup_test_key_path(&test_key);
+    }
+
+    #[test]
+    fn test_reg_kind_raw_round_trip() {
+        for raw in 0..=11 {
+            assert_eq!(RegKind::from_raw(raw).to_raw(), raw);
+        }
+        assert_eq!(RegKind::from_raw(4), RegKind::Dword);
+        assert_eq!(RegKind::from_raw(99), RegKind::Other(99));
+    }
+
     #[test]
     fn test_qword_value() {
         let test_key = get_unique_test_key();
@@ -830,6 +1910,61 @@ mod tests {
         cleanup_test_key_path(&test_key);
     }
 
+    #[test]
+    fn test_delete_subkey_recursive() {
+        let test_key = get_unique_test_key();
+
+        cleanup_test_key_path(&test_key);
+
+        if let Ok(key) = Key::create(RootKey::CURRENT_USER, &test_key, Access::ALL) {
+            let leaf = key
+                .create_subkey("Tree\\Middle\\Leaf", Access::ALL)
+                .unwrap();
+            leaf.set_value("v", &Value::Dword(1)).unwrap();
+            drop(leaf);
+            key.create_subkey("Tree\\Sibling", Access::ALL).unwrap();
+
+            // A plain delete refuses a key with children
+            assert!(key.delete_subkey("Tree").is_err());
+
+            key.delete_subkey_recursive("Tree").unwrap();
+            assert!(key.open_subkey("Tree", Access::READ).is_err());
+            assert!(key.subkeys().unwrap().is_empty());
+
+            let err = key.delete_subkey_recursive("Tree").unwrap_err();
+            assert!(
+                matches!(&err, Error::Windows(e) if e.code() == ERROR_FILE_NOT_FOUND.to_hresult())
+            );
+            assert!(key.delete_subkey_recursive("").is_err());
+        }
+
+        cleanup_test_key_path(&test_key);
+    }
+
+    #[test]
+    fn test_rename_subkey() {
+        let test_key = get_unique_test_key();
+
+        cleanup_test_key_path(&test_key);
+
+        if let Ok(key) = Key::create(RootKey::CURRENT_USER, &test_key, Access::ALL) {
+            let sub = key.create_subkey("Before", Access::ALL).unwrap();
+            sub.set_value("kept", &Value::Dword(5)).unwrap();
+            drop(sub);
+
+            key.rename_subkey("Before", "After").unwrap();
+            assert_eq!(key.subkeys().unwrap(), vec!["After".to_string()]);
+            let renamed = key.open_subkey("After", Access::READ).unwrap();
+            assert_eq!(renamed.get_value("kept").unwrap().as_dword(), Some(5));
+            drop(renamed);
+
+            assert!(key.rename_subkey("Before", "Again").is_err());
+            key.delete_subkey("After").unwrap();
+        }
+
+        cleanup_test_key_path(&test_key);
+    }
+
     #[test]
     fn test_enumerate_subkeys() {
         let test_key = get_unique_test_key();
@@ -878,6 +2013,169 @@ mod tests {
         cleanup_test_key_path(&test_key);
     }
 
+    #[test]
+    fn test_lazy_enumeration() {
+        let test_key = get_unique_test_key();
+        cleanup_test_key_path(&test_key);
+
+        if let Ok(key) = Key::create(RootKey::CURRENT_USER, &test_key, Access::ALL) {
+            // Longer than the initial 256-character name buffer
+            let long_value = "v".repeat(600);
+            key.set_value(&long_value, &Value::Dword(1)).unwrap();
+            key.set_value("short", &Value::Dword(2)).unwrap();
+            key.create_subkey("A", Access::ALL).unwrap();
+            key.create_subkey("B", Access::ALL).unwrap();
+
+            let mut values = key.iter_values().collect::<Result<Vec<_>>>().unwrap();
+            values.sort();
+            assert_eq!(values, vec!["short".to_string(), long_value]);
+
+            let first = key.iter_subkeys().next().unwrap().unwrap();
+            assert!(first == "A" || first == "B");
+            assert_eq!(key.iter_subkeys().count(), 2);
+
+            let mut iter = key.iter_subkeys();
+            iter.by_ref().for_each(drop);
+            assert!(iter.next().is_none());
+
+            key.delete_subkey("A").unwrap();
+            key.delete_subkey("B").unwrap();
+        }
+
+        cleanup_test_key_path(&test_key);
+    }
+
+    #[test]
+    fn test_watch_value_change() {
+        let test_key = get_unique_test_key();
+        cleanup_test_key_path(&test_key);
+
+        if Key::create(RootKey::CURRENT_USER, &test_key, Access::ALL).is_ok() {
+            let (armed_tx, armed_rx) = std::sync::mpsc::channel();
+            let path = test_key.clone();
+            let watcher = std::thread::spawn(move || {
+                let key = Key::open(RootKey::CURRENT_USER, &path, Access::READ).unwrap();
+                let watcher = key.watch(WatchOptions::new()).unwrap();
+                assert!(!watcher.wait_timeout(Duration::from_millis(10)).unwrap());
+                armed_tx.send(()).unwrap();
+                watcher.wait_timeout(Duration::from_secs(10)).unwrap()
+            });
+
+            armed_rx.recv().unwrap();
+            let key = Key::open(RootKey::CURRENT_USER, &test_key, Access::WRITE).unwrap();
+            key.set_value("changed", &Value::Dword(1)).unwrap();
+            assert!(watcher.join().unwrap());
+        }
+
+        cleanup_test_key_path(&test_key);
+    }
+
+    #[test]
+    fn test_watch_options_flags() {
+        let options = WatchOptions::new().names(false).security(true);
+        assert_eq!(
+            options.filter,
+            REG_NOTIFY_CHANGE_LAST_SET | REG_NOTIFY_CHANGE_SECURITY
+        );
+        assert!(!options.subtree);
+        assert!(WatchOptions::default().subtree(true).subtree);
+    }
+
+    #[test]
+    fn test_transaction_rollback() {
+        let test_key = get_unique_test_key();
+        cleanup_test_key_path(&test_key);
+
+        let Ok(tx) = RegistryTransaction::new() else {
+            return;
+        };
+        let key = tx
+            .create_key(RootKey::CURRENT_USER, &test_key, Access::ALL)
+            .unwrap();
+        key.set_value("v", &Value::Dword(1)).unwrap();
+        let sub = key.create_subkey("Child", Access::ALL).unwrap();
+        sub.set_value("w", &Value::string("x")).unwrap();
+
+        // Invisible outside the transaction until committed
+        assert!(Key::open(RootKey::CURRENT_USER, &test_key, Access::READ).is_err());
+
+        drop((key, sub));
+        tx.rollback().unwrap();
+        assert!(Key::open(RootKey::CURRENT_USER, &test_key, Access::READ).is_err());
+
+        cleanup_test_key_path(&test_key);
+    }
+
+    #[test]
+    fn test_transaction_commit() {
+        let test_key = get_unique_test_key();
+        cleanup_test_key_path(&test_key);
+
+        let Ok(tx) = RegistryTransaction::new() else {
+            return;
+        };
+        let key = tx
+            .create_key(RootKey::CURRENT_USER, &test_key, Access::ALL)
+            .unwrap();
+        key.set_value("v", &Value::Dword(7)).unwrap();
+        drop(key);
+        tx.commit().unwrap();
+
+        let key = Key::open(RootKey::CURRENT_USER, &test_key, Access::READ).unwrap();
+        assert_eq!(key.get_value("v").unwrap().as_dword(), Some(7));
+        drop(key);
+
+        cleanup_test_key_path(&test_key);
+    }
+
+    #[test]
+    fn test_get_value_or() {
+        let test_key = get_unique_test_key();
+
+        cleanup_test_key_path(&test_key);
+
+        if let Ok(key) = Key::create(RootKey::CURRENT_USER, &test_key, Access::ALL) {
+            let _ = key.set_value("Present", &Value::Dword(7));
+
+            let present = key.get_value_or("Present", Value::Dword(0)).unwrap();
+            assert_eq!(present.as_dword(), Some(7));
+
+            let absent = key.get_value_or("Absent", Value::Dword(3)).unwrap();
+            assert_eq!(absent.as_dword(), Some(3));
+
+            let absent = key.get_value_or("Absent", Value::string("fallback"));
+            assert_eq!(absent.unwrap().as_string(), Some("fallback"));
+
+            // Errors other than not-found are not swallowed
+            let write_only =
+                Key::open(RootKey::CURRENT_USER, &test_key, Access::SET_VALUE).unwrap();
+            assert!(write_only.get_value_or("Present", Value::Dword(0)).is_err());
+        }
+
+        cleanup_test_key_path(&test_key);
+    }
+
+    #[test]
+    fn test_entries() {
+        let test_key = get_unique_test_key();
+
+        cleanup_test_key_path(&test_key);
+
+        if let Ok(key) = Key::create(RootKey::CURRENT_USER, &test_key, Access::ALL) {
+            let _ = key.set_value("Num", &Value::Dword(1));
+            let _ = key.set_value("Text", &Value::string("a longer string value"));
+
+            let mut entries = key.entries().unwrap();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            assert_eq!(entries.len(), 2);
+            assert_eq!(entries[0].0, "Num");
+            assert_eq!(entries[0].1.as_dword(), Some(1));
+            assert_eq!(entries[1].1.as_string(), Some("a longer string value"));
+        }
+
+        cleanup_test_key_path(&test_key);
+    }
+
     #[test]
     fn test_delete_value() {
         let test_key = get_unique_test_key();
@@ -963,5 +2261,54 @@ mod tests {
 
         let b = Value::binary(vec![1, 2, 3]);
         assert_eq!(b.as_binary(), Some(&[1u8, 2, 3][..]));
+
+        let m = Value::multi_string(vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(
+            m.as_multi_string(),
+            Some(&["a".to_string(), "b".to_string()][..])
+        );
+        assert_eq!(s.as_multi_string(), None);
+    }
+
+    #[test]
+    fn test_settings_round_trip() {
+        #[derive(Debug, Default, PartialEq)]
+        struct Prefs {
+            width: u32,
+            theme: String,
+            autosave: bool,
+        }
+
+        fn fields() -> [RegField<Prefs>; 3] {
+            [
+                RegField::dword("Width", |p| p.width, |p, v| p.width = v),
+                RegField::string("Theme", |p| p.theme.clone(), |p, v| p.theme = v),
+                RegField::bool("AutoSave", |p| p.autosave, |p, v| p.autosave = v),
+            ]
+        }
+
+        let test_key = get_unique_test_key();
+        cleanup_test_key_path(&test_key);
+        let key = Key::create(RootKey::CURRENT_USER, &test_key, Access::ALL).unwrap();
+
+        // Nothing stored yet: defaults are kept
+        let mut loaded = Prefs::default();
+        Settings::load_into(&key, &mut loaded, &fields()).unwrap();
+        assert_eq!(loaded, Prefs::default());
+
+        let prefs = Prefs {
+            width: 1024,
+            theme: "dark".to_string(),
+            autosave: true,
+        };
+        Settings::save_from(&key, &prefs, &fields()).unwrap();
+        Settings::load_into(&key, &mut loaded, &fields()).unwrap();
+        assert_eq!(loaded, prefs);
+
+        key.set_value("Width", &Value::string("wide")).unwrap();
+        assert!(Settings::load_into(&key, &mut loaded, &fields()).is_err());
+
+        drop(key);
+        cleanup_test_key_path(&test_key);
     }
 }
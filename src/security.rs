@@ -3,15 +3,39 @@
 //! Provides safe wrappers for Windows security operations
 //! including tokens, privileges, and access control.
 
-use crate::error::Result;
+use crate::error::{Error, Result};
 use crate::handle::OwnedHandle;
 use crate::string::WideString;
-use windows::Win32::Foundation::{HANDLE, LUID};
+use windows::Win32::Foundation::{
+    GetLastError, LocalFree, BOOL, ERROR_INSUFFICIENT_BUFFER, ERROR_NOT_ALL_ASSIGNED,
+    ERROR_NO_IMPERSONATION_TOKEN, HANDLE, HLOCAL, LUID,
+};
+use windows::Win32::Security::Authorization::{
+    ConvertSecurityDescriptorToStringSecurityDescriptorW, ConvertSidToStringSidW,
+    ConvertStringSecurityDescriptorToSecurityDescriptorW, SDDL_REVISION_1,
+};
 use windows::Win32::Security::{
-    AdjustTokenPrivileges, GetTokenInformation, LookupPrivilegeNameW, LookupPrivilegeValueW,
-    TokenElevation, TokenPrivileges, LUID_AND_ATTRIBUTES, SE_PRIVILEGE_ENABLED, TOKEN_ACCESS_MASK,
-    TOKEN_ADJUST_PRIVILEGES, TOKEN_ELEVATION, TOKEN_PRIVILEGES, TOKEN_QUERY,
+    AccessCheck, AdjustTokenPrivileges, CheckTokenMembership, CopySid, CreateRestrictedToken,
+    CreateWellKnownSid, DuplicateToken, GetLengthSid, GetSecurityDescriptorLength,
+    GetSidSubAuthority, GetSidSubAuthorityCount, GetTokenInformation, LookupPrivilegeNameW,
+    LookupPrivilegeValueW, MapGenericMask, SecurityIdentification, SetTokenInformation,
+    TokenElevation, TokenElevationType, TokenElevationTypeFull, TokenElevationTypeLimited,
+    TokenIntegrityLevel, TokenLinkedToken, TokenLogonSid, TokenOrigin, TokenPrivileges,
+    TokenSessionId, WinBuiltinAdministratorsSid, CREATE_RESTRICTED_TOKEN_FLAGS,
+    DACL_SECURITY_INFORMATION, DISABLE_MAX_PRIVILEGE, GENERIC_MAPPING, GROUP_SECURITY_INFORMATION,
+    LABEL_SECURITY_INFORMATION, LUA_TOKEN, LUID_AND_ATTRIBUTES, OBJECT_SECURITY_INFORMATION,
+    OWNER_SECURITY_INFORMATION, PRIVILEGE_SET, PSECURITY_DESCRIPTOR, PSID,
+    SACL_SECURITY_INFORMATION, SECURITY_MANDATORY_LABEL_AUTHORITY, SECURITY_MAX_SID_SIZE,
+    SE_PRIVILEGE_ENABLED, SID, SID_AND_ATTRIBUTES, TOKEN_ACCESS_MASK, TOKEN_ADJUST_PRIVILEGES,
+    TOKEN_ELEVATION, TOKEN_ELEVATION_TYPE, TOKEN_GROUPS, TOKEN_INFORMATION_CLASS,
+    TOKEN_LINKED_TOKEN, TOKEN_MANDATORY_LABEL, TOKEN_ORIGIN, TOKEN_PRIVILEGES, TOKEN_QUERY,
+    WELL_KNOWN_SID_TYPE, WRITE_RESTRICTED,
+};
+use windows::Win32::Storage::FileSystem::{
+    FILE_ALL_ACCESS, FILE_GENERIC_EXECUTE, FILE_GENERIC_READ, FILE_GENERIC_WRITE,
 };
+use windows::Win32::System::Registry::{KEY_ALL_ACCESS, KEY_EXECUTE, KEY_READ, KEY_WRITE};
+use windows::Win32::System::SystemServices::SE_GROUP_INTEGRITY;
 use windows::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
 
 /// Well-known privilege names.
@@ -38,6 +62,346 @@ pub mod privileges {
     pub const SE_CREATE_SYMBOLIC_LINK_NAME: &str = "SeCreateSymbolicLinkPrivilege";
 }
 
+/// Mandatory integrity level of a token.
+///
+/// Levels are ordered, so `level >= IntegrityLevel::Medium` works as expected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum IntegrityLevel {
+    /// Untrusted (anonymous) integrity.
+    Untrusted,
+    /// Low integrity, used for sandboxed processes such as protected-mode browsers.
+    Low,
+    /// Medium integrity, the default for standard users.
+    Medium,
+    /// Medium-plus integrity.
+    MediumPlus,
+    /// High integrity, used by elevated administrators.
+    High,
+    /// System integrity, used by services.
+    System,
+    /// Protected process integrity.
+    Protected,
+}
+
+impl IntegrityLevel {
+    /// Returns the relative identifier (RID) of the integrity level's SID.
+    pub fn rid(self) -> u32 {
+        match self {
+            IntegrityLevel::Untrusted => 0x0000,
+            IntegrityLevel::Low => 0x1000,
+            IntegrityLevel::Medium => 0x2000,
+            IntegrityLevel::MediumPlus => 0x2100,
+            IntegrityLevel::High => 0x3000,
+            IntegrityLevel::System => 0x4000,
+            IntegrityLevel::Protected => 0x5000,
+        }
+    }
+
+    /// Converts an integrity RID to the highest level it satisfies.
+    pub fn from_rid(rid: u32) -> Self {
+        match rid {
+            r if r >= 0x5000 => IntegrityLevel::Protected,
+            r if r >= 0x4000 => IntegrityLevel::System,
+            r if r >= 0x3000 => IntegrityLevel::High,
+            r if r >= 0x2100 => IntegrityLevel::MediumPlus,
+            r if r >= 0x2000 => IntegrityLevel::Medium,
+            r if r >= 0x1000 => IntegrityLevel::Low,
+            _ => IntegrityLevel::Untrusted,
+        }
+    }
+}
+
+/// UAC elevation type of a token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ElevationType {
+    /// UAC is disabled or the user is not an administrator; there is no linked token.
+    Default,
+    /// The token is elevated (the full administrator token).
+    Full,
+    /// The token is a filtered administrator token with an elevated linked token.
+    Limited,
+}
+
+/// An owned security identifier (SID).
+#[derive(Clone, PartialEq, Eq)]
+pub struct Sid {
+    // u32 storage keeps the SID DWORD-aligned as the API expects.
+    buffer: Vec<u32>,
+}
+
+impl Sid {
+    /// Creates one of the well-known SIDs, such as `WinBuiltinAdministratorsSid`.
+    pub fn well_known(kind: WELL_KNOWN_SID_TYPE) -> Result<Self> {
+        let mut buffer = vec![0u32; SECURITY_MAX_SID_SIZE as usize / 4];
+        let mut size = SECURITY_MAX_SID_SIZE;
+        // SAFETY: buffer holds SECURITY_MAX_SID_SIZE bytes, enough for any SID
+        unsafe {
+            CreateWellKnownSid(
+                kind,
+                PSID::default(),
+                PSID(buffer.as_mut_ptr() as *mut _),
+                &mut size,
+            )?;
+        }
+        buffer.truncate((size as usize).div_ceil(4));
+        Ok(Self { buffer })
+    }
+
+    /// The SID of the built-in Administrators group (`S-1-5-32-544`).
+    pub fn administrators() -> Result<Self> {
+        Self::well_known(WinBuiltinAdministratorsSid)
+    }
+
+    /// Copies a SID from a raw pointer.
+    ///
+    /// # Safety
+    ///
+    /// `sid` must point to a valid SID.
+    pub unsafe fn from_psid(sid: PSID) -> Result<Self> {
+        let size = GetLengthSid(sid);
+        let mut buffer = vec![0u32; (size as usize).div_ceil(4)];
+        CopySid(size, PSID(buffer.as_mut_ptr() as *mut _), sid)?;
+        Ok(Self { buffer })
+    }
+
+    /// Returns a pointer to the SID for passing to Windows APIs.
+    ///
+    /// The pointer is valid for as long as `self` is.
+    #[inline]
+    pub fn as_psid(&self) -> PSID {
+        PSID(self.buffer.as_ptr() as *mut _)
+    }
+
+    /// Formats the SID in its string form, e.g. `S-1-5-32-544`.
+    pub fn to_string_sid(&self) -> Result<String> {
+        let mut raw = windows::core::PWSTR::null();
+        // SAFETY: self holds a valid SID; the returned string is freed with LocalFree
+        unsafe {
+            ConvertSidToStringSidW(self.as_psid(), &mut raw)?;
+            let result = crate::string::from_wide(raw.as_wide());
+            let _ = LocalFree(HLOCAL(raw.0 as *mut _));
+            result
+        }
+    }
+}
+
+impl std::fmt::Debug for Sid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.to_string_sid() {
+            Ok(s) => f.debug_tuple("Sid").field(&s).finish(),
+            Err(_) => f.debug_tuple("Sid").field(&"<invalid>").finish(),
+        }
+    }
+}
+
+/// Which parts of a security descriptor to read or write.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SecurityInfo(pub OBJECT_SECURITY_INFORMATION);
+
+impl SecurityInfo {
+    /// The owner SID.
+    pub const OWNER: Self = Self(OWNER_SECURITY_INFORMATION);
+
+    /// The primary group SID.
+    pub const GROUP: Self = Self(GROUP_SECURITY_INFORMATION);
+
+    /// The discretionary ACL, which controls who can access the object.
+    pub const DACL: Self = Self(DACL_SECURITY_INFORMATION);
+
+    /// The system ACL, which controls auditing.
+    ///
+    /// Reading or writing it requires `SeSecurityPrivilege` to be enabled
+    /// (see [`privileges::SE_SECURITY_NAME`]).
+    pub const SACL: Self = Self(SACL_SECURITY_INFORMATION);
+
+    /// The mandatory integrity label, which stops lower-integrity tokens from
+    /// writing to (and optionally reading or executing) the object.
+    ///
+    /// Unlike the rest of the SACL, reading it only needs `READ_CONTROL`.
+    pub const LABEL: Self = Self(LABEL_SECURITY_INFORMATION);
+
+    /// Combines two sets of parts.
+    pub fn with(self, other: Self) -> Self {
+        Self(OBJECT_SECURITY_INFORMATION(self.0 .0 | other.0 .0))
+    }
+}
+
+/// An owned self-relative security descriptor.
+///
+/// Most easily built from SDDL, e.g. `D:(A;;KR;;;BU)(A;;KA;;;BA)` grants
+/// built-in users read and administrators full access to a registry key.
+#[derive(Clone)]
+pub struct SecurityDescriptor {
+    // u32 storage keeps the descriptor DWORD-aligned as the API expects.
+    buffer: Vec<u32>,
+}
+
+impl SecurityDescriptor {
+    /// Parses a security descriptor from its SDDL string form.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ergonomic_windows::security::{SecurityDescriptor, SecurityInfo};
+    ///
+    /// let sd = SecurityDescriptor::from_sddl("D:(A;;GA;;;BA)")?;
+    /// assert_eq!(sd.to_sddl(SecurityInfo::DACL)?, "D:(A;;GA;;;BA)");
+    /// # Ok::<(), ergonomic_windows::error::Error>(())
+    /// ```
+    pub fn from_sddl(sddl: &str) -> Result<Self> {
+        let wide = WideString::new(sddl);
+        let mut raw = PSECURITY_DESCRIPTOR::default();
+        let mut size = 0u32;
+        // SAFETY: wide is a valid null-terminated string; the returned descriptor
+        // is self-relative, `size` bytes long and freed with LocalFree
+        unsafe {
+            ConvertStringSecurityDescriptorToSecurityDescriptorW(
+                wide.as_pcwstr(),
+                SDDL_REVISION_1,
+                &mut raw,
+                Some(&mut size),
+            )?;
+            let mut buffer = vec![0u32; (size as usize).div_ceil(4)];
+            std::ptr::copy_nonoverlapping(
+                raw.0 as *const u8,
+                buffer.as_mut_ptr() as *mut u8,
+                size as usize,
+            );
+            let _ = LocalFree(HLOCAL(raw.0));
+            Ok(Self { buffer })
+        }
+    }
+
+    /// Copies a self-relative descriptor allocated by the system, such as one
+    /// returned by `GetNamedSecurityInfoW`, and frees the original.
+    ///
+    /// # Safety
+    ///
+    /// `raw` must be a valid self-relative descriptor allocated with `LocalAlloc`.
+    pub(crate) unsafe fn from_local(raw: PSECURITY_DESCRIPTOR) -> Self {
+        let size = GetSecurityDescriptorLength(raw) as usize;
+        let mut buffer = vec![0u32; size.div_ceil(4)];
+        std::ptr::copy_nonoverlapping(raw.0 as *const u8, buffer.as_mut_ptr() as *mut u8, size);
+        let _ = LocalFree(HLOCAL(raw.0));
+        Self { buffer }
+    }
+
+    /// Wraps a buffer filled in by an API that returns a self-relative descriptor.
+    pub(crate) fn from_buffer(buffer: Vec<u32>) -> Self {
+        Self { buffer }
+    }
+
+    /// Formats the requested parts of the descriptor as SDDL.
+    pub fn to_sddl(&self, info: SecurityInfo) -> Result<String> {
+        let mut raw = windows::core::PWSTR::null();
+        // SAFETY: self holds a valid self-relative descriptor; the returned string
+        // is freed with LocalFree
+        unsafe {
+            ConvertSecurityDescriptorToStringSecurityDescriptorW(
+                self.as_raw(),
+                SDDL_REVISION_1,
+                info.0,
+                &mut raw,
+                None,
+            )?;
+            let result = crate::string::from_wide(raw.as_wide());
+            let _ = LocalFree(HLOCAL(raw.0 as *mut _));
+            result
+        }
+    }
+
+    /// Returns a pointer to the descriptor for passing to Windows APIs.
+    ///
+    /// The pointer is valid for as long as `self` is.
+    #[inline]
+    pub fn as_raw(&self) -> PSECURITY_DESCRIPTOR {
+        PSECURITY_DESCRIPTOR(self.buffer.as_ptr() as *mut _)
+    }
+}
+
+impl std::fmt::Debug for SecurityDescriptor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let parts = SecurityInfo::OWNER
+            .with(SecurityInfo::GROUP)
+            .with(SecurityInfo::DACL);
+        match self.to_sddl(parts) {
+            Ok(s) => f.debug_tuple("SecurityDescriptor").field(&s).finish(),
+            Err(_) => f
+                .debug_tuple("SecurityDescriptor")
+                .field(&"<invalid>")
+                .finish(),
+        }
+    }
+}
+
+/// Options for [`Token::create_restricted`].
+///
+/// A restricted token on its own only narrows what the process can access.
+/// Combined with a low integrity level ([`Token::set_integrity_level`]) and
+/// a job object that limits process creation and UI access, it forms a basic
+/// sandbox for untrusted code.
+#[derive(Debug, Clone, Default)]
+pub struct RestrictOptions {
+    flags: CREATE_RESTRICTED_TOKEN_FLAGS,
+    sids_to_disable: Vec<Sid>,
+    privileges_to_delete: Vec<String>,
+    restricting_sids: Vec<Sid>,
+}
+
+impl RestrictOptions {
+    /// Creates options that restrict nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Deletes every privilege except `SeChangeNotifyPrivilege` (`DISABLE_MAX_PRIVILEGE`).
+    pub fn disable_max_privilege(mut self, disable: bool) -> Self {
+        self.set_flag(DISABLE_MAX_PRIVILEGE, disable);
+        self
+    }
+
+    /// Checks restricting SIDs only for write access (`WRITE_RESTRICTED`).
+    pub fn write_restricted(mut self, write_only: bool) -> Self {
+        self.set_flag(WRITE_RESTRICTED, write_only);
+        self
+    }
+
+    /// Creates a UAC-filtered token, like the one a split-token administrator runs with (`LUA_TOKEN`).
+    pub fn lua_token(mut self, lua: bool) -> Self {
+        self.set_flag(LUA_TOKEN, lua);
+        self
+    }
+
+    /// Marks a SID as deny-only: it can deny access but never grant it.
+    pub fn deny_sid(mut self, sid: Sid) -> Self {
+        self.sids_to_disable.push(sid);
+        self
+    }
+
+    /// Removes a privilege, such as [`privileges::SE_SHUTDOWN_NAME`], from the token.
+    pub fn delete_privilege(mut self, privilege_name: impl Into<String>) -> Self {
+        self.privileges_to_delete.push(privilege_name.into());
+        self
+    }
+
+    /// Adds a restricting SID.
+    ///
+    /// With any restricting SIDs, access is granted only if both the normal
+    /// SIDs and the restricting SIDs allow it.
+    pub fn restrict_to(mut self, sid: Sid) -> Self {
+        self.restricting_sids.push(sid);
+        self
+    }
+
+    fn set_flag(&mut self, flag: CREATE_RESTRICTED_TOKEN_FLAGS, on: bool) {
+        if on {
+            self.flags.0 |= flag.0;
+        } else {
+            self.flags.0 &= !flag.0;
+        }
+    }
+}
+
 /// A Windows access token.
 pub struct Token {
     handle: OwnedHandle,
@@ -103,7 +467,206 @@ impl Token {
         Ok(elevation.TokenIsElevated != 0)
     }
 
+    /// Gets the UAC elevation type of the token.
+    ///
+    /// A `Limited` token belongs to a split-token administrator, who can elevate
+    /// without supplying credentials.
+    pub fn elevation_type(&self) -> Result<ElevationType> {
+        let mut elevation_type = TOKEN_ELEVATION_TYPE::default();
+        let mut size = 0u32;
+
+        // SAFETY: GetTokenInformation is safe with valid parameters
+        unsafe {
+            GetTokenInformation(
+                self.handle.as_raw(),
+                TokenElevationType,
+                Some(&mut elevation_type as *mut _ as *mut _),
+                std::mem::size_of::<TOKEN_ELEVATION_TYPE>() as u32,
+                &mut size,
+            )?;
+        }
+
+        Ok(if elevation_type == TokenElevationTypeFull {
+            ElevationType::Full
+        } else if elevation_type == TokenElevationTypeLimited {
+            ElevationType::Limited
+        } else {
+            ElevationType::Default
+        })
+    }
+
+    /// Gets the token linked to this one by UAC.
+    ///
+    /// For a limited token this is the elevated administrator token, and for a full
+    /// token it is the filtered one. Fails if the token has no linked token.
+    pub fn linked_token(&self) -> Result<Token> {
+        let mut linked = TOKEN_LINKED_TOKEN::default();
+        let mut size = 0u32;
+
+        // SAFETY: GetTokenInformation is safe with valid parameters.
+        // On success we own the returned handle.
+        unsafe {
+            GetTokenInformation(
+                self.handle.as_raw(),
+                TokenLinkedToken,
+                Some(&mut linked as *mut _ as *mut _),
+                std::mem::size_of::<TOKEN_LINKED_TOKEN>() as u32,
+                &mut size,
+            )?;
+        }
+
+        Ok(Token {
+            handle: OwnedHandle::new(linked.LinkedToken)?,
+        })
+    }
+
+    /// Queries variable-length token information.
+    ///
+    /// The buffer is returned as `u64`s so that it is suitably aligned for the
+    /// structures `GetTokenInformation` writes into it.
+    fn query_information(&self, class: TOKEN_INFORMATION_CLASS) -> Result<Vec<u64>> {
+        let mut size = 0u32;
+        // SAFETY: Querying with a null buffer only returns the required size
+        let _ = unsafe { GetTokenInformation(self.handle.as_raw(), class, None, 0, &mut size) };
+
+        if size == 0 {
+            return Err(crate::error::last_error());
+        }
+
+        let mut buffer = vec![0u64; (size as usize).div_ceil(8)];
+
+        // SAFETY: buffer is at least size bytes and properly aligned
+        unsafe {
+            GetTokenInformation(
+                self.handle.as_raw(),
+                class,
+                Some(buffer.as_mut_ptr() as *mut _),
+                size,
+                &mut size,
+            )?;
+        }
+
+        Ok(buffer)
+    }
+
+    /// Gets the mandatory integrity level of the token.
+    pub fn integrity_level(&self) -> Result<IntegrityLevel> {
+        let buffer = self.query_information(TokenIntegrityLevel)?;
+
+        // SAFETY: The buffer holds a TOKEN_MANDATORY_LABEL whose SID points into
+        // the same buffer. The integrity RID is the last sub-authority of the SID.
+        let rid = unsafe {
+            let label = &*(buffer.as_ptr() as *const TOKEN_MANDATORY_LABEL);
+            let sid = label.Label.Sid;
+            let count = *GetSidSubAuthorityCount(sid) as u32;
+            if count == 0 {
+                return Err(crate::error::Error::custom("Integrity SID has no RID"));
+            }
+            *GetSidSubAuthority(sid, count - 1)
+        };
+
+        Ok(IntegrityLevel::from_rid(rid))
+    }
+
+    /// Sets the mandatory integrity level of the token.
+    ///
+    /// The token must have been opened with `TOKEN_ADJUST_DEFAULT` access. A process can
+    /// lower its own integrity level, but raising it requires `SeTcbPrivilege`.
+    pub fn set_integrity_level(&self, level: IntegrityLevel) -> Result<()> {
+        let mut sid = SID {
+            Revision: 1,
+            SubAuthorityCount: 1,
+            IdentifierAuthority: SECURITY_MANDATORY_LABEL_AUTHORITY,
+            SubAuthority: [level.rid()],
+        };
+
+        let label = TOKEN_MANDATORY_LABEL {
+            Label: SID_AND_ATTRIBUTES {
+                Sid: PSID(&mut sid as *mut _ as *mut _),
+                Attributes: SE_GROUP_INTEGRITY as u32,
+            },
+        };
+
+        // SAFETY: label and the SID it points to live until the call returns
+        unsafe {
+            SetTokenInformation(
+                self.handle.as_raw(),
+                TokenIntegrityLevel,
+                &label as *const _ as *const _,
+                std::mem::size_of::<TOKEN_MANDATORY_LABEL>() as u32
+                    + std::mem::size_of::<SID>() as u32,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Gets the Terminal Services session the token belongs to.
+    ///
+    /// Session-scoped object names live under `Session\<id>\`.
+    pub fn session_id(&self) -> Result<u32> {
+        let mut session_id = 0u32;
+        let mut size = 0u32;
+
+        // SAFETY: GetTokenInformation is safe with valid parameters
+        unsafe {
+            GetTokenInformation(
+                self.handle.as_raw(),
+                TokenSessionId,
+                Some(&mut session_id as *mut _ as *mut _),
+                std::mem::size_of::<u32>() as u32,
+                &mut size,
+            )?;
+        }
+
+        Ok(session_id)
+    }
+
+    /// Gets the logon SID (`S-1-5-5-X-Y`) identifying the token's logon session.
+    ///
+    /// Useful for naming or securing objects that should be shared by every
+    /// process of one logon, but not across logons.
+    pub fn logon_sid(&self) -> Result<Sid> {
+        let buffer = self.query_information(TokenLogonSid)?;
+
+        // SAFETY: The buffer holds a TOKEN_GROUPS whose SIDs point into the same
+        // buffer; the SID is copied out before the buffer is dropped.
+        unsafe {
+            let groups = &*(buffer.as_ptr() as *const TOKEN_GROUPS);
+            if groups.GroupCount == 0 {
+                return Err(Error::custom("Token has no logon SID"));
+            }
+            Sid::from_psid(groups.Groups[0].Sid)
+        }
+    }
+
+    /// Gets the LUID of the logon session that created the token, as a `u64`.
+    ///
+    /// Tokens from the same authentication share an origin; it is zero for
+    /// tokens created without explicit credentials.
+    pub fn origin(&self) -> Result<u64> {
+        let mut origin = TOKEN_ORIGIN::default();
+        let mut size = 0u32;
+
+        // SAFETY: GetTokenInformation is safe with valid parameters
+        unsafe {
+            GetTokenInformation(
+                self.handle.as_raw(),
+                TokenOrigin,
+                Some(&mut origin as *mut _ as *mut _),
+                std::mem::size_of::<TOKEN_ORIGIN>() as u32,
+                &mut size,
+            )?;
+        }
+
+        let luid = origin.OriginatingLogonSession;
+        Ok(((luid.HighPart as u32 as u64) << 32) | luid.LowPart as u64)
+    }
+
     /// Enables a privilege in the token.
+    ///
+    /// Returns true if the privilege was previously enabled. Fails with
+    /// `ERROR_NOT_ALL_ASSIGNED` if the token does not hold the privilege.
     pub fn enable_privilege(&self, privilege_name: &str) -> Result<bool> {
         self.adjust_privilege(privilege_name, true)
     }
@@ -152,6 +715,13 @@ impl Token {
             )?;
         }
 
+        // AdjustTokenPrivileges succeeds even when the privilege isn't held by
+        // the token; the only indication is the thread's last error.
+        // SAFETY: GetLastError has no preconditions
+        if enable && unsafe { GetLastError() } == ERROR_NOT_ALL_ASSIGNED {
+            return Err(Error::from_win32_code(ERROR_NOT_ALL_ASSIGNED.0));
+        }
+
         // Return whether it was previously enabled
         if previous_state.PrivilegeCount > 0 {
             Ok(previous_state.Privileges[0].Attributes.0 & SE_PRIVILEGE_ENABLED.0 != 0)
@@ -210,6 +780,62 @@ impl Token {
         Ok(false)
     }
 
+    /// Creates a restricted copy of this token with `CreateRestrictedToken`.
+    ///
+    /// The token must have been opened with `TOKEN_DUPLICATE` access, and the
+    /// new token has the same access rights. Pass it to
+    /// [`Command::spawn_as_user`](crate::process::Command::spawn_as_user) to run
+    /// a child process with reduced rights; that also needs `TOKEN_QUERY` and
+    /// `TOKEN_ASSIGN_PRIMARY` on the original token.
+    pub fn create_restricted(&self, options: RestrictOptions) -> Result<Token> {
+        let to_attributes = |sids: &[Sid]| -> Vec<SID_AND_ATTRIBUTES> {
+            sids.iter()
+                .map(|sid| SID_AND_ATTRIBUTES {
+                    Sid: sid.as_psid(),
+                    Attributes: 0,
+                })
+                .collect()
+        };
+        let disable = to_attributes(&options.sids_to_disable);
+        let restrict = to_attributes(&options.restricting_sids);
+        let delete = options
+            .privileges_to_delete
+            .iter()
+            .map(|name| {
+                let name_wide = WideString::new(name);
+                let mut luid = LUID::default();
+                // SAFETY: LookupPrivilegeValueW is safe with valid parameters
+                unsafe { LookupPrivilegeValueW(None, name_wide.as_pcwstr(), &mut luid)? };
+                Ok(LUID_AND_ATTRIBUTES {
+                    Luid: luid,
+                    Attributes: Default::default(),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        fn non_empty<T>(v: &[T]) -> Option<&[T]> {
+            (!v.is_empty()).then_some(v)
+        }
+
+        let mut handle = HANDLE::default();
+        // SAFETY: The SID pointers borrow from options, which outlives the call.
+        // On success we own the returned handle.
+        unsafe {
+            CreateRestrictedToken(
+                self.handle.as_raw(),
+                options.flags,
+                non_empty(&disable),
+                non_empty(&delete),
+                non_empty(&restrict),
+                &mut handle,
+            )?;
+        }
+
+        Ok(Token {
+            handle: OwnedHandle::new(handle)?,
+        })
+    }
+
     /// Returns the raw token handle.
     pub fn as_raw(&self) -> HANDLE {
         self.handle.as_raw()
@@ -221,6 +847,176 @@ pub fn is_elevated() -> Result<bool> {
     Token::current_process()?.is_elevated()
 }
 
+/// Checks if the current thread's token is a member of the given group.
+///
+/// Uses the impersonation token if the thread is impersonating, otherwise the
+/// process token. Deny-only groups (as in a filtered UAC token) do not count.
+pub fn is_user_in_group(sid: &Sid) -> Result<bool> {
+    check_membership(HANDLE::default(), sid)
+}
+
+/// Checks if the current user belongs to the Administrators group.
+///
+/// This is not the same as [`is_elevated`]. Under UAC an administrator runs with
+/// a filtered token in which Administrators is deny-only, so the user *is* an
+/// admin but the process is not elevated: `is_admin` returns `true` while
+/// `is_elevated` returns `false`. Use `is_elevated` to decide whether an
+/// admin-only operation will succeed right now, and `is_admin` to decide whether
+/// offering to elevate makes sense.
+pub fn is_admin() -> Result<bool> {
+    let admins = Sid::administrators()?;
+    if is_user_in_group(&admins)? {
+        return Ok(true);
+    }
+
+    // A filtered admin token links to the full token, where the group is enabled
+    let token = Token::current_process()?;
+    if token.elevation_type()? == ElevationType::Limited {
+        let linked = token.linked_token()?;
+        return check_membership(linked.as_raw(), &admins);
+    }
+    Ok(false)
+}
+
+/// Runs `CheckTokenMembership` against an impersonation token, or the caller's
+/// effective token if `token` is null.
+fn check_membership(token: HANDLE, sid: &Sid) -> Result<bool> {
+    let mut is_member = BOOL(0);
+    // SAFETY: sid is a valid SID that outlives the call
+    unsafe {
+        CheckTokenMembership(token, sid.as_psid(), &mut is_member)?;
+    }
+    Ok(is_member.as_bool())
+}
+
+/// Requests every access right the descriptor would grant, for use as the
+/// `desired` mask of [`access_check`].
+pub const MAXIMUM_ALLOWED: u32 = windows::Win32::System::SystemServices::MAXIMUM_ALLOWED;
+
+/// How generic rights map to specific rights for files and directories.
+pub const FILE_GENERIC_MAPPING: GENERIC_MAPPING = GENERIC_MAPPING {
+    GenericRead: FILE_GENERIC_READ.0,
+    GenericWrite: FILE_GENERIC_WRITE.0,
+    GenericExecute: FILE_GENERIC_EXECUTE.0,
+    GenericAll: FILE_ALL_ACCESS.0,
+};
+
+/// How generic rights map to specific rights for registry keys.
+pub const REGISTRY_GENERIC_MAPPING: GENERIC_MAPPING = GENERIC_MAPPING {
+    GenericRead: KEY_READ.0,
+    GenericWrite: KEY_WRITE.0,
+    GenericExecute: KEY_EXECUTE.0,
+    GenericAll: KEY_ALL_ACCESS.0,
+};
+
+/// Checks what access `token` would be granted to an object protected by
+/// `descriptor`, without opening the object.
+///
+/// Returns whether all of `desired` would be granted, and the granted mask.
+/// Pass [`MAXIMUM_ALLOWED`] to get everything the token could open the object
+/// with. Generic rights in `desired` and in the descriptor's ACEs are mapped
+/// with `mapping`, which must match the object's type: [`FILE_GENERIC_MAPPING`],
+/// [`REGISTRY_GENERIC_MAPPING`], or the mapping documented for other objects.
+///
+/// If the descriptor includes a mandatory label (see [`SecurityInfo::LABEL`]),
+/// the token's integrity level is checked against it as well.
+///
+/// The descriptor must include its owner, group and DACL. A primary token,
+/// such as one from [`Token::current_process`], is duplicated at
+/// identification level first, which needs `TOKEN_DUPLICATE` access.
+///
+/// The result reflects the descriptor only: share permissions, read-only
+/// attributes and privileges such as backup can still change what an actual
+/// open does.
+///
+/// # Example
+///
+/// ```no_run
+/// use ergonomic_windows::security::{
+///     access_check, SecurityDescriptor, Token, FILE_GENERIC_MAPPING,
+/// };
+/// use windows::Win32::Security::{TOKEN_DUPLICATE, TOKEN_QUERY};
+/// use windows::Win32::Storage::FileSystem::FILE_GENERIC_READ;
+///
+/// let sd = SecurityDescriptor::from_sddl("O:BAG:BAD:(A;;FR;;;WD)")?;
+/// let token = Token::current_process_with_access(TOKEN_QUERY | TOKEN_DUPLICATE)?;
+/// let (granted, _) = access_check(&sd, &token, FILE_GENERIC_READ.0, &FILE_GENERIC_MAPPING)?;
+/// assert!(granted);
+/// # Ok::<(), ergonomic_windows::error::Error>(())
+/// ```
+pub fn access_check(
+    descriptor: &SecurityDescriptor,
+    token: &Token,
+    desired: u32,
+    mapping: &GENERIC_MAPPING,
+) -> Result<(bool, u32)> {
+    match access_check_raw(descriptor, token.as_raw(), desired, mapping) {
+        Err(e) if e.code() == ERROR_NO_IMPERSONATION_TOKEN.to_hresult() => {
+            let mut handle = HANDLE::default();
+            // SAFETY: token is a valid token handle; the duplicate is owned below
+            unsafe {
+                DuplicateToken(token.as_raw(), SecurityIdentification, &mut handle)?;
+            }
+            let impersonation = OwnedHandle::new(handle)?;
+            Ok(access_check_raw(
+                descriptor,
+                impersonation.as_raw(),
+                desired,
+                mapping,
+            )?)
+        }
+        result => Ok(result?),
+    }
+}
+
+fn access_check_raw(
+    descriptor: &SecurityDescriptor,
+    token: HANDLE,
+    mut desired: u32,
+    mapping: &GENERIC_MAPPING,
+) -> windows::core::Result<(bool, u32)> {
+    // SAFETY: desired and mapping are valid for the call
+    unsafe { MapGenericMask(&mut desired, mapping) };
+
+    // The privilege set receives any privileges used to grant access; it
+    // starts with room for a few and grows if AccessCheck asks for more.
+    let mut privileges = vec![0u32; std::mem::size_of::<PRIVILEGE_SET>().div_ceil(4) * 4];
+    loop {
+        let mut length = (privileges.len() * 4) as u32;
+        let mut granted = 0u32;
+        let mut status = BOOL(0);
+        // SAFETY: descriptor is a valid self-relative descriptor, privileges
+        // is a DWORD-aligned buffer of `length` bytes and the out-pointers are valid
+        let result = unsafe {
+            AccessCheck(
+                descriptor.as_raw(),
+                token,
+                desired,
+                mapping,
+                Some(privileges.as_mut_ptr() as *mut PRIVILEGE_SET),
+                &mut length,
+                &mut granted,
+                &mut status,
+            )
+        };
+        match result {
+            Ok(()) => return Ok((status.as_bool(), granted)),
+            Err(e)
+                if e.code() == ERROR_INSUFFICIENT_BUFFER.to_hresult()
+                    && length as usize > privileges.len() * 4 =>
+            {
+                privileges.resize((length as usize).div_ceil(4), 0);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Gets the integrity level of the current process.
+pub fn current_integrity_level() -> Result<IntegrityLevel> {
+    Token::current_process_with_access(TOKEN_QUERY)?.integrity_level()
+}
+
 /// Gets the name of a privilege from its LUID.
 pub fn privilege_name(luid: LUID) -> Result<String> {
     let mut size = 0u32;
@@ -247,9 +1043,57 @@ pub fn privilege_name(luid: LUID) -> Result<String> {
     crate::string::from_wide(&buffer[..size as usize])
 }
 
+/// Enables `SeDebugPrivilege` on the current process token.
+///
+/// The privilege is restored to its previous state when the returned guard is
+/// dropped. It is only present in the tokens of elevated members of the
+/// Administrators group; for anyone else this fails with
+/// `ERROR_NOT_ALL_ASSIGNED`.
+///
+/// Holding the privilege is what allows [`Process::open`] to succeed against
+/// protected or other-user processes, and is therefore the prerequisite for
+/// inspecting them with calls such as [`Process::environment`].
+///
+/// [`Process::open`]: crate::process::Process::open
+/// [`Process::environment`]: crate::process::Process::environment
+///
+/// # Example
+///
+/// ```no_run
+/// use ergonomic_windows::security::enable_debug_privilege;
+///
+/// let _debug = enable_debug_privilege()?;
+/// // Open and inspect other processes here.
+/// # Ok::<(), ergonomic_windows::error::Error>(())
+/// ```
+pub fn enable_debug_privilege() -> Result<PrivilegeGuard<'static>> {
+    let token = Token::current_process()?;
+    let was_enabled = token.enable_privilege(privileges::SE_DEBUG_NAME)?;
+    Ok(PrivilegeGuard {
+        token: TokenRef::Owned(token),
+        privilege_name: privileges::SE_DEBUG_NAME.to_string(),
+        was_enabled,
+    })
+}
+
+/// A token that is either borrowed from the caller or owned by a guard.
+enum TokenRef<'a> {
+    Borrowed(&'a Token),
+    Owned(Token),
+}
+
+impl TokenRef<'_> {
+    fn get(&self) -> &Token {
+        match self {
+            TokenRef::Borrowed(token) => token,
+            TokenRef::Owned(token) => token,
+        }
+    }
+}
+
 /// RAII guard that restores a privilege to its original state when dropped.
 pub struct PrivilegeGuard<'a> {
-    token: &'a Token,
+    token: TokenRef<'a>,
     privilege_name: String,
     was_enabled: bool,
 }
@@ -259,7 +1103,7 @@ impl<'a> PrivilegeGuard<'a> {
     pub fn enable(token: &'a Token, privilege_name: &str) -> Result<Self> {
         let was_enabled = token.enable_privilege(privilege_name)?;
         Ok(Self {
-            token,
+            token: TokenRef::Borrowed(token),
             privilege_name: privilege_name.to_string(),
             was_enabled,
         })
@@ -269,7 +1113,7 @@ impl<'a> PrivilegeGuard<'a> {
 impl Drop for PrivilegeGuard<'_> {
     fn drop(&mut self) {
         if !self.was_enabled {
-            let _ = self.token.disable_privilege(&self.privilege_name);
+            let _ = self.token.get().disable_privilege(&self.privilege_name);
         }
     }
 }
@@ -277,6 +1121,7 @@ impl Drop for PrivilegeGuard<'_> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use windows::Win32::Security::TOKEN_DUPLICATE;
 
     #[test]
     fn test_current_process_token() {
@@ -292,6 +1137,84 @@ mod tests {
         println!("Running elevated: {}", elevated);
     }
 
+    #[test]
+    fn test_well_known_sid() {
+        let admins = Sid::administrators().unwrap();
+        assert_eq!(admins.to_string_sid().unwrap(), "S-1-5-32-544");
+
+        let copy = unsafe { Sid::from_psid(admins.as_psid()) }.unwrap();
+        assert_eq!(copy, admins);
+    }
+
+    #[test]
+    fn test_security_descriptor_sddl_round_trip() {
+        let sd = SecurityDescriptor::from_sddl("O:BAD:(A;;KR;;;BU)(A;;KA;;;BA)").unwrap();
+        assert_eq!(
+            sd.to_sddl(SecurityInfo::DACL).unwrap(),
+            "D:(A;;KR;;;BU)(A;;KA;;;BA)"
+        );
+        assert_eq!(sd.to_sddl(SecurityInfo::OWNER).unwrap(), "O:BA");
+
+        assert!(SecurityDescriptor::from_sddl("not sddl").is_err());
+    }
+
+    #[test]
+    fn test_access_check() {
+        let token = Token::current_process_with_access(TOKEN_QUERY | TOKEN_DUPLICATE).unwrap();
+        let read = FILE_GENERIC_READ.0;
+
+        let open = SecurityDescriptor::from_sddl("O:BAG:BAD:(A;;GA;;;WD)").unwrap();
+        let (granted, mask) = access_check(&open, &token, read, &FILE_GENERIC_MAPPING).unwrap();
+        assert!(granted);
+        assert_eq!(mask, read);
+
+        let (_, mask) =
+            access_check(&open, &token, MAXIMUM_ALLOWED, &FILE_GENERIC_MAPPING).unwrap();
+        assert_eq!(mask & FILE_ALL_ACCESS.0, FILE_ALL_ACCESS.0);
+
+        // An empty DACL grants nothing beyond the owner's implicit rights
+        let closed = SecurityDescriptor::from_sddl("O:BAG:BAD:").unwrap();
+        let (granted, _) = access_check(&closed, &token, read, &FILE_GENERIC_MAPPING).unwrap();
+        assert!(!granted);
+    }
+
+    #[test]
+    fn test_access_check_mapping_and_label() {
+        let token = Token::current_process_with_access(TOKEN_QUERY | TOKEN_DUPLICATE).unwrap();
+
+        // Generic read in the ACE means KEY_READ for a key, not FILE_GENERIC_READ
+        let readable = SecurityDescriptor::from_sddl("O:BAG:BAD:(A;;GR;;;WD)").unwrap();
+        let (_, mask) = access_check(
+            &readable,
+            &token,
+            MAXIMUM_ALLOWED,
+            &REGISTRY_GENERIC_MAPPING,
+        )
+        .unwrap();
+        assert_eq!(mask & KEY_READ.0, KEY_READ.0);
+        assert_eq!(mask & KEY_WRITE.0 & !KEY_READ.0, 0);
+
+        // A system-integrity no-write-up label denies writes to everyone else
+        if current_integrity_level().unwrap() < IntegrityLevel::System {
+            let labeled =
+                SecurityDescriptor::from_sddl("O:BAG:BAD:(A;;GA;;;WD)S:(ML;;NW;;;SI)").unwrap();
+            let write = FILE_GENERIC_WRITE.0;
+            let (granted, _) =
+                access_check(&labeled, &token, write, &FILE_GENERIC_MAPPING).unwrap();
+            assert!(!granted);
+        }
+    }
+
+    #[test]
+    fn test_is_admin() {
+        let admin = is_admin().unwrap();
+        println!("Administrators member: {}", admin);
+
+        // Everyone always includes the current user
+        let everyone = Sid::well_known(windows::Win32::Security::WinWorldSid).unwrap();
+        assert!(is_user_in_group(&everyone).unwrap());
+    }
+
     #[test]
     fn test_privilege_check() {
         let token = Token::current_process().unwrap();
@@ -300,4 +1223,110 @@ mod tests {
         // Just verify we can check
         println!("Has SeChangeNotifyPrivilege: {:?}", has_change_notify);
     }
+
+    #[test]
+    fn test_integrity_level_rid_roundtrip() {
+        for level in [
+            IntegrityLevel::Untrusted,
+            IntegrityLevel::Low,
+            IntegrityLevel::Medium,
+            IntegrityLevel::MediumPlus,
+            IntegrityLevel::High,
+            IntegrityLevel::System,
+            IntegrityLevel::Protected,
+        ] {
+            assert_eq!(IntegrityLevel::from_rid(level.rid()), level);
+        }
+        assert!(IntegrityLevel::Low < IntegrityLevel::Medium);
+    }
+
+    #[test]
+    fn test_current_integrity_level() {
+        let level = current_integrity_level().unwrap();
+        assert!(level >= IntegrityLevel::Medium);
+    }
+
+    #[test]
+    fn test_token_session_and_logon() {
+        let token = Token::current_process().unwrap();
+        assert_eq!(
+            token.session_id().unwrap(),
+            crate::sysinfo::session_id().unwrap()
+        );
+
+        // Interactive logons have a logon SID; service accounts may not
+        if let Ok(sid) = token.logon_sid() {
+            assert!(sid.to_string_sid().unwrap().starts_with("S-1-5-5-"));
+        }
+        let _ = token.origin().unwrap();
+    }
+
+    #[test]
+    fn test_elevation_type() {
+        let token = Token::current_process().unwrap();
+        let elevation_type = token.elevation_type().unwrap();
+        assert!(matches!(
+            elevation_type,
+            ElevationType::Default | ElevationType::Full | ElevationType::Limited
+        ));
+
+        // Only split-token administrators have a linked token
+        if elevation_type != ElevationType::Default {
+            assert!(token.linked_token().is_ok());
+        }
+    }
+
+    /// Returns (total, enabled) privilege counts of a token.
+    fn privilege_counts(token: &Token) -> (usize, usize) {
+        let buffer = token.query_information(TokenPrivileges).unwrap();
+        let privs = buffer.as_ptr() as *const TOKEN_PRIVILEGES;
+        unsafe {
+            let count = (*privs).PrivilegeCount as usize;
+            let entries = std::slice::from_raw_parts((*privs).Privileges.as_ptr(), count);
+            let enabled = entries
+                .iter()
+                .filter(|p| p.Attributes.0 & SE_PRIVILEGE_ENABLED.0 != 0)
+                .count();
+            (count, enabled)
+        }
+    }
+
+    #[test]
+    fn test_create_restricted_token() {
+        use windows::Win32::Security::TOKEN_DUPLICATE;
+
+        let token = Token::current_process_with_access(TOKEN_QUERY | TOKEN_DUPLICATE).unwrap();
+        let restricted = token
+            .create_restricted(
+                RestrictOptions::new()
+                    .disable_max_privilege(true)
+                    .deny_sid(Sid::administrators().unwrap()),
+            )
+            .unwrap();
+
+        let (total, enabled) = privilege_counts(&token);
+        let (restricted_total, restricted_enabled) = privilege_counts(&restricted);
+        // Only SeChangeNotifyPrivilege survives DISABLE_MAX_PRIVILEGE
+        assert!(restricted_total <= 1);
+        assert!(restricted_total < total);
+        assert!(restricted_enabled <= enabled);
+    }
+
+    #[test]
+    fn test_enable_debug_privilege() {
+        use windows::Win32::Foundation::ERROR_NOT_ALL_ASSIGNED;
+
+        match enable_debug_privilege() {
+            Ok(_guard) => {
+                assert!(is_elevated().unwrap());
+                let token = Token::current_process().unwrap();
+                assert!(token.has_privilege(privileges::SE_DEBUG_NAME).unwrap());
+            }
+            Err(Error::Windows(e)) => {
+                assert!(!is_elevated().unwrap());
+                assert_eq!(e.code(), ERROR_NOT_ALL_ASSIGNED.to_hresult());
+            }
+            Err(e) => panic!("unexpected error: {}", e),
+        }
+    }
 }
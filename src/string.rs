@@ -4,8 +4,13 @@
 //! This module provides ergonomic conversions between these formats.
 
 use crate::error::{Error, Result};
+use std::cmp::Ordering;
+use std::ffi::OsStr;
 use std::os::windows::ffi::OsStrExt;
 use std::path::Path;
+use windows::Win32::Globalization::{
+    CompareStringOrdinal, CSTR_EQUAL, CSTR_GREATER_THAN, CSTR_LESS_THAN,
+};
 
 /// Converts a Rust string to a null-terminated UTF-16 vector.
 ///
@@ -54,10 +59,8 @@ pub fn path_to_wide(path: &Path) -> Vec<u16> {
 /// ```
 #[inline]
 pub fn from_wide(wide: &[u16]) -> Result<String> {
-    // Find null terminator if present
-    let len = wide.iter().position(|&c| c == 0).unwrap_or(wide.len());
     // Use String::from_utf16 directly instead of going through OsString
-    String::from_utf16(&wide[..len])
+    String::from_utf16(&wide[..wide_len(wide)])
         .map_err(|_| Error::string_conversion("Invalid UTF-16 sequence"))
 }
 
@@ -139,12 +142,158 @@ pub fn from_wide_with_len(wide: &[u16], len: usize) -> Result<String> {
 /// ```
 #[inline]
 pub fn from_wide_buffer(buffer: &[u16]) -> Result<String> {
-    // Find null terminator or use entire buffer
-    let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
-    String::from_utf16(&buffer[..len])
+    String::from_utf16(&buffer[..wide_len(buffer)])
         .map_err(|_| Error::string_conversion("Invalid UTF-16 sequence"))
 }
 
+/// Returns the index of the first null in `wide`, or its length if there is none.
+///
+/// `&wide[..wide_len(wide)]` is the string without its terminator.
+#[inline]
+pub fn wide_len(wide: &[u16]) -> usize {
+    wide.iter().position(|&c| c == 0).unwrap_or(wide.len())
+}
+
+/// Converts a UTF-16 slice to a Rust `String`, replacing invalid data with U+FFFD.
+///
+/// The whole slice is decoded, including any nulls; use
+/// [`from_wide_until_nul_lossy`] for null-terminated data.
+#[inline]
+pub fn from_wide_lossy(wide: &[u16]) -> String {
+    String::from_utf16_lossy(wide)
+}
+
+/// Converts UTF-16 up to the first null to a Rust `String`, replacing invalid
+/// data with U+FFFD.
+///
+/// The terminator scan and decoding happen in a single pass, and this never fails.
+///
+/// # Example
+///
+/// ```
+/// use ergonomic_windows::string::from_wide_until_nul_lossy;
+///
+/// // A lone surrogate followed by the terminator and buffer padding
+/// let buffer = [0x48, 0xD800, 0x69, 0, 0x41];
+/// assert_eq!(from_wide_until_nul_lossy(&buffer), "H\u{FFFD}i");
+/// ```
+pub fn from_wide_until_nul_lossy(wide: &[u16]) -> String {
+    char::decode_utf16(wide.iter().copied().take_while(|&c| c != 0))
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect()
+}
+
+/// Packs strings into a double-null-terminated UTF-16 list.
+///
+/// Each item is followed by a null and the list by one more, giving
+/// `a\0\0b\0\0` for `["a", "", "b"]`. This is the form of `REG_MULTI_SZ`
+/// values, environment blocks and `SHFILEOPSTRUCTW` file lists. An empty list
+/// is `\0\0`, so readers that scan for the double null stop in time; a list of
+/// a single empty string packs to the same units.
+///
+/// # Example
+///
+/// ```
+/// use ergonomic_windows::string::{pack_multi, unpack_multi};
+///
+/// let packed = pack_multi(&["a", "", "b"]);
+/// assert_eq!(packed, [0x61, 0, 0, 0x62, 0, 0]);
+/// assert_eq!(unpack_multi(&packed), ["a", "", "b"]);
+/// ```
+pub fn pack_multi(items: &[&str]) -> Vec<u16> {
+    pack_multi_wide(items.iter().map(|item| item.encode_utf16()))
+}
+
+/// Packs already-encoded items into a double-null-terminated list.
+///
+/// See [`pack_multi`]; this lets paths be packed without a UTF-8 round trip.
+pub(crate) fn pack_multi_wide<I, W>(items: I) -> Vec<u16>
+where
+    I: IntoIterator<Item = W>,
+    W: IntoIterator<Item = u16>,
+{
+    let mut packed = Vec::new();
+    for item in items {
+        packed.extend(item);
+        packed.push(0);
+    }
+    if packed.is_empty() {
+        packed.push(0);
+    }
+    packed.push(0);
+    packed
+}
+
+/// Unpacks a double-null-terminated UTF-16 list, replacing invalid data with U+FFFD.
+///
+/// `buf` must be exactly the list, as sized by the API that produced it: the
+/// final null ends the list, every other null ends an item, so empty items in
+/// the middle of the list are kept. Lists missing their terminators are
+/// accepted, with the last item ending at the end of `buf`. Both `\0\0` and a
+/// lone `\0` are the empty list.
+pub fn unpack_multi(buf: &[u16]) -> Vec<String> {
+    split_multi(buf)
+        .into_iter()
+        .map(String::from_utf16_lossy)
+        .collect()
+}
+
+/// Splits a double-null-terminated list into its items, without terminators.
+///
+/// The parsing behind [`unpack_multi`], for callers that decode strictly.
+pub(crate) fn split_multi(buf: &[u16]) -> Vec<&[u16]> {
+    // Drop the list terminator, then the last item's terminator
+    let buf = buf.strip_suffix(&[0]).unwrap_or(buf);
+    let body = buf.strip_suffix(&[0]).unwrap_or(buf);
+    if body.is_empty() {
+        return Vec::new();
+    }
+    body.split(|&c| c == 0).collect()
+}
+
+/// Compares two strings ordinally, ignoring case the way the file system does.
+///
+/// Uses `CompareStringOrdinal`, which compares UTF-16 code units after mapping
+/// each through the operating system's uppercase table. This is how Windows
+/// matches file names, registry key names and environment variable names: it
+/// is independent of the user's locale, covers all of Unicode rather than just
+/// ASCII, and never expands characters (so `ß` does not equal `SS` and the `ﬁ`
+/// ligature does not equal `fi`). Unlike comparing `str`s directly, code
+/// points above U+FFFF sort by their surrogates, before U+E000..U+FFFF.
+///
+/// # Example
+///
+/// ```no_run
+/// use ergonomic_windows::string::compare_ordinal_ignore_case;
+/// use std::cmp::Ordering;
+///
+/// assert_eq!(compare_ordinal_ignore_case("Readme.TXT", "README.txt"), Ordering::Equal);
+/// assert_eq!(compare_ordinal_ignore_case("a", "B"), Ordering::Less);
+/// ```
+pub fn compare_ordinal_ignore_case(a: &str, b: &str) -> Ordering {
+    let a: Vec<u16> = a.encode_utf16().collect();
+    let b: Vec<u16> = b.encode_utf16().collect();
+    compare_wide_ignore_case(&a, &b)
+}
+
+/// Returns true if two strings are equal under [`compare_ordinal_ignore_case`].
+pub fn equals_ignore_case(a: &str, b: &str) -> bool {
+    compare_ordinal_ignore_case(a, b) == Ordering::Equal
+}
+
+/// Compares raw UTF-16 with `CompareStringOrdinal`, ignoring case.
+fn compare_wide_ignore_case(a: &[u16], b: &[u16]) -> Ordering {
+    // SAFETY: both slices are valid for their length; no null terminator is needed
+    let result = unsafe { CompareStringOrdinal(a, b, true) };
+    match result {
+        CSTR_LESS_THAN => Ordering::Less,
+        CSTR_EQUAL => Ordering::Equal,
+        CSTR_GREATER_THAN => Ordering::Greater,
+        // Only fails on invalid parameters, which slices cannot produce.
+        _ => a.cmp(b),
+    }
+}
+
 /// A builder for creating wide strings with proper null termination.
 #[derive(Default)]
 pub struct WideStringBuilder {
@@ -414,6 +563,80 @@ impl WideString {
             WideStringRepr::Heap(vec) => vec,
         }
     }
+
+    /// Returns true if both strings are equal ignoring case, using the OS comparison.
+    ///
+    /// Compares the raw UTF-16 without decoding, so unpaired surrogates are
+    /// compared as-is. See [`compare_ordinal_ignore_case`] for the rules.
+    pub fn eq_ignore_case(&self, other: &WideString) -> bool {
+        let a = &self.as_slice()[..self.len()];
+        let b = &other.as_slice()[..other.len()];
+        compare_wide_ignore_case(a, b) == Ordering::Equal
+    }
+
+    /// Appends a Rust string, keeping the string null-terminated.
+    ///
+    /// Moves the string to the heap if it no longer fits inline.
+    pub fn push_str(&mut self, s: &str) {
+        let extra = s.chars().map(|c| c.len_utf16()).sum();
+        self.extend_units(extra, s.encode_utf16());
+    }
+
+    /// Appends a path component, inserting a `\` separator if needed.
+    ///
+    /// No separator is added to an empty string or one that already ends in a separator.
+    pub fn push_path_component(&mut self, component: &OsStr) {
+        let needs_separator = match self.as_slice() {
+            [.., last, 0] => *last != u16::from(b'\\') && *last != u16::from(b'/'),
+            _ => false,
+        };
+        if needs_separator {
+            self.extend_units(1, std::iter::once(u16::from(b'\\')));
+        }
+        let extra = component.encode_wide().count();
+        self.extend_units(extra, component.encode_wide());
+    }
+
+    /// Resets to an empty inline string.
+    pub fn clear(&mut self) {
+        self.repr = WideStringRepr::Inline {
+            buf: [0u16; INLINE_CAP],
+            len: 1,
+        };
+    }
+
+    /// Appends `extra` code units before the null terminator.
+    fn extend_units(&mut self, extra: usize, units: impl Iterator<Item = u16>) {
+        if extra == 0 {
+            return;
+        }
+        let current = self.len();
+        let total = current + extra + 1;
+
+        if let WideStringRepr::Inline { buf, len } = &mut self.repr {
+            if total <= INLINE_CAP {
+                let mut idx = current;
+                for unit in units {
+                    buf[idx] = unit;
+                    idx += 1;
+                }
+                buf[idx] = 0;
+                *len = total as u8;
+                return;
+            }
+            let mut vec = Vec::with_capacity(total);
+            vec.extend_from_slice(&buf[..current]);
+            self.repr = WideStringRepr::Heap(vec);
+        }
+
+        if let WideStringRepr::Heap(vec) = &mut self.repr {
+            // Heap strings from `with_capacity` start without a terminator.
+            vec.truncate(current);
+            vec.reserve(extra + 1);
+            vec.extend(units);
+            vec.push(0);
+        }
+    }
 }
 
 impl From<&str> for WideString {
@@ -665,6 +888,76 @@ impl PooledWideString {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_pack_multi() {
+        assert_eq!(pack_multi(&[]), [0, 0]);
+        assert_eq!(pack_multi(&[""]), [0, 0]);
+        assert_eq!(pack_multi(&["", ""]), [0, 0, 0]);
+        assert_eq!(pack_multi(&["ab"]), [0x61, 0x62, 0, 0]);
+        assert_eq!(pack_multi(&["a", "", "b"]), [0x61, 0, 0, 0x62, 0, 0]);
+        assert_eq!(pack_multi(&["a", ""]), [0x61, 0, 0, 0]);
+        assert_eq!(pack_multi(&["", "a"]), [0, 0x61, 0, 0]);
+    }
+
+    #[test]
+    fn test_compare_ordinal_ignore_case() {
+        assert_eq!(compare_ordinal_ignore_case("FILE", "file"), Ordering::Equal);
+        assert_eq!(compare_ordinal_ignore_case("a", "B"), Ordering::Less);
+        assert_eq!(
+            compare_ordinal_ignore_case("file2", "FILE10"),
+            Ordering::Greater
+        );
+        assert!(equals_ignore_case("Ünïcödé", "ÜNÏCÖDÉ"));
+        // No expansion: ß is a single character that uppercases to itself
+        assert!(!equals_ignore_case("straße", "STRASSE"));
+
+        // Surrogate pairs compare by code unit, consistently in both directions
+        let pair = "\u{1D400}x";
+        assert!(equals_ignore_case(pair, "\u{1D400}X"));
+        assert_eq!(
+            compare_ordinal_ignore_case(pair, "\u{FFFD}"),
+            Ordering::Less
+        );
+        assert_eq!(
+            compare_ordinal_ignore_case("\u{FFFD}", pair),
+            Ordering::Greater
+        );
+
+        assert!(WideString::new("Program Files").eq_ignore_case(&WideString::new("PROGRAM FILES")));
+        assert!(!WideString::new("abc").eq_ignore_case(&WideString::new("abcd")));
+    }
+
+    #[test]
+    fn test_unpack_multi() {
+        assert!(unpack_multi(&[]).is_empty());
+        assert!(unpack_multi(&[0]).is_empty());
+        assert!(unpack_multi(&[0, 0]).is_empty());
+        assert_eq!(unpack_multi(&[0, 0, 0]), ["", ""]);
+        assert_eq!(unpack_multi(&[0x61, 0]), ["a"]);
+        assert_eq!(unpack_multi(&[0x61]), ["a"]);
+        assert_eq!(unpack_multi(&[0x61, 0, 0x62]), ["a", "b"]);
+        assert_eq!(unpack_multi(&[0x61, 0xD800, 0, 0]), ["a\u{FFFD}"]);
+    }
+
+    #[test]
+    fn test_multi_round_trip() {
+        let lists: [&[&str]; 8] = [
+            &[],
+            &["", ""],
+            &["", "", ""],
+            &["single"],
+            &["a", "", "b"],
+            &["", "a"],
+            &["a", ""],
+            &["path\\with spaces", "ünïcödé 🌍", "", "x"],
+        ];
+        for list in lists {
+            assert_eq!(unpack_multi(&pack_multi(list)), list, "{:?}", list);
+        }
+        // A single empty item is indistinguishable from the empty list
+        assert!(unpack_multi(&pack_multi(&[""])).is_empty());
+    }
+
     #[test]
     fn test_roundtrip() {
         let original = "Hello, World! 🌍";
@@ -673,6 +966,18 @@ mod tests {
         assert_eq!(original, back);
     }
 
+    #[test]
+    fn test_lossy_lone_surrogate() {
+        let wide = [0x61, 0xDC00, 0x62, 0, 0x63];
+
+        assert!(from_wide(&wide).is_err());
+        assert_eq!(from_wide_until_nul_lossy(&wide), "a\u{FFFD}b");
+        assert_eq!(from_wide_lossy(&wide), "a\u{FFFD}b\0c");
+        assert_eq!(wide_len(&wide), 3);
+        assert_eq!(wide_len(&[0x61, 0x62]), 2);
+        assert_eq!(from_wide_until_nul_lossy(&to_wide("Hello 🌍")), "Hello 🌍");
+    }
+
     #[test]
     fn test_roundtrip_control_char() {
         // Test with U+001F (unit separator) - found by fuzzer
@@ -790,6 +1095,42 @@ mod tests {
         assert!(!ws4.is_inline());
     }
 
+    #[test]
+    fn test_wide_string_push_str() {
+        let mut ws = WideString::new("C:");
+        ws.push_str("\\Users");
+        assert!(ws.is_inline());
+
+        ws.push_path_component(OsStr::new("someone"));
+        ws.push_path_component(OsStr::new("Documents"));
+        assert!(!ws.is_inline());
+        assert_eq!(ws.to_string_lossy(), "C:\\Users\\someone\\Documents");
+        assert_eq!(*ws.as_slice().last().unwrap(), 0);
+
+        ws.clear();
+        assert!(ws.is_empty());
+        assert!(ws.is_inline());
+        ws.push_path_component(OsStr::new("file.txt"));
+        assert_eq!(ws.to_string_lossy(), "file.txt");
+    }
+
+    #[test]
+    fn test_wide_string_push_boundary() {
+        let mut expected = String::new();
+        let mut ws = WideString::new("");
+        for _ in 0..30 {
+            ws.push_str("ab");
+            expected.push_str("ab");
+            assert_eq!(ws.to_string_lossy(), expected);
+            assert_eq!(ws.len(), expected.len());
+        }
+
+        let mut ws = WideString::with_capacity(64);
+        ws.push_str(&"x".repeat(40));
+        assert_eq!(ws.len(), 40);
+        assert_eq!(*ws.as_slice().last().unwrap(), 0);
+    }
+
     #[test]
     fn test_wide_string_pool_basic() {
         let mut pool = WideStringPool::new();
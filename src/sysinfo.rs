@@ -4,14 +4,46 @@
 //! including OS version, hardware, and computer details.
 
 use crate::error::Result;
-use crate::string::from_wide;
+use crate::fs::{device_io_control, device_io_control_raw};
+use crate::handle::OwnedHandle;
+use crate::string::{from_wide, WideString};
 use std::path::PathBuf;
+use windows::core::PCWSTR;
+use windows::Win32::Foundation::ERROR_INSUFFICIENT_BUFFER;
+use windows::Win32::Globalization::{
+    GetLocaleInfoEx, GetUserDefaultLocaleName, LCIDToLocaleName, LOCALE_IMEASURE, LOCALE_SDECIMAL,
+    LOCALE_SLOCALIZEDDISPLAYNAME, LOCALE_SLONGDATE, LOCALE_SSHORTDATE, LOCALE_STHOUSAND,
+    LOCALE_STIMEFORMAT,
+};
+use windows::Win32::Graphics::Dxgi::{CreateDXGIFactory1, IDXGIFactory1};
+use windows::Win32::Graphics::Gdi::{
+    EnumDisplayDevicesW, DISPLAY_DEVICEW, DISPLAY_DEVICE_ATTACHED_TO_DESKTOP,
+    DISPLAY_DEVICE_MIRRORING_DRIVER, DISPLAY_DEVICE_PRIMARY_DEVICE,
+};
+use windows::Win32::Storage::FileSystem::{
+    CreateFileW, FILE_FLAGS_AND_ATTRIBUTES, FILE_GENERIC_READ, FILE_SHARE_READ, FILE_SHARE_WRITE,
+    OPEN_EXISTING, STORAGE_BUS_TYPE,
+};
+use windows::Win32::System::Ioctl::{
+    PropertyStandardQuery, StorageDeviceProperty, StorageDeviceSeekPenaltyProperty,
+    DEVICE_SEEK_PENALTY_DESCRIPTOR, GET_LENGTH_INFORMATION, IOCTL_DISK_GET_LENGTH_INFO,
+    IOCTL_STORAGE_QUERY_PROPERTY, STORAGE_DEVICE_DESCRIPTOR, STORAGE_PROPERTY_QUERY,
+};
+use windows::Win32::System::RemoteDesktop::ProcessIdToSessionId;
 use windows::Win32::System::SystemInformation::{
     ComputerNameDnsDomain, ComputerNameDnsFullyQualified, ComputerNameDnsHostname,
     ComputerNameNetBIOS, ComputerNamePhysicalDnsDomain, ComputerNamePhysicalDnsFullyQualified,
-    ComputerNamePhysicalDnsHostname, ComputerNamePhysicalNetBIOS, GetComputerNameExW,
-    GetNativeSystemInfo, GetVersionExW, OSVERSIONINFOEXW, SYSTEM_INFO,
+    ComputerNamePhysicalDnsHostname, ComputerNamePhysicalNetBIOS, FirmwareTypeBios,
+    FirmwareTypeUefi, GetComputerNameExW, GetFirmwareType, GetLogicalProcessorInformationEx,
+    GetNativeSystemInfo, GetPhysicallyInstalledSystemMemory, GetVersionExW, RelationAll,
+    RelationCache, RelationGroup, RelationNumaNode, RelationProcessorCore,
+    RelationProcessorPackage, FIRMWARE_TYPE, OSVERSIONINFOEXW, SYSTEM_INFO,
+    SYSTEM_LOGICAL_PROCESSOR_INFORMATION_EX,
 };
+use windows::Win32::System::SystemServices::LOCALE_NAME_MAX_LENGTH;
+use windows::Win32::System::Threading::GetCurrentProcessId;
+use windows::Win32::UI::Input::KeyboardAndMouse::{GetKeyboardLayoutList, HKL};
+use windows::Win32::UI::WindowsAndMessaging::{GetSystemMetrics, SM_REMOTESESSION};
 
 /// Processor architecture.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -89,6 +121,106 @@ pub fn processor_info() -> ProcessorInfo {
     }
 }
 
+/// Processor topology of the system.
+///
+/// Counts span all processor groups. Cache sizes are the totals, in bytes, of all
+/// caches at that level (e.g. the sum of every core's private L2 cache).
+#[derive(Debug, Clone, Default)]
+pub struct CpuTopology {
+    /// Number of physical processor cores.
+    pub physical_cores: u32,
+    /// Number of logical processors (hardware threads).
+    pub logical_processors: u32,
+    /// Number of physical processor packages (sockets).
+    pub packages: u32,
+    /// Number of NUMA nodes.
+    pub numa_nodes: u32,
+    /// Number of active processor groups.
+    pub processor_groups: u32,
+    /// Total L1 cache size in bytes (data and instruction).
+    pub l1_cache_size: u64,
+    /// Total L2 cache size in bytes.
+    pub l2_cache_size: u64,
+    /// Total L3 cache size in bytes.
+    pub l3_cache_size: u64,
+}
+
+/// Gets the logical and physical processor topology of the system.
+pub fn cpu_topology() -> Result<CpuTopology> {
+    let mut size = 0u32;
+    let mut buffer: Vec<u64> = Vec::new();
+
+    // The required size can change between calls (e.g. hot-added processors),
+    // so keep growing the buffer until the call succeeds.
+    loop {
+        let ptr = if buffer.is_empty() {
+            None
+        } else {
+            Some(buffer.as_mut_ptr() as *mut SYSTEM_LOGICAL_PROCESSOR_INFORMATION_EX)
+        };
+
+        // SAFETY: ptr is either None (size query) or points to a buffer of at least size bytes
+        match unsafe { GetLogicalProcessorInformationEx(RelationAll, ptr, &mut size) } {
+            Ok(()) => break,
+            Err(e) if e.code() == ERROR_INSUFFICIENT_BUFFER.to_hresult() => {
+                buffer = vec![0u64; (size as usize).div_ceil(8)];
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    let mut topology = CpuTopology::default();
+    let base = buffer.as_ptr() as *const u8;
+    let mut offset = 0usize;
+
+    while offset < size as usize {
+        // SAFETY: Each record lies within the first size bytes of the buffer, and
+        // its Size field gives the offset of the next record.
+        let info =
+            unsafe { &*(base.add(offset) as *const SYSTEM_LOGICAL_PROCESSOR_INFORMATION_EX) };
+        if info.Size == 0 {
+            break;
+        }
+
+        if info.Relationship == RelationProcessorCore {
+            topology.physical_cores += 1;
+            // SAFETY: Processor is the active union member for this relationship.
+            // GroupMask holds GroupCount entries.
+            unsafe {
+                let processor = &info.Anonymous.Processor;
+                let masks = std::slice::from_raw_parts(
+                    processor.GroupMask.as_ptr(),
+                    processor.GroupCount as usize,
+                );
+                for mask in masks {
+                    topology.logical_processors += mask.Mask.count_ones();
+                }
+            }
+        } else if info.Relationship == RelationProcessorPackage {
+            topology.packages += 1;
+        } else if info.Relationship == RelationNumaNode {
+            topology.numa_nodes += 1;
+        } else if info.Relationship == RelationCache {
+            // SAFETY: Cache is the active union member for this relationship
+            let cache = unsafe { &info.Anonymous.Cache };
+            let bytes = cache.CacheSize as u64;
+            match cache.Level {
+                1 => topology.l1_cache_size += bytes,
+                2 => topology.l2_cache_size += bytes,
+                3 => topology.l3_cache_size += bytes,
+                _ => {}
+            }
+        } else if info.Relationship == RelationGroup {
+            // SAFETY: Group is the active union member for this relationship
+            topology.processor_groups = unsafe { info.Anonymous.Group.ActiveGroupCount } as u32;
+        }
+
+        offset += info.Size as usize;
+    }
+
+    Ok(topology)
+}
+
 /// Operating system version information.
 #[derive(Debug, Clone)]
 pub struct OsVersion {
@@ -284,6 +416,610 @@ pub fn windows_directories() -> Result<WindowsDirectories> {
     })
 }
 
+/// Gets the amount of physically installed RAM in bytes.
+///
+/// This is the total reported by the SMBIOS tables, which is larger than
+/// [`MemoryStatus::total_physical`](crate::mem::MemoryStatus::total_physical)
+/// because it includes memory reserved by firmware and hardware. It fails on
+/// systems (including some virtual machines) whose firmware does not report SMBIOS
+/// memory information.
+pub fn installed_memory() -> Result<u64> {
+    let mut kilobytes = 0u64;
+    // SAFETY: kilobytes is a valid output parameter
+    unsafe {
+        GetPhysicallyInstalledSystemMemory(&mut kilobytes)?;
+    }
+    Ok(kilobytes * 1024)
+}
+
+/// The kind of firmware the system booted from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FirmwareType {
+    /// Legacy BIOS.
+    Bios,
+    /// UEFI.
+    Uefi,
+    /// The firmware type could not be determined.
+    Unknown,
+}
+
+/// BIOS/firmware details.
+#[derive(Debug, Clone)]
+pub struct FirmwareInfo {
+    /// Firmware vendor (e.g. "American Megatrends Inc.").
+    pub vendor: String,
+    /// Firmware version string.
+    pub version: String,
+    /// Firmware release date, as reported by the firmware (usually `MM/DD/YYYY`).
+    pub release_date: String,
+    /// Whether the system booted from BIOS or UEFI.
+    pub firmware_type: FirmwareType,
+    /// Whether UEFI Secure Boot is enabled. Always false on BIOS systems.
+    pub secure_boot: bool,
+}
+
+impl FirmwareInfo {
+    /// Returns true if the system booted from UEFI.
+    pub fn is_uefi(&self) -> bool {
+        self.firmware_type == FirmwareType::Uefi
+    }
+}
+
+/// Gets BIOS/firmware vendor, version, release date and type.
+///
+/// Vendor, version and date come from the SMBIOS data Windows caches under
+/// `HKLM\HARDWARE\DESCRIPTION\System\BIOS`; fields the firmware does not report are empty.
+/// Secure Boot state comes from `HKLM\SYSTEM\CurrentControlSet\Control\SecureBoot\State`,
+/// which only UEFI systems populate.
+pub fn firmware_info() -> Result<FirmwareInfo> {
+    use crate::registry::{Access, Key, RootKey, Value};
+
+    let key = Key::open(
+        RootKey::LOCAL_MACHINE,
+        r"HARDWARE\DESCRIPTION\System\BIOS",
+        Access::READ,
+    )?;
+    let read = |name: &str| {
+        key.get_value_or(name, Value::string(""))
+            .map(|v| v.as_string().unwrap_or_default().to_string())
+    };
+
+    let mut firmware_type = FIRMWARE_TYPE::default();
+    // SAFETY: firmware_type is a valid output parameter
+    let firmware_type = match unsafe { GetFirmwareType(&mut firmware_type) } {
+        Ok(()) if firmware_type == FirmwareTypeBios => FirmwareType::Bios,
+        Ok(()) if firmware_type == FirmwareTypeUefi => FirmwareType::Uefi,
+        _ => FirmwareType::Unknown,
+    };
+
+    let secure_boot = firmware_type == FirmwareType::Uefi
+        && Key::open(
+            RootKey::LOCAL_MACHINE,
+            r"SYSTEM\CurrentControlSet\Control\SecureBoot\State",
+            Access::READ,
+        )
+        .ok()
+        .and_then(|state| state.get_value("UEFISecureBootEnabled").ok())
+        .and_then(|v| v.as_dword())
+        .is_some_and(|enabled| enabled != 0);
+
+    Ok(FirmwareInfo {
+        vendor: read("BIOSVendor")?,
+        version: read("BIOSVersion")?,
+        release_date: read("BIOSReleaseDate")?,
+        firmware_type,
+        secure_boot,
+    })
+}
+
+/// A display adapter (graphics device output) known to the system.
+#[derive(Debug, Clone)]
+pub struct DisplayAdapter {
+    /// The GDI device name, such as `\\.\DISPLAY1`.
+    pub name: String,
+    /// The adapter description, such as the GPU model.
+    pub description: String,
+    /// Raw `DISPLAY_DEVICE_*` state flags.
+    pub state_flags: u32,
+    /// Whether this is the primary display device.
+    pub is_primary: bool,
+    /// Whether the device is part of the desktop.
+    pub is_attached: bool,
+    /// Dedicated video memory in bytes, if DXGI reports an adapter with the same description.
+    pub dedicated_video_memory: Option<u64>,
+}
+
+/// Lists the display adapters, best effort.
+///
+/// Mirroring drivers are skipped. A machine without graphics hardware (or a
+/// session without display access) yields an empty list rather than an error.
+pub fn display_adapters() -> Vec<DisplayAdapter> {
+    let memory = dxgi_video_memory();
+    let mut adapters = Vec::new();
+
+    for index in 0.. {
+        let mut device = DISPLAY_DEVICEW {
+            cb: std::mem::size_of::<DISPLAY_DEVICEW>() as u32,
+            ..Default::default()
+        };
+        // SAFETY: device is a valid DISPLAY_DEVICEW with cb set
+        if !unsafe { EnumDisplayDevicesW(PCWSTR::null(), index, &mut device, 0) }.as_bool() {
+            break;
+        }
+        if device.StateFlags & DISPLAY_DEVICE_MIRRORING_DRIVER != 0 {
+            continue;
+        }
+
+        let description = from_wide(&device.DeviceString).unwrap_or_default();
+        let dedicated_video_memory = memory
+            .iter()
+            .find(|(name, _)| *name == description)
+            .map(|&(_, bytes)| bytes);
+
+        adapters.push(DisplayAdapter {
+            name: from_wide(&device.DeviceName).unwrap_or_default(),
+            description,
+            state_flags: device.StateFlags,
+            is_primary: device.StateFlags & DISPLAY_DEVICE_PRIMARY_DEVICE != 0,
+            is_attached: device.StateFlags & DISPLAY_DEVICE_ATTACHED_TO_DESKTOP != 0,
+            dedicated_video_memory,
+        });
+    }
+
+    adapters
+}
+
+/// Collects `(description, dedicated video memory)` for each DXGI adapter.
+fn dxgi_video_memory() -> Vec<(String, u64)> {
+    // SAFETY: CreateDXGIFactory1 has no preconditions
+    let Ok(factory) = (unsafe { CreateDXGIFactory1::<IDXGIFactory1>() }) else {
+        return Vec::new();
+    };
+
+    let mut memory = Vec::new();
+    // SAFETY: EnumAdapters1 fails with DXGI_ERROR_NOT_FOUND past the last adapter
+    while let Ok(adapter) = unsafe { factory.EnumAdapters1(memory.len() as u32) } {
+        // SAFETY: adapter is a valid IDXGIAdapter1
+        let desc = unsafe { adapter.GetDesc1() }.unwrap_or_default();
+        let name = from_wide(&desc.Description).unwrap_or_default();
+        memory.push((name, desc.DedicatedVideoMemory as u64));
+    }
+    memory
+}
+
+/// The bus a physical disk is attached through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BusType {
+    /// SCSI.
+    Scsi,
+    /// ATAPI.
+    Atapi,
+    /// Parallel ATA.
+    Ata,
+    /// USB.
+    Usb,
+    /// Hardware RAID controller.
+    Raid,
+    /// Serial Attached SCSI.
+    Sas,
+    /// Serial ATA.
+    Sata,
+    /// SD card.
+    Sd,
+    /// MultiMediaCard.
+    Mmc,
+    /// A virtual disk, such as a Hyper-V disk.
+    Virtual,
+    /// A file-backed virtual disk, such as a mounted VHD.
+    FileBackedVirtual,
+    /// Storage Spaces.
+    Spaces,
+    /// NVM Express.
+    Nvme,
+    /// Another bus, with the raw `STORAGE_BUS_TYPE` value.
+    Other(i32),
+}
+
+impl BusType {
+    fn from_raw(bus: STORAGE_BUS_TYPE) -> Self {
+        match bus.0 {
+            1 => BusType::Scsi,
+            2 => BusType::Atapi,
+            3 => BusType::Ata,
+            7 => BusType::Usb,
+            8 => BusType::Raid,
+            10 => BusType::Sas,
+            11 => BusType::Sata,
+            12 => BusType::Sd,
+            13 => BusType::Mmc,
+            14 => BusType::Virtual,
+            15 => BusType::FileBackedVirtual,
+            16 => BusType::Spaces,
+            17 => BusType::Nvme,
+            other => BusType::Other(other),
+        }
+    }
+}
+
+/// A physical disk attached to the system.
+#[derive(Debug, Clone)]
+pub struct PhysicalDisk {
+    /// The drive number `N` of `\\.\PhysicalDriveN`.
+    pub index: u32,
+    /// Vendor and product identification, such as `Samsung SSD 980 PRO 1TB`.
+    pub model: String,
+    /// Serial number, or empty if the device does not report one.
+    pub serial: String,
+    /// Total size in bytes, or 0 if it could not be read.
+    pub size_bytes: u64,
+    /// The bus the disk is attached through.
+    pub bus_type: BusType,
+    /// Whether the disk has no seek penalty (an SSD), or `None` if the driver does not say.
+    pub is_ssd: Option<bool>,
+}
+
+/// Highest `\\.\PhysicalDriveN` number probed by [`physical_disks`].
+const MAX_PHYSICAL_DRIVES: u32 = 64;
+
+/// Lists the physical disks, best effort.
+///
+/// Probes `\\.\PhysicalDrive0` through `\\.\PhysicalDrive63` and skips drive
+/// numbers that cannot be opened, so gaps left by removed disks are fine. Model,
+/// bus and SSD detection work without elevation; the size needs read access and
+/// is 0 when the disk cannot be opened for reading.
+///
+/// # Example
+///
+/// ```no_run
+/// use ergonomic_windows::sysinfo::physical_disks;
+///
+/// for disk in physical_disks()? {
+///     println!("{}: {} ({} bytes, ssd: {:?})", disk.index, disk.model, disk.size_bytes, disk.is_ssd);
+/// }
+/// # Ok::<(), ergonomic_windows::error::Error>(())
+/// ```
+pub fn physical_disks() -> Result<Vec<PhysicalDisk>> {
+    let mut disks = Vec::new();
+    for index in 0..MAX_PHYSICAL_DRIVES {
+        let path = format!(r"\\.\PhysicalDrive{}", index);
+        let (handle, readable) = match open_device(&path, FILE_GENERIC_READ.0) {
+            Ok(handle) => (handle, true),
+            Err(_) => match open_device(&path, 0) {
+                Ok(handle) => (handle, false),
+                Err(_) => continue,
+            },
+        };
+        let Ok((model, serial, bus_type)) = query_device_descriptor(&handle) else {
+            continue;
+        };
+        let size_bytes = if readable {
+            disk_length(&handle).unwrap_or(0)
+        } else {
+            0
+        };
+
+        disks.push(PhysicalDisk {
+            index,
+            model,
+            serial,
+            size_bytes,
+            bus_type,
+            is_ssd: query_seek_penalty(&handle).map(|penalty| !penalty),
+        });
+    }
+    Ok(disks)
+}
+
+/// Opens a device path with the given access, sharing it with everyone else.
+fn open_device(path: &str, access: u32) -> Result<OwnedHandle> {
+    let wide = WideString::new(path);
+    // SAFETY: wide is a valid null-terminated wide string
+    let handle = unsafe {
+        CreateFileW(
+            wide.as_pcwstr(),
+            access,
+            FILE_SHARE_READ | FILE_SHARE_WRITE,
+            None,
+            OPEN_EXISTING,
+            FILE_FLAGS_AND_ATTRIBUTES(0),
+            None,
+        )?
+    };
+    OwnedHandle::new(handle)
+}
+
+/// Queries model, serial number and bus type with `StorageDeviceProperty`.
+fn query_device_descriptor(handle: &OwnedHandle) -> Result<(String, String, BusType)> {
+    let query = STORAGE_PROPERTY_QUERY {
+        PropertyId: StorageDeviceProperty,
+        QueryType: PropertyStandardQuery,
+        ..Default::default()
+    };
+    // SAFETY: STORAGE_PROPERTY_QUERY is a plain struct without pointers
+    let input = unsafe {
+        std::slice::from_raw_parts(
+            &query as *const STORAGE_PROPERTY_QUERY as *const u8,
+            std::mem::size_of::<STORAGE_PROPERTY_QUERY>(),
+        )
+    };
+    let mut buffer = vec![0u8; 1024];
+    // SAFETY: the buffers contain no pointers
+    let len =
+        unsafe { device_io_control_raw(handle, IOCTL_STORAGE_QUERY_PROPERTY, input, &mut buffer)? }
+            as usize;
+    if len < std::mem::size_of::<STORAGE_DEVICE_DESCRIPTOR>() {
+        return Err(crate::error::Error::buffer_too_small(
+            std::mem::size_of::<STORAGE_DEVICE_DESCRIPTOR>(),
+            len,
+        ));
+    }
+    let buffer = &buffer[..len];
+    // SAFETY: buffer holds at least a full descriptor; read_unaligned copes with alignment
+    let descriptor =
+        unsafe { std::ptr::read_unaligned(buffer.as_ptr() as *const STORAGE_DEVICE_DESCRIPTOR) };
+
+    let vendor = descriptor_string(buffer, descriptor.VendorIdOffset);
+    let product = descriptor_string(buffer, descriptor.ProductIdOffset);
+    let model = match (vendor.is_empty(), product.is_empty()) {
+        (false, false) => format!("{} {}", vendor, product),
+        (true, _) => product,
+        (false, true) => vendor,
+    };
+
+    Ok((
+        model,
+        descriptor_string(buffer, descriptor.SerialNumberOffset),
+        BusType::from_raw(descriptor.BusType),
+    ))
+}
+
+/// Reads a null-terminated ANSI string at `offset` in a descriptor buffer.
+///
+/// An offset of 0 means the device does not report the field.
+fn descriptor_string(buffer: &[u8], offset: u32) -> String {
+    let offset = offset as usize;
+    if offset == 0 || offset >= buffer.len() {
+        return String::new();
+    }
+    let bytes = &buffer[offset..];
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).trim().to_string()
+}
+
+/// Queries whether the disk incurs a seek penalty, if the driver reports it.
+fn query_seek_penalty(handle: &OwnedHandle) -> Option<bool> {
+    let query = STORAGE_PROPERTY_QUERY {
+        PropertyId: StorageDeviceSeekPenaltyProperty,
+        QueryType: PropertyStandardQuery,
+        ..Default::default()
+    };
+    let mut penalty = DEVICE_SEEK_PENALTY_DESCRIPTOR::default();
+    // SAFETY: both structures match IOCTL_STORAGE_QUERY_PROPERTY and contain no pointers
+    unsafe {
+        device_io_control(
+            handle,
+            IOCTL_STORAGE_QUERY_PROPERTY,
+            Some(&query),
+            Some(&mut penalty),
+        )
+    }
+    .ok()?;
+    Some(penalty.IncursSeekPenalty.as_bool())
+}
+
+/// Reads the size of a disk opened for reading.
+fn disk_length(handle: &OwnedHandle) -> Result<u64> {
+    let mut info = GET_LENGTH_INFORMATION::default();
+    // SAFETY: GET_LENGTH_INFORMATION matches IOCTL_DISK_GET_LENGTH_INFO
+    unsafe {
+        device_io_control::<(), _>(handle, IOCTL_DISK_GET_LENGTH_INFO, None, Some(&mut info))?
+    };
+    Ok(info.Length as u64)
+}
+
+/// The measurement system used by a locale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeasurementSystem {
+    /// Metric units.
+    Metric,
+    /// U.S. customary units.
+    UsCustomary,
+}
+
+/// Formatting conventions of a locale.
+#[derive(Debug, Clone)]
+pub struct LocaleInfo {
+    /// BCP-47 locale name, such as `en-US`.
+    pub name: String,
+    /// Display name in the user interface language, such as "English (United States)".
+    pub display_name: String,
+    /// Decimal separator, such as `.`.
+    pub decimal_separator: String,
+    /// Digit grouping separator, such as `,`.
+    pub thousands_separator: String,
+    /// Short date format picture, such as `M/d/yyyy`.
+    pub short_date_format: String,
+    /// Long date format picture, such as `dddd, MMMM d, yyyy`.
+    pub long_date_format: String,
+    /// Time format picture, such as `h:mm:ss tt`.
+    pub time_format: String,
+    /// Measurement system.
+    pub measurement: MeasurementSystem,
+}
+
+/// Gets the formatting conventions of the user's default locale.
+pub fn user_locale() -> Result<LocaleInfo> {
+    let mut name = [0u16; LOCALE_NAME_MAX_LENGTH as usize];
+    // SAFETY: name is a valid buffer of LOCALE_NAME_MAX_LENGTH characters
+    let len = crate::error::nonzero(unsafe { GetUserDefaultLocaleName(&mut name) })?;
+    let name = &name[..len as usize];
+
+    Ok(LocaleInfo {
+        name: from_wide(name)?,
+        display_name: locale_string(name, LOCALE_SLOCALIZEDDISPLAYNAME)?,
+        decimal_separator: locale_string(name, LOCALE_SDECIMAL)?,
+        thousands_separator: locale_string(name, LOCALE_STHOUSAND)?,
+        short_date_format: locale_string(name, LOCALE_SSHORTDATE)?,
+        long_date_format: locale_string(name, LOCALE_SLONGDATE)?,
+        time_format: locale_string(name, LOCALE_STIMEFORMAT)?,
+        measurement: match locale_string(name, LOCALE_IMEASURE)?.as_str() {
+            "1" => MeasurementSystem::UsCustomary,
+            _ => MeasurementSystem::Metric,
+        },
+    })
+}
+
+/// Reads a string locale setting; `name` must include the null terminator.
+fn locale_string(name: &[u16], lctype: u32) -> Result<String> {
+    let name = PCWSTR(name.as_ptr());
+    // SAFETY: name is a null-terminated locale name; None queries the required size
+    let size = unsafe { GetLocaleInfoEx(name, lctype, None) };
+    if size == 0 {
+        return Err(crate::error::last_error());
+    }
+
+    let mut buffer = vec![0u16; size as usize];
+    // SAFETY: buffer has room for the size reported above
+    let len = unsafe { GetLocaleInfoEx(name, lctype, Some(&mut buffer)) };
+    if len == 0 {
+        return Err(crate::error::last_error());
+    }
+    from_wide(&buffer[..len as usize])
+}
+
+/// Lists the input languages of the installed keyboard layouts, as BCP-47 names.
+///
+/// The order is the one shown in the language bar. A layout whose language
+/// has no locale name is reported as its hexadecimal layout handle.
+pub fn keyboard_layouts() -> Result<Vec<String>> {
+    // SAFETY: None queries the number of layouts
+    let count = unsafe { GetKeyboardLayoutList(None) };
+    let mut layouts = vec![HKL::default(); count.max(0) as usize];
+    // SAFETY: layouts has room for count handles
+    let count = unsafe { GetKeyboardLayoutList(Some(&mut layouts)) };
+    if count == 0 && !layouts.is_empty() {
+        return Err(crate::error::last_error());
+    }
+    layouts.truncate(count as usize);
+
+    Ok(layouts
+        .into_iter()
+        .map(|hkl| {
+            // The low word of a layout handle is the input language identifier
+            let langid = hkl.0 as usize as u32 & 0xFFFF;
+            let mut name = [0u16; LOCALE_NAME_MAX_LENGTH as usize];
+            // SAFETY: name is a valid buffer of LOCALE_NAME_MAX_LENGTH characters
+            let len = unsafe { LCIDToLocaleName(langid, Some(&mut name), 0) };
+            if len > 0 {
+                from_wide(&name[..len as usize]).unwrap_or_default()
+            } else {
+                format!("{:08X}", hkl.0 as usize)
+            }
+        })
+        .collect())
+}
+
+/// Returns true if the current session is a Remote Desktop session.
+///
+/// Sessions shown through other remoting software that drives the physical
+/// console, such as VNC, report false.
+pub fn is_remote_session() -> bool {
+    // SAFETY: GetSystemMetrics has no preconditions
+    unsafe { GetSystemMetrics(SM_REMOTESESSION) != 0 }
+}
+
+/// Gets the Remote Desktop Services session the current process runs in.
+///
+/// Services run in session 0; interactive logons get session 1 or higher.
+pub fn session_id() -> Result<u32> {
+    let mut session = 0u32;
+    // SAFETY: session is a valid output parameter
+    unsafe { ProcessIdToSessionId(GetCurrentProcessId(), &mut session)? };
+    Ok(session)
+}
+
+/// A hypervisor identified by its CPUID vendor signature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Hypervisor {
+    /// Microsoft Hyper-V (`Microsoft Hv`).
+    HyperV,
+    /// VMware (`VMwareVMware`).
+    VMware,
+    /// KVM (`KVMKVMKVM`).
+    Kvm,
+    /// Oracle VirtualBox (`VBoxVBoxVBox`).
+    VirtualBox,
+    /// Xen (`XenVMMXenVMM`).
+    Xen,
+    /// QEMU without KVM acceleration (`TCGTCGTCGTCG`).
+    Qemu,
+    /// Parallels (` lrpepyh  vr`).
+    Parallels,
+    /// Any other signature, as reported.
+    Other(String),
+}
+
+impl Hypervisor {
+    fn from_signature(signature: &str) -> Self {
+        match signature.trim_end_matches('\0') {
+            "Microsoft Hv" => Hypervisor::HyperV,
+            "VMwareVMware" => Hypervisor::VMware,
+            "KVMKVMKVM" => Hypervisor::Kvm,
+            "VBoxVBoxVBox" => Hypervisor::VirtualBox,
+            "XenVMMXenVMM" => Hypervisor::Xen,
+            "TCGTCGTCGTCG" => Hypervisor::Qemu,
+            " lrpepyh  vr" | "prl hyperv  " => Hypervisor::Parallels,
+            other => Hypervisor::Other(other.to_string()),
+        }
+    }
+}
+
+/// Identifies the hypervisor the system runs under, if CPUID reports one.
+///
+/// Reads the hypervisor-present bit (leaf 1, ECX bit 31) and the vendor
+/// signature from leaf `0x40000000`. This is a heuristic: hypervisors can hide
+/// both, and with virtualization-based security or WSL 2 enabled even a
+/// physical machine's Windows runs on Hyper-V and reports it. Always `None` on
+/// ARM64, which has no CPUID.
+pub fn hypervisor() -> Option<Hypervisor> {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        #[cfg(target_arch = "x86")]
+        use std::arch::x86::__cpuid;
+        #[cfg(target_arch = "x86_64")]
+        use std::arch::x86_64::__cpuid;
+
+        // CPUID is always available on x86-64 and on every CPU Windows supports;
+        // newer toolchains no longer consider it unsafe.
+        #[allow(unused_unsafe)]
+        // SAFETY: see above
+        let (features, vendor) = unsafe { (__cpuid(1), __cpuid(0x4000_0000)) };
+        if features.ecx & (1 << 31) == 0 {
+            return None;
+        }
+        let signature: Vec<u8> = [vendor.ebx, vendor.ecx, vendor.edx]
+            .iter()
+            .flat_map(|r| r.to_le_bytes())
+            .collect();
+        Some(Hypervisor::from_signature(&String::from_utf8_lossy(
+            &signature,
+        )))
+    }
+    #[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+    {
+        None
+    }
+}
+
+/// Returns true if the system appears to run in a virtual machine.
+///
+/// Equivalent to `hypervisor().is_some()`; see [`hypervisor`] for why this is
+/// only a heuristic. It is easy for a VM to evade and can also be true on a
+/// physical machine, so do not rely on it for anything security-sensitive.
+pub fn is_virtual_machine() -> bool {
+    hypervisor().is_some()
+}
+
 /// Summary of system information.
 #[derive(Debug)]
 pub struct SystemSummary {
@@ -295,6 +1031,8 @@ pub struct SystemSummary {
     pub hostname: String,
     /// Memory status.
     pub memory: crate::mem::MemoryStatus,
+    /// Display adapters; empty on systems without graphics hardware.
+    pub display_adapters: Vec<DisplayAdapter>,
 }
 
 /// Gets a summary of system information.
@@ -304,6 +1042,7 @@ pub fn system_summary() -> Result<SystemSummary> {
         processor: processor_info(),
         hostname: hostname()?,
         memory: crate::mem::memory_status()?,
+        display_adapters: display_adapters(),
     })
 }
 
@@ -341,5 +1080,111 @@ mod tests {
             "Memory: {} MB total",
             summary.memory.total_physical / 1024 / 1024
         );
+        for adapter in &summary.display_adapters {
+            assert!(!adapter.name.is_empty());
+            println!("Display: {} ({})", adapter.description, adapter.name);
+        }
+        assert!(
+            summary
+                .display_adapters
+                .iter()
+                .filter(|a| a.is_primary)
+                .count()
+                <= 1
+        );
+    }
+
+    #[test]
+    fn test_cpu_topology() {
+        let topology = cpu_topology().unwrap();
+        assert!(topology.physical_cores >= 1);
+        assert!(topology.logical_processors >= 1);
+        assert!(topology.physical_cores <= topology.logical_processors);
+        assert!(topology.numa_nodes >= 1);
+        assert!(topology.processor_groups >= 1);
+    }
+
+    #[test]
+    fn test_installed_memory() {
+        // Not every system reports SMBIOS memory information
+        if let Ok(installed) = installed_memory() {
+            let status = crate::mem::memory_status().unwrap();
+            assert!(installed >= status.total_physical);
+        }
+    }
+
+    #[test]
+    fn test_firmware_info() {
+        let info = firmware_info().unwrap();
+        // GetFirmwareType works on every supported Windows, VMs included
+        assert_ne!(info.firmware_type, FirmwareType::Unknown);
+        assert!(!info.secure_boot || info.is_uefi());
+        println!(
+            "{:?} firmware from {:?}, secure boot: {}",
+            info.firmware_type, info.vendor, info.secure_boot
+        );
+    }
+
+    #[test]
+    fn test_user_locale() {
+        let locale = user_locale().unwrap();
+        assert!(!locale.name.is_empty());
+        assert!(locale.name.contains('-') || locale.name.chars().all(|c| c.is_alphabetic()));
+        assert!(!locale.decimal_separator.is_empty());
+        assert_ne!(locale.decimal_separator, locale.thousands_separator);
+        assert!(!locale.short_date_format.is_empty());
+    }
+
+    #[test]
+    fn test_execution_environment() {
+        let session = session_id().unwrap();
+        // Tests run from an interactive logon or a service such as a CI agent
+        assert!(session < 65536);
+        println!("Session {} (remote: {})", session, is_remote_session());
+
+        assert_eq!(is_virtual_machine(), hypervisor().is_some());
+        if let Some(Hypervisor::Other(signature)) = hypervisor() {
+            println!("Unknown hypervisor: {:?}", signature);
+        }
+    }
+
+    #[test]
+    fn test_hypervisor_signatures() {
+        assert_eq!(
+            Hypervisor::from_signature("Microsoft Hv"),
+            Hypervisor::HyperV
+        );
+        assert_eq!(
+            Hypervisor::from_signature("KVMKVMKVM\0\0\0"),
+            Hypervisor::Kvm
+        );
+        assert_eq!(
+            Hypervisor::from_signature("NewHypervisr"),
+            Hypervisor::Other("NewHypervisr".into())
+        );
+    }
+
+    #[test]
+    fn test_keyboard_layouts() {
+        // Services and some CI sessions have no keyboard layouts loaded
+        for layout in keyboard_layouts().unwrap() {
+            assert!(!layout.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_physical_disks() {
+        // Sandboxed sessions may not be able to open any disk
+        let disks = physical_disks().unwrap();
+        for disk in &disks {
+            assert!(disk.index < MAX_PHYSICAL_DRIVES);
+            println!(
+                "Disk {}: {} {:?} ssd={:?}",
+                disk.index, disk.model, disk.bus_type, disk.is_ssd
+            );
+        }
+        if !disks.is_empty() && crate::security::is_elevated().unwrap_or(false) {
+            assert!(disks.iter().any(|d| d.size_bytes > 0));
+        }
     }
 }
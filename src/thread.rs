@@ -4,16 +4,25 @@
 //! mutexes, events, semaphores, and critical sections.
 
 use crate::error::{Error, Result};
-use crate::handle::OwnedHandle;
+use crate::handle::{OwnedHandle, Waitable};
 use crate::string::WideString;
+use std::cell::Cell;
+use std::marker::PhantomData;
 use std::time::Duration;
-use windows::Win32::Foundation::{HANDLE, WAIT_ABANDONED, WAIT_OBJECT_0, WAIT_TIMEOUT};
+use windows::Win32::Foundation::{
+    BOOLEAN, HANDLE, INVALID_HANDLE_VALUE, WAIT_ABANDONED_0, WAIT_OBJECT_0, WAIT_TIMEOUT,
+};
+use windows::Win32::System::SystemServices::MAXIMUM_WAIT_OBJECTS;
 use windows::Win32::System::Threading::{
     CreateEventW, CreateMutexW, CreateSemaphoreW, CreateThread, GetCurrentThreadId,
-    GetExitCodeThread, GetThreadId, OpenEventW, OpenMutexW, OpenSemaphoreW, ReleaseMutex,
-    ReleaseSemaphore, ResetEvent, ResumeThread, SetEvent, SuspendThread, TerminateThread,
-    WaitForSingleObject, EVENT_ALL_ACCESS, EVENT_MODIFY_STATE, INFINITE, MUTEX_ALL_ACCESS,
-    SEMAPHORE_ALL_ACCESS, THREAD_CREATION_FLAGS,
+    GetExitCodeThread, GetThreadId, OpenEventW, OpenMutexW, OpenSemaphoreW,
+    RegisterWaitForSingleObject, ReleaseMutex, ReleaseSemaphore, ResetEvent, ResumeThread,
+    SetEvent, SetThreadAffinityMask, SetThreadIdealProcessor, SuspendThread, TerminateThread,
+    TlsAlloc, TlsFree, TlsGetValue, TlsSetValue, UnregisterWaitEx, WaitForMultipleObjects,
+    EVENT_ALL_ACCESS, EVENT_MODIFY_STATE, MUTEX_ALL_ACCESS, SEMAPHORE_ALL_ACCESS,
+    STACK_SIZE_PARAM_IS_A_RESERVATION, THREAD_CREATE_SUSPENDED, THREAD_CREATION_FLAGS,
+    TLS_OUT_OF_INDEXES, WORKER_THREAD_FLAGS, WT_EXECUTEDEFAULT, WT_EXECUTEINWAITTHREAD,
+    WT_EXECUTELONGFUNCTION, WT_EXECUTEONLYONCE,
 };
 
 /// Result of waiting on a synchronization object.
@@ -27,6 +36,183 @@ pub enum WaitResult {
     Abandoned,
 }
 
+/// Waits until any one of the objects is signaled, or the timeout elapses.
+///
+/// Returns the index of the signaled object, or `None` on timeout. If several
+/// are signaled, the lowest index wins. An abandoned mutex counts as signaled.
+/// At most 64 objects can be waited on at once.
+///
+/// # Example
+///
+/// ```no_run
+/// use ergonomic_windows::thread::{wait_any, Event};
+/// use ergonomic_windows::time::Deadline;
+/// use std::time::Duration;
+///
+/// let stop = Event::new_manual(false)?;
+/// let deadline = Deadline::after(Duration::from_secs(5))?;
+///
+/// match wait_any(&[&stop, &deadline], None)? {
+///     Some(0) => println!("stopped"),
+///     _ => println!("timed out"),
+/// }
+/// # Ok::<(), ergonomic_windows::error::Error>(())
+/// ```
+pub fn wait_any(objects: &[&dyn Waitable], timeout: Option<Duration>) -> Result<Option<usize>> {
+    if objects.is_empty() || objects.len() > MAXIMUM_WAIT_OBJECTS as usize {
+        return Err(Error::custom("wait_any requires between 1 and 64 objects"));
+    }
+    let handles: Vec<HANDLE> = objects.iter().map(|o| o.raw_handle()).collect();
+
+    // SAFETY: every handle comes from a live Waitable borrowed for this call
+    let result =
+        unsafe { WaitForMultipleObjects(&handles, false, crate::handle::timeout_ms(timeout)) };
+
+    let count = handles.len() as u32;
+    if result == WAIT_TIMEOUT {
+        Ok(None)
+    } else if (WAIT_OBJECT_0.0..WAIT_OBJECT_0.0 + count).contains(&result.0) {
+        Ok(Some((result.0 - WAIT_OBJECT_0.0) as usize))
+    } else if (WAIT_ABANDONED_0.0..WAIT_ABANDONED_0.0 + count).contains(&result.0) {
+        Ok(Some((result.0 - WAIT_ABANDONED_0.0) as usize))
+    } else {
+        Err(crate::error::last_error())
+    }
+}
+
+/// Options for [`register_wait`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WaitFlags(pub WORKER_THREAD_FLAGS);
+
+impl WaitFlags {
+    /// Run the callback on a thread-pool worker each time the wait completes.
+    pub const DEFAULT: Self = Self(WT_EXECUTEDEFAULT);
+
+    /// Stop waiting after the callback has run once.
+    pub const ONLY_ONCE: Self = Self(WT_EXECUTEONLYONCE);
+
+    /// Run the callback on the wait thread itself; only for very short callbacks,
+    /// since other waits on that thread are delayed meanwhile.
+    pub const IN_WAIT_THREAD: Self = Self(WT_EXECUTEINWAITTHREAD);
+
+    /// Hint that the callback may block for a long time.
+    pub const LONG_FUNCTION: Self = Self(WT_EXECUTELONGFUNCTION);
+
+    /// Combines two sets of flags.
+    pub fn with(self, other: Self) -> Self {
+        Self(WORKER_THREAD_FLAGS(self.0 .0 | other.0 .0))
+    }
+}
+
+/// The boxed callback of a [`WaitRegistration`].
+///
+/// The mutex serializes invocations, which the thread pool may otherwise run
+/// concurrently when the object signals again before a callback returns.
+type WaitCallback = std::sync::Mutex<Box<dyn FnMut(bool) + Send>>;
+
+/// A callback registered with [`register_wait`].
+///
+/// Dropping it unregisters the wait and blocks until any running callback has
+/// returned, so the closure is never freed while in use. Do not drop it from
+/// inside its own callback, which would wait for itself forever.
+pub struct WaitRegistration<'a> {
+    wait: HANDLE,
+    callback: *mut WaitCallback,
+    _object: PhantomData<&'a ()>,
+}
+
+/// Runs `callback` on the system thread pool whenever `object` is signaled.
+///
+/// The callback receives `true` if it was invoked because `timeout` elapsed
+/// rather than because the object was signaled. Without
+/// [`WaitFlags::ONLY_ONCE`] the wait is re-armed after every callback, so an
+/// object that stays signaled (a manual-reset event, a process that has
+/// exited) invokes the callback over and over; pair those with `ONLY_ONCE`.
+/// No thread blocks on the object for the caller; a shared pool thread waits
+/// on it alongside many others.
+///
+/// `object` must stay open while the wait is registered, which the borrow on
+/// the returned [`WaitRegistration`] enforces.
+///
+/// # Example
+///
+/// ```no_run
+/// use ergonomic_windows::thread::{register_wait, Event, WaitFlags};
+///
+/// let event = Event::new_auto(false)?;
+/// let registration = register_wait(
+///     &event,
+///     |timed_out| println!("signaled (timed out: {})", timed_out),
+///     None,
+///     WaitFlags::DEFAULT,
+/// )?;
+/// event.set()?;
+/// # Ok::<(), ergonomic_windows::error::Error>(())
+/// ```
+pub fn register_wait<'a, W>(
+    object: &'a W,
+    callback: impl FnMut(bool) + Send + 'static,
+    timeout: Option<Duration>,
+    flags: WaitFlags,
+) -> Result<WaitRegistration<'a>>
+where
+    W: Waitable + ?Sized,
+{
+    let callback: Box<WaitCallback> = Box::new(std::sync::Mutex::new(Box::new(callback)));
+    let callback = Box::into_raw(callback);
+
+    let mut wait = HANDLE::default();
+    // SAFETY: object's handle outlives the registration via its borrow, and
+    // callback stays allocated until the wait is unregistered in Drop
+    let result = unsafe {
+        RegisterWaitForSingleObject(
+            &mut wait,
+            object.raw_handle(),
+            Some(wait_callback),
+            Some(callback as *const _),
+            crate::handle::timeout_ms(timeout),
+            flags.0,
+        )
+    };
+    if let Err(e) = result {
+        // SAFETY: registration failed, so the pool never saw the pointer
+        drop(unsafe { Box::from_raw(callback) });
+        return Err(e.into());
+    }
+
+    Ok(WaitRegistration {
+        wait,
+        callback,
+        _object: PhantomData,
+    })
+}
+
+unsafe extern "system" fn wait_callback(context: *mut std::ffi::c_void, timed_out: BOOLEAN) {
+    // SAFETY: context is the WaitCallback registered in register_wait, which is
+    // only freed after UnregisterWaitEx has waited for running callbacks
+    let callback = unsafe { &*(context as *const WaitCallback) };
+    let mut callback = callback
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    // Unwinding into the thread pool would abort the process.
+    let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        callback(timed_out.as_bool())
+    }));
+}
+
+impl Drop for WaitRegistration<'_> {
+    fn drop(&mut self) {
+        // SAFETY: wait is a registered wait handle; INVALID_HANDLE_VALUE makes
+        // UnregisterWaitEx block until every running callback has returned
+        unsafe {
+            let _ = UnregisterWaitEx(self.wait, INVALID_HANDLE_VALUE);
+        }
+        // SAFETY: callback came from Box::into_raw in register_wait and no
+        // callback can be running or start any more
+        drop(unsafe { Box::from_raw(self.callback) });
+    }
+}
+
 /// A Windows thread handle with RAII cleanup.
 pub struct Thread {
     handle: OwnedHandle,
@@ -53,26 +239,7 @@ impl Thread {
     where
         F: FnOnce() -> u32 + Send + 'static,
     {
-        // Box the closure and leak it - the thread proc will reclaim it
-        let boxed: Box<dyn FnOnce() -> u32 + Send> = Box::new(f);
-        let raw = Box::into_raw(Box::new(boxed));
-
-        // SAFETY: CreateThread is safe to call with valid parameters.
-        // The thread procedure will reclaim the boxed closure.
-        let handle = unsafe {
-            CreateThread(
-                None,
-                0,
-                Some(thread_proc),
-                Some(raw as *const _),
-                THREAD_CREATION_FLAGS(0),
-                None,
-            )?
-        };
-
-        Ok(Self {
-            handle: OwnedHandle::new(handle)?,
-        })
+        Builder::new().spawn(f)
     }
 
     /// Returns the thread ID.
@@ -89,17 +256,7 @@ impl Thread {
 
     /// Waits for the thread to finish with an optional timeout.
     pub fn wait(&self, timeout: Option<Duration>) -> Result<WaitResult> {
-        let timeout_ms = timeout.map(|d| d.as_millis() as u32).unwrap_or(INFINITE);
-
-        // SAFETY: handle is valid
-        let result = unsafe { WaitForSingleObject(self.handle.as_raw(), timeout_ms) };
-
-        match result {
-            WAIT_OBJECT_0 => Ok(WaitResult::Signaled),
-            WAIT_TIMEOUT => Ok(WaitResult::Timeout),
-            WAIT_ABANDONED => Ok(WaitResult::Abandoned),
-            _ => Err(crate::error::last_error()),
-        }
+        Waitable::wait(self, timeout)
     }
 
     /// Gets the exit code of the thread.
@@ -140,6 +297,30 @@ impl Thread {
         }
     }
 
+    /// Restricts the thread to the logical processors set in `mask`.
+    ///
+    /// Returns the previous affinity mask. The mask must be a subset of the process
+    /// affinity mask and only addresses the (at most 64) processors of the thread's
+    /// processor group.
+    pub fn set_affinity_mask(&self, mask: usize) -> Result<usize> {
+        // SAFETY: handle is valid
+        crate::error::nonzero(unsafe { SetThreadAffinityMask(self.handle.as_raw(), mask) })
+    }
+
+    /// Sets the preferred processor for the thread, as a scheduling hint.
+    ///
+    /// `processor` is a zero-based processor number within the thread's processor group.
+    /// Returns the previous ideal processor.
+    pub fn set_ideal_processor(&self, processor: u32) -> Result<u32> {
+        // SAFETY: handle is valid
+        let previous = unsafe { SetThreadIdealProcessor(self.handle.as_raw(), processor) };
+        if previous == u32::MAX {
+            Err(crate::error::last_error())
+        } else {
+            Ok(previous)
+        }
+    }
+
     /// Terminates the thread with the given exit code.
     ///
     /// # Safety
@@ -157,6 +338,130 @@ impl Thread {
     }
 }
 
+/// Configures and spawns a [`Thread`].
+///
+/// # Example
+///
+/// ```no_run
+/// use ergonomic_windows::thread::Builder;
+///
+/// let thread = Builder::new()
+///     .name("worker")
+///     .stack_size(8 * 1024 * 1024)
+///     .suspended(true)
+///     .spawn(|| 0)?;
+/// thread.resume()?;
+/// thread.join()?;
+/// # Ok::<(), ergonomic_windows::error::Error>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Builder {
+    stack_size: usize,
+    suspended: bool,
+    name: Option<String>,
+}
+
+impl Builder {
+    /// Creates a builder with the default stack size that starts the thread immediately.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the stack size to reserve, in bytes.
+    ///
+    /// Zero (the default) uses the stack reserve size from the executable's header,
+    /// typically 1 MB.
+    pub fn stack_size(mut self, bytes: usize) -> Self {
+        self.stack_size = bytes;
+        self
+    }
+
+    /// Creates the thread suspended; call [`Thread::resume`] to start it.
+    pub fn suspended(mut self, suspended: bool) -> Self {
+        self.suspended = suspended;
+        self
+    }
+
+    /// Sets the thread description shown in debuggers and crash dumps.
+    ///
+    /// Naming is best-effort: `SetThreadDescription` requires Windows 10 1607 or
+    /// later and is skipped on older systems.
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    /// Spawns a thread that executes the given closure.
+    pub fn spawn<F>(self, f: F) -> Result<Thread>
+    where
+        F: FnOnce() -> u32 + Send + 'static,
+    {
+        let mut flags = THREAD_CREATION_FLAGS(0);
+        if self.stack_size != 0 {
+            flags |= STACK_SIZE_PARAM_IS_A_RESERVATION;
+        }
+        // Stay suspended until named, so the name is visible from the first instruction
+        let start_suspended = self.suspended || self.name.is_some();
+        if start_suspended {
+            flags |= THREAD_CREATE_SUSPENDED;
+        }
+
+        // Box the closure and leak it - the thread proc will reclaim it
+        let boxed: Box<dyn FnOnce() -> u32 + Send> = Box::new(f);
+        let raw = Box::into_raw(Box::new(boxed));
+
+        // SAFETY: CreateThread is safe to call with valid parameters.
+        // The thread procedure will reclaim the boxed closure.
+        let handle = unsafe {
+            CreateThread(
+                None,
+                self.stack_size,
+                Some(thread_proc),
+                Some(raw as *const _),
+                flags,
+                None,
+            )
+        };
+        let handle = match handle {
+            Ok(handle) => handle,
+            Err(e) => {
+                // SAFETY: The thread was not created, so the closure is still ours
+                drop(unsafe { Box::from_raw(raw) });
+                return Err(e.into());
+            }
+        };
+
+        let thread = Thread {
+            handle: OwnedHandle::new(handle)?,
+        };
+
+        if let Some(name) = &self.name {
+            set_thread_description(&thread, name);
+        }
+        if start_suspended && !self.suspended {
+            thread.resume()?;
+        }
+
+        Ok(thread)
+    }
+}
+
+/// Sets a thread's description if `SetThreadDescription` is available.
+fn set_thread_description(thread: &Thread, name: &str) {
+    type SetThreadDescriptionFn =
+        unsafe extern "system" fn(HANDLE, windows::core::PCWSTR) -> windows::core::HRESULT;
+
+    if let Ok(set_description) = crate::resolve!(
+        "kernel32.dll",
+        "SetThreadDescription",
+        SetThreadDescriptionFn
+    ) {
+        let name = WideString::new(name);
+        // SAFETY: the handle is valid and name is a null-terminated wide string
+        let _ = unsafe { set_description(thread.as_raw(), name.as_pcwstr()) };
+    }
+}
+
 /// Thread procedure that executes the boxed closure.
 unsafe extern "system" fn thread_proc(param: *mut std::ffi::c_void) -> u32 {
     // Reclaim the boxed closure
@@ -164,6 +469,164 @@ unsafe extern "system" fn thread_proc(param: *mut std::ffi::c_void) -> u32 {
     boxed()
 }
 
+/// Creates a scope for spawning threads that borrow local data.
+///
+/// Modeled on [`std::thread::scope`]: every thread spawned through the
+/// [`Scope`] is joined before `scope` returns, so closures may borrow
+/// non-`'static` data from the enclosing function.
+///
+/// If `f` or any scoped thread panics, the panic is resumed once all threads
+/// have been joined. A panicking thread's exit code is 101.
+///
+/// If waiting for a thread keeps failing, the other threads are still joined
+/// and then the process aborts with the error: returning or unwinding while
+/// that thread may still run would free data it borrows.
+///
+/// # Example
+///
+/// ```no_run
+/// use ergonomic_windows::thread;
+/// use std::sync::atomic::{AtomicU64, Ordering};
+///
+/// let data = vec![1u64, 2, 3, 4];
+/// let total = AtomicU64::new(0);
+/// thread::scope(|s| {
+///     for half in data.chunks(2) {
+///         s.spawn(|| {
+///             total.fetch_add(half.iter().sum(), Ordering::Relaxed);
+///             0
+///         })?;
+///     }
+///     Ok::<(), ergonomic_windows::error::Error>(())
+/// })?;
+/// assert_eq!(total.into_inner(), 10);
+/// # Ok::<(), ergonomic_windows::error::Error>(())
+/// ```
+pub fn scope<'env, F, T>(f: F) -> T
+where
+    F: for<'scope> FnOnce(&'scope Scope<'scope, 'env>) -> T,
+{
+    let scope = Scope {
+        threads: std::cell::RefCell::new(Vec::new()),
+        panic: std::sync::Arc::new(std::sync::Mutex::new(None)),
+        scope: PhantomData,
+        env: PhantomData,
+    };
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| f(&scope)));
+
+    // Borrowed data must outlive every thread, whatever happened above
+    let mut join_error = None;
+    loop {
+        let next = scope.threads.borrow_mut().pop();
+        let Some(thread) = next else {
+            break;
+        };
+        if let Err(e) = join_scoped(&thread) {
+            join_error.get_or_insert(e);
+        }
+    }
+    if let Some(e) = join_error {
+        eprintln!("thread::scope: failed to join a scoped thread: {}", e);
+        std::process::abort();
+    }
+
+    let thread_panic = scope
+        .panic
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .take();
+    match (result, thread_panic) {
+        (Err(payload), _) | (Ok(_), Some(payload)) => std::panic::resume_unwind(payload),
+        (Ok(value), None) => value,
+    }
+}
+
+/// Waits for a scoped thread, retrying a few times before giving up.
+fn join_scoped(thread: &Thread) -> Result<()> {
+    const ATTEMPTS: u32 = 5;
+    let mut attempt = 1;
+    loop {
+        match thread.wait(None) {
+            Ok(_) => return Ok(()),
+            Err(e) if attempt == ATTEMPTS => return Err(e),
+            Err(_) => {
+                std::thread::sleep(Duration::from_millis(10 << attempt));
+                attempt += 1;
+            }
+        }
+    }
+}
+
+/// A scope for spawning threads that may borrow data outliving `'env`.
+///
+/// Created by [`scope`].
+pub struct Scope<'scope, 'env: 'scope> {
+    threads: std::cell::RefCell<Vec<std::rc::Rc<Thread>>>,
+    panic: std::sync::Arc<std::sync::Mutex<Option<Box<dyn std::any::Any + Send>>>>,
+    scope: PhantomData<&'scope mut &'scope ()>,
+    env: PhantomData<&'env mut &'env ()>,
+}
+
+impl<'scope> Scope<'scope, '_> {
+    /// Spawns a thread that is joined when the scope ends.
+    pub fn spawn<F>(&'scope self, f: F) -> Result<ScopedThread<'scope>>
+    where
+        F: FnOnce() -> u32 + Send + 'scope,
+    {
+        const PANIC_EXIT_CODE: u32 = 101;
+
+        let panic = self.panic.clone();
+        let run: Box<dyn FnOnce() -> u32 + Send + 'scope> =
+            Box::new(
+                move || match std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)) {
+                    Ok(code) => code,
+                    Err(payload) => {
+                        panic
+                            .lock()
+                            .unwrap_or_else(std::sync::PoisonError::into_inner)
+                            .get_or_insert(payload);
+                        PANIC_EXIT_CODE
+                    }
+                },
+            );
+        // SAFETY: `scope` joins every thread before 'scope ends, so the closure
+        // and everything it borrows outlive the thread.
+        let run: Box<dyn FnOnce() -> u32 + Send + 'static> = unsafe { std::mem::transmute(run) };
+
+        let thread = std::rc::Rc::new(Builder::new().spawn(run)?);
+        self.threads.borrow_mut().push(thread.clone());
+        Ok(ScopedThread {
+            thread,
+            scope: PhantomData,
+        })
+    }
+}
+
+/// A thread spawned in a [`Scope`].
+pub struct ScopedThread<'scope> {
+    thread: std::rc::Rc<Thread>,
+    scope: PhantomData<&'scope ()>,
+}
+
+impl ScopedThread<'_> {
+    /// Returns the thread ID.
+    pub fn id(&self) -> u32 {
+        self.thread.id()
+    }
+
+    /// Waits for the thread to finish and returns its exit code.
+    pub fn join(self) -> Result<u32> {
+        self.thread.wait(None)?;
+        self.thread.exit_code()
+    }
+
+    /// Returns the raw handle.
+    pub fn as_raw(&self) -> HANDLE {
+        self.thread.as_raw()
+    }
+}
+
 /// Gets the current thread ID.
 #[inline]
 pub fn current_thread_id() -> u32 {
@@ -173,7 +636,7 @@ pub fn current_thread_id() -> u32 {
 
 /// A Windows mutex (mutual exclusion) object.
 pub struct Mutex {
-    handle: OwnedHandle,
+    handle: ObjectHandle,
 }
 
 impl Mutex {
@@ -184,7 +647,7 @@ impl Mutex {
         // SAFETY: CreateMutexW is safe with these parameters
         let handle = unsafe { CreateMutexW(None, initial_owner, None)? };
         Ok(Self {
-            handle: OwnedHandle::new(handle)?,
+            handle: ObjectHandle::Owned(OwnedHandle::new(handle)?),
         })
     }
 
@@ -196,7 +659,7 @@ impl Mutex {
         // SAFETY: CreateMutexW is safe with valid string
         let handle = unsafe { CreateMutexW(None, initial_owner, name_wide.as_pcwstr())? };
         Ok(Self {
-            handle: OwnedHandle::new(handle)?,
+            handle: ObjectHandle::Owned(OwnedHandle::new(handle)?),
         })
     }
 
@@ -206,38 +669,113 @@ impl Mutex {
         // SAFETY: OpenMutexW is safe with valid string
         let handle = unsafe { OpenMutexW(MUTEX_ALL_ACCESS, false, name_wide.as_pcwstr())? };
         Ok(Self {
-            handle: OwnedHandle::new(handle)?,
+            handle: ObjectHandle::Owned(OwnedHandle::new(handle)?),
         })
     }
 
+    /// Wraps an existing mutex handle, such as one inherited from a parent process.
+    ///
+    /// If `owned` is true the handle is closed on drop; otherwise the caller
+    /// remains responsible for closing it.
+    ///
+    /// # Safety
+    ///
+    /// `handle` must be a valid mutex handle that stays open for the lifetime of
+    /// the returned value. Adopting a handle to a different kind of object is
+    /// undefined behavior once it is waited on or signaled.
+    pub unsafe fn from_raw(handle: HANDLE, owned: bool) -> Self {
+        Self {
+            handle: ObjectHandle::adopt(handle, owned),
+        }
+    }
+
     /// Acquires the mutex, blocking until available.
+    ///
+    /// An abandoned mutex is still acquired; check
+    /// [`MutexGuard::was_abandoned`] or use [`lock_checked`](Self::lock_checked)
+    /// when the mutex guards state shared with other processes.
     pub fn lock(&self) -> Result<MutexGuard<'_>> {
         self.lock_timeout(None)
     }
 
+    /// Acquires the mutex, blocking until available, for callers that must
+    /// handle abandonment.
+    ///
+    /// When the previous owner thread exited (or its process crashed) without
+    /// releasing the mutex, Windows hands ownership to the next waiter and
+    /// reports `WAIT_ABANDONED`. The mutex is then held exactly as after a
+    /// normal acquisition, so this returns `Ok` rather than an error, but as
+    /// [`Acquired::Abandoned`] so the case can't be overlooked: the guard it
+    /// holds must still be dropped to release the mutex.
+    ///
+    /// Abandonment means the previous owner may have stopped halfway through
+    /// updating whatever the mutex protects. Windows only reports it to the
+    /// single waiter that inherits ownership; every later acquisition looks
+    /// normal, so the state must be validated or repaired before this guard
+    /// is dropped.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ergonomic_windows::thread::{Acquired, Mutex};
+    ///
+    /// let mutex = Mutex::new_named("Local\\MyAppState", false)?;
+    /// let _guard = match mutex.lock_checked()? {
+    ///     Acquired::Clean(guard) => guard,
+    ///     Acquired::Abandoned(guard) => {
+    ///         // Previous owner died while holding the lock; rebuild shared state
+    ///         guard
+    ///     }
+    /// };
+    /// # Ok::<(), ergonomic_windows::error::Error>(())
+    /// ```
+    pub fn lock_checked(&self) -> Result<Acquired<'_>> {
+        let guard = self.lock_timeout(None)?;
+        Ok(if guard.was_abandoned() {
+            Acquired::Abandoned(guard)
+        } else {
+            Acquired::Clean(guard)
+        })
+    }
+
     /// Tries to acquire the mutex with a timeout.
+    ///
+    /// See [`lock_checked`](Self::lock_checked) for how abandonment is reported.
     pub fn lock_timeout(&self, timeout: Option<Duration>) -> Result<MutexGuard<'_>> {
-        let timeout_ms = timeout.map(|d| d.as_millis() as u32).unwrap_or(INFINITE);
-
-        // SAFETY: handle is valid
-        let result = unsafe { WaitForSingleObject(self.handle.as_raw(), timeout_ms) };
-
-        match result {
-            WAIT_OBJECT_0 | WAIT_ABANDONED => Ok(MutexGuard { mutex: self }),
-            WAIT_TIMEOUT => Err(Error::custom("Mutex lock timed out")),
-            _ => Err(crate::error::last_error()),
+        match Waitable::wait(self, timeout)? {
+            WaitResult::Signaled => Ok(MutexGuard::new(self, false)),
+            WaitResult::Abandoned => Ok(MutexGuard::new(self, true)),
+            WaitResult::Timeout => Err(Error::custom("Mutex lock timed out")),
         }
     }
 
     /// Tries to acquire the mutex without blocking.
+    ///
+    /// See [`lock_checked`](Self::lock_checked) for how abandonment is reported.
     pub fn try_lock(&self) -> Result<Option<MutexGuard<'_>>> {
-        // SAFETY: handle is valid
-        let result = unsafe { WaitForSingleObject(self.handle.as_raw(), 0) };
+        match Waitable::wait(self, Some(Duration::ZERO))? {
+            WaitResult::Signaled => Ok(Some(MutexGuard::new(self, false))),
+            WaitResult::Abandoned => Ok(Some(MutexGuard::new(self, true))),
+            WaitResult::Timeout => Ok(None),
+        }
+    }
+}
 
-        match result {
-            WAIT_OBJECT_0 | WAIT_ABANDONED => Ok(Some(MutexGuard { mutex: self })),
-            WAIT_TIMEOUT => Ok(None),
-            _ => Err(crate::error::last_error()),
+/// How [`Mutex::lock_checked`] acquired the mutex.
+#[must_use = "dropping it releases the mutex before abandonment is handled"]
+pub enum Acquired<'a> {
+    /// The mutex was free, or released normally by its previous owner.
+    Clean(MutexGuard<'a>),
+    /// The previous owner exited without releasing the mutex; the state it
+    /// protects may be inconsistent.
+    Abandoned(MutexGuard<'a>),
+}
+
+impl<'a> Acquired<'a> {
+    /// Returns the guard, however the mutex was acquired.
+    pub fn into_guard(self) -> MutexGuard<'a> {
+        match self {
+            Acquired::Clean(guard) | Acquired::Abandoned(guard) => guard,
         }
     }
 }
@@ -245,6 +783,23 @@ impl Mutex {
 /// RAII guard for a locked mutex.
 pub struct MutexGuard<'a> {
     mutex: &'a Mutex,
+    abandoned: bool,
+}
+
+impl<'a> MutexGuard<'a> {
+    fn new(mutex: &'a Mutex, abandoned: bool) -> Self {
+        Self { mutex, abandoned }
+    }
+
+    /// Returns true if the mutex was acquired because its previous owner
+    /// exited without releasing it.
+    ///
+    /// The lock is held either way; a `true` result means the protected data
+    /// may be inconsistent. See [`Mutex::lock_checked`].
+    #[inline]
+    pub fn was_abandoned(&self) -> bool {
+        self.abandoned
+    }
 }
 
 impl Drop for MutexGuard<'_> {
@@ -258,7 +813,7 @@ impl Drop for MutexGuard<'_> {
 
 /// A Windows event object for thread signaling.
 pub struct Event {
-    handle: OwnedHandle,
+    handle: ObjectHandle,
 }
 
 impl Event {
@@ -269,7 +824,7 @@ impl Event {
         // SAFETY: CreateEventW is safe with these parameters
         let handle = unsafe { CreateEventW(None, true, initial_state, None)? };
         Ok(Self {
-            handle: OwnedHandle::new(handle)?,
+            handle: ObjectHandle::Owned(OwnedHandle::new(handle)?),
         })
     }
 
@@ -280,7 +835,7 @@ impl Event {
         // SAFETY: CreateEventW is safe with these parameters
         let handle = unsafe { CreateEventW(None, false, initial_state, None)? };
         Ok(Self {
-            handle: OwnedHandle::new(handle)?,
+            handle: ObjectHandle::Owned(OwnedHandle::new(handle)?),
         })
     }
 
@@ -290,7 +845,7 @@ impl Event {
         // SAFETY: CreateEventW is safe with valid string
         let handle = unsafe { CreateEventW(None, true, initial_state, name_wide.as_pcwstr())? };
         Ok(Self {
-            handle: OwnedHandle::new(handle)?,
+            handle: ObjectHandle::Owned(OwnedHandle::new(handle)?),
         })
     }
 
@@ -300,7 +855,7 @@ impl Event {
         // SAFETY: CreateEventW is safe with valid string
         let handle = unsafe { CreateEventW(None, false, initial_state, name_wide.as_pcwstr())? };
         Ok(Self {
-            handle: OwnedHandle::new(handle)?,
+            handle: ObjectHandle::Owned(OwnedHandle::new(handle)?),
         })
     }
 
@@ -316,10 +871,26 @@ impl Event {
             )?
         };
         Ok(Self {
-            handle: OwnedHandle::new(handle)?,
+            handle: ObjectHandle::Owned(OwnedHandle::new(handle)?),
         })
     }
 
+    /// Wraps an existing event handle, such as one inherited from a parent process.
+    ///
+    /// If `owned` is true the handle is closed on drop; otherwise the caller
+    /// remains responsible for closing it.
+    ///
+    /// # Safety
+    ///
+    /// `handle` must be a valid event handle that stays open for the lifetime of
+    /// the returned value. Adopting a handle to a different kind of object is
+    /// undefined behavior once it is waited on or signaled.
+    pub unsafe fn from_raw(handle: HANDLE, owned: bool) -> Self {
+        Self {
+            handle: ObjectHandle::adopt(handle, owned),
+        }
+    }
+
     /// Sets the event to signaled state.
     pub fn set(&self) -> Result<()> {
         // SAFETY: handle is valid
@@ -345,22 +916,44 @@ impl Event {
 
     /// Waits for the event with a timeout.
     pub fn wait_timeout(&self, timeout: Option<Duration>) -> Result<WaitResult> {
-        let timeout_ms = timeout.map(|d| d.as_millis() as u32).unwrap_or(INFINITE);
+        Waitable::wait(self, timeout)
+    }
 
-        // SAFETY: handle is valid
-        let result = unsafe { WaitForSingleObject(self.handle.as_raw(), timeout_ms) };
+    /// Returns the raw handle.
+    pub fn as_raw(&self) -> HANDLE {
+        self.handle.as_raw()
+    }
+}
 
-        match result {
-            WAIT_OBJECT_0 => Ok(WaitResult::Signaled),
-            WAIT_TIMEOUT => Ok(WaitResult::Timeout),
-            _ => Err(crate::error::last_error()),
-        }
+/// An unnamed event owned as a raw handle value, so it can be shared with
+/// other threads; handles themselves are not `Send`.
+#[derive(Debug)]
+pub(crate) struct SharedEvent(usize);
+
+impl SharedEvent {
+    /// Takes ownership of `event`, which must own its handle.
+    pub(crate) fn new(event: Event) -> Self {
+        let event = std::mem::ManuallyDrop::new(event);
+        Self(event.as_raw().0 as usize)
+    }
+
+    /// Borrows the event; the returned value doesn't close it.
+    pub(crate) fn event(&self) -> Event {
+        // SAFETY: the handle stays open until self is dropped
+        unsafe { Event::from_raw(HANDLE(self.0 as *mut _), false) }
+    }
+}
+
+impl Drop for SharedEvent {
+    fn drop(&mut self) {
+        // SAFETY: self owns the handle, released from its Event in new
+        drop(unsafe { OwnedHandle::new_unchecked(HANDLE(self.0 as *mut _)) });
     }
 }
 
 /// A Windows semaphore object.
 pub struct Semaphore {
-    handle: OwnedHandle,
+    handle: ObjectHandle,
 }
 
 impl Semaphore {
@@ -371,7 +964,7 @@ impl Semaphore {
         // SAFETY: CreateSemaphoreW is safe with these parameters
         let handle = unsafe { CreateSemaphoreW(None, initial_count, max_count, None)? };
         Ok(Self {
-            handle: OwnedHandle::new(handle)?,
+            handle: ObjectHandle::Owned(OwnedHandle::new(handle)?),
         })
     }
 
@@ -382,7 +975,7 @@ impl Semaphore {
         let handle =
             unsafe { CreateSemaphoreW(None, initial_count, max_count, name_wide.as_pcwstr())? };
         Ok(Self {
-            handle: OwnedHandle::new(handle)?,
+            handle: ObjectHandle::Owned(OwnedHandle::new(handle)?),
         })
     }
 
@@ -392,10 +985,26 @@ impl Semaphore {
         // SAFETY: OpenSemaphoreW is safe with valid string
         let handle = unsafe { OpenSemaphoreW(SEMAPHORE_ALL_ACCESS, false, name_wide.as_pcwstr())? };
         Ok(Self {
-            handle: OwnedHandle::new(handle)?,
+            handle: ObjectHandle::Owned(OwnedHandle::new(handle)?),
         })
     }
 
+    /// Wraps an existing semaphore handle, such as one inherited from a parent process.
+    ///
+    /// If `owned` is true the handle is closed on drop; otherwise the caller
+    /// remains responsible for closing it.
+    ///
+    /// # Safety
+    ///
+    /// `handle` must be a valid semaphore handle that stays open for the lifetime of
+    /// the returned value. Adopting a handle to a different kind of object is
+    /// undefined behavior once it is waited on or signaled.
+    pub unsafe fn from_raw(handle: HANDLE, owned: bool) -> Self {
+        Self {
+            handle: ObjectHandle::adopt(handle, owned),
+        }
+    }
+
     /// Acquires the semaphore, blocking until available.
     pub fn acquire(&self) -> Result<()> {
         self.acquire_timeout(None).map(|_| ())
@@ -403,16 +1012,7 @@ impl Semaphore {
 
     /// Tries to acquire the semaphore with a timeout.
     pub fn acquire_timeout(&self, timeout: Option<Duration>) -> Result<WaitResult> {
-        let timeout_ms = timeout.map(|d| d.as_millis() as u32).unwrap_or(INFINITE);
-
-        // SAFETY: handle is valid
-        let result = unsafe { WaitForSingleObject(self.handle.as_raw(), timeout_ms) };
-
-        match result {
-            WAIT_OBJECT_0 => Ok(WaitResult::Signaled),
-            WAIT_TIMEOUT => Ok(WaitResult::Timeout),
-            _ => Err(crate::error::last_error()),
-        }
+        Waitable::wait(self, timeout)
     }
 
     /// Releases the semaphore, incrementing its count.
@@ -435,6 +1035,198 @@ impl Semaphore {
     }
 }
 
+/// The handle of a synchronization object, which is either owned or adopted
+/// from elsewhere without taking ownership.
+enum ObjectHandle {
+    Owned(OwnedHandle),
+    Borrowed(HANDLE),
+}
+
+impl ObjectHandle {
+    /// Wraps a raw handle, closing it on drop only if `owned`.
+    ///
+    /// # Safety
+    ///
+    /// `handle` must be a valid handle.
+    unsafe fn adopt(handle: HANDLE, owned: bool) -> Self {
+        if owned {
+            Self::Owned(OwnedHandle::new_unchecked(handle))
+        } else {
+            Self::Borrowed(handle)
+        }
+    }
+
+    fn as_raw(&self) -> HANDLE {
+        match self {
+            Self::Owned(handle) => handle.as_raw(),
+            Self::Borrowed(handle) => *handle,
+        }
+    }
+}
+
+/// A typed Win32 thread-local storage slot.
+///
+/// The slot index is allocated once per process, but each thread sees its own
+/// value, initially `None`. This is useful for passing context to C callbacks that
+/// have no user-data parameter.
+///
+/// Win32 TLS has no destructors: a value that is not [`take`](Self::take)n before
+/// its thread exits, or before the slot is dropped, is leaked.
+///
+/// [`get`](Self::get) hands out a [`TlsRef`] guard, tracked like a `RefCell`
+/// borrow: replacing or taking the value while a guard is alive on the same
+/// thread panics instead of freeing it from under the reference.
+pub struct TlsSlot<T> {
+    index: u32,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> TlsSlot<T> {
+    /// Allocates a new TLS slot.
+    pub fn new() -> Result<Self> {
+        // SAFETY: TlsAlloc has no preconditions
+        let index = unsafe { TlsAlloc() };
+        if index == TLS_OUT_OF_INDEXES {
+            return Err(crate::error::last_error());
+        }
+        Ok(Self {
+            index,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Stores a value for the current thread, dropping any previous value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the current thread's value is borrowed through a [`TlsRef`].
+    pub fn set(&self, value: Box<T>) -> Result<()> {
+        let previous = self.take();
+        let ptr = Box::into_raw(Box::new(TlsEntry {
+            value,
+            borrows: Cell::new(0),
+        }));
+        // SAFETY: index is a slot allocated by TlsAlloc
+        if let Err(e) = unsafe { TlsSetValue(self.index, Some(ptr as *const _)) } {
+            // SAFETY: ptr came from Box::into_raw and was not stored
+            drop(unsafe { Box::from_raw(ptr) });
+            return Err(e.into());
+        }
+        drop(previous);
+        Ok(())
+    }
+
+    /// Borrows the current thread's value.
+    ///
+    /// While the returned guard is alive, [`set`](Self::set) and
+    /// [`take`](Self::take) on this thread panic.
+    pub fn get(&self) -> Option<TlsRef<'_, T>> {
+        let entry = self.entry()?;
+        entry.borrows.set(entry.borrows.get() + 1);
+        Some(TlsRef {
+            entry,
+            _not_send: PhantomData,
+        })
+    }
+
+    /// Removes and returns the current thread's value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the current thread's value is borrowed through a [`TlsRef`].
+    pub fn take(&self) -> Option<Box<T>> {
+        let entry = self.entry()?;
+        assert_eq!(
+            entry.borrows.get(),
+            0,
+            "TlsSlot value replaced or taken while borrowed"
+        );
+        let ptr = entry as *const TlsEntry<T> as *mut TlsEntry<T>;
+        // SAFETY: index is a slot allocated by TlsAlloc
+        let _ = unsafe { TlsSetValue(self.index, None) };
+        // SAFETY: ptr came from Box::into_raw in set and has now been cleared from
+        // the slot, so ownership is transferred back exactly once; no guard
+        // refers to it.
+        Some(unsafe { Box::from_raw(ptr) }.value)
+    }
+
+    /// Gets the current thread's entry, if any.
+    fn entry(&self) -> Option<&TlsEntry<T>> {
+        // SAFETY: index is a slot allocated by TlsAlloc; a non-null value is a
+        // pointer from Box::into_raw stored by set on this thread, and it is only
+        // freed by take on this thread, which refuses while it is borrowed.
+        unsafe { (TlsGetValue(self.index) as *const TlsEntry<T>).as_ref() }
+    }
+
+    /// Returns the raw TLS index.
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+}
+
+/// A thread's value in a [`TlsSlot`], with its borrow count.
+struct TlsEntry<T> {
+    value: Box<T>,
+    borrows: Cell<usize>,
+}
+
+/// A borrow of the current thread's value in a [`TlsSlot`].
+///
+/// Created by [`TlsSlot::get`]. It can't leave the thread the value belongs to.
+pub struct TlsRef<'a, T> {
+    entry: &'a TlsEntry<T>,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl<T> std::ops::Deref for TlsRef<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.entry.value
+    }
+}
+
+impl<T> Drop for TlsRef<'_, T> {
+    fn drop(&mut self) {
+        self.entry.borrows.set(self.entry.borrows.get() - 1);
+    }
+}
+
+impl<T> Drop for TlsSlot<T> {
+    fn drop(&mut self) {
+        // The current thread's value can be reclaimed; values on other threads leak.
+        drop(self.take());
+        // SAFETY: index is a slot allocated by TlsAlloc
+        unsafe {
+            let _ = TlsFree(self.index);
+        }
+    }
+}
+
+impl Waitable for Thread {
+    fn raw_handle(&self) -> HANDLE {
+        self.handle.as_raw()
+    }
+}
+
+impl Waitable for Mutex {
+    fn raw_handle(&self) -> HANDLE {
+        self.handle.as_raw()
+    }
+}
+
+impl Waitable for Event {
+    fn raw_handle(&self) -> HANDLE {
+        self.handle.as_raw()
+    }
+}
+
+impl Waitable for Semaphore {
+    fn raw_handle(&self) -> HANDLE {
+        self.handle.as_raw()
+    }
+}
+
 /// Sleeps the current thread for the specified duration.
 pub fn sleep(duration: Duration) {
     use windows::Win32::System::Threading::Sleep;
@@ -464,6 +1256,69 @@ mod tests {
         assert!(id > 0);
     }
 
+    #[test]
+    fn test_register_wait_once() {
+        use std::sync::atomic::{AtomicU32, Ordering};
+        use std::sync::Arc;
+
+        let event = Event::new_auto(false).unwrap();
+        let calls = Arc::new(AtomicU32::new(0));
+        let timeouts = Arc::new(AtomicU32::new(0));
+        let (c, t) = (calls.clone(), timeouts.clone());
+        let registration = register_wait(
+            &event,
+            move |timed_out| {
+                c.fetch_add(1, Ordering::SeqCst);
+                if timed_out {
+                    t.fetch_add(1, Ordering::SeqCst);
+                }
+            },
+            None,
+            WaitFlags::ONLY_ONCE,
+        )
+        .unwrap();
+
+        event.set().unwrap();
+        for _ in 0..500 {
+            if calls.load(Ordering::SeqCst) > 0 {
+                break;
+            }
+            sleep(Duration::from_millis(10));
+        }
+        // A second signal must not run the callback again
+        event.set().unwrap();
+        sleep(Duration::from_millis(100));
+        drop(registration);
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(timeouts.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn test_register_wait_timeout() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::sync::Arc;
+
+        let event = Event::new_manual(false).unwrap();
+        let timed_out = Arc::new(AtomicBool::new(false));
+        let flag = timed_out.clone();
+        let _registration = register_wait(
+            &event,
+            move |t| flag.store(t, Ordering::SeqCst),
+            Some(Duration::from_millis(20)),
+            WaitFlags::ONLY_ONCE,
+        )
+        .unwrap();
+
+        for _ in 0..500 {
+            if timed_out.load(Ordering::SeqCst) {
+                break;
+            }
+            sleep(Duration::from_millis(10));
+        }
+        assert!(timed_out.load(Ordering::SeqCst));
+    }
+
     #[test]
     fn test_thread_spawn_join() {
         let thread = Thread::spawn(|| 42).unwrap();
@@ -471,6 +1326,43 @@ mod tests {
         assert_eq!(exit_code, 42);
     }
 
+    #[test]
+    fn test_scoped_threads_borrow() {
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        let data: Vec<u64> = (1..=100).collect();
+        let total = AtomicU64::new(0);
+        let exit_code = scope(|s| {
+            let (left, right) = data.split_at(50);
+            s.spawn(|| {
+                total.fetch_add(left.iter().sum(), Ordering::SeqCst);
+                0
+            })
+            .unwrap();
+            s.spawn(|| {
+                total.fetch_add(right.iter().sum(), Ordering::SeqCst);
+                7
+            })
+            .unwrap()
+            .join()
+            .unwrap()
+        });
+
+        assert_eq!(exit_code, 7);
+        assert_eq!(total.load(Ordering::SeqCst), 5050);
+    }
+
+    #[test]
+    fn test_scoped_thread_panic_resurfaces() {
+        let result = std::panic::catch_unwind(|| {
+            scope(|s| {
+                s.spawn(|| panic!("scoped thread panic")).unwrap();
+            })
+        });
+        let payload = result.unwrap_err();
+        assert_eq!(payload.downcast_ref::<&str>(), Some(&"scoped thread panic"));
+    }
+
     #[test]
     fn test_mutex_basic() {
         let mutex = Mutex::new(false).unwrap();
@@ -490,6 +1382,29 @@ mod tests {
         drop(guard);
     }
 
+    #[test]
+    fn test_mutex_abandoned() {
+        let name = format!("Local\\ew_abandoned_{}", std::process::id());
+        let mutex = Mutex::new_named(&name, false).unwrap();
+
+        // Owner thread exits while still holding the mutex
+        let owner_name = name.clone();
+        std::thread::spawn(move || {
+            let owner = Mutex::open(&owner_name).unwrap();
+            std::mem::forget(owner.lock().unwrap());
+        })
+        .join()
+        .unwrap();
+
+        let acquired = mutex.lock_checked().unwrap();
+        assert!(matches!(acquired, Acquired::Abandoned(_)));
+        assert!(acquired.into_guard().was_abandoned());
+
+        // Only the inheriting waiter sees the abandonment
+        let guard = mutex.try_lock().unwrap().unwrap();
+        assert!(!guard.was_abandoned());
+    }
+
     #[test]
     fn test_event_manual() {
         let event = Event::new_manual(false).unwrap();
@@ -515,6 +1430,31 @@ mod tests {
         assert_eq!(result, WaitResult::Timeout);
     }
 
+    #[test]
+    fn test_event_from_raw() {
+        let event = Event::new_manual(false).unwrap();
+        // Stand-in for an inherited handle: a duplicate of the event's handle
+        let original =
+            std::mem::ManuallyDrop::new(unsafe { OwnedHandle::new_unchecked(event.as_raw()) });
+        let duplicate = original.try_clone().unwrap();
+
+        let adopted = unsafe { Event::from_raw(duplicate.into_raw(), true) };
+        adopted.set().unwrap();
+        assert_eq!(
+            event.wait_timeout(Some(Duration::ZERO)).unwrap(),
+            WaitResult::Signaled
+        );
+
+        // A non-owning wrapper leaves the handle open
+        let borrowed = unsafe { Event::from_raw(event.as_raw(), false) };
+        drop(borrowed);
+        event.reset().unwrap();
+        assert_eq!(
+            adopted.wait_timeout(Some(Duration::ZERO)).unwrap(),
+            WaitResult::Timeout
+        );
+    }
+
     #[test]
     fn test_event_auto() {
         let event = Event::new_auto(true).unwrap();
@@ -557,4 +1497,87 @@ mod tests {
         let elapsed = start.elapsed();
         assert!(elapsed >= Duration::from_millis(40)); // Allow some tolerance
     }
+
+    #[test]
+    fn test_builder_large_stack() {
+        fn recurse(depth: u32) -> u32 {
+            // 16 KB per frame: 128 frames need ~2 MB, past the 1 MB default
+            let frame = std::hint::black_box([1u8; 16 * 1024]);
+            if depth == 0 {
+                0
+            } else {
+                recurse(depth - 1) + frame[depth as usize] as u32
+            }
+        }
+
+        let thread = Builder::new()
+            .stack_size(4 * 1024 * 1024)
+            .name("deep-recursion")
+            .spawn(|| recurse(128))
+            .unwrap();
+        assert_eq!(thread.join().unwrap(), 128);
+    }
+
+    #[test]
+    fn test_builder_suspended() {
+        let thread = Builder::new().suspended(true).spawn(|| 7).unwrap();
+        assert_eq!(
+            thread.wait(Some(Duration::from_millis(50))).unwrap(),
+            WaitResult::Timeout
+        );
+        assert_eq!(thread.resume().unwrap(), 1);
+        assert_eq!(thread.join().unwrap(), 7);
+    }
+
+    #[test]
+    fn test_thread_affinity() {
+        let thread = Thread::spawn(|| {
+            sleep(Duration::from_millis(200));
+            0
+        })
+        .unwrap();
+
+        // Processor 0 always exists in the thread's group, so this works on single-CPU machines.
+        let previous = thread.set_affinity_mask(1).unwrap();
+        assert_ne!(previous, 0);
+        assert_eq!(thread.set_affinity_mask(previous).unwrap(), 1);
+        thread.set_ideal_processor(0).unwrap();
+
+        thread.join().unwrap();
+    }
+
+    #[test]
+    fn test_tls_slot_per_thread() {
+        let slot = TlsSlot::<String>::new().unwrap();
+        slot.set(Box::new("main".to_string())).unwrap();
+
+        std::thread::scope(|scope| {
+            scope
+                .spawn(|| {
+                    assert!(slot.get().is_none());
+                    slot.set(Box::new("worker".to_string())).unwrap();
+                    assert_eq!(slot.take().as_deref().map(String::as_str), Some("worker"));
+                })
+                .join()
+                .unwrap();
+        });
+
+        assert_eq!(slot.get().as_deref().map(String::as_str), Some("main"));
+        assert_eq!(*slot.take().unwrap(), "main");
+        assert!(slot.take().is_none());
+    }
+
+    #[test]
+    fn test_tls_slot_take_while_borrowed_panics() {
+        let slot = TlsSlot::<u32>::new().unwrap();
+        slot.set(Box::new(7)).unwrap();
+
+        let value = slot.get().unwrap();
+        let taken = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| slot.take()));
+        assert!(taken.is_err());
+        assert_eq!(*value, 7);
+
+        drop(value);
+        assert_eq!(slot.take().as_deref(), Some(&7));
+    }
 }
@@ -3,11 +3,19 @@
 //! Provides safe wrappers for Windows high-resolution timers,
 //! system time, and time zone information.
 
-use crate::error::Result;
-use std::time::Duration;
-use windows::Win32::Foundation::{FILETIME, SYSTEMTIME};
+use crate::error::{Error, Result};
+use crate::handle::{OwnedHandle, Waitable};
+use crate::thread::{wait_any, Event, SharedEvent, Thread, WaitResult};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex as StdMutex};
+use std::time::{Duration, Instant};
+use windows::Win32::Foundation::{FILETIME, HANDLE, SYSTEMTIME};
 use windows::Win32::System::Performance::{QueryPerformanceCounter, QueryPerformanceFrequency};
 use windows::Win32::System::SystemInformation::{GetLocalTime, GetSystemTime, GetTickCount64};
+use windows::Win32::System::Threading::{
+    CreateWaitableTimerExW, SetWaitableTimer, CREATE_WAITABLE_TIMER_HIGH_RESOLUTION,
+    CREATE_WAITABLE_TIMER_MANUAL_RESET, TIMER_ALL_ACCESS,
+};
 use windows::Win32::System::Time::{
     FileTimeToSystemTime, GetTimeZoneInformation, SystemTimeToFileTime, TIME_ZONE_INFORMATION,
 };
@@ -363,6 +371,273 @@ impl Stopwatch {
     }
 }
 
+/// A one-shot waitable timer that becomes signaled once a duration has elapsed.
+///
+/// Unlike a timeout parameter, a deadline is a real kernel object, so it can be
+/// combined with other objects in [`wait_any`](crate::thread::wait_any). It stays
+/// signaled after it fires.
+pub struct Deadline {
+    handle: OwnedHandle,
+    expires: Instant,
+}
+
+impl Deadline {
+    /// Creates a deadline that fires after `duration`, armed immediately.
+    ///
+    /// A high-resolution timer is used where available (Windows 10 1803+).
+    pub fn after(duration: Duration) -> Result<Self> {
+        let expires = Instant::now() + duration;
+
+        let handle = create_timer(CREATE_WAITABLE_TIMER_MANUAL_RESET)?;
+        arm_timer(&handle, duration)?;
+
+        Ok(Self { handle, expires })
+    }
+
+    /// Returns the time left until the deadline, or zero if it has passed.
+    ///
+    /// Returns zero once the timer has fired, even if the kernel rounded the
+    /// due time slightly ahead of the requested duration.
+    pub fn remaining(&self) -> Duration {
+        let left = self.expires.saturating_duration_since(Instant::now());
+        if left > Duration::ZERO
+            && matches!(self.wait(Some(Duration::ZERO)), Ok(WaitResult::Signaled))
+        {
+            return Duration::ZERO;
+        }
+        left
+    }
+
+    /// Returns the raw timer handle.
+    #[inline]
+    pub fn as_raw(&self) -> HANDLE {
+        self.handle.as_raw()
+    }
+}
+
+impl Waitable for Deadline {
+    fn raw_handle(&self) -> HANDLE {
+        self.handle.as_raw()
+    }
+}
+
+/// Creates an unarmed waitable timer, high-resolution where available
+/// (Windows 10 1803+).
+fn create_timer(flags: u32) -> Result<OwnedHandle> {
+    // SAFETY: No name or security attributes; we own the returned handle
+    let handle = unsafe {
+        CreateWaitableTimerExW(
+            None,
+            None,
+            flags | CREATE_WAITABLE_TIMER_HIGH_RESOLUTION,
+            TIMER_ALL_ACCESS.0,
+        )
+        .or_else(|_| CreateWaitableTimerExW(None, None, flags, TIMER_ALL_ACCESS.0))?
+    };
+    OwnedHandle::new(handle)
+}
+
+/// Arms a waitable timer to fire once after `duration`.
+fn arm_timer(timer: &OwnedHandle, duration: Duration) -> Result<()> {
+    // Negative due times are relative, in 100ns units
+    let due = -((duration.as_nanos() / 100).min(i64::MAX as u128) as i64);
+    // SAFETY: timer is a valid timer handle and due outlives the call
+    unsafe {
+        SetWaitableTimer(timer.as_raw(), &due, 0, None, None, false)?;
+    }
+    Ok(())
+}
+
+/// Runs callbacks at fixed intervals on a dedicated background thread.
+///
+/// The thread sleeps on a waitable timer armed for the next due task, so an
+/// idle scheduler costs nothing. Callbacks run one at a time on that thread;
+/// a slow callback delays the others, and a panicking one is caught and stays
+/// scheduled. Dropping the scheduler shuts it down like [`shutdown`](Self::shutdown).
+///
+/// # Example
+///
+/// ```no_run
+/// use ergonomic_windows::time::Scheduler;
+/// use std::time::Duration;
+///
+/// let scheduler = Scheduler::new()?;
+/// let heartbeat = scheduler.every(Duration::from_secs(5), || println!("alive"));
+/// // ...
+/// heartbeat.cancel();
+/// scheduler.shutdown()?;
+/// # Ok::<(), ergonomic_windows::error::Error>(())
+/// ```
+pub struct Scheduler {
+    shared: Arc<SchedulerShared>,
+    wake: Arc<SharedEvent>,
+    thread: Option<Thread>,
+}
+
+/// State shared between a [`Scheduler`] and its thread.
+struct SchedulerShared {
+    tasks: StdMutex<Vec<ScheduledTask>>,
+    shutdown: AtomicBool,
+}
+
+struct ScheduledTask {
+    interval: Duration,
+    next_due: Instant,
+    cancelled: Arc<AtomicBool>,
+    callback: Box<dyn Fn() + Send>,
+}
+
+impl Scheduler {
+    /// Starts a scheduler thread with no tasks.
+    pub fn new() -> Result<Self> {
+        let wake = Arc::new(SharedEvent::new(Event::new_auto(false)?));
+        let shared = Arc::new(SchedulerShared {
+            tasks: StdMutex::new(Vec::new()),
+            shutdown: AtomicBool::new(false),
+        });
+
+        let (ready_tx, ready_rx) = mpsc::channel();
+        let thread_shared = Arc::clone(&shared);
+        let thread_wake = Arc::clone(&wake);
+        let thread = Thread::spawn(move || match create_timer(0) {
+            Ok(timer) => {
+                let _ = ready_tx.send(Ok(()));
+                run_scheduler(&thread_shared, &thread_wake.event(), &timer);
+                0
+            }
+            Err(e) => {
+                let _ = ready_tx.send(Err(e));
+                1
+            }
+        })?;
+        ready_rx
+            .recv()
+            .map_err(|_| Error::custom("Scheduler thread exited during startup"))??;
+
+        Ok(Self {
+            shared,
+            wake,
+            thread: Some(thread),
+        })
+    }
+
+    /// Runs `f` every `interval`, starting one interval from now.
+    ///
+    /// Intervals below a millisecond are rounded up to one. The task runs until
+    /// it is cancelled through the returned handle or the scheduler shuts down;
+    /// dropping the handle does not cancel it.
+    pub fn every(&self, interval: Duration, f: impl Fn() + Send + 'static) -> TaskHandle {
+        let interval = interval.max(Duration::from_millis(1));
+        let cancelled = Arc::new(AtomicBool::new(false));
+        self.shared
+            .tasks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(ScheduledTask {
+                interval,
+                next_due: Instant::now() + interval,
+                cancelled: Arc::clone(&cancelled),
+                callback: Box::new(f),
+            });
+        // Wake the thread so it re-arms its timer for the new task; setting a
+        // valid event can't fail
+        let _ = self.wake.event().set();
+        TaskHandle { cancelled }
+    }
+
+    /// Stops the scheduler thread and waits for it to exit.
+    ///
+    /// A callback that is running finishes first; no further callbacks run.
+    pub fn shutdown(mut self) -> Result<()> {
+        self.stop()
+    }
+
+    fn stop(&mut self) -> Result<()> {
+        let Some(thread) = self.thread.take() else {
+            return Ok(());
+        };
+        self.shared.shutdown.store(true, Ordering::Release);
+        self.wake.event().set()?;
+        thread.join()?;
+        Ok(())
+    }
+}
+
+impl Drop for Scheduler {
+    fn drop(&mut self) {
+        let _ = self.stop();
+    }
+}
+
+/// The body of the scheduler thread.
+fn run_scheduler(shared: &SchedulerShared, wake: &Event, timer: &OwnedHandle) {
+    while !shared.shutdown.load(Ordering::Acquire) {
+        // Take the due tasks out, so callbacks run without the lock held and
+        // may schedule or cancel tasks themselves
+        let now = Instant::now();
+        let mut due = {
+            let mut tasks = shared.tasks.lock().unwrap_or_else(|e| e.into_inner());
+            tasks.retain(|task| !task.cancelled.load(Ordering::Acquire));
+            let (due, pending) = std::mem::take(&mut *tasks)
+                .into_iter()
+                .partition::<Vec<_>, _>(|task| task.next_due <= now);
+            *tasks = pending;
+            due
+        };
+
+        for task in &mut due {
+            if shared.shutdown.load(Ordering::Acquire) {
+                return;
+            }
+            if task.cancelled.load(Ordering::Acquire) {
+                continue;
+            }
+            let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| (task.callback)()));
+            // Skip missed ticks rather than running them back to back
+            while task.next_due <= Instant::now() {
+                task.next_due += task.interval;
+            }
+        }
+
+        let next_due = {
+            let mut tasks = shared.tasks.lock().unwrap_or_else(|e| e.into_inner());
+            tasks.extend(due);
+            tasks.iter().map(|task| task.next_due).min()
+        };
+
+        let waited = match next_due {
+            Some(next_due) => arm_timer(timer, next_due.saturating_duration_since(Instant::now()))
+                .and_then(|()| wait_any(&[wake, timer], None)),
+            None => wait_any(&[wake], None),
+        };
+        if waited.is_err() {
+            // Without a working wait the loop would spin
+            return;
+        }
+    }
+}
+
+/// A handle to a task scheduled with [`Scheduler::every`].
+#[derive(Debug, Clone)]
+pub struct TaskHandle {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl TaskHandle {
+    /// Cancels the task.
+    ///
+    /// No new invocation starts once this returns; one already under way on
+    /// the scheduler thread runs to completion.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Release);
+    }
+
+    /// Returns true if the task has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Acquire)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -420,4 +695,70 @@ mod tests {
         let elapsed2 = sw.elapsed().unwrap();
         assert_eq!(elapsed, elapsed2);
     }
+
+    #[test]
+    fn test_deadline_signals() {
+        use crate::thread::{wait_any, Event};
+
+        let deadline = Deadline::after(Duration::from_millis(50)).unwrap();
+        assert!(deadline.remaining() > Duration::ZERO);
+
+        let event = Event::new_manual(false).unwrap();
+        let index = wait_any(&[&event, &deadline], Some(Duration::from_secs(5))).unwrap();
+        assert_eq!(index, Some(1));
+
+        assert_eq!(
+            Waitable::wait(&deadline, Some(Duration::from_secs(5))).unwrap(),
+            WaitResult::Signaled
+        );
+        assert_eq!(deadline.remaining(), Duration::ZERO);
+
+        // Stays signaled after firing
+        assert_eq!(
+            Waitable::wait(&deadline, Some(Duration::ZERO)).unwrap(),
+            WaitResult::Signaled
+        );
+    }
+
+    #[test]
+    fn test_scheduler_runs_and_cancels() {
+        use std::sync::atomic::AtomicU32;
+
+        let scheduler = Scheduler::new().unwrap();
+        let count = Arc::new(AtomicU32::new(0));
+        let task_count = Arc::clone(&count);
+        let task = scheduler.every(Duration::from_millis(20), move || {
+            task_count.fetch_add(1, Ordering::SeqCst);
+        });
+
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(count.load(Ordering::SeqCst) >= 3);
+
+        task.cancel();
+        assert!(task.is_cancelled());
+        // Let an invocation that was already starting finish
+        std::thread::sleep(Duration::from_millis(10));
+        let after_cancel = count.load(Ordering::SeqCst);
+        std::thread::sleep(Duration::from_millis(100));
+        assert_eq!(count.load(Ordering::SeqCst), after_cancel);
+
+        scheduler.shutdown().unwrap();
+    }
+
+    #[test]
+    fn test_scheduler_survives_panicking_task() {
+        use std::sync::atomic::AtomicU32;
+
+        let scheduler = Scheduler::new().unwrap();
+        let count = Arc::new(AtomicU32::new(0));
+        let task_count = Arc::clone(&count);
+        scheduler.every(Duration::from_millis(10), || panic!("bad task"));
+        scheduler.every(Duration::from_millis(10), move || {
+            task_count.fetch_add(1, Ordering::SeqCst);
+        });
+
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(count.load(Ordering::SeqCst) >= 2);
+        drop(scheduler);
+    }
 }
@@ -3,18 +3,68 @@
 //! Provides ergonomic wrappers for creating windows and handling Windows messages.
 
 use crate::error::Result;
-use crate::string::WideString;
+use crate::string::{from_wide, from_wide_ptr, WideString};
 use std::cell::RefCell;
-use windows::Win32::Foundation::{HWND, LPARAM, LRESULT, WPARAM};
-use windows::Win32::Graphics::Gdi::{GetStockObject, HBRUSH, WHITE_BRUSH};
+use std::path::{Path, PathBuf};
+use windows::core::{GUID, PCWSTR};
+use windows::Win32::Foundation::{
+    BOOL, E_INVALIDARG, HANDLE, HWND, LPARAM, LRESULT, POINT, RECT, WPARAM,
+};
+use windows::Win32::Graphics::Dwm::{
+    DwmExtendFrameIntoClientArea, DwmFlush, DWMSBT_AUTO, DWMSBT_MAINWINDOW, DWMSBT_NONE,
+    DWMSBT_TABBEDWINDOW, DWMSBT_TRANSIENTWINDOW, DWMWA_SYSTEMBACKDROP_TYPE,
+    DWMWA_USE_IMMERSIVE_DARK_MODE, DWMWA_WINDOW_CORNER_PREFERENCE, DWMWCP_DEFAULT,
+    DWMWCP_DONOTROUND, DWMWCP_ROUND, DWMWCP_ROUNDSMALL, DWMWINDOWATTRIBUTE,
+    DWM_SYSTEMBACKDROP_TYPE, DWM_WINDOW_CORNER_PREFERENCE,
+};
+use windows::Win32::Graphics::Gdi::{
+    BeginPaint, BitBlt, CreateCompatibleDC, CreateDIBSection, DeleteDC, DeleteObject, EndPaint,
+    GdiFlush, GetDC, GetMonitorInfoW, GetStockObject, GetWindowDC, InvalidateRect,
+    MonitorFromWindow, ReleaseDC, SelectObject, BITMAPINFO, BITMAPINFOHEADER, BI_RGB, CAPTUREBLT,
+    DIB_RGB_COLORS, HBITMAP, HBRUSH, HDC, HGDIOBJ, MONITORINFO, MONITOR_DEFAULTTONEAREST,
+    PAINTSTRUCT, SRCCOPY, WHITE_BRUSH,
+};
+use windows::Win32::Storage::Xps::{PrintWindow, PRINT_WINDOW_FLAGS};
 use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::System::Power::{
+    RegisterPowerSettingNotification, UnregisterPowerSettingNotification, HPOWERNOTIFY,
+    POWERBROADCAST_SETTING,
+};
+use windows::Win32::System::RemoteDesktop::{
+    WTSRegisterSessionNotification, WTSUnRegisterSessionNotification, NOTIFY_FOR_THIS_SESSION,
+};
+use windows::Win32::UI::Controls::MARGINS;
+use windows::Win32::UI::Input::KeyboardAndMouse::{EnableWindow, SetActiveWindow};
+use windows::Win32::UI::Shell::{
+    DragAcceptFiles, DragFinish, DragQueryFileW, DragQueryPoint, HDROP,
+};
 use windows::Win32::UI::WindowsAndMessaging::{
-    CreateWindowExW, DefWindowProcW, DestroyWindow, DispatchMessageW, GetMessageW,
-    GetWindowLongPtrW, LoadCursorW, PostQuitMessage, RegisterClassExW, SetWindowLongPtrW,
-    ShowWindow, TranslateMessage, UnregisterClassW, CS_HREDRAW, CS_VREDRAW, CW_USEDEFAULT,
-    GWLP_USERDATA, IDC_ARROW, MSG, SW_HIDE, SW_SHOW, SW_SHOWDEFAULT, WINDOW_EX_STYLE, WINDOW_STYLE,
-    WM_CLOSE, WM_CREATE, WM_DESTROY, WM_NCCREATE, WNDCLASSEXW, WS_CAPTION, WS_OVERLAPPEDWINDOW,
-    WS_SYSMENU, WS_VISIBLE,
+    CreateWindowExW, DefWindowProcW, DestroyWindow, DispatchMessageW, EnumWindows, FindWindowW,
+    GetClassNameW, GetMessageW, GetPropW, GetSystemMetrics, GetWindowLongPtrW, GetWindowPlacement,
+    GetWindowRect, GetWindowTextLengthW, GetWindowTextW, GetWindowThreadProcessId, IsIconic,
+    IsWindow, IsWindowVisible, IsZoomed, LoadCursorW, PostMessageW, PostQuitMessage,
+    RegisterClassExW, RegisterDeviceNotificationW, RegisterWindowMessageW, RemovePropW, SetPropW,
+    SetWindowLongPtrW, SetWindowPlacement, SetWindowPos, ShowWindow, TranslateMessage,
+    UnregisterClassW, UnregisterDeviceNotification, CS_HREDRAW, CS_VREDRAW, CW_USEDEFAULT,
+    DBT_DEVICEARRIVAL, DBT_DEVICEQUERYREMOVE, DBT_DEVICEQUERYREMOVEFAILED,
+    DBT_DEVICEREMOVECOMPLETE, DBT_DEVICEREMOVEPENDING, DBT_DEVNODES_CHANGED,
+    DBT_DEVTYP_DEVICEINTERFACE, DBT_DEVTYP_PORT, DBT_DEVTYP_VOLUME,
+    DEVICE_NOTIFY_ALL_INTERFACE_CLASSES, DEVICE_NOTIFY_WINDOW_HANDLE,
+    DEV_BROADCAST_DEVICEINTERFACE_W, DEV_BROADCAST_HDR, DEV_BROADCAST_PORT_W, DEV_BROADCAST_VOLUME,
+    GWLP_USERDATA, GWL_STYLE, HDEVNOTIFY, HTBOTTOM, HTBOTTOMLEFT, HTBOTTOMRIGHT, HTCAPTION,
+    HTCLIENT, HTCLOSE, HTLEFT, HTMAXBUTTON, HTMINBUTTON, HTNOWHERE, HTRIGHT, HTSYSMENU, HTTOP,
+    HTTOPLEFT, HTTOPRIGHT, HTTRANSPARENT, HWND_MESSAGE, IDC_ARROW, MSG, NCCALCSIZE_PARAMS,
+    PBT_APMPOWERSTATUSCHANGE, PBT_APMRESUMEAUTOMATIC, PBT_APMRESUMESUSPEND, PBT_APMSUSPEND,
+    PBT_POWERSETTINGCHANGE, PW_RENDERFULLCONTENT, SM_CXVIRTUALSCREEN, SM_CYVIRTUALSCREEN,
+    SM_XVIRTUALSCREEN, SM_YVIRTUALSCREEN, SWP_FRAMECHANGED, SWP_NOACTIVATE, SWP_NOMOVE, SWP_NOSIZE,
+    SWP_NOZORDER, SW_HIDE, SW_MAXIMIZE, SW_MINIMIZE, SW_RESTORE, SW_SHOW, SW_SHOWDEFAULT,
+    SW_SHOWMAXIMIZED, SW_SHOWMINIMIZED, WINDOWPLACEMENT, WINDOWPLACEMENT_FLAGS, WINDOW_EX_STYLE,
+    WINDOW_STYLE, WM_CLOSE, WM_CREATE, WM_DESTROY, WM_DEVICECHANGE, WM_DROPFILES, WM_NCCALCSIZE,
+    WM_NCCREATE, WM_NCHITTEST, WM_PAINT, WM_POWERBROADCAST, WM_SIZE, WM_WTSSESSION_CHANGE,
+    WNDCLASSEXW, WS_CAPTION, WS_MAXIMIZEBOX, WS_MINIMIZEBOX, WS_OVERLAPPEDWINDOW, WS_POPUP,
+    WS_SYSMENU, WS_THICKFRAME, WS_VISIBLE, WTS_CONSOLE_CONNECT, WTS_CONSOLE_DISCONNECT,
+    WTS_REMOTE_CONNECT, WTS_REMOTE_DISCONNECT, WTS_SESSION_LOCK, WTS_SESSION_LOGOFF,
+    WTS_SESSION_LOGON, WTS_SESSION_REMOTE_CONTROL, WTS_SESSION_UNLOCK,
 };
 
 /// Window styles for creating windows.
@@ -67,6 +117,311 @@ impl ShowCommand {
 
     /// Hides the window.
     pub const HIDE: Self = Self(SW_HIDE);
+
+    /// Minimizes the window.
+    pub const MINIMIZE: Self = Self(SW_MINIMIZE);
+
+    /// Maximizes the window.
+    pub const MAXIMIZE: Self = Self(SW_MAXIMIZE);
+
+    /// Restores a minimized or maximized window to its normal size and position.
+    pub const RESTORE: Self = Self(SW_RESTORE);
+}
+
+/// The show state and geometry of a window, for saving and restoring window layout.
+///
+/// All fields are plain numbers so they can be persisted directly. Coordinates are
+/// workspace coordinates, as used by `GetWindowPlacement`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WindowPlacement {
+    /// The show state (`SW_SHOWNORMAL` = 1, `SW_SHOWMINIMIZED` = 2, `SW_SHOWMAXIMIZED` = 3).
+    pub show_cmd: u32,
+    /// `WPF_*` flags.
+    pub flags: u32,
+    /// X position of the minimized window.
+    pub min_x: i32,
+    /// Y position of the minimized window.
+    pub min_y: i32,
+    /// X position of the maximized window.
+    pub max_x: i32,
+    /// Y position of the maximized window.
+    pub max_y: i32,
+    /// Left edge of the restored window.
+    pub normal_left: i32,
+    /// Top edge of the restored window.
+    pub normal_top: i32,
+    /// Right edge of the restored window.
+    pub normal_right: i32,
+    /// Bottom edge of the restored window.
+    pub normal_bottom: i32,
+}
+
+impl WindowPlacement {
+    /// Returns true if the placement is minimized.
+    pub fn is_minimized(&self) -> bool {
+        self.show_cmd == SW_SHOWMINIMIZED.0 as u32
+    }
+
+    /// Returns true if the placement is maximized.
+    pub fn is_maximized(&self) -> bool {
+        self.show_cmd == SW_SHOWMAXIMIZED.0 as u32
+    }
+
+    fn from_native(wp: &WINDOWPLACEMENT) -> Self {
+        Self {
+            show_cmd: wp.showCmd,
+            flags: wp.flags.0,
+            min_x: wp.ptMinPosition.x,
+            min_y: wp.ptMinPosition.y,
+            max_x: wp.ptMaxPosition.x,
+            max_y: wp.ptMaxPosition.y,
+            normal_left: wp.rcNormalPosition.left,
+            normal_top: wp.rcNormalPosition.top,
+            normal_right: wp.rcNormalPosition.right,
+            normal_bottom: wp.rcNormalPosition.bottom,
+        }
+    }
+
+    fn to_native(self) -> WINDOWPLACEMENT {
+        WINDOWPLACEMENT {
+            length: std::mem::size_of::<WINDOWPLACEMENT>() as u32,
+            flags: WINDOWPLACEMENT_FLAGS(self.flags),
+            showCmd: self.show_cmd,
+            ptMinPosition: POINT {
+                x: self.min_x,
+                y: self.min_y,
+            },
+            ptMaxPosition: POINT {
+                x: self.max_x,
+                y: self.max_y,
+            },
+            rcNormalPosition: RECT {
+                left: self.normal_left,
+                top: self.normal_top,
+                right: self.normal_right,
+                bottom: self.normal_bottom,
+            },
+        }
+    }
+}
+
+/// The material DWM draws behind a window's title bar and transparent areas.
+///
+/// Requires Windows 11 22H2 (build 22621); older systems ignore the setting.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum BackdropType {
+    /// Let DWM decide, which is usually no material.
+    #[default]
+    Auto,
+    /// No system backdrop.
+    None,
+    /// Mica, the wallpaper-tinted material for long-lived main windows.
+    Mica,
+    /// Acrylic, the blurred translucent material for transient windows.
+    Acrylic,
+    /// Mica Alt, the tinted material for windows with a tabbed title bar.
+    Tabbed,
+}
+
+impl BackdropType {
+    fn to_native(self) -> DWM_SYSTEMBACKDROP_TYPE {
+        match self {
+            BackdropType::Auto => DWMSBT_AUTO,
+            BackdropType::None => DWMSBT_NONE,
+            BackdropType::Mica => DWMSBT_MAINWINDOW,
+            BackdropType::Acrylic => DWMSBT_TRANSIENTWINDOW,
+            BackdropType::Tabbed => DWMSBT_TABBEDWINDOW,
+        }
+    }
+}
+
+/// How DWM rounds the corners of a top-level window.
+///
+/// Requires Windows 11 (build 22000); older systems never round corners.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum CornerPreference {
+    /// Let the system decide.
+    #[default]
+    Default,
+    /// Round the corners.
+    Round,
+    /// Round the corners with a smaller radius.
+    RoundSmall,
+    /// Never round the corners.
+    DoNotRound,
+}
+
+impl CornerPreference {
+    fn to_native(self) -> DWM_WINDOW_CORNER_PREFERENCE {
+        match self {
+            CornerPreference::Default => DWMWCP_DEFAULT,
+            CornerPreference::Round => DWMWCP_ROUND,
+            CornerPreference::RoundSmall => DWMWCP_ROUNDSMALL,
+            CornerPreference::DoNotRound => DWMWCP_DONOTROUND,
+        }
+    }
+}
+
+/// The pre-release attribute number for dark mode on Windows 10 1809 to 1909.
+const DWMWA_USE_IMMERSIVE_DARK_MODE_BEFORE_20H1: DWMWINDOWATTRIBUTE = DWMWINDOWATTRIBUTE(19);
+
+/// Signature of `DwmSetWindowAttribute`, resolved at runtime so a missing
+/// dwmapi.dll (as on some Server Core installs) isn't a load-time failure.
+type DwmSetWindowAttributeFn = unsafe extern "system" fn(
+    HWND,
+    DWMWINDOWATTRIBUTE,
+    *const std::ffi::c_void,
+    u32,
+) -> windows::core::HRESULT;
+
+/// Sets a DWM window attribute, returning false if this Windows version does not know it.
+fn set_dwm_attribute<T>(hwnd: HWND, attribute: DWMWINDOWATTRIBUTE, value: &T) -> Result<bool> {
+    let Ok(set_attribute) = crate::resolve!(
+        "dwmapi.dll",
+        "DwmSetWindowAttribute",
+        DwmSetWindowAttributeFn
+    ) else {
+        return Ok(false);
+    };
+    // SAFETY: value points to size_of::<T>() readable bytes for the duration of the call
+    let result = unsafe {
+        set_attribute(
+            hwnd,
+            attribute,
+            value as *const T as *const _,
+            std::mem::size_of::<T>() as u32,
+        )
+    }
+    .ok();
+    match result {
+        Ok(()) => Ok(true),
+        // Unknown attributes are rejected as invalid arguments
+        Err(e) if e.code() == E_INVALIDARG => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Where a point lies in a window, as reported to `WM_NCHITTEST`.
+///
+/// Returning one of these from [`MessageHandler::on_nc_hit_test`] tells Windows
+/// how to treat the mouse there: `Caption` makes a region drag the window, and
+/// the border and corner values make it a resize handle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HitTestResult {
+    /// Not over any part of the window.
+    Nowhere,
+    /// The client area.
+    Client,
+    /// The title bar; dragging moves the window and double-clicking maximizes it.
+    Caption,
+    /// The window menu icon.
+    SysMenu,
+    /// The minimize button.
+    MinButton,
+    /// The maximize button.
+    MaxButton,
+    /// The close button.
+    Close,
+    /// The left resize border.
+    Left,
+    /// The right resize border.
+    Right,
+    /// The top resize border.
+    Top,
+    /// The bottom resize border.
+    Bottom,
+    /// The top-left resize corner.
+    TopLeft,
+    /// The top-right resize corner.
+    TopRight,
+    /// The bottom-left resize corner.
+    BottomLeft,
+    /// The bottom-right resize corner.
+    BottomRight,
+    /// Covered by another window in the same thread; the point passes through.
+    Transparent,
+}
+
+impl HitTestResult {
+    /// Returns the `HT*` code for this result.
+    pub fn to_raw(self) -> i32 {
+        let code = match self {
+            Self::Nowhere => HTNOWHERE,
+            Self::Client => HTCLIENT,
+            Self::Caption => HTCAPTION,
+            Self::SysMenu => HTSYSMENU,
+            Self::MinButton => HTMINBUTTON,
+            Self::MaxButton => HTMAXBUTTON,
+            Self::Close => HTCLOSE,
+            Self::Left => HTLEFT,
+            Self::Right => HTRIGHT,
+            Self::Top => HTTOP,
+            Self::Bottom => HTBOTTOM,
+            Self::TopLeft => HTTOPLEFT,
+            Self::TopRight => HTTOPRIGHT,
+            Self::BottomLeft => HTBOTTOMLEFT,
+            Self::BottomRight => HTBOTTOMRIGHT,
+            Self::Transparent => return HTTRANSPARENT,
+        };
+        code as i32
+    }
+}
+
+/// Window property that marks a window as borderless for `WM_NCCALCSIZE`.
+const BORDERLESS_PROP: windows::core::PCWSTR = windows::core::w!("ErgonomicWindows.Borderless");
+
+/// Window property that marks a window whose destruction shouldn't post
+/// `WM_QUIT` for the whole thread, such as modal and message-only windows.
+const NO_QUIT_PROP: windows::core::PCWSTR = windows::core::w!("ErgonomicWindows.NoQuit");
+
+/// An in-progress paint of a window, started with `BeginPaint`.
+///
+/// `EndPaint` is called when the context is dropped, which validates the
+/// update region. Only create one while handling `WM_PAINT`.
+pub struct PaintContext {
+    hwnd: HWND,
+    ps: PAINTSTRUCT,
+}
+
+impl PaintContext {
+    /// Begins painting the given window.
+    pub fn begin(hwnd: HWND) -> Result<Self> {
+        let mut ps = PAINTSTRUCT::default();
+        // SAFETY: ps is a valid PAINTSTRUCT for BeginPaint to fill
+        let hdc = unsafe { BeginPaint(hwnd, &mut ps) };
+        if hdc.is_invalid() {
+            return Err(crate::error::last_error());
+        }
+        Ok(Self { hwnd, ps })
+    }
+
+    /// Returns the device context to draw with, clipped to the update region.
+    #[inline]
+    pub fn hdc(&self) -> HDC {
+        self.ps.hdc
+    }
+
+    /// Returns the rectangle that needs repainting, in client coordinates.
+    #[inline]
+    pub fn rect(&self) -> RECT {
+        self.ps.rcPaint
+    }
+
+    /// Returns `true` if the background has not been erased and the handler
+    /// should erase it.
+    #[inline]
+    pub fn erase_background(&self) -> bool {
+        self.ps.fErase.as_bool()
+    }
+}
+
+impl Drop for PaintContext {
+    fn drop(&mut self) {
+        // SAFETY: ps was filled in by BeginPaint for this window
+        unsafe {
+            let _ = EndPaint(self.hwnd, &self.ps);
+        }
+    }
 }
 
 /// A Windows message.
@@ -89,6 +444,14 @@ impl Message {
     pub const DESTROY: u32 = WM_DESTROY;
     /// WM_CLOSE message.
     pub const CLOSE: u32 = WM_CLOSE;
+    /// WM_DROPFILES message.
+    pub const DROP_FILES: u32 = WM_DROPFILES;
+    /// WM_PAINT message.
+    pub const PAINT: u32 = WM_PAINT;
+    /// WM_NCHITTEST message.
+    pub const NC_HIT_TEST: u32 = WM_NCHITTEST;
+    /// WM_SIZE message.
+    pub const SIZE: u32 = WM_SIZE;
 }
 
 /// Trait for handling window messages.
@@ -114,6 +477,56 @@ pub trait MessageHandler {
         }
         true
     }
+
+    /// Called when files are dropped onto the window.
+    ///
+    /// Only delivered after [`Window::accept_files`] has been enabled. `point` is the
+    /// drop position in client coordinates.
+    fn on_drop_files(&mut self, _files: Vec<PathBuf>, _point: (i32, i32)) {}
+
+    /// Called when part of the window needs repainting.
+    ///
+    /// `WM_PAINT` is offered to [`handle_message`](Self::handle_message) first;
+    /// this is only called if that returns `None`. The paint is begun before
+    /// this is called and ended afterwards, so the update region is validated
+    /// even if the handler draws nothing.
+    fn on_paint(&mut self, _hwnd: HWND, _ctx: &PaintContext) {}
+
+    /// Called after the window's client area has been resized.
+    ///
+    /// The message is still passed to [`handle_message`](Self::handle_message)
+    /// afterwards.
+    fn on_size(&mut self, _hwnd: HWND, _width: u32, _height: u32) {}
+
+    /// Called to find out what part of the window is at `point`, in screen coordinates.
+    ///
+    /// Return `None` to pass the message on to
+    /// [`handle_message`](Self::handle_message), and from there to the default
+    /// hit-testing. A borderless window (see
+    /// [`Window::set_borderless`]) has no frame of its own, so it must report
+    /// `Caption` for its drag region and the border values for resize handles.
+    fn on_nc_hit_test(&mut self, _point: (i32, i32)) -> Option<HitTestResult> {
+        None
+    }
+
+    /// Called for `WM_DEVICECHANGE`, e.g. after [`register_device_notification`].
+    ///
+    /// The message is still passed to [`handle_message`](Self::handle_message)
+    /// afterwards; return `BROADCAST_QUERY_DENY` from there to veto a
+    /// [`DeviceChange::QueryRemove`].
+    fn on_device_change(&mut self, _hwnd: HWND, _change: DeviceChange) {}
+
+    /// Called for `WM_POWERBROADCAST`, e.g. after [`register_power_notification`].
+    ///
+    /// The message is still passed to [`handle_message`](Self::handle_message)
+    /// afterwards.
+    fn on_power_broadcast(&mut self, _hwnd: HWND, _event: PowerEvent) {}
+
+    /// Called for `WM_WTSSESSION_CHANGE` after [`register_session_notification`].
+    ///
+    /// `session_id` is the session the event is about. The message is still
+    /// passed to [`handle_message`](Self::handle_message) afterwards.
+    fn on_session_change(&mut self, _hwnd: HWND, _change: SessionChange, _session_id: u32) {}
 }
 
 /// A default message handler that does nothing.
@@ -135,6 +548,7 @@ pub struct WindowBuilder {
     y: i32,
     width: i32,
     height: i32,
+    owner: Option<HWND>,
 }
 
 impl Default for WindowBuilder {
@@ -155,6 +569,7 @@ impl WindowBuilder {
             y: CW_USEDEFAULT,
             width: CW_USEDEFAULT,
             height: CW_USEDEFAULT,
+            owner: None,
         }
     }
 
@@ -196,6 +611,28 @@ impl WindowBuilder {
         self
     }
 
+    /// Sets the owner window.
+    ///
+    /// An owned window always stays above its owner, is hidden when the owner
+    /// is minimized, and is destroyed along with it.
+    pub fn owner(mut self, owner: HWND) -> Self {
+        self.owner = Some(owner);
+        self
+    }
+
+    /// Makes this a message-only window, a child of `HWND_MESSAGE`.
+    ///
+    /// A message-only window has a window procedure and receives sent and
+    /// posted messages, but is never visible, can't be shown or have child
+    /// windows, and doesn't receive broadcast messages. It suits headless
+    /// components that need a message target, e.g. for clipboard format
+    /// listener notifications or cross-thread messages. Destroying it doesn't
+    /// post `WM_QUIT`.
+    pub fn message_only(mut self) -> Self {
+        self.owner = Some(HWND_MESSAGE);
+        self
+    }
+
     /// Creates the window.
     ///
     /// # Errors
@@ -252,13 +689,20 @@ impl WindowBuilder {
                 self.y,
                 self.width,
                 self.height,
-                None,
+                self.owner.unwrap_or_default(),
                 None,
                 hinstance,
                 Some(handler_ptr as *const _),
             )?
         };
 
+        if self.owner == Some(HWND_MESSAGE) {
+            // SAFETY: hwnd was just created on this thread
+            unsafe {
+                SetPropW(hwnd, NO_QUIT_PROP, HANDLE(1 as *mut _))?;
+            }
+        }
+
         Ok(Window {
             hwnd,
             class_name: class_name_wide,
@@ -292,6 +736,155 @@ impl<H: MessageHandler> Window<H> {
         }
     }
 
+    /// Minimizes the window.
+    pub fn minimize(&self) {
+        self.show(ShowCommand::MINIMIZE);
+    }
+
+    /// Maximizes the window.
+    pub fn maximize(&self) {
+        self.show(ShowCommand::MAXIMIZE);
+    }
+
+    /// Restores the window from a minimized or maximized state.
+    pub fn restore(&self) {
+        self.show(ShowCommand::RESTORE);
+    }
+
+    /// Checks if the window is minimized.
+    pub fn is_minimized(&self) -> bool {
+        // SAFETY: IsIconic is safe with a valid window handle
+        unsafe { IsIconic(self.hwnd).as_bool() }
+    }
+
+    /// Checks if the window is maximized.
+    pub fn is_maximized(&self) -> bool {
+        // SAFETY: IsZoomed is safe with a valid window handle
+        unsafe { IsZoomed(self.hwnd).as_bool() }
+    }
+
+    /// Gets the window's show state and restored, minimized, and maximized positions.
+    ///
+    /// The restored rectangle is reported even while the window is minimized or
+    /// maximized, which makes this the right way to persist window geometry.
+    pub fn placement(&self) -> Result<WindowPlacement> {
+        let mut wp = WINDOWPLACEMENT {
+            length: std::mem::size_of::<WINDOWPLACEMENT>() as u32,
+            ..Default::default()
+        };
+        // SAFETY: wp is a valid WINDOWPLACEMENT with length set
+        unsafe {
+            GetWindowPlacement(self.hwnd, &mut wp)?;
+        }
+        Ok(WindowPlacement::from_native(&wp))
+    }
+
+    /// Applies a previously saved window placement.
+    pub fn set_placement(&self, placement: &WindowPlacement) -> Result<()> {
+        let wp = placement.to_native();
+        // SAFETY: wp is a valid WINDOWPLACEMENT with length set
+        unsafe {
+            SetWindowPlacement(self.hwnd, &wp)?;
+        }
+        Ok(())
+    }
+
+    /// Marks the whole client area as needing repainting.
+    ///
+    /// A `WM_PAINT` is delivered the next time the message queue is empty. If
+    /// `erase` is `true`, the background is erased before painting.
+    pub fn invalidate(&self, erase: bool) {
+        // SAFETY: self.hwnd is a valid window handle; None invalidates the whole client area
+        unsafe {
+            let _ = InvalidateRect(self.hwnd, None, erase);
+        }
+    }
+
+    /// Switches the title bar and window frame between dark and light.
+    ///
+    /// Supported from Windows 10 1809; on older systems this does nothing and
+    /// returns `Ok`. The client area is unaffected and is up to the handler.
+    pub fn set_dark_mode(&self, dark: bool) -> Result<()> {
+        let value = BOOL::from(dark);
+        if !set_dwm_attribute(self.hwnd, DWMWA_USE_IMMERSIVE_DARK_MODE, &value)? {
+            set_dwm_attribute(self.hwnd, DWMWA_USE_IMMERSIVE_DARK_MODE_BEFORE_20H1, &value)?;
+        }
+        Ok(())
+    }
+
+    /// Sets the system backdrop material, such as Mica or Acrylic.
+    ///
+    /// Requires Windows 11 22H2 (build 22621); on older systems this does
+    /// nothing and returns `Ok`. The material only shows through where nothing
+    /// is painted, so the handler must leave those areas transparent, typically
+    /// after extending the frame into the client area.
+    pub fn set_backdrop(&self, backdrop: BackdropType) -> Result<()> {
+        set_dwm_attribute(self.hwnd, DWMWA_SYSTEMBACKDROP_TYPE, &backdrop.to_native())?;
+        Ok(())
+    }
+
+    /// Sets how the window's corners are rounded.
+    ///
+    /// Requires Windows 11 (build 22000); on older systems this does nothing
+    /// and returns `Ok`.
+    pub fn set_corner_preference(&self, preference: CornerPreference) -> Result<()> {
+        set_dwm_attribute(
+            self.hwnd,
+            DWMWA_WINDOW_CORNER_PREFERENCE,
+            &preference.to_native(),
+        )?;
+        Ok(())
+    }
+
+    /// Removes the standard title bar and borders, keeping the drop shadow.
+    ///
+    /// The whole window becomes client area, so dragging and resizing only work
+    /// where [`MessageHandler::on_nc_hit_test`] reports `Caption` or a border.
+    /// The window keeps its resizable frame style so that snapping, the
+    /// minimize/maximize animations and the DWM shadow still work.
+    pub fn set_borderless(&self) -> Result<()> {
+        let style =
+            WS_POPUP | WS_THICKFRAME | WS_CAPTION | WS_SYSMENU | WS_MINIMIZEBOX | WS_MAXIMIZEBOX;
+        // A one-pixel margin is enough for DWM to draw the shadow
+        let margins = MARGINS {
+            cxLeftWidth: 1,
+            cxRightWidth: 1,
+            cyTopHeight: 1,
+            cyBottomHeight: 1,
+        };
+
+        // SAFETY: self.hwnd is a valid window handle; margins outlives the call
+        unsafe {
+            SetPropW(self.hwnd, BORDERLESS_PROP, HANDLE(1 as *mut _))?;
+            let visible = GetWindowLongPtrW(self.hwnd, GWL_STYLE) as u32 & WS_VISIBLE.0;
+            SetWindowLongPtrW(self.hwnd, GWL_STYLE, (style.0 | visible) as isize);
+            DwmExtendFrameIntoClientArea(self.hwnd, &margins)?;
+            // Make the frame change take effect so WM_NCCALCSIZE is sent again
+            SetWindowPos(
+                self.hwnd,
+                None,
+                0,
+                0,
+                0,
+                0,
+                SWP_FRAMECHANGED | SWP_NOMOVE | SWP_NOSIZE | SWP_NOZORDER | SWP_NOACTIVATE,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Enables or disables accepting files dropped from Explorer.
+    ///
+    /// Dropped files are delivered to [`MessageHandler::on_drop_files`]. This covers
+    /// simple `WM_DROPFILES` drops; OLE drag-and-drop (`IDropTarget`) with drag
+    /// images and custom data formats is not supported.
+    pub fn accept_files(&self, enable: bool) {
+        // SAFETY: self.hwnd is a valid window handle
+        unsafe {
+            DragAcceptFiles(self.hwnd, enable);
+        }
+    }
+
     /// Gets a mutable reference to the message handler.
     ///
     /// # Panics
@@ -395,8 +988,14 @@ unsafe extern "system" fn window_proc<H: MessageHandler>(
             }
         }
         WM_DESTROY => {
+            let no_quit = !RemovePropW(hwnd, NO_QUIT_PROP)
+                .unwrap_or_default()
+                .is_invalid();
+            let _ = RemovePropW(hwnd, BORDERLESS_PROP);
             handler.borrow_mut().on_destroy();
-            PostQuitMessage(0);
+            if !no_quit {
+                PostQuitMessage(0);
+            }
             LRESULT(0)
         }
         WM_CLOSE => {
@@ -404,6 +1003,93 @@ unsafe extern "system" fn window_proc<H: MessageHandler>(
             let _ = handler.on_close(hwnd);
             LRESULT(0)
         }
+        WM_DROPFILES => {
+            // SAFETY: For WM_DROPFILES, wparam is the HDROP, which we must release
+            let hdrop = HDROP(wparam.0 as *mut _);
+            let files = dropped_files(hdrop);
+            let point = drop_point(hdrop);
+            DragFinish(hdrop);
+
+            handler.borrow_mut().on_drop_files(files, point);
+            LRESULT(0)
+        }
+        WM_SIZE => {
+            let width = (lparam.0 & 0xFFFF) as u32;
+            let height = ((lparam.0 >> 16) & 0xFFFF) as u32;
+            let mut handler = handler.borrow_mut();
+            handler.on_size(hwnd, width, height);
+            if let Some(result) = handler.handle_message(message) {
+                result
+            } else {
+                DefWindowProcW(hwnd, msg, wparam, lparam)
+            }
+        }
+        WM_DEVICECHANGE | WM_POWERBROADCAST | WM_WTSSESSION_CHANGE => {
+            let mut handler = handler.borrow_mut();
+            match msg {
+                // SAFETY: The parameters come from a real message of this kind
+                WM_DEVICECHANGE => {
+                    handler.on_device_change(hwnd, DeviceChange::from_message(wparam, lparam))
+                }
+                WM_POWERBROADCAST => {
+                    handler.on_power_broadcast(hwnd, PowerEvent::from_message(wparam, lparam))
+                }
+                _ => handler.on_session_change(
+                    hwnd,
+                    SessionChange::from_raw(wparam.0 as u32),
+                    lparam.0 as u32,
+                ),
+            }
+            if let Some(result) = handler.handle_message(message) {
+                result
+            } else {
+                DefWindowProcW(hwnd, msg, wparam, lparam)
+            }
+        }
+        WM_NCHITTEST => {
+            // Screen coordinates are signed on multi-monitor setups
+            let x = (lparam.0 & 0xFFFF) as i16 as i32;
+            let y = ((lparam.0 >> 16) & 0xFFFF) as i16 as i32;
+            let mut handler = handler.borrow_mut();
+            if let Some(hit) = handler.on_nc_hit_test((x, y)) {
+                LRESULT(hit.to_raw() as isize)
+            } else if let Some(result) = handler.handle_message(message) {
+                result
+            } else {
+                DefWindowProcW(hwnd, msg, wparam, lparam)
+            }
+        }
+        WM_NCCALCSIZE if wparam.0 != 0 && !GetPropW(hwnd, BORDERLESS_PROP).is_invalid() => {
+            // Leaving the proposed rectangle alone makes the whole window client area.
+            // A maximized window hangs over the screen edge by its frame size, so it
+            // is clamped to the monitor's work area instead.
+            if IsZoomed(hwnd).as_bool() {
+                let params = &mut *(lparam.0 as *mut NCCALCSIZE_PARAMS);
+                let mut info = MONITORINFO {
+                    cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+                    ..Default::default()
+                };
+                let monitor = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST);
+                if GetMonitorInfoW(monitor, &mut info).as_bool() {
+                    params.rgrc[0] = info.rcWork;
+                }
+            }
+            LRESULT(0)
+        }
+        WM_PAINT => {
+            let mut handler = handler.borrow_mut();
+            // Handlers that paint in handle_message keep working
+            if let Some(result) = handler.handle_message(message) {
+                return result;
+            }
+            match PaintContext::begin(hwnd) {
+                Ok(ctx) => {
+                    handler.on_paint(hwnd, &ctx);
+                    LRESULT(0)
+                }
+                Err(_) => DefWindowProcW(hwnd, msg, wparam, lparam),
+            }
+        }
         _ => {
             let mut handler = handler.borrow_mut();
             if let Some(result) = handler.handle_message(message) {
@@ -415,6 +1101,35 @@ unsafe extern "system" fn window_proc<H: MessageHandler>(
     }
 }
 
+/// Extracts the file paths from a drop handle.
+///
+/// Paths that are not valid UTF-16 are skipped.
+pub(crate) fn dropped_files(hdrop: HDROP) -> Vec<PathBuf> {
+    // SAFETY: Index 0xFFFFFFFF queries the number of files
+    let count = unsafe { DragQueryFileW(hdrop, u32::MAX, None) };
+
+    (0..count)
+        .filter_map(|i| {
+            // SAFETY: A null buffer queries the length (excluding the null terminator)
+            let len = unsafe { DragQueryFileW(hdrop, i, None) } as usize;
+            let mut buffer = vec![0u16; len + 1];
+            // SAFETY: buffer has room for the path and its null terminator
+            let copied = unsafe { DragQueryFileW(hdrop, i, Some(&mut buffer)) } as usize;
+            from_wide(&buffer[..copied]).ok().map(PathBuf::from)
+        })
+        .collect()
+}
+
+/// Gets the drop position, in client coordinates, from a drop handle.
+fn drop_point(hdrop: HDROP) -> (i32, i32) {
+    let mut point = POINT::default();
+    // SAFETY: point is a valid output parameter
+    unsafe {
+        let _ = DragQueryPoint(hdrop, &mut point);
+    }
+    (point.x, point.y)
+}
+
 /// Runs the message loop until WM_QUIT is received.
 ///
 /// This function blocks until the application receives a WM_QUIT message,
@@ -467,3 +1182,1430 @@ pub fn process_messages() -> bool {
 
     false
 }
+
+/// Blocks until the desktop compositor presents its next frame.
+///
+/// This uses `DwmFlush`, which waits for the next composition pass, so
+/// calling it once per frame paces rendering to the display's refresh rate.
+/// How closely it tracks vblank depends on the composition state: with
+/// several monitors it follows the primary one, and it fails while
+/// composition is unavailable (e.g. in some remote sessions).
+///
+/// # Errors
+///
+/// Returns an error if the DWM is not composing.
+pub fn wait_for_vblank() -> Result<()> {
+    // SAFETY: DwmFlush has no preconditions
+    unsafe { DwmFlush()? };
+    Ok(())
+}
+
+/// A render loop paced to the display refresh instead of a `WM_TIMER`.
+///
+/// Each iteration handles pending window messages with [`process_messages`],
+/// calls the frame callback with the time since the previous frame, then
+/// waits for the next vblank with [`wait_for_vblank`]. If the DWM isn't
+/// composing, frames fall back to a fixed ~60 Hz sleep.
+///
+/// # Example
+///
+/// ```no_run
+/// use ergonomic_windows::window::AnimationLoop;
+///
+/// let mut angle = 0.0f32;
+/// AnimationLoop::new().run(|delta| {
+///     angle += 90.0 * delta.as_secs_f32();
+///     // ... redraw ...
+///     angle < 360.0
+/// });
+/// ```
+#[derive(Debug, Default)]
+pub struct AnimationLoop {
+    frames: u64,
+}
+
+impl AnimationLoop {
+    /// Fallback frame time when vsync pacing is unavailable.
+    const FALLBACK_FRAME: std::time::Duration = std::time::Duration::from_micros(16_667);
+
+    /// Creates a new animation loop.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of frames rendered so far.
+    pub fn frame_count(&self) -> u64 {
+        self.frames
+    }
+
+    /// Runs frames until the callback returns `false` or `WM_QUIT` is received.
+    ///
+    /// The callback receives the elapsed time since the previous frame (zero
+    /// for the first). Returns `true` if the loop ended because of `WM_QUIT`.
+    pub fn run(&mut self, mut on_frame: impl FnMut(std::time::Duration) -> bool) -> bool {
+        let mut last = std::time::Instant::now();
+        let mut first = true;
+
+        loop {
+            if process_messages() {
+                return true;
+            }
+
+            let now = std::time::Instant::now();
+            let delta = if first {
+                std::time::Duration::ZERO
+            } else {
+                now - last
+            };
+            last = now;
+            first = false;
+
+            self.frames += 1;
+            if !on_frame(delta) {
+                return false;
+            }
+
+            if wait_for_vblank().is_err() {
+                std::thread::sleep(Self::FALLBACK_FRAME.saturating_sub(last.elapsed()));
+            }
+        }
+    }
+}
+
+/// How a window run with [`run_modal`] ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModalResult {
+    /// The handler called [`close_modal`] with this result code.
+    Closed(i32),
+    /// The window was destroyed some other way, or `WM_QUIT` was received.
+    Dismissed,
+}
+
+impl ModalResult {
+    /// Returns the result code passed to [`close_modal`], if any.
+    pub fn code(self) -> Option<i32> {
+        match self {
+            Self::Closed(code) => Some(code),
+            Self::Dismissed => None,
+        }
+    }
+}
+
+/// Returns the registered message used by [`close_modal`].
+fn close_modal_message() -> u32 {
+    // SAFETY: The string is a valid null-terminated wide string literal.
+    unsafe { RegisterWindowMessageW(windows::core::w!("ErgonomicWindows.CloseModal")) }
+}
+
+/// Ends the modal loop started by [`run_modal`] for `hwnd` with `result`.
+///
+/// This is usually called from a [`MessageHandler`] callback of the modal
+/// window, e.g. when an OK or Cancel button is clicked. The message is
+/// posted, so the loop ends once the current message has been handled.
+pub fn close_modal(hwnd: HWND, result: i32) -> Result<()> {
+    // SAFETY: PostMessageW only queues the message; an invalid hwnd is reported as an error.
+    unsafe {
+        PostMessageW(
+            hwnd,
+            close_modal_message(),
+            WPARAM(result as isize as usize),
+            LPARAM(0),
+        )?;
+    }
+    Ok(())
+}
+
+/// Disables an owner window and re-enables and activates it on drop.
+struct DisabledOwner(HWND);
+
+impl DisabledOwner {
+    fn new(owner: HWND) -> Self {
+        // SAFETY: EnableWindow is safe with any window handle.
+        unsafe {
+            let _ = EnableWindow(owner, false);
+        }
+        Self(owner)
+    }
+}
+
+impl Drop for DisabledOwner {
+    fn drop(&mut self) {
+        // SAFETY: EnableWindow and SetActiveWindow are safe with any window handle.
+        // The owner must be enabled before the modal window is destroyed, or
+        // Windows activates some other application's window.
+        unsafe {
+            let _ = EnableWindow(self.0, true);
+            let _ = SetActiveWindow(self.0);
+        }
+    }
+}
+
+/// Shows a window modally over `owner` and runs a nested message loop.
+///
+/// The window is created from `builder` with `owner` as its owner, and the
+/// owner is disabled until the loop ends. The loop ends when the handler
+/// calls [`close_modal`], when the window is destroyed (e.g. closed from the
+/// title bar), or when `WM_QUIT` is received, in which case the quit
+/// message is re-posted for the outer loop. The owner is then re-enabled
+/// and activated, and the modal window is destroyed.
+///
+/// If `builder` has no class name a unique one is generated, so the modal
+/// window doesn't collide with the owner's default class.
+///
+/// # Example
+///
+/// ```no_run
+/// use ergonomic_windows::window::{
+///     close_modal, run_modal, DefaultHandler, Message, MessageHandler, ModalResult,
+///     WindowBuilder,
+/// };
+/// use windows::Win32::Foundation::{HWND, LRESULT};
+///
+/// struct Confirm;
+///
+/// impl MessageHandler for Confirm {
+///     fn handle_message(&mut self, _msg: Message) -> Option<LRESULT> {
+///         None
+///     }
+///
+///     fn on_close(&mut self, hwnd: HWND) -> bool {
+///         let _ = close_modal(hwnd, 1);
+///         false
+///     }
+/// }
+///
+/// let main = WindowBuilder::new().title("Main").build(DefaultHandler)?;
+/// let builder = WindowBuilder::new().title("Confirm").size(300, 150);
+/// if run_modal(&main, builder, Confirm)? == ModalResult::Closed(1) {
+///     println!("confirmed");
+/// }
+/// # Ok::<(), ergonomic_windows::error::Error>(())
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if the window can't be created or `GetMessageW` fails.
+pub fn run_modal<O: MessageHandler, H: MessageHandler + 'static>(
+    owner: &Window<O>,
+    builder: WindowBuilder,
+    handler: H,
+) -> Result<ModalResult> {
+    static NEXT_CLASS: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
+
+    let close_msg = close_modal_message();
+    let mut builder = builder.owner(owner.hwnd());
+    if builder.class_name.is_empty() {
+        let n = NEXT_CLASS.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        builder.class_name = format!("ErgonomicModal_{}_{}", std::process::id(), n);
+    }
+
+    let modal = builder.build(handler)?;
+    // SAFETY: modal.hwnd is a valid window handle owned by this thread.
+    unsafe {
+        SetPropW(modal.hwnd, NO_QUIT_PROP, HANDLE(1 as *mut _))?;
+    }
+    // Declared after `modal` so the owner is restored before the modal window is destroyed.
+    let _owner = DisabledOwner::new(owner.hwnd());
+    modal.show(ShowCommand::SHOW);
+
+    let mut msg = MSG::default();
+    loop {
+        // SAFETY: IsWindow is safe with any window handle.
+        if !unsafe { IsWindow(modal.hwnd) }.as_bool() {
+            return Ok(ModalResult::Dismissed);
+        }
+
+        // SAFETY: msg is a valid stack-allocated MSG struct.
+        let ret = unsafe { GetMessageW(&mut msg, None, 0, 0) };
+        if ret.0 == -1 {
+            return Err(crate::error::last_error());
+        }
+        if ret.0 == 0 {
+            // SAFETY: PostQuitMessage is always safe to call.
+            unsafe { PostQuitMessage(msg.wParam.0 as i32) };
+            return Ok(ModalResult::Dismissed);
+        }
+        if msg.message == close_msg && msg.hwnd == modal.hwnd {
+            return Ok(ModalResult::Closed(msg.wParam.0 as i32));
+        }
+
+        // SAFETY: msg was filled in by GetMessageW.
+        unsafe {
+            let _ = TranslateMessage(&msg);
+            DispatchMessageW(&msg);
+        }
+    }
+}
+
+/// Information about a top-level window, from [`enumerate`].
+#[derive(Debug, Clone)]
+pub struct WindowInfo {
+    /// The window handle.
+    pub hwnd: HWND,
+    /// The window title, empty if it has none.
+    pub title: String,
+    /// The name of the window's class.
+    pub class_name: String,
+    /// The ID of the process that created the window.
+    pub pid: u32,
+    /// Whether the window has the `WS_VISIBLE` style.
+    pub visible: bool,
+}
+
+/// Lists the top-level windows on the current desktop, in Z order.
+///
+/// This includes hidden and message-less helper windows of every process;
+/// filter on [`WindowInfo::visible`] for what the user can see. Windows may be
+/// destroyed at any time, so a handle can be stale by the time it is used.
+pub fn enumerate() -> Vec<WindowInfo> {
+    let mut handles: Vec<HWND> = Vec::new();
+    // SAFETY: The callback only uses lparam as the Vec, which outlives the call.
+    // EnumWindows can fail when the callback stops early; what was collected
+    // so far is still returned.
+    unsafe {
+        let _ = EnumWindows(
+            Some(collect_window),
+            LPARAM(&mut handles as *mut Vec<HWND> as isize),
+        );
+    }
+
+    handles
+        .into_iter()
+        .map(|hwnd| {
+            let mut pid = 0u32;
+            // SAFETY: pid is a valid output parameter; a stale hwnd leaves it 0
+            unsafe { GetWindowThreadProcessId(hwnd, Some(&mut pid)) };
+            WindowInfo {
+                hwnd,
+                title: window_text(hwnd),
+                class_name: class_name(hwnd),
+                pid,
+                // SAFETY: IsWindowVisible accepts any handle
+                visible: unsafe { IsWindowVisible(hwnd) }.as_bool(),
+            }
+        })
+        .collect()
+}
+
+/// Finds a top-level window whose title contains `substring`.
+///
+/// The match is case-sensitive. If several windows match, the topmost in Z
+/// order is returned.
+pub fn find_by_title(substring: &str) -> Option<HWND> {
+    enumerate()
+        .into_iter()
+        .find(|info| info.title.contains(substring))
+        .map(|info| info.hwnd)
+}
+
+/// Finds a top-level window by its exact class name, such as `"Shell_TrayWnd"`.
+pub fn find_by_class(class: &str) -> Option<HWND> {
+    let class = WideString::new(class);
+    // SAFETY: class is a valid null-terminated string
+    unsafe { FindWindowW(class.as_pcwstr(), PCWSTR::null()) }.ok()
+}
+
+/// `EnumWindows` callback that appends each window to the `Vec<HWND>` in `lparam`.
+///
+/// Unwinding into `EnumWindows` would be undefined behavior, so a panic is
+/// caught and stops the enumeration instead.
+unsafe extern "system" fn collect_window(hwnd: HWND, lparam: LPARAM) -> BOOL {
+    // SAFETY: enumerate passes a pointer to a Vec<HWND> that outlives the enumeration
+    let handles = &mut *(lparam.0 as *mut Vec<HWND>);
+    let pushed = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| handles.push(hwnd)));
+    BOOL::from(pushed.is_ok())
+}
+
+/// Gets a window's title.
+fn window_text(hwnd: HWND) -> String {
+    // SAFETY: GetWindowTextLengthW accepts any handle and returns 0 on failure
+    let len = unsafe { GetWindowTextLengthW(hwnd) };
+    if len <= 0 {
+        return String::new();
+    }
+    let mut buffer = vec![0u16; len as usize + 1];
+    // SAFETY: buffer is a valid writable slice
+    let copied = unsafe { GetWindowTextW(hwnd, &mut buffer) };
+    String::from_utf16_lossy(&buffer[..copied.max(0) as usize])
+}
+
+/// Gets the name of a window's class.
+fn class_name(hwnd: HWND) -> String {
+    // Class names are at most 256 characters
+    let mut buffer = [0u16; 257];
+    // SAFETY: buffer is a valid writable slice
+    let copied = unsafe { GetClassNameW(hwnd, &mut buffer) };
+    String::from_utf16_lossy(&buffer[..copied.max(0) as usize])
+}
+
+/// Which devices [`register_device_notification`] reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceFilter {
+    /// Devices exposing one interface class, e.g. `GUID_DEVINTERFACE_USB_DEVICE`.
+    Interface(GUID),
+    /// Devices of every interface class.
+    AllInterfaces,
+}
+
+/// A registration from [`register_device_notification`]. Unregisters on drop.
+#[derive(Debug)]
+pub struct DeviceNotification(HDEVNOTIFY);
+
+impl Drop for DeviceNotification {
+    fn drop(&mut self) {
+        // SAFETY: The handle came from RegisterDeviceNotificationW
+        unsafe {
+            let _ = UnregisterDeviceNotification(self.0);
+        }
+    }
+}
+
+/// A registration from [`register_power_notification`]. Unregisters on drop.
+#[derive(Debug)]
+pub struct PowerNotification(HPOWERNOTIFY);
+
+impl Drop for PowerNotification {
+    fn drop(&mut self) {
+        // SAFETY: The handle came from RegisterPowerSettingNotification
+        unsafe {
+            let _ = UnregisterPowerSettingNotification(self.0);
+        }
+    }
+}
+
+/// A registration from [`register_session_notification`]. Unregisters on drop.
+#[derive(Debug)]
+pub struct SessionNotification(HWND);
+
+impl Drop for SessionNotification {
+    fn drop(&mut self) {
+        // SAFETY: The window was registered by WTSRegisterSessionNotification
+        unsafe {
+            let _ = WTSUnRegisterSessionNotification(self.0);
+        }
+    }
+}
+
+/// Asks for `WM_DEVICECHANGE` messages about device interfaces to be sent to `hwnd`.
+///
+/// Arrivals and removals are delivered to [`MessageHandler::on_device_change`].
+/// Volume changes (drive letters coming and going) are broadcast to top-level
+/// windows without registering; a message-only window doesn't receive them.
+pub fn register_device_notification(
+    hwnd: HWND,
+    filter: DeviceFilter,
+) -> Result<DeviceNotification> {
+    let (class, flags) = match filter {
+        DeviceFilter::Interface(class) => (class, DEVICE_NOTIFY_WINDOW_HANDLE),
+        DeviceFilter::AllInterfaces => (
+            GUID::zeroed(),
+            DEVICE_NOTIFY_WINDOW_HANDLE | DEVICE_NOTIFY_ALL_INTERFACE_CLASSES,
+        ),
+    };
+    let broadcast = DEV_BROADCAST_DEVICEINTERFACE_W {
+        dbcc_size: std::mem::size_of::<DEV_BROADCAST_DEVICEINTERFACE_W>() as u32,
+        dbcc_devicetype: DBT_DEVTYP_DEVICEINTERFACE.0,
+        dbcc_classguid: class,
+        ..Default::default()
+    };
+
+    // SAFETY: broadcast is a valid filter that outlives the call
+    let handle =
+        unsafe { RegisterDeviceNotificationW(hwnd, &broadcast as *const _ as *const _, flags)? };
+    Ok(DeviceNotification(handle))
+}
+
+/// Asks for `PBT_POWERSETTINGCHANGE` broadcasts about one power setting to be sent to `hwnd`.
+///
+/// `setting` is a power setting GUID such as `GUID_ACDC_POWER_SOURCE` or
+/// `GUID_CONSOLE_DISPLAY_STATE`. The current value is delivered right away,
+/// then again on every change, through [`MessageHandler::on_power_broadcast`].
+/// Suspend and resume broadcasts arrive without registering, but only at
+/// top-level windows; a message-only window needs
+/// `RegisterSuspendResumeNotification` to get them.
+pub fn register_power_notification(hwnd: HWND, setting: &GUID) -> Result<PowerNotification> {
+    // SAFETY: setting is a valid GUID that outlives the call
+    let handle = unsafe {
+        RegisterPowerSettingNotification(HANDLE(hwnd.0), setting, DEVICE_NOTIFY_WINDOW_HANDLE)?
+    };
+    Ok(PowerNotification(handle))
+}
+
+/// Asks for `WM_WTSSESSION_CHANGE` messages about the current session to be sent to `hwnd`.
+///
+/// Lock, unlock, logon, logoff and connection changes are delivered to
+/// [`MessageHandler::on_session_change`]. A message-only window works well as
+/// the recipient for a background process.
+pub fn register_session_notification(hwnd: HWND) -> Result<SessionNotification> {
+    // SAFETY: WTSRegisterSessionNotification fails for invalid windows
+    unsafe {
+        WTSRegisterSessionNotification(hwnd, NOTIFY_FOR_THIS_SESSION)?;
+    }
+    Ok(SessionNotification(hwnd))
+}
+
+/// The device a [`DeviceChange`] is about.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeviceBroadcast {
+    /// A device interface, from a [`register_device_notification`] registration.
+    Interface {
+        /// The interface class GUID.
+        class: GUID,
+        /// The device interface path, which can be opened with `CreateFileW`.
+        path: String,
+    },
+    /// Logical volumes, by drive letter.
+    Volume {
+        /// The affected drive letters, e.g. `['E']`.
+        drives: Vec<char>,
+    },
+    /// A serial or parallel port, e.g. `"COM3"`.
+    Port(String),
+    /// Another kind of device; the value is the `DBT_DEVTYP_*` type.
+    Other(u32),
+}
+
+/// A decoded `WM_DEVICECHANGE` message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeviceChange {
+    /// A device was inserted and is available.
+    Arrival(DeviceBroadcast),
+    /// Permission is requested to remove a device.
+    QueryRemove(DeviceBroadcast),
+    /// A request to remove a device was cancelled.
+    QueryRemoveFailed(DeviceBroadcast),
+    /// A device is about to be removed.
+    RemovePending(DeviceBroadcast),
+    /// A device was removed.
+    RemoveComplete(DeviceBroadcast),
+    /// A device was added to or removed from the system.
+    NodesChanged,
+    /// Another event; the value is the `DBT_*` code.
+    Other(u32),
+}
+
+impl DeviceChange {
+    /// Decodes the parameters of a `WM_DEVICECHANGE` message.
+    ///
+    /// # Safety
+    ///
+    /// For events carrying a device, `lparam` must point to a valid
+    /// `DEV_BROADCAST_HDR`, as it does in a real `WM_DEVICECHANGE` message.
+    unsafe fn from_message(wparam: WPARAM, lparam: LPARAM) -> Self {
+        let event = wparam.0 as u32;
+        let with_device = |make: fn(DeviceBroadcast) -> Self| {
+            if lparam.0 == 0 {
+                Self::Other(event)
+            } else {
+                make(DeviceBroadcast::from_header(
+                    lparam.0 as *const DEV_BROADCAST_HDR,
+                ))
+            }
+        };
+
+        match event {
+            DBT_DEVICEARRIVAL => with_device(Self::Arrival),
+            DBT_DEVICEQUERYREMOVE => with_device(Self::QueryRemove),
+            DBT_DEVICEQUERYREMOVEFAILED => with_device(Self::QueryRemoveFailed),
+            DBT_DEVICEREMOVEPENDING => with_device(Self::RemovePending),
+            DBT_DEVICEREMOVECOMPLETE => with_device(Self::RemoveComplete),
+            DBT_DEVNODES_CHANGED => Self::NodesChanged,
+            other => Self::Other(other),
+        }
+    }
+}
+
+impl DeviceBroadcast {
+    /// # Safety
+    ///
+    /// `header` must point to a valid broadcast structure of the type it names.
+    unsafe fn from_header(header: *const DEV_BROADCAST_HDR) -> Self {
+        let device_type = (*header).dbch_devicetype;
+        if device_type == DBT_DEVTYP_DEVICEINTERFACE {
+            let interface = &*(header as *const DEV_BROADCAST_DEVICEINTERFACE_W);
+            Self::Interface {
+                class: interface.dbcc_classguid,
+                path: from_wide_ptr(interface.dbcc_name.as_ptr()).unwrap_or_default(),
+            }
+        } else if device_type == DBT_DEVTYP_VOLUME {
+            let volume = &*(header as *const DEV_BROADCAST_VOLUME);
+            Self::Volume {
+                drives: drive_letters(volume.dbcv_unitmask),
+            }
+        } else if device_type == DBT_DEVTYP_PORT {
+            let port = &*(header as *const DEV_BROADCAST_PORT_W);
+            Self::Port(from_wide_ptr(port.dbcp_name.as_ptr()).unwrap_or_default())
+        } else {
+            Self::Other(device_type.0)
+        }
+    }
+}
+
+/// Converts a volume unit mask (bit 0 is `A:`) to drive letters.
+fn drive_letters(mask: u32) -> Vec<char> {
+    (0..26u8)
+        .filter(|bit| mask & (1 << bit) != 0)
+        .map(|bit| (b'A' + bit) as char)
+        .collect()
+}
+
+/// A decoded `WM_POWERBROADCAST` message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PowerEvent {
+    /// The power source or battery status changed.
+    StatusChange,
+    /// The system is about to suspend.
+    Suspend,
+    /// The system resumed from suspend because of user input.
+    ResumeSuspend,
+    /// The system resumed from suspend, with or without a user present.
+    ResumeAutomatic,
+    /// A power setting registered with [`register_power_notification`] changed.
+    SettingChange {
+        /// The power setting GUID.
+        setting: GUID,
+        /// The new value; its layout depends on the setting.
+        data: Vec<u8>,
+    },
+    /// Another event; the value is the `PBT_*` code.
+    Other(u32),
+}
+
+impl PowerEvent {
+    /// Decodes the parameters of a `WM_POWERBROADCAST` message.
+    ///
+    /// # Safety
+    ///
+    /// For `PBT_POWERSETTINGCHANGE`, `lparam` must point to a valid
+    /// `POWERBROADCAST_SETTING`, as it does in a real `WM_POWERBROADCAST` message.
+    unsafe fn from_message(wparam: WPARAM, lparam: LPARAM) -> Self {
+        match wparam.0 as u32 {
+            PBT_APMPOWERSTATUSCHANGE => Self::StatusChange,
+            PBT_APMSUSPEND => Self::Suspend,
+            PBT_APMRESUMESUSPEND => Self::ResumeSuspend,
+            PBT_APMRESUMEAUTOMATIC => Self::ResumeAutomatic,
+            PBT_POWERSETTINGCHANGE if lparam.0 != 0 => {
+                let setting = &*(lparam.0 as *const POWERBROADCAST_SETTING);
+                Self::SettingChange {
+                    setting: setting.PowerSetting,
+                    data: std::slice::from_raw_parts(
+                        setting.Data.as_ptr(),
+                        setting.DataLength as usize,
+                    )
+                    .to_vec(),
+                }
+            }
+            other => Self::Other(other),
+        }
+    }
+}
+
+/// A decoded `WM_WTSSESSION_CHANGE` event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionChange {
+    /// The session was connected to the console.
+    ConsoleConnect,
+    /// The session was disconnected from the console.
+    ConsoleDisconnect,
+    /// The session was connected to a remote terminal.
+    RemoteConnect,
+    /// The session was disconnected from a remote terminal.
+    RemoteDisconnect,
+    /// A user logged on to the session.
+    Logon,
+    /// A user logged off the session.
+    Logoff,
+    /// The session was locked.
+    Lock,
+    /// The session was unlocked.
+    Unlock,
+    /// The session's remote control status changed.
+    RemoteControl,
+    /// Another event; the value is the `WTS_*` code.
+    Other(u32),
+}
+
+impl SessionChange {
+    fn from_raw(event: u32) -> Self {
+        match event {
+            WTS_CONSOLE_CONNECT => Self::ConsoleConnect,
+            WTS_CONSOLE_DISCONNECT => Self::ConsoleDisconnect,
+            WTS_REMOTE_CONNECT => Self::RemoteConnect,
+            WTS_REMOTE_DISCONNECT => Self::RemoteDisconnect,
+            WTS_SESSION_LOGON => Self::Logon,
+            WTS_SESSION_LOGOFF => Self::Logoff,
+            WTS_SESSION_LOCK => Self::Lock,
+            WTS_SESSION_UNLOCK => Self::Unlock,
+            WTS_SESSION_REMOTE_CONTROL => Self::RemoteControl,
+            other => Self::Other(other),
+        }
+    }
+}
+
+/// Pixels captured by [`capture_window`] or [`capture_screen`].
+#[derive(Clone, PartialEq, Eq)]
+pub struct CapturedImage {
+    /// Width in pixels.
+    pub width: u32,
+    /// Height in pixels.
+    pub height: u32,
+    /// Opaque 32-bit BGRA pixels, top row first, `width * 4` bytes per row.
+    pub pixels: Vec<u8>,
+}
+
+impl CapturedImage {
+    /// Saves the image as a PNG file.
+    pub fn save_png(&self, path: impl AsRef<Path>) -> Result<()> {
+        crate::d2d::save_bgra_pixels(
+            self.width,
+            self.height,
+            &self.pixels,
+            path.as_ref(),
+            crate::d2d::ImageFormat::Png,
+        )
+    }
+}
+
+impl std::fmt::Debug for CapturedImage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CapturedImage")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Captures a window, including its frame, as it would be drawn on screen.
+///
+/// Uses `PrintWindow` with `PW_RENDERFULLCONTENT`, which also captures
+/// DWM-composited content such as UWP apps and hardware-accelerated windows, and
+/// works for windows that are covered by others. If the window can't print
+/// itself, its on-screen pixels are copied instead.
+pub fn capture_window(hwnd: HWND) -> Result<CapturedImage> {
+    let mut rect = RECT::default();
+    // SAFETY: rect is a valid output parameter
+    unsafe { GetWindowRect(hwnd, &mut rect)? };
+
+    let capture = DibCapture::new(rect.right - rect.left, rect.bottom - rect.top)?;
+
+    // SAFETY: The memory DC has the DIB selected and stays valid for the call
+    let printed =
+        unsafe { PrintWindow(hwnd, capture.dc.0, PRINT_WINDOW_FLAGS(PW_RENDERFULLCONTENT)) };
+    if !printed.as_bool() {
+        let window_dc = WindowDc::window(hwnd)?;
+        capture.copy_from(window_dc.hdc, 0, 0)?;
+    }
+
+    Ok(capture.into_image())
+}
+
+/// Captures a region of the screen, or the whole virtual screen if `region` is `None`.
+///
+/// `region` is in virtual-screen coordinates, which may be negative on
+/// multi-monitor setups. Layered windows are included.
+pub fn capture_screen(region: Option<RECT>) -> Result<CapturedImage> {
+    let region = region.unwrap_or_else(|| {
+        // SAFETY: GetSystemMetrics has no preconditions
+        unsafe {
+            let left = GetSystemMetrics(SM_XVIRTUALSCREEN);
+            let top = GetSystemMetrics(SM_YVIRTUALSCREEN);
+            RECT {
+                left,
+                top,
+                right: left + GetSystemMetrics(SM_CXVIRTUALSCREEN),
+                bottom: top + GetSystemMetrics(SM_CYVIRTUALSCREEN),
+            }
+        }
+    });
+
+    let capture = DibCapture::new(region.right - region.left, region.bottom - region.top)?;
+    let screen_dc = WindowDc::screen()?;
+    capture.copy_from(screen_dc.hdc, region.left, region.top)?;
+    Ok(capture.into_image())
+}
+
+/// A DC from `GetDC`/`GetWindowDC`, released on drop.
+struct WindowDc {
+    hwnd: HWND,
+    hdc: HDC,
+}
+
+impl WindowDc {
+    fn screen() -> Result<Self> {
+        // SAFETY: GetDC(None) returns the screen DC
+        Self::checked(HWND::default(), unsafe { GetDC(None) })
+    }
+
+    fn window(hwnd: HWND) -> Result<Self> {
+        // SAFETY: GetWindowDC accepts any handle and fails for invalid ones
+        Self::checked(hwnd, unsafe { GetWindowDC(hwnd) })
+    }
+
+    fn checked(hwnd: HWND, hdc: HDC) -> Result<Self> {
+        if hdc.is_invalid() {
+            return Err(crate::error::Error::custom("Failed to get device context"));
+        }
+        Ok(Self { hwnd, hdc })
+    }
+}
+
+impl Drop for WindowDc {
+    fn drop(&mut self) {
+        // SAFETY: hdc was obtained for hwnd by GetDC or GetWindowDC
+        unsafe {
+            ReleaseDC(self.hwnd, self.hdc);
+        }
+    }
+}
+
+/// A memory DC, deleted on drop.
+struct MemoryDc(HDC);
+
+impl Drop for MemoryDc {
+    fn drop(&mut self) {
+        // SAFETY: The DC was created by CreateCompatibleDC and is no longer used
+        unsafe {
+            let _ = DeleteDC(self.0);
+        }
+    }
+}
+
+/// A GDI bitmap, deleted on drop.
+struct GdiBitmap(HBITMAP);
+
+impl Drop for GdiBitmap {
+    fn drop(&mut self) {
+        // SAFETY: The bitmap is owned and no longer selected into a DC
+        unsafe {
+            let _ = DeleteObject(self.0);
+        }
+    }
+}
+
+/// A top-down 32-bit DIB section selected into a memory DC.
+struct DibCapture {
+    previous: HGDIOBJ,
+    dc: MemoryDc,
+    #[allow(dead_code)] // Owns the DIB section selected into `dc`
+    bitmap: GdiBitmap,
+    bits: *const u8,
+    width: u32,
+    height: u32,
+}
+
+impl DibCapture {
+    fn new(width: i32, height: i32) -> Result<Self> {
+        if width <= 0 || height <= 0 {
+            return Err(crate::error::Error::custom(format!(
+                "Nothing to capture in a {}x{} area",
+                width, height
+            )));
+        }
+
+        let info = BITMAPINFO {
+            bmiHeader: BITMAPINFOHEADER {
+                biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+                biWidth: width,
+                // Negative height makes the DIB top-down
+                biHeight: -height,
+                biPlanes: 1,
+                biBitCount: 32,
+                biCompression: BI_RGB.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let screen_dc = WindowDc::screen()?;
+        // SAFETY: screen_dc is valid; a null result is checked below
+        let dc = MemoryDc(unsafe { CreateCompatibleDC(screen_dc.hdc) });
+        if dc.0.is_invalid() {
+            return Err(crate::error::Error::custom("Failed to create memory DC"));
+        }
+
+        let mut bits = std::ptr::null_mut();
+        // SAFETY: info describes a 32-bit DIB; bits receives its pixel pointer,
+        // which stays valid until the bitmap is deleted.
+        let bitmap = GdiBitmap(unsafe {
+            CreateDIBSection(dc.0, &info, DIB_RGB_COLORS, &mut bits, None, 0)?
+        });
+        // SAFETY: Both the DC and the bitmap are valid
+        let previous = unsafe { SelectObject(dc.0, bitmap.0) };
+
+        Ok(Self {
+            previous,
+            dc,
+            bitmap,
+            bits: bits as *const u8,
+            width: width as u32,
+            height: height as u32,
+        })
+    }
+
+    /// Copies the pixels at `(x, y)` in `source` into the DIB.
+    fn copy_from(&self, source: HDC, x: i32, y: i32) -> Result<()> {
+        // SAFETY: Both DCs are valid and the copy stays within the DIB
+        unsafe {
+            BitBlt(
+                self.dc.0,
+                0,
+                0,
+                self.width as i32,
+                self.height as i32,
+                source,
+                x,
+                y,
+                SRCCOPY | CAPTUREBLT,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn into_image(self) -> CapturedImage {
+        let len = self.width as usize * self.height as usize * 4;
+        // SAFETY: GDI drawing into the DIB has completed (GdiFlush), and the
+        // DIB holds exactly width * height 32-bit pixels.
+        let mut pixels = unsafe {
+            let _ = GdiFlush();
+            std::slice::from_raw_parts(self.bits, len).to_vec()
+        };
+
+        // GDI leaves the alpha channel undefined; captured pixels are opaque
+        for alpha in pixels.iter_mut().skip(3).step_by(4) {
+            *alpha = 0xFF;
+        }
+
+        CapturedImage {
+            width: self.width,
+            height: self.height,
+            pixels,
+        }
+    }
+}
+
+impl Drop for DibCapture {
+    fn drop(&mut self) {
+        // SAFETY: Restores the DC's original bitmap so the DIB can be deleted
+        unsafe {
+            SelectObject(self.dc.0, self.previous);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use windows::Win32::Foundation::{GlobalFree, BOOL, HGLOBAL};
+    use windows::Win32::System::Memory::{GlobalAlloc, GlobalLock, GlobalUnlock, GHND};
+    use windows::Win32::UI::Shell::DROPFILES;
+    use windows::Win32::UI::WindowsAndMessaging::WM_APP;
+
+    /// Builds an HDROP the same way Explorer does: a DROPFILES header
+    /// followed by a double-null-terminated list of wide paths.
+    fn synthetic_hdrop(paths: &[&str], point: (i32, i32)) -> HGLOBAL {
+        let mut list: Vec<u16> = Vec::new();
+        for path in paths {
+            list.extend(path.encode_utf16());
+            list.push(0);
+        }
+        list.push(0);
+
+        let header = std::mem::size_of::<DROPFILES>();
+        let size = header + list.len() * 2;
+
+        unsafe {
+            let hglobal = GlobalAlloc(GHND, size).unwrap();
+            let ptr = GlobalLock(hglobal) as *mut u8;
+            *(ptr as *mut DROPFILES) = DROPFILES {
+                pFiles: header as u32,
+                pt: POINT {
+                    x: point.0,
+                    y: point.1,
+                },
+                fNC: BOOL(0),
+                fWide: BOOL(1),
+            };
+            std::ptr::copy_nonoverlapping(list.as_ptr(), ptr.add(header) as *mut u16, list.len());
+            let _ = GlobalUnlock(hglobal);
+            hglobal
+        }
+    }
+
+    struct CloseImmediately;
+
+    impl MessageHandler for CloseImmediately {
+        fn handle_message(&mut self, _msg: Message) -> Option<LRESULT> {
+            None
+        }
+
+        fn on_create(&mut self, hwnd: HWND) -> bool {
+            close_modal(hwnd, 42).is_ok()
+        }
+    }
+
+    #[test]
+    fn test_run_modal_closes_with_result() {
+        use windows::Win32::UI::Input::KeyboardAndMouse::IsWindowEnabled;
+
+        let Some(owner) = test_window("modal_owner") else {
+            return;
+        };
+
+        let builder = WindowBuilder::new().title("Modal").size(200, 100);
+        let result = run_modal(&owner, builder, CloseImmediately).unwrap();
+        assert_eq!(result, ModalResult::Closed(42));
+        assert_eq!(result.code(), Some(42));
+        assert!(unsafe { IsWindowEnabled(owner.hwnd()) }.as_bool());
+
+        // Destroying the modal window must not have posted WM_QUIT
+        assert!(!process_messages());
+    }
+
+    #[test]
+    fn test_wait_for_vblank() {
+        // Fails without desktop composition (e.g. some CI sessions)
+        let start = std::time::Instant::now();
+        if wait_for_vblank().is_ok() {
+            assert!(start.elapsed() < std::time::Duration::from_millis(100));
+        }
+    }
+
+    #[test]
+    fn test_animation_loop_deltas() {
+        let mut deltas = Vec::new();
+        let mut animation = AnimationLoop::new();
+        let quit = animation.run(|delta| {
+            deltas.push(delta);
+            deltas.len() < 3
+        });
+
+        assert!(!quit);
+        assert_eq!(animation.frame_count(), 3);
+        assert_eq!(deltas[0], std::time::Duration::ZERO);
+        assert!(deltas[1..].iter().all(|d| !d.is_zero()));
+    }
+
+    fn test_window(name: &str) -> Option<Window<DefaultHandler>> {
+        // Window creation may fail in headless environments
+        WindowBuilder::new()
+            .class_name(format!(
+                "ErgonomicWindowTest_{}_{}",
+                name,
+                std::process::id()
+            ))
+            .size(320, 240)
+            .build(DefaultHandler)
+            .ok()
+    }
+
+    #[test]
+    fn test_minimize_restore() {
+        let Some(window) = test_window("minimize") else {
+            return;
+        };
+        window.show(ShowCommand::SHOW);
+
+        window.minimize();
+        assert!(window.is_minimized());
+        assert!(window.placement().unwrap().is_minimized());
+
+        window.restore();
+        assert!(!window.is_minimized());
+
+        window.maximize();
+        assert!(window.is_maximized());
+        window.restore();
+        assert!(!window.is_maximized());
+    }
+
+    #[test]
+    fn test_dwm_attributes() {
+        let Some(window) = test_window("dwm") else {
+            return;
+        };
+        // Unsupported attributes are ignored, so these succeed on any version
+        window.set_dark_mode(true).unwrap();
+        window.set_dark_mode(false).unwrap();
+        window.set_backdrop(BackdropType::Mica).unwrap();
+        window.set_backdrop(BackdropType::None).unwrap();
+        window
+            .set_corner_preference(CornerPreference::DoNotRound)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_placement_round_trip() {
+        let Some(window) = test_window("placement") else {
+            return;
+        };
+
+        let mut placement = window.placement().unwrap();
+        placement.normal_left = 50;
+        placement.normal_top = 60;
+        placement.normal_right = 450;
+        placement.normal_bottom = 360;
+        window.set_placement(&placement).unwrap();
+
+        let restored = window.placement().unwrap();
+        assert_eq!(restored.normal_left, 50);
+        assert_eq!(restored.normal_bottom, 360);
+    }
+
+    #[derive(Default)]
+    struct MessageRecorder {
+        received: Vec<(u32, usize)>,
+    }
+
+    impl MessageHandler for MessageRecorder {
+        fn handle_message(&mut self, msg: Message) -> Option<LRESULT> {
+            if msg.msg >= WM_APP {
+                self.received.push((msg.msg, msg.wparam.0));
+                return Some(LRESULT(0));
+            }
+            None
+        }
+    }
+
+    #[test]
+    fn test_message_only_window() {
+        let Ok(window) = WindowBuilder::new()
+            .class_name(format!(
+                "ErgonomicWindowTest_msgonly_{}",
+                std::process::id()
+            ))
+            .message_only()
+            .build(MessageRecorder::default())
+        else {
+            return;
+        };
+
+        unsafe { PostMessageW(window.hwnd(), WM_APP + 1, WPARAM(42), LPARAM(0)) }.unwrap();
+        assert!(!process_messages());
+        assert_eq!(window.handler().received, vec![(WM_APP + 1, 42)]);
+
+        // Destroying a message-only window must not post WM_QUIT
+        drop(window);
+        assert!(!process_messages());
+    }
+
+    #[derive(Default)]
+    struct PaintCounter {
+        paints: u32,
+    }
+
+    impl MessageHandler for PaintCounter {
+        fn handle_message(&mut self, _msg: Message) -> Option<LRESULT> {
+            None
+        }
+
+        fn on_paint(&mut self, _hwnd: HWND, ctx: &PaintContext) {
+            assert!(!ctx.hdc().is_invalid());
+            self.paints += 1;
+        }
+    }
+
+    #[test]
+    fn test_on_paint_after_invalidate() {
+        let Ok(window) = WindowBuilder::new()
+            .class_name(format!("ErgonomicWindowTest_paint_{}", std::process::id()))
+            .size(320, 240)
+            .build(PaintCounter::default())
+        else {
+            return;
+        };
+        window.show(ShowCommand::SHOW);
+        process_messages();
+
+        let before = window.handler().paints;
+        window.invalidate(true);
+        process_messages();
+        assert!(window.handler().paints > before);
+    }
+
+    #[derive(Default)]
+    struct LegacyPainter {
+        paints: u32,
+        on_paint_calls: u32,
+    }
+
+    impl MessageHandler for LegacyPainter {
+        fn handle_message(&mut self, msg: Message) -> Option<LRESULT> {
+            if msg.msg != WM_PAINT {
+                return None;
+            }
+            let ctx = PaintContext::begin(msg.hwnd).ok()?;
+            assert!(!ctx.hdc().is_invalid());
+            self.paints += 1;
+            Some(LRESULT(0))
+        }
+
+        fn on_paint(&mut self, _hwnd: HWND, _ctx: &PaintContext) {
+            self.on_paint_calls += 1;
+        }
+    }
+
+    #[test]
+    fn test_paint_in_handle_message() {
+        let Ok(window) = WindowBuilder::new()
+            .class_name(format!(
+                "ErgonomicWindowTest_legacypaint_{}",
+                std::process::id()
+            ))
+            .size(320, 240)
+            .build(LegacyPainter::default())
+        else {
+            return;
+        };
+        window.show(ShowCommand::SHOW);
+        process_messages();
+
+        let before = window.handler().paints;
+        window.invalidate(true);
+        process_messages();
+        assert!(window.handler().paints > before);
+        assert_eq!(window.handler().on_paint_calls, 0);
+    }
+
+    struct DragRegion {
+        hits: Vec<(i32, i32)>,
+    }
+
+    impl MessageHandler for DragRegion {
+        fn handle_message(&mut self, _msg: Message) -> Option<LRESULT> {
+            None
+        }
+
+        fn on_nc_hit_test(&mut self, point: (i32, i32)) -> Option<HitTestResult> {
+            self.hits.push(point);
+            Some(HitTestResult::Caption)
+        }
+    }
+
+    #[test]
+    fn test_hit_test_codes() {
+        assert_eq!(HitTestResult::Caption.to_raw(), HTCAPTION as i32);
+        assert_eq!(HitTestResult::BottomRight.to_raw(), HTBOTTOMRIGHT as i32);
+        assert_eq!(HitTestResult::Transparent.to_raw(), -1);
+    }
+
+    #[test]
+    fn test_on_nc_hit_test_dispatched() {
+        use windows::Win32::UI::WindowsAndMessaging::SendMessageW;
+
+        let Ok(window) = WindowBuilder::new()
+            .class_name(format!(
+                "ErgonomicWindowTest_hittest_{}",
+                std::process::id()
+            ))
+            .size(320, 240)
+            .build(DragRegion { hits: Vec::new() })
+        else {
+            return;
+        };
+        let _ = window.set_borderless();
+
+        // Negative coordinates must survive the lparam packing
+        let lparam = LPARAM((((-5i16 as u16 as u32) << 16) | 40) as isize);
+        let result = unsafe { SendMessageW(window.hwnd(), WM_NCHITTEST, WPARAM(0), lparam) };
+
+        assert_eq!(result.0, HTCAPTION as isize);
+        assert_eq!(window.handler().hits.last(), Some(&(40, -5)));
+    }
+
+    struct LegacyHitTest;
+
+    impl MessageHandler for LegacyHitTest {
+        fn handle_message(&mut self, msg: Message) -> Option<LRESULT> {
+            (msg.msg == WM_NCHITTEST).then_some(LRESULT(HTCAPTION as isize))
+        }
+    }
+
+    #[test]
+    fn test_nc_hit_test_falls_back_to_handle_message() {
+        use windows::Win32::UI::WindowsAndMessaging::SendMessageW;
+
+        let Ok(window) = WindowBuilder::new()
+            .class_name(format!(
+                "ErgonomicWindowTest_legacyhittest_{}",
+                std::process::id()
+            ))
+            .size(320, 240)
+            .build(LegacyHitTest)
+        else {
+            return;
+        };
+
+        let result = unsafe { SendMessageW(window.hwnd(), WM_NCHITTEST, WPARAM(0), LPARAM(0)) };
+        assert_eq!(result.0, HTCAPTION as isize);
+    }
+
+    #[test]
+    fn test_dropped_files() {
+        let hglobal = synthetic_hdrop(&[r"C:\one.txt", r"C:\dir\two.txt"], (10, 20));
+        let hdrop = HDROP(hglobal.0);
+
+        assert_eq!(
+            dropped_files(hdrop),
+            [
+                PathBuf::from(r"C:\one.txt"),
+                PathBuf::from(r"C:\dir\two.txt")
+            ]
+        );
+        assert_eq!(drop_point(hdrop), (10, 20));
+
+        unsafe {
+            let _ = GlobalFree(hglobal);
+        }
+    }
+
+    #[test]
+    fn test_dropped_files_empty() {
+        let hglobal = synthetic_hdrop(&[], (0, 0));
+        assert!(dropped_files(HDROP(hglobal.0)).is_empty());
+
+        unsafe {
+            let _ = GlobalFree(hglobal);
+        }
+    }
+
+    #[test]
+    fn test_enumerate_and_find() {
+        let class = format!("ErgonomicWindowTest_enum_{}", std::process::id());
+        let title = format!("Enumeration test {}", std::process::id());
+        let Ok(window) = WindowBuilder::new()
+            .class_name(&class)
+            .title(&title)
+            .build(DefaultHandler)
+        else {
+            return;
+        };
+
+        let windows = enumerate();
+        let ours = windows
+            .iter()
+            .find(|info| info.hwnd == window.hwnd())
+            .expect("enumerate should list our window");
+        assert_eq!(ours.title, title);
+        assert_eq!(ours.class_name, class);
+        assert_eq!(ours.pid, std::process::id());
+
+        assert_eq!(find_by_title(&title), Some(window.hwnd()));
+        assert_eq!(find_by_class(&class), Some(window.hwnd()));
+        assert_eq!(find_by_class("ErgonomicWindowTest_no_such_class"), None);
+
+        // Only present in an interactive session with Explorer running
+        if let Some(tray) = find_by_class("Shell_TrayWnd") {
+            assert!(windows.iter().any(|info| info.visible));
+            assert!(windows
+                .iter()
+                .any(|info| info.hwnd == tray && info.class_name == "Shell_TrayWnd"));
+        }
+    }
+
+    #[test]
+    fn test_capture_desktop() {
+        use windows::Win32::UI::WindowsAndMessaging::GetDesktopWindow;
+
+        // Capturing needs an interactive desktop
+        let Ok(image) = capture_window(unsafe { GetDesktopWindow() }) else {
+            return;
+        };
+        assert!(image.width > 0 && image.height > 0);
+        assert_eq!(
+            image.pixels.len(),
+            image.width as usize * image.height as usize * 4
+        );
+
+        let region = RECT {
+            left: 0,
+            top: 0,
+            right: 16,
+            bottom: 8,
+        };
+        let image = capture_screen(Some(region)).unwrap();
+        assert_eq!((image.width, image.height), (16, 8));
+        assert!(image.pixels.chunks(4).all(|px| px[3] == 0xFF));
+
+        let path = std::env::temp_dir().join(format!("ew_capture_{}.png", std::process::id()));
+        image.save_png(&path).unwrap();
+        assert!(std::fs::metadata(&path).unwrap().len() > 0);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_register_session_notification() {
+        let Ok(window) = WindowBuilder::new()
+            .class_name(format!(
+                "ErgonomicWindowTest_session_{}",
+                std::process::id()
+            ))
+            .message_only()
+            .build(DefaultHandler)
+        else {
+            return;
+        };
+
+        let registration = register_session_notification(window.hwnd()).unwrap();
+        drop(registration);
+        let _ = register_device_notification(window.hwnd(), DeviceFilter::AllInterfaces);
+    }
+
+    #[test]
+    fn test_decode_notifications() {
+        assert_eq!(drive_letters(0b1_0000_0101), vec!['A', 'C', 'I']);
+        assert_eq!(
+            SessionChange::from_raw(WTS_SESSION_LOCK),
+            SessionChange::Lock
+        );
+        assert_eq!(SessionChange::from_raw(99), SessionChange::Other(99));
+
+        let volume = DEV_BROADCAST_VOLUME {
+            dbcv_size: std::mem::size_of::<DEV_BROADCAST_VOLUME>() as u32,
+            dbcv_devicetype: DBT_DEVTYP_VOLUME.0,
+            dbcv_unitmask: 1 << 4,
+            ..Default::default()
+        };
+        let change = unsafe {
+            DeviceChange::from_message(
+                WPARAM(DBT_DEVICEARRIVAL as usize),
+                LPARAM(&volume as *const _ as isize),
+            )
+        };
+        assert_eq!(
+            change,
+            DeviceChange::Arrival(DeviceBroadcast::Volume { drives: vec!['E'] })
+        );
+        let change =
+            unsafe { DeviceChange::from_message(WPARAM(DBT_DEVNODES_CHANGED as usize), LPARAM(0)) };
+        assert_eq!(change, DeviceChange::NodesChanged);
+
+        // A POWERBROADCAST_SETTING carrying a DWORD value
+        #[repr(C)]
+        struct Setting {
+            guid: GUID,
+            len: u32,
+            data: u32,
+        }
+        let guid = GUID::from_u128(0x5d3e9a59_e9d5_4b00_a6bd_ff34ff516548);
+        let setting = Setting {
+            guid,
+            len: 4,
+            data: 1,
+        };
+        let event = unsafe {
+            PowerEvent::from_message(
+                WPARAM(PBT_POWERSETTINGCHANGE as usize),
+                LPARAM(&setting as *const _ as isize),
+            )
+        };
+        assert_eq!(
+            event,
+            PowerEvent::SettingChange {
+                setting: guid,
+                data: 1u32.to_le_bytes().to_vec(),
+            }
+        );
+        let event = unsafe { PowerEvent::from_message(WPARAM(PBT_APMSUSPEND as usize), LPARAM(0)) };
+        assert_eq!(event, PowerEvent::Suspend);
+    }
+}